@@ -18,6 +18,10 @@ struct Args {
     /// Input file
     #[clap(short, long)]
     input: PathBuf,
+
+    /// Apply algebraic simplification before building tapes
+    #[clap(long)]
+    simplify: bool,
 }
 
 #[derive(Subcommand)]
@@ -56,9 +60,13 @@ enum Command {
 #[derive(ValueEnum, Clone)]
 enum EvalMode {
     Vm,
+    Vm64,
+    VmSound,
 
     #[cfg(feature = "jit")]
     Jit,
+    #[cfg(feature = "jit")]
+    JitSound,
 }
 
 #[derive(Parser)]
@@ -82,6 +90,14 @@ struct ImageSettings {
     /// Image size
     #[clap(short, long, default_value_t = 128)]
     size: u32,
+
+    /// Use affine arithmetic to cull tiles
+    #[clap(long)]
+    affine: bool,
+
+    /// Value for the W input
+    #[clap(short, long, default_value_t = 0.0)]
+    w: f32,
 }
 
 #[derive(Parser)]
@@ -109,6 +125,14 @@ struct MeshSettings {
     /// Number of times to render (for benchmarking)
     #[clap(short = 'N', default_value_t = 1)]
     n: usize,
+
+    /// Use affine arithmetic to cull octree cells
+    #[clap(long)]
+    affine: bool,
+
+    /// Value for the W input
+    #[clap(short, long, default_value_t = 0.0)]
+    w: f32,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -133,6 +157,9 @@ fn run3d<I: fidget::eval::Family>(
         threads: settings.threads,
 
         mat,
+
+        affine: settings.affine,
+        w: settings.w,
     };
 
     let start = Instant::now();
@@ -219,6 +246,9 @@ fn run2d<I: fidget::eval::Family>(
             threads: settings.threads,
 
             mat: nalgebra::Transform2::identity(),
+
+            affine: settings.affine,
+            w: settings.w,
         };
         let start = Instant::now();
         let out = if sdf {
@@ -271,6 +301,8 @@ fn run_mesh<I: fidget::eval::Family>(
             threads: settings.threads,
             min_depth: settings.depth,
             max_depth: settings.max_depth.unwrap_or(settings.depth),
+            affine: settings.affine,
+            w: settings.w,
         };
         let octree = fidget::mesh::Octree::build(&tape, settings);
         mesh = octree.walk_dual(settings);
@@ -285,9 +317,20 @@ fn main() -> Result<()> {
     let now = Instant::now();
     let args = Args::parse();
     let mut file = std::fs::File::open(&args.input)?;
-    let (ctx, root) = Context::from_text(&mut file)?;
+    let (mut ctx, mut root) = Context::from_text(&mut file)?;
     info!("Loaded file in {:?}", now.elapsed());
 
+    if args.simplify {
+        let now = Instant::now();
+        let before = ctx.node_count(root)?;
+        let (simplified, removed) = ctx.simplify(root)?;
+        root = simplified;
+        info!(
+            "Simplified in {:?}, removing {removed} of {before} nodes",
+            now.elapsed(),
+        );
+    }
+
     match args.cmd {
         Command::Render2d {
             settings,
@@ -302,6 +345,16 @@ fn main() -> Result<()> {
                 EvalMode::Vm => {
                     run2d::<fidget::vm::Eval>(&ctx, root, &settings, brute, sdf)
                 }
+                #[cfg(feature = "jit")]
+                EvalMode::JitSound => run2d::<fidget::jit::SoundEval>(
+                    &ctx, root, &settings, brute, sdf,
+                ),
+                EvalMode::Vm64 => run2d::<fidget::vm::Eval64>(
+                    &ctx, root, &settings, brute, sdf,
+                ),
+                EvalMode::VmSound => run2d::<fidget::vm::SoundEval>(
+                    &ctx, root, &settings, brute, sdf,
+                ),
             };
 
             info!(
@@ -334,6 +387,16 @@ fn main() -> Result<()> {
                 EvalMode::Vm => run3d::<fidget::vm::Eval>(
                     &ctx, root, &settings, isometric, color,
                 ),
+                #[cfg(feature = "jit")]
+                EvalMode::JitSound => run3d::<fidget::jit::SoundEval>(
+                    &ctx, root, &settings, isometric, color,
+                ),
+                EvalMode::Vm64 => run3d::<fidget::vm::Eval64>(
+                    &ctx, root, &settings, isometric, color,
+                ),
+                EvalMode::VmSound => run3d::<fidget::vm::SoundEval>(
+                    &ctx, root, &settings, isometric, color,
+                ),
             };
             info!(
                 "Rendered {}x at {:?} ms/frame",
@@ -363,6 +426,16 @@ fn main() -> Result<()> {
                 EvalMode::Vm => {
                     run_mesh::<fidget::vm::Eval>(&ctx, root, &settings)
                 }
+                #[cfg(feature = "jit")]
+                EvalMode::JitSound => {
+                    run_mesh::<fidget::jit::SoundEval>(&ctx, root, &settings)
+                }
+                EvalMode::Vm64 => {
+                    run_mesh::<fidget::vm::Eval64>(&ctx, root, &settings)
+                }
+                EvalMode::VmSound => {
+                    run_mesh::<fidget::vm::SoundEval>(&ctx, root, &settings)
+                }
             };
             info!(
                 "Rendered {}x at {:?} ms/iter",
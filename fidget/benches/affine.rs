@@ -0,0 +1,101 @@
+use criterion::{
+    black_box, criterion_group, criterion_main, BenchmarkId, Criterion,
+};
+use fidget::eval::{
+    types::{Affine, Interval},
+    Family, Tape,
+};
+
+const PROSPERO: &str = include_str!("../../models/prospero.vm");
+
+/// Recursively counts tiles that are neither empty nor full at each level
+///
+/// Each level subdivides the tile into `splits[level]` pieces on each axis,
+/// mirroring the tile sizes used by `render2d`.
+fn count_tiles<F: Family>(
+    tape: &Tape<F>,
+    x: Interval,
+    y: Interval,
+    level: usize,
+    splits: &[usize],
+    affine: bool,
+    counts: &mut [usize],
+) {
+    let n = splits[level];
+    for j in 0..n {
+        for i in 0..n {
+            let split = |v: Interval, k: usize| {
+                let w = v.width() / n as f32;
+                Interval::new(
+                    v.lower() + w * k as f32,
+                    v.lower() + w * (k + 1) as f32,
+                )
+            };
+            let (tx, ty) = (split(x, i), split(y, j));
+            let (v, next) = if affine {
+                let eval = tape.new_affine_evaluator();
+                let (v, trace) = eval
+                    .eval(Affine::x(tx), Affine::y(ty), 0.0.into(), &[])
+                    .unwrap();
+                (Interval::from(v), trace.map(|t| t.simplify().unwrap()))
+            } else {
+                let eval = tape.new_interval_evaluator();
+                let (v, trace) = eval.eval(tx, ty, 0.0.into(), &[]).unwrap();
+                (v, trace.map(|t| t.simplify().unwrap()))
+            };
+            if v.upper() < 0.0 || v.lower() > 0.0 {
+                continue;
+            }
+            counts[level] += 1;
+            if level + 1 < splits.len() {
+                let next = next.unwrap_or_else(|| tape.clone());
+                count_tiles(&next, tx, ty, level + 1, splits, affine, counts);
+            }
+        }
+    }
+}
+
+pub fn prospero_tile_counts(c: &mut Criterion) {
+    let (ctx, root) = fidget::Context::from_text(PROSPERO.as_bytes()).unwrap();
+    let tape = &ctx.get_tape::<fidget::vm::Eval>(root).unwrap();
+
+    // Equivalent to rendering a 1024 x 1024 image with tiles of 128, 32, 8
+    let splits = [8, 4, 4];
+    let full = Interval::new(-1.0, 1.0);
+    for affine in [false, true] {
+        let mut counts = [0; 3];
+        count_tiles(tape, full, full, 0, &splits, affine, &mut counts);
+        let name = if affine { "affine" } else { "interval" };
+        println!("prospero ambiguous tiles ({name}): {counts:?}");
+    }
+
+    let mut group =
+        c.benchmark_group("interval vs affine (prospero, 2d) (8 threads)");
+    for size in [256, 512, 1024] {
+        for affine in [false, true] {
+            let cfg = &fidget::render::RenderConfig {
+                image_size: size,
+                tile_sizes: fidget::vm::Eval::tile_sizes_2d().to_vec(),
+                threads: 8,
+
+                mat: nalgebra::Transform2::identity(),
+                affine,
+                w: 0.0,
+            };
+            let name = if affine { "affine" } else { "interval" };
+            group.bench_function(BenchmarkId::new(name, size), move |b| {
+                b.iter(|| {
+                    let tape = tape.clone();
+                    black_box(fidget::render::render2d(
+                        tape,
+                        cfg,
+                        &fidget::render::BitRenderMode,
+                    ))
+                })
+            });
+        }
+    }
+}
+
+criterion_group!(benches, prospero_tile_counts);
+criterion_main!(benches);
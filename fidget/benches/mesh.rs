@@ -16,6 +16,8 @@ pub fn colonnade_octree_thread_sweep(c: &mut Criterion) {
             min_depth: 6,
             max_depth: 6,
             threads,
+            affine: false,
+            w: 0.0,
         };
         group.bench_function(BenchmarkId::new("jit", threads), move |b| {
             b.iter(|| {
@@ -39,6 +41,8 @@ pub fn colonnade_mesh(c: &mut Criterion) {
         min_depth: 8,
         max_depth: 8,
         threads: 8,
+        affine: false,
+        w: 0.0,
     };
     let octree = &fidget::mesh::Octree::build(tape_vm, cfg);
 
@@ -20,6 +20,10 @@ pub fn prospero_size_sweep(c: &mut Criterion) {
             threads: 8,
 
             mat: nalgebra::Transform2::identity(),
+
+            affine: false,
+
+            w: 0.0,
         };
         group.bench_function(BenchmarkId::new("jit", size), move |b| {
             b.iter(|| {
@@ -58,6 +62,10 @@ pub fn prospero_thread_sweep(c: &mut Criterion) {
             threads,
 
             mat: nalgebra::Transform2::identity(),
+
+            affine: false,
+
+            w: 0.0,
         };
         group.bench_function(BenchmarkId::new("jit", threads), move |b| {
             b.iter(|| {
@@ -0,0 +1,475 @@
+//! Stable binary serialization for [`Context`] graphs
+//!
+//! A file contains the following items, in order:
+//!
+//! - The magic bytes `FDGT`
+//! - Format version, as a little-endian `u32` (currently [`VERSION`])
+//! - Variable names, as a count followed by that many strings
+//! - Nodes, as a count followed by that many nodes (see below)
+//! - Named roots, as a count followed by that many (string, node index) pairs
+//!
+//! Counts and indexes are unsigned LEB128 integers, and strings are a length
+//! followed by that many bytes of UTF-8.
+//!
+//! Each node is a one-byte tag followed by its payload:
+//!
+//! | Tag | Node     | Payload                                       |
+//! |-----|----------|-----------------------------------------------|
+//! | 0   | Input    | Index into the variable names (`X/Y/Z/W`)     |
+//! | 1   | Variable | Index into the variable names                 |
+//! | 2   | Constant | Little-endian `f64` bits                      |
+//! | 3   | Unary    | Opcode byte, child                            |
+//! | 4   | Binary   | Opcode byte, left-hand child, right-hand child |
+//! | 5   | Select   | Condition, left-hand child, right-hand child  |
+//!
+//! Children are written as backwards offsets from the current node, so they
+//! must always refer to an earlier node in the file (and usually fit in a
+//! single byte).  Opcodes are indexes into [`UNARY_OPCODES`] and
+//! [`BINARY_OPCODES`].
+use crate::{
+    context::{BinaryOpcode, Context, Node, Op, UnaryOpcode, VarNode},
+    Error,
+};
+use std::{
+    collections::BTreeMap,
+    io::{Read, Write},
+};
+
+/// Magic bytes at the start of every file
+const MAGIC: &[u8; 4] = b"FDGT";
+
+/// Current version of the binary format
+const VERSION: u32 = 1;
+
+/// Unary opcodes, in the order used for their binary encoding
+///
+/// New opcodes must only be added to the end of this list.
+const UNARY_OPCODES: [UnaryOpcode; 17] = [
+    UnaryOpcode::Neg,
+    UnaryOpcode::Abs,
+    UnaryOpcode::Recip,
+    UnaryOpcode::Sqrt,
+    UnaryOpcode::Square,
+    UnaryOpcode::Exp,
+    UnaryOpcode::Sin,
+    UnaryOpcode::Cos,
+    UnaryOpcode::Tan,
+    UnaryOpcode::Asin,
+    UnaryOpcode::Acos,
+    UnaryOpcode::Atan,
+    UnaryOpcode::Ln,
+    UnaryOpcode::Floor,
+    UnaryOpcode::Ceil,
+    UnaryOpcode::Round,
+    UnaryOpcode::Not,
+];
+
+/// Binary opcodes, in the order used for their binary encoding
+///
+/// New opcodes must only be added to the end of this list.
+const BINARY_OPCODES: [BinaryOpcode; 14] = [
+    BinaryOpcode::Add,
+    BinaryOpcode::Sub,
+    BinaryOpcode::Mul,
+    BinaryOpcode::Div,
+    BinaryOpcode::Min,
+    BinaryOpcode::Max,
+    BinaryOpcode::Atan2,
+    BinaryOpcode::Pow,
+    BinaryOpcode::Mod,
+    BinaryOpcode::Lt,
+    BinaryOpcode::Gt,
+    BinaryOpcode::Eq,
+    BinaryOpcode::And,
+    BinaryOpcode::Or,
+];
+
+const TAG_INPUT: u8 = 0;
+const TAG_VAR: u8 = 1;
+const TAG_CONST: u8 = 2;
+const TAG_UNARY: u8 = 3;
+const TAG_BINARY: u8 = 4;
+const TAG_SELECT: u8 = 5;
+
+impl Context {
+    /// Writes the subgraphs rooted at the given nodes in a compact binary
+    /// format, which can be read back with [`Context::read_binary`].
+    ///
+    /// Each root is stored with a name, which need not be unique.  Only nodes
+    /// reachable from the roots are written; constants are stored exactly, as
+    /// `f64` values.  The format begins with a version number, so that files
+    /// remain readable as it evolves.
+    ///
+    /// ```
+    /// # use fidget::context::Context;
+    /// let mut ctx = Context::new();
+    /// let x = ctx.x();
+    /// let a = ctx.var("a").unwrap();
+    /// let sum = ctx.add(x, a).unwrap();
+    ///
+    /// let mut data = vec![];
+    /// ctx.write_binary(&[("sum", sum)], &mut data).unwrap();
+    ///
+    /// let (ctx, roots) = Context::read_binary(data.as_slice()).unwrap();
+    /// assert_eq!(roots.len(), 1);
+    /// assert_eq!(roots[0].0, "sum");
+    /// assert_eq!(ctx.len(), 3);
+    /// ```
+    pub fn write_binary<W: Write>(
+        &self,
+        roots: &[(&str, Node)],
+        mut w: W,
+    ) -> Result<(), Error> {
+        // Find every reachable node.  Children are always created before their
+        // parents, so sorting by node index puts them in a valid order.
+        let mut nodes = BTreeMap::new();
+        let mut todo = roots.iter().map(|(_, n)| *n).collect::<Vec<_>>();
+        while let Some(node) = todo.pop() {
+            if nodes.contains_key(&node) {
+                continue;
+            }
+            let op = self.get_op(node).ok_or(Error::BadNode)?;
+            todo.extend(op.iter_children());
+            nodes.insert(node, 0);
+        }
+        for (i, v) in nodes.values_mut().enumerate() {
+            *v = i;
+        }
+
+        // Collect variable names, in order of first use
+        let mut vars: BTreeMap<VarNode, usize> = BTreeMap::new();
+        let mut var_names = vec![];
+        for node in nodes.keys() {
+            if let Op::Input(v) | Op::Var(v) = self.get_op(*node).unwrap() {
+                if !vars.contains_key(v) {
+                    vars.insert(*v, var_names.len());
+                    var_names.push(self.get_var_by_index(*v)?);
+                }
+            }
+        }
+
+        w.write_all(MAGIC)?;
+        w.write_all(&VERSION.to_le_bytes())?;
+
+        write_usize(&mut w, var_names.len())?;
+        for name in var_names {
+            write_str(&mut w, name)?;
+        }
+
+        write_usize(&mut w, nodes.len())?;
+        for (i, node) in nodes.keys().enumerate() {
+            let child = |c: &Node| i - nodes[c];
+            match self.get_op(*node).unwrap() {
+                Op::Input(v) => {
+                    w.write_all(&[TAG_INPUT])?;
+                    write_usize(&mut w, vars[v])?;
+                }
+                Op::Var(v) => {
+                    w.write_all(&[TAG_VAR])?;
+                    write_usize(&mut w, vars[v])?;
+                }
+                Op::Const(c) => {
+                    w.write_all(&[TAG_CONST])?;
+                    w.write_all(&c.0.to_le_bytes())?;
+                }
+                Op::Unary(op, a) => {
+                    let code = UNARY_OPCODES.iter().position(|o| o == op);
+                    w.write_all(&[TAG_UNARY, code.unwrap() as u8])?;
+                    write_usize(&mut w, child(a))?;
+                }
+                Op::Binary(op, a, b) => {
+                    let code = BINARY_OPCODES.iter().position(|o| o == op);
+                    w.write_all(&[TAG_BINARY, code.unwrap() as u8])?;
+                    write_usize(&mut w, child(a))?;
+                    write_usize(&mut w, child(b))?;
+                }
+                Op::Select(c, a, b) => {
+                    w.write_all(&[TAG_SELECT])?;
+                    write_usize(&mut w, child(c))?;
+                    write_usize(&mut w, child(a))?;
+                    write_usize(&mut w, child(b))?;
+                }
+            }
+        }
+
+        write_usize(&mut w, roots.len())?;
+        for (name, node) in roots {
+            write_str(&mut w, name)?;
+            write_usize(&mut w, nodes[node])?;
+        }
+        Ok(())
+    }
+
+    /// Reads a file written by [`Context::write_binary`], returning a new
+    /// context and its named roots (in the order that they were written).
+    ///
+    /// Nodes are rebuilt with the usual constructors (e.g. [`Context::add`]),
+    /// so the result is deduplicated and constant-folded.
+    ///
+    /// Malformed data returns `Error::BadBinary` (or `Error::IoError` if it is
+    /// truncated), and files from an unknown version of the format return
+    /// `Error::UnsupportedVersion`.
+    pub fn read_binary<R: Read>(
+        mut r: R,
+    ) -> Result<(Self, Vec<(String, Node)>), Error> {
+        let mut magic = [0u8; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(Error::BadBinary("invalid magic bytes".to_owned()));
+        }
+        let mut version = [0u8; 4];
+        r.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+
+        let var_count = read_usize(&mut r)?;
+        let mut var_names = vec![];
+        for _ in 0..var_count {
+            var_names.push(read_str(&mut r)?);
+        }
+        let var_name = |i: usize| {
+            var_names.get(i).ok_or_else(|| {
+                Error::BadBinary(format!("invalid variable index {i}"))
+            })
+        };
+
+        let mut ctx = Self::new();
+        let node_count = read_usize(&mut r)?;
+        let mut nodes: Vec<Node> = vec![];
+        for i in 0..node_count {
+            let child = |r: &mut R, nodes: &[Node]| {
+                let offset = read_usize(r)?;
+                match i.checked_sub(offset) {
+                    Some(c) if offset > 0 => Ok(nodes[c]),
+                    _ => Err(Error::BadBinary(format!(
+                        "invalid child offset {offset} at node {i}"
+                    ))),
+                }
+            };
+            let node = match read_u8(&mut r)? {
+                TAG_INPUT => match var_name(read_usize(&mut r)?)?.as_str() {
+                    "X" => ctx.x(),
+                    "Y" => ctx.y(),
+                    "Z" => ctx.z(),
+                    "W" => ctx.w(),
+                    v => return Err(Error::UnknownVariable(v.to_owned())),
+                },
+                TAG_VAR => ctx.var(var_name(read_usize(&mut r)?)?)?,
+                TAG_CONST => {
+                    let mut c = [0u8; 8];
+                    r.read_exact(&mut c)?;
+                    ctx.constant(f64::from_le_bytes(c))
+                }
+                TAG_UNARY => {
+                    let op = read_opcode(&mut r, &UNARY_OPCODES)?;
+                    let a = child(&mut r, &nodes)?;
+                    ctx.op_unary(a, op)?
+                }
+                TAG_BINARY => {
+                    let op = read_opcode(&mut r, &BINARY_OPCODES)?;
+                    let a = child(&mut r, &nodes)?;
+                    let b = child(&mut r, &nodes)?;
+                    ctx.build_binary(op, a, b)?
+                }
+                TAG_SELECT => {
+                    let c = child(&mut r, &nodes)?;
+                    let a = child(&mut r, &nodes)?;
+                    let b = child(&mut r, &nodes)?;
+                    ctx.select(c, a, b)?
+                }
+                t => {
+                    return Err(Error::BadBinary(format!(
+                        "invalid tag {t} at node {i}"
+                    )))
+                }
+            };
+            nodes.push(node);
+        }
+
+        let root_count = read_usize(&mut r)?;
+        let mut roots = vec![];
+        for _ in 0..root_count {
+            let name = read_str(&mut r)?;
+            let i = read_usize(&mut r)?;
+            let node = nodes.get(i).ok_or_else(|| {
+                Error::BadBinary(format!("invalid root index {i}"))
+            })?;
+            roots.push((name, *node));
+        }
+        Ok((ctx, roots))
+    }
+}
+
+fn write_usize<W: Write>(w: &mut W, mut v: usize) -> Result<(), Error> {
+    loop {
+        let byte = (v & 0x7F) as u8;
+        v >>= 7;
+        if v == 0 {
+            w.write_all(&[byte])?;
+            return Ok(());
+        }
+        w.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn write_str<W: Write>(w: &mut W, s: &str) -> Result<(), Error> {
+    write_usize(w, s.len())?;
+    w.write_all(s.as_bytes())?;
+    Ok(())
+}
+
+fn read_u8<R: Read>(r: &mut R) -> Result<u8, Error> {
+    let mut b = [0u8; 1];
+    r.read_exact(&mut b)?;
+    Ok(b[0])
+}
+
+fn read_usize<R: Read>(r: &mut R) -> Result<usize, Error> {
+    let mut out = 0usize;
+    for shift in (0..usize::BITS).step_by(7) {
+        let b = read_u8(r)?;
+        let v = ((b & 0x7F) as usize)
+            .checked_shl(shift)
+            .filter(|v| v >> shift == (b & 0x7F) as usize)
+            .ok_or_else(|| Error::BadBinary("integer overflow".to_owned()))?;
+        out |= v;
+        if b & 0x80 == 0 {
+            return Ok(out);
+        }
+    }
+    Err(Error::BadBinary("integer overflow".to_owned()))
+}
+
+fn read_str<R: Read>(r: &mut R) -> Result<String, Error> {
+    let len = read_usize(r)?;
+    let mut s = vec![];
+    r.take(len as u64).read_to_end(&mut s)?;
+    if s.len() != len {
+        let e = std::io::Error::from(std::io::ErrorKind::UnexpectedEof);
+        return Err(e.into());
+    }
+    String::from_utf8(s)
+        .map_err(|_| Error::BadBinary("invalid UTF-8 string".to_owned()))
+}
+
+fn read_opcode<R: Read, T: Copy>(r: &mut R, ops: &[T]) -> Result<T, Error> {
+    let code = read_u8(r)?;
+    ops.get(code as usize)
+        .cloned()
+        .ok_or_else(|| Error::BadBinary(format!("invalid opcode {code}")))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_prospero() {
+        const PROSPERO: &str = include_str!("../../../../models/prospero.vm");
+        let (ctx, root) = Context::from_text(PROSPERO.as_bytes()).unwrap();
+
+        let mut data = vec![];
+        ctx.write_binary(&[("prospero", root)], &mut data).unwrap();
+        let (out, roots) = Context::read_binary(data.as_slice()).unwrap();
+        assert_eq!(roots.len(), 1);
+        assert_eq!(roots[0].0, "prospero");
+        let r = roots[0].1;
+        assert_eq!(out.node_count(r).unwrap(), ctx.node_count(root).unwrap());
+
+        for (x, y) in [(0.1, 0.2), (-0.5, 0.7), (0.9, -0.3), (-0.2, -0.8)] {
+            let a = ctx.eval_xyz(root, x, y, 0.0).unwrap();
+            let b = out.eval_xyz(r, x, y, 0.0).unwrap();
+            assert_eq!(a, b);
+        }
+
+        // Writing the result again should give the same bytes
+        let mut again = vec![];
+        out.write_binary(&[("prospero", r)], &mut again).unwrap();
+        assert_eq!(data, again);
+    }
+
+    #[test]
+    fn test_round_trip_multi() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let w = ctx.w();
+        let a = ctx.var("a").unwrap();
+        let c = ctx.constant(0.1);
+        let sum = ctx.add(x, c).unwrap();
+        let sum = ctx.mul(sum, a).unwrap();
+        let lt = ctx.lt(x, w).unwrap();
+        let sel = ctx.select(lt, sum, c).unwrap();
+        let sqrt = ctx.sqrt(sel).unwrap();
+
+        let mut data = vec![];
+        ctx.write_binary(&[("s", sqrt), ("c", c), ("s2", sqrt)], &mut data)
+            .unwrap();
+        let (out, roots) = Context::read_binary(data.as_slice()).unwrap();
+
+        let names = roots.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["s", "c", "s2"]);
+        assert_eq!(roots[0].1, roots[2].1);
+        assert_eq!(out.const_value(roots[1].1).unwrap(), Some(0.1));
+        assert_eq!(out.len(), ctx.len());
+
+        let vars = [("X", 0.5), ("W", 1.0), ("a", 3.0)]
+            .into_iter()
+            .map(|(k, v)| (k.to_owned(), v))
+            .collect();
+        assert_eq!(
+            out.eval(roots[0].1, &vars).unwrap(),
+            ctx.eval(sqrt, &vars).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_bad_binary() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let s = ctx.sin(x).unwrap();
+        let mut data = vec![];
+        ctx.write_binary(&[("s", s)], &mut data).unwrap();
+
+        // Truncated files are reported as I/O errors
+        for i in 0..data.len() {
+            assert!(matches!(
+                Context::read_binary(&data[..i]),
+                Err(Error::IoError(..))
+            ));
+        }
+
+        let mut bad = data.clone();
+        bad[0] = b'X';
+        assert!(matches!(
+            Context::read_binary(bad.as_slice()),
+            Err(Error::BadBinary(..))
+        ));
+
+        let mut bad = data.clone();
+        bad[4] = 2;
+        assert!(matches!(
+            Context::read_binary(bad.as_slice()),
+            Err(Error::UnsupportedVersion(2))
+        ));
+
+        // Point the `sin` node's child at itself
+        let mut bad = data.clone();
+        let n = bad.len();
+        assert_eq!(bad[n - 7..n - 4], [TAG_UNARY, 6, 1]);
+        bad[n - 5] = 0;
+        assert!(matches!(
+            Context::read_binary(bad.as_slice()),
+            Err(Error::BadBinary(..))
+        ));
+
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        assert!(matches!(
+            ctx.write_binary(&[("x", Node(100))], &mut vec![]),
+            Err(Error::BadNode)
+        ));
+        assert!(ctx.write_binary(&[("x", x)], &mut vec![]).is_ok());
+    }
+}
@@ -0,0 +1,913 @@
+//! Parsing and printing infix math expressions (e.g. `sqrt(x*x + y*y) - 1`)
+use crate::{
+    context::{BinaryOpcode, Context, Node, Op, UnaryOpcode},
+    Error,
+};
+use std::{borrow::Cow, collections::BTreeMap, fmt::Write};
+
+/// Binary operators, from lowest to highest precedence
+///
+/// All of these operators are left-associative.  Exponentiation (`^`) binds
+/// more tightly than any of them (and than unary `-` and `!`), and is
+/// right-associative.
+const BINARY_OPERATORS: [&[(&str, BinaryOpcode)]; 5] = [
+    &[("||", BinaryOpcode::Or)],
+    &[("&&", BinaryOpcode::And)],
+    &[
+        ("==", BinaryOpcode::Eq),
+        ("<", BinaryOpcode::Lt),
+        (">", BinaryOpcode::Gt),
+    ],
+    &[("+", BinaryOpcode::Add), ("-", BinaryOpcode::Sub)],
+    &[
+        ("*", BinaryOpcode::Mul),
+        ("/", BinaryOpcode::Div),
+        ("%", BinaryOpcode::Mod),
+    ],
+];
+
+/// Precedence of `*`, `/`, and `%`, which is used when printing constants
+const MUL: usize = 4;
+/// Precedence of unary `-` and `!`
+const UNARY: usize = BINARY_OPERATORS.len();
+/// Precedence of exponentiation (`^`)
+const POWER: usize = UNARY + 1;
+/// Precedence of numbers, variables, and function calls
+const ATOM: usize = POWER + 1;
+
+/// Maximum nesting depth of parentheses, function calls, and unary operators
+///
+/// The parser is recursive, so this prevents deeply nested input from
+/// overflowing the stack.
+const MAX_DEPTH: usize = 1024;
+
+/// Punctuation, with multi-character symbols first so that they take priority
+const PUNCTUATION: [&str; 17] = [
+    "||", "&&", "==", "<", ">", "+", "-", "*", "/", "%", "^", "!", "(", ")",
+    ",", "=", ";",
+];
+
+impl Context {
+    /// Parses an infix math expression, returning its root node
+    ///
+    /// ```
+    /// # use fidget::context::Context;
+    /// let mut ctx = Context::new();
+    /// let circle = ctx.parse_expr("sqrt(x*x + y*y) - 1").unwrap();
+    /// assert_eq!(ctx.eval_xyz(circle, 3.0, 4.0, 0.0).unwrap(), 4.0);
+    /// ```
+    ///
+    /// The expression may use
+    /// - Numbers (e.g. `1`, `0.5`, `1e-3`)
+    /// - The input axes `x`, `y`, `z`, and `w`; any other identifier becomes
+    ///   a variable (see [`Context::var`])
+    /// - The operators `+ - * / % ^ < > == && || !`, with conventional
+    ///   precedence (`^` is exponentiation and binds tighter than unary `-`)
+    /// - Function calls for every [`UnaryOpcode`] and [`BinaryOpcode`] by
+    ///   name (e.g. `abs(x)`, `atan2(y, x)`), plus `log2(a)` (see
+    ///   [`Context::log2`]) and `select(cond, a, b)` (see [`Context::select`])
+    ///
+    /// The expression may be preceded by `let name = expr;` bindings, which
+    /// are visible to later bindings and shadow variables of the same name:
+    /// ```
+    /// # use fidget::context::Context;
+    /// let mut ctx = Context::new();
+    /// let txt = "let r2 = x^2 + y^2; max(r2 - 1, -z)";
+    /// let node = ctx.parse_expr(txt).unwrap();
+    /// assert_eq!(ctx.eval_xyz(node, 1.0, 1.0, -3.0).unwrap(), 3.0);
+    /// ```
+    ///
+    /// Malformed input returns an [`Error::TextError`], which wraps the
+    /// underlying error with its (1-indexed) line and column.  This includes
+    /// [`Error::TooDeep`] if parentheses, function calls, and unary operators
+    /// are nested more than 1024 levels deep.
+    pub fn parse_expr(&mut self, text: &str) -> Result<Node, Error> {
+        let mut parser = Parser::new(self, text)?;
+        while parser.eat("let").is_some() {
+            let name = parser.ident("variable name")?;
+            parser.expect("=", "`=`")?;
+            let node = parser.expr()?;
+            parser.expect(";", "`;`")?;
+            parser.bindings.insert(name, node);
+        }
+        let out = parser.expr()?;
+        match parser.peek() {
+            Some(t) => Err(t.unexpected()),
+            None => Ok(out),
+        }
+    }
+
+    /// Writes the subgraph rooted at `root` as an infix expression
+    ///
+    /// The output uses the syntax read by [`Context::parse_expr`], with only
+    /// as many parentheses as are needed to preserve its structure:
+    /// ```
+    /// # use fidget::context::Context;
+    /// let mut ctx = Context::new();
+    /// let node = ctx.parse_expr("((x * y) + (y * z)) - (1 / z)").unwrap();
+    /// assert_eq!(ctx.to_infix(node).unwrap(), "x * y + y * z - 1 / z");
+    /// ```
+    ///
+    /// Shared subexpressions are written out in full at each use, so the
+    /// output can be exponentially larger than the graph; see
+    /// [`Context::to_infix_with_bindings`] for a linear-size alternative.
+    ///
+    /// Variables with names that aren't valid identifiers in the expression
+    /// syntax return [`Error::BadVarName`].
+    pub fn to_infix(&self, root: Node) -> Result<String, Error> {
+        self.write_infix(root, false)
+    }
+
+    /// Writes the subgraph rooted at `root` as an infix expression, binding
+    /// shared subexpressions to variables
+    ///
+    /// Each node that is used more than once is written a single time, as a
+    /// `let` binding, so the output size is linear in the size of the graph.
+    /// ```
+    /// # use fidget::context::Context;
+    /// let mut ctx = Context::new();
+    /// let node = ctx.parse_expr("sin(x + y) * cos(x + y)").unwrap();
+    /// assert_eq!(
+    ///     ctx.to_infix_with_bindings(node).unwrap(),
+    ///     "let _0 = x + y;\nsin(_0) * cos(_0)"
+    /// );
+    /// ```
+    pub fn to_infix_with_bindings(&self, root: Node) -> Result<String, Error> {
+        self.write_infix(root, true)
+    }
+
+    fn write_infix(&self, root: Node, bind: bool) -> Result<String, Error> {
+        self.check_node(root)?;
+
+        // Count how many times each node is used within the subgraph
+        let mut uses: BTreeMap<Node, usize> = BTreeMap::new();
+        let mut todo = vec![root];
+        while let Some(node) = todo.pop() {
+            let op = self.get_op(node).unwrap();
+            if uses.insert(node, 0).is_none() {
+                todo.extend(op.iter_children());
+            }
+        }
+        for node in uses.keys().cloned().collect::<Vec<_>>() {
+            for c in self.get_op(node).unwrap().iter_children() {
+                *uses.get_mut(&c).unwrap() += 1;
+            }
+        }
+
+        // Pick a prefix for bindings that can't collide with variable names
+        let mut prefix = String::from("_");
+        for node in uses.keys() {
+            if let Op::Var(v) = self.get_op(*node).unwrap() {
+                let name = self.get_var_by_index(*v)?;
+                while name.starts_with(&prefix) {
+                    prefix.push('_');
+                }
+            }
+        }
+
+        // Children are always created before their parents, so iterating in
+        // order writes each binding after the bindings that it uses.
+        let mut names: BTreeMap<Node, String> = BTreeMap::new();
+        let mut out = String::new();
+        for (&node, &count) in &uses {
+            if bind
+                && count > 1
+                && !matches!(
+                    self.get_op(node).unwrap(),
+                    Op::Input(..) | Op::Var(..) | Op::Const(..)
+                )
+            {
+                let name = format!("{prefix}{}", names.len());
+                write!(out, "let {name} = ").unwrap();
+                self.write_infix_node(node, &names, &mut out)?;
+                out += ";\n";
+                names.insert(node, name);
+            }
+        }
+        self.write_infix_node(root, &names, &mut out)?;
+        Ok(out)
+    }
+
+    /// Appends the text for a single node to `out`
+    ///
+    /// Nodes in `names` are written as their binding's name.  This uses an
+    /// explicit stack (rather than recursion), so that deep graphs can't
+    /// overflow the call stack.
+    fn write_infix_node(
+        &self,
+        root: Node,
+        names: &BTreeMap<Node, String>,
+        out: &mut String,
+    ) -> Result<(), Error> {
+        let mut todo = vec![Piece::Node(root, 0)];
+        while let Some(piece) = todo.pop() {
+            let (node, prec) = match piece {
+                Piece::Text(text) => {
+                    *out += &text;
+                    continue;
+                }
+                Piece::Node(node, prec) => (node, prec),
+            };
+            if let Some(name) = names.get(&node) {
+                *out += name;
+                continue;
+            }
+            let (p, pieces) = self.infix_pieces(node)?;
+            if p < prec {
+                out.push('(');
+                todo.push(Piece::Text(")".into()));
+            }
+            todo.extend(pieces.into_iter().rev());
+        }
+        Ok(())
+    }
+
+    /// Splits a node into text and children, returning its precedence
+    fn infix_pieces(&self, node: Node) -> Result<(usize, Vec<Piece>), Error> {
+        use Piece::{Node as N, Text as T};
+        let out = match *self.get_op(node).unwrap() {
+            Op::Input(v) => {
+                let name = match self.get_var_by_index(v)? {
+                    "X" => "x",
+                    "Y" => "y",
+                    "Z" => "z",
+                    "W" => "w",
+                    name => return Err(Error::BadVarName(name.to_owned())),
+                };
+                (ATOM, vec![T(name.into())])
+            }
+            Op::Var(v) => {
+                let name = self.get_var_by_index(v)?;
+                let mut chars = name.chars();
+                if !chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+                    || !chars.all(|c| c.is_alphanumeric() || c == '_')
+                    || matches!(name, "x" | "y" | "z" | "w" | "let")
+                {
+                    return Err(Error::BadVarName(name.to_owned()));
+                }
+                (ATOM, vec![T(name.to_owned().into())])
+            }
+            Op::Const(c) => {
+                let c = c.0;
+                if c.is_nan() {
+                    (ATOM, vec![T("sqrt(-1)".into())])
+                } else if c.is_infinite() {
+                    let s = if c < 0.0 { "-1 / 0" } else { "1 / 0" };
+                    (MUL, vec![T(s.into())])
+                } else if c.is_sign_negative() {
+                    (UNARY, vec![T(format!("-{}", -c).into())])
+                } else {
+                    (ATOM, vec![T(format!("{c}").into())])
+                }
+            }
+            Op::Unary(UnaryOpcode::Neg, a) => {
+                (UNARY, vec![T("-".into()), N(a, UNARY)])
+            }
+            Op::Unary(UnaryOpcode::Not, a) => {
+                (UNARY, vec![T("!".into()), N(a, UNARY)])
+            }
+            Op::Unary(op, a) => (
+                ATOM,
+                vec![
+                    T(op.name().into()),
+                    T("(".into()),
+                    N(a, 0),
+                    T(")".into()),
+                ],
+            ),
+            Op::Binary(BinaryOpcode::Pow, a, b) => {
+                (POWER, vec![N(a, ATOM), T("^".into()), N(b, UNARY)])
+            }
+            Op::Binary(op, a, b) => {
+                match (0..).zip(BINARY_OPERATORS).find_map(|(i, ops)| {
+                    ops.iter().find(|(_, o)| *o == op).map(|(s, _)| (i, s))
+                }) {
+                    Some((prec, s)) => (
+                        prec,
+                        vec![
+                            N(a, prec),
+                            T(format!(" {s} ").into()),
+                            N(b, prec + 1),
+                        ],
+                    ),
+                    None => (
+                        ATOM,
+                        vec![
+                            T(op.name().into()),
+                            T("(".into()),
+                            N(a, 0),
+                            T(", ".into()),
+                            N(b, 0),
+                            T(")".into()),
+                        ],
+                    ),
+                }
+            }
+            Op::Select(c, a, b) => (
+                ATOM,
+                vec![
+                    T("select(".into()),
+                    N(c, 0),
+                    T(", ".into()),
+                    N(a, 0),
+                    T(", ".into()),
+                    N(b, 0),
+                    T(")".into()),
+                ],
+            ),
+        };
+        Ok(out)
+    }
+}
+
+/// Part of a node's infix text, used when printing
+enum Piece {
+    /// Literal text
+    Text(Cow<'static, str>),
+    /// Child node, which is parenthesized if its precedence is below the
+    /// given value
+    Node(Node, usize),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum TokenKind {
+    Number(f64),
+    Ident,
+    Punct,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+    line: usize,
+    column: usize,
+}
+
+impl Token<'_> {
+    fn error(&self, error: Error) -> Error {
+        Error::TextError {
+            line: self.line,
+            column: self.column,
+            error: Box::new(error),
+        }
+    }
+
+    fn unexpected(&self) -> Error {
+        self.error(Error::UnexpectedToken(self.text.to_owned()))
+    }
+}
+
+/// Splits the input text into tokens
+fn tokenize(text: &str) -> Result<(Vec<Token<'_>>, usize, usize), Error> {
+    let mut out = vec![];
+    let mut line = 1;
+    let mut column = 1;
+    let mut iter = text.char_indices().peekable();
+    while let Some(&(i, c)) = iter.peek() {
+        let start = column;
+        let kind = if c == '\n' {
+            iter.next();
+            line += 1;
+            column = 1;
+            continue;
+        } else if c.is_whitespace() {
+            iter.next();
+            column += 1;
+            continue;
+        } else if c.is_ascii_digit() || c == '.' {
+            // Accept digits and dots, plus an exponent with an optional sign
+            let mut prev = c;
+            while let Some(&(_, c)) = iter.peek() {
+                if c.is_ascii_alphanumeric()
+                    || c == '.'
+                    || (matches!(c, '+' | '-') && matches!(prev, 'e' | 'E'))
+                {
+                    iter.next();
+                    column += 1;
+                    prev = c;
+                } else {
+                    break;
+                }
+            }
+            None
+        } else if c.is_alphabetic() || c == '_' {
+            while let Some(&(_, c)) = iter.peek() {
+                if c.is_alphanumeric() || c == '_' {
+                    iter.next();
+                    column += 1;
+                } else {
+                    break;
+                }
+            }
+            Some(TokenKind::Ident)
+        } else if let Some(p) =
+            PUNCTUATION.iter().find(|p| text[i..].starts_with(*p))
+        {
+            for _ in 0..p.len() {
+                iter.next();
+                column += 1;
+            }
+            Some(TokenKind::Punct)
+        } else {
+            return Err(Error::TextError {
+                line,
+                column,
+                error: Box::new(Error::UnexpectedToken(c.to_string())),
+            });
+        };
+        let end = iter.peek().map(|(j, _)| *j).unwrap_or(text.len());
+        let mut t = Token {
+            kind: kind.unwrap_or(TokenKind::Number(0.0)),
+            text: &text[i..end],
+            line,
+            column: start,
+        };
+        if kind.is_none() {
+            let v = t
+                .text
+                .parse()
+                .map_err(|_| t.error(Error::BadNumber(t.text.to_owned())))?;
+            t.kind = TokenKind::Number(v);
+        }
+        out.push(t);
+    }
+    Ok((out, line, column))
+}
+
+/// Recursive-descent parser, which builds nodes as it goes
+struct Parser<'a, 'c> {
+    ctx: &'c mut Context,
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+    /// Position just past the end of the input, used for missing tokens
+    end: (usize, usize),
+    bindings: BTreeMap<&'a str, Node>,
+    /// Current nesting depth, which is limited to [`MAX_DEPTH`]
+    depth: usize,
+}
+
+impl<'a, 'c> Parser<'a, 'c> {
+    fn new(ctx: &'c mut Context, text: &'a str) -> Result<Self, Error> {
+        let (tokens, line, column) = tokenize(text)?;
+        Ok(Self {
+            ctx,
+            tokens,
+            pos: 0,
+            end: (line, column),
+            bindings: BTreeMap::new(),
+            depth: 0,
+        })
+    }
+
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).cloned()
+    }
+
+    /// Returns an error for a missing token at the end of the input
+    fn missing(&self, what: &'static str) -> Error {
+        Error::TextError {
+            line: self.end.0,
+            column: self.end.1,
+            error: Box::new(Error::ExpectedToken(what)),
+        }
+    }
+
+    /// Consumes the next token if it's the given punctuation or keyword
+    fn eat(&mut self, text: &str) -> Option<Token<'a>> {
+        let t = self.peek().filter(|t| t.text == text)?;
+        self.pos += 1;
+        Some(t)
+    }
+
+    /// Returns an error at the next token (or the end of the input)
+    fn error_here(&self, error: Error) -> Error {
+        match self.peek() {
+            Some(t) => t.error(error),
+            None => Error::TextError {
+                line: self.end.0,
+                column: self.end.1,
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// Consumes the given punctuation, returning an error if it's not found
+    fn expect(&mut self, text: &str, what: &'static str) -> Result<(), Error> {
+        if self.eat(text).is_some() {
+            Ok(())
+        } else {
+            Err(self.error_here(Error::ExpectedToken(what)))
+        }
+    }
+
+    /// Consumes an identifier, returning an error if it's not found
+    fn ident(&mut self, what: &'static str) -> Result<&'a str, Error> {
+        match self.peek() {
+            Some(t) if t.kind == TokenKind::Ident && t.text != "let" => {
+                self.pos += 1;
+                Ok(t.text)
+            }
+            Some(t) => Err(t.error(Error::ExpectedToken(what))),
+            None => Err(self.missing(what)),
+        }
+    }
+
+    /// Parses a chain of binary operators
+    ///
+    /// This keeps a stack of pending operators, rather than recursing once per
+    /// precedence level, so that each level of nesting uses less of the call
+    /// stack.
+    fn expr(&mut self) -> Result<Node, Error> {
+        let mut args = vec![self.unary()?];
+        let mut ops: Vec<(Token<'a>, usize, BinaryOpcode)> = vec![];
+        while let Some((t, prec, op)) = self.peek().and_then(|t| {
+            (0..).zip(BINARY_OPERATORS).find_map(|(prec, ops)| {
+                ops.iter()
+                    .find(|(s, _)| t.text == *s)
+                    .map(|(_, op)| (t, prec, *op))
+            })
+        }) {
+            self.pos += 1;
+            // All binary operators are left-associative, so pending operators
+            // with the same (or higher) precedence are applied first
+            while ops.last().is_some_and(|(_, p, _)| *p >= prec) {
+                self.apply(&mut args, &mut ops)?;
+            }
+            ops.push((t, prec, op));
+            args.push(self.unary()?);
+        }
+        while !ops.is_empty() {
+            self.apply(&mut args, &mut ops)?;
+        }
+        Ok(args.pop().unwrap())
+    }
+
+    /// Applies the last pending binary operator to the last two arguments
+    fn apply(
+        &mut self,
+        args: &mut Vec<Node>,
+        ops: &mut Vec<(Token<'a>, usize, BinaryOpcode)>,
+    ) -> Result<(), Error> {
+        let (t, _, op) = ops.pop().unwrap();
+        let rhs = args.pop().unwrap();
+        let lhs = args.pop().unwrap();
+        let out = self
+            .ctx
+            .build_binary(op, lhs, rhs)
+            .map_err(|e| t.error(e))?;
+        args.push(out);
+        Ok(())
+    }
+
+    /// Parses a unary expression
+    ///
+    /// Every level of nesting passes through this function, so it's where the
+    /// nesting depth is checked.
+    fn unary(&mut self) -> Result<Node, Error> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error_here(Error::TooDeep(MAX_DEPTH)));
+        }
+        self.depth += 1;
+        let out = self.unary_inner();
+        self.depth -= 1;
+        out
+    }
+
+    fn unary_inner(&mut self) -> Result<Node, Error> {
+        let op = if self.eat("-").is_some() {
+            UnaryOpcode::Neg
+        } else if self.eat("!").is_some() {
+            UnaryOpcode::Not
+        } else {
+            return self.power();
+        };
+        let a = self.unary()?;
+        self.ctx.op_unary(a, op)
+    }
+
+    fn power(&mut self) -> Result<Node, Error> {
+        let base = self.atom()?;
+        match self.eat("^") {
+            Some(t) => {
+                // Recurse through `unary` so that `x^-2` and `x^y^z` work
+                let exp = self.unary()?;
+                self.ctx.pow(base, exp).map_err(|e| t.error(e))
+            }
+            None => Ok(base),
+        }
+    }
+
+    fn atom(&mut self) -> Result<Node, Error> {
+        let Some(t) = self.peek() else {
+            return Err(self.missing("expression"));
+        };
+        match t.kind {
+            TokenKind::Number(v) => {
+                self.pos += 1;
+                Ok(self.ctx.constant(v))
+            }
+            TokenKind::Ident if t.text != "let" => {
+                self.pos += 1;
+                if self.eat("(").is_some() {
+                    self.call(t)
+                } else {
+                    self.variable(t)
+                }
+            }
+            TokenKind::Punct if t.text == "(" => {
+                self.pos += 1;
+                let out = self.expr()?;
+                self.expect(")", "`)`")?;
+                Ok(out)
+            }
+            _ => Err(t.unexpected()),
+        }
+    }
+
+    fn variable(&mut self, t: Token<'a>) -> Result<Node, Error> {
+        if let Some(n) = self.bindings.get(t.text) {
+            return Ok(*n);
+        }
+        match t.text {
+            "x" => Ok(self.ctx.x()),
+            "y" => Ok(self.ctx.y()),
+            "z" => Ok(self.ctx.z()),
+            "w" => Ok(self.ctx.w()),
+            name => self.ctx.var(name).map_err(|e| t.error(e)),
+        }
+    }
+
+    /// Parses a function call, after the opening parenthesis
+    fn call(&mut self, t: Token<'a>) -> Result<Node, Error> {
+        let mut args = vec![];
+        if self.eat(")").is_none() {
+            loop {
+                args.push(self.expr()?);
+                if self.eat(")").is_some() {
+                    break;
+                }
+                self.expect(",", "`,` or `)`")?;
+            }
+        }
+        let name = t.text;
+        let ops = (UnaryOpcode::from_name(name), BinaryOpcode::from_name(name));
+        let arity = match ops {
+            (Some(..), _) => 1,
+            (_, Some(..)) => 2,
+            _ if name == "log2" => 1,
+            _ if name == "select" => 3,
+            _ => return Err(t.error(Error::UnknownOpcode(name.to_owned()))),
+        };
+        if args.len() != arity {
+            let e = Error::BadArgCount(name.to_owned(), arity, args.len());
+            return Err(t.error(e));
+        }
+        let out = match ops {
+            (Some(op), _) => self.ctx.op_unary(args[0], op),
+            (_, Some(op)) => self.ctx.build_binary(op, args[0], args[1]),
+            _ if name == "log2" => self.ctx.log2(args[0]),
+            _ => self.ctx.select(args[0], args[1], args[2]),
+        };
+        out.map_err(|e| t.error(e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn eval(txt: &str, x: f64, y: f64, z: f64) -> f64 {
+        let mut ctx = Context::new();
+        let node = ctx.parse_expr(txt).unwrap();
+        ctx.eval_xyz(node, x, y, z).unwrap()
+    }
+
+    #[test]
+    fn test_precedence() {
+        assert_eq!(eval("1 + 2 * 3", 0.0, 0.0, 0.0), 7.0);
+        assert_eq!(eval("(1 + 2) * 3", 0.0, 0.0, 0.0), 9.0);
+        assert_eq!(eval("x - y - z", 1.0, 2.0, 3.0), -4.0);
+        assert_eq!(eval("x / y / z", 8.0, 2.0, 2.0), 2.0);
+        assert_eq!(eval("-x^2", 3.0, 0.0, 0.0), -9.0);
+        assert_eq!(eval("2^-x", 1.0, 0.0, 0.0), 0.5);
+        assert_eq!(eval("2^3^2", 0.0, 0.0, 0.0), 512.0);
+        assert_eq!(eval("--x", 3.0, 0.0, 0.0), 3.0);
+        assert_eq!(eval("7 % 4 * 2", 0.0, 0.0, 0.0), 6.0);
+        assert_eq!(eval("x < y == 1", 1.0, 2.0, 0.0), 1.0);
+        assert_eq!(eval("x + 1 > y * 2", 1.0, 2.0, 0.0), 0.0);
+        assert_eq!(eval("x < 0 || y > 0 && z > 0", 1.0, 1.0, -1.0), 0.0);
+        assert_eq!(eval("!(x > 0)", 1.0, 0.0, 0.0), 0.0);
+        assert_eq!(eval("1.5e1 + .5 + 2E-1", 0.0, 0.0, 0.0), 15.7);
+    }
+
+    #[test]
+    fn test_functions() {
+        assert_eq!(eval("sqrt(x*x + y*y) - 1", 3.0, 4.0, 0.0), 4.0);
+        assert_eq!(eval("max(x, min(y, z))", 0.0, 2.0, 1.0), 1.0);
+        assert_eq!(eval("recip(square(x))", 2.0, 0.0, 0.0), 0.25);
+        assert_eq!(eval("abs(neg(x))", 2.0, 0.0, 0.0), 2.0);
+        assert_eq!(eval("atan2(y, x)", 1.0, 0.0, 0.0), 0.0);
+        assert_eq!(eval("select(x, y, z)", -1.0, 2.0, 3.0), 2.0);
+        assert_eq!(eval("floor(x) + ceil(x)", 1.5, 0.0, 0.0), 3.0);
+        assert_eq!(eval("pow(x, 3) + mod(y, 2)", 2.0, 3.0, 0.0), 9.0);
+        assert_eq!(eval("log2(x) + ln(y)", 1024.0, 1.0, 0.0), 10.0);
+    }
+
+    #[test]
+    fn test_vars_and_bindings() {
+        let mut ctx = Context::new();
+        let node = ctx
+            .parse_expr(
+                "let r = sqrt(x^2 + y^2);
+                 let r = r - radius;
+                 max(r, abs(z + w) - height)",
+            )
+            .unwrap();
+        let vars = [("X", 3.0), ("Y", 4.0), ("Z", 0.0), ("W", 0.0)]
+            .into_iter()
+            .chain([("radius", 1.0), ("height", 2.0)])
+            .map(|(a, b)| (a.to_string(), b))
+            .collect();
+        assert_eq!(ctx.eval(node, &vars).unwrap(), 4.0);
+
+        // Parsing into the same context reuses existing nodes
+        let len = ctx.len();
+        let again = ctx.parse_expr("radius").unwrap();
+        assert_eq!(ctx.len(), len);
+        assert_eq!(ctx.var_name(again).unwrap(), Some("radius"));
+    }
+
+    /// Parses the text, expecting an error at the given position
+    fn check_error(txt: &str, line: usize, column: usize) -> Error {
+        let mut ctx = Context::new();
+        match ctx.parse_expr(txt) {
+            Err(Error::TextError {
+                line: a,
+                column: b,
+                error,
+            }) => {
+                assert_eq!((a, b), (line, column), "bad position for {error}");
+                *error
+            }
+            Err(e) => panic!("unexpected error {e}"),
+            Ok(..) => panic!("expected an error"),
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let e = check_error("x + ", 1, 5);
+        assert!(matches!(e, Error::ExpectedToken("expression")));
+
+        let e = check_error("(x + y", 1, 7);
+        assert!(matches!(e, Error::ExpectedToken("`)`")));
+
+        let e = check_error("x y", 1, 3);
+        assert!(matches!(e, Error::UnexpectedToken(s) if s == "y"));
+
+        let e = check_error("x + $", 1, 5);
+        assert!(matches!(e, Error::UnexpectedToken(s) if s == "$"));
+
+        let e = check_error("let a = 1;\n  frob(a)", 2, 3);
+        assert!(matches!(e, Error::UnknownOpcode(s) if s == "frob"));
+
+        let e = check_error("1 +\n atan2(x)", 2, 2);
+        assert!(matches!(e, Error::BadArgCount(s, 2, 1) if s == "atan2"));
+
+        let e = check_error("2 * 1.2.3", 1, 5);
+        assert!(matches!(e, Error::BadNumber(s) if s == "1.2.3"));
+
+        let e = check_error("let a = 1 a", 1, 11);
+        assert!(matches!(e, Error::ExpectedToken("`;`")));
+
+        let e = check_error("let = 1; x", 1, 5);
+        assert!(matches!(e, Error::ExpectedToken("variable name")));
+
+        let e = check_error("max(x,, y)", 1, 7);
+        assert!(matches!(e, Error::UnexpectedToken(s) if s == ","));
+
+        let e = check_error("x + X", 1, 5);
+        assert!(matches!(e, Error::ReservedName));
+    }
+
+    #[test]
+    fn test_parse_depth() {
+        let nested = |n| format!("{}x{}", "(".repeat(n), ")".repeat(n));
+        let mut ctx = Context::new();
+        assert!(ctx.parse_expr(&nested(MAX_DEPTH - 1)).is_ok());
+
+        let e = check_error(&nested(MAX_DEPTH), 1, MAX_DEPTH + 1);
+        assert!(matches!(e, Error::TooDeep(MAX_DEPTH)));
+        let e = check_error(&nested(10_000), 1, MAX_DEPTH + 1);
+        assert!(matches!(e, Error::TooDeep(MAX_DEPTH)));
+        let e = check_error(&"-".repeat(10_000), 1, MAX_DEPTH + 1);
+        assert!(matches!(e, Error::TooDeep(MAX_DEPTH)));
+        let e = check_error(&"sin(".repeat(10_000), 1, 4 * MAX_DEPTH + 1);
+        assert!(matches!(e, Error::TooDeep(MAX_DEPTH)));
+    }
+
+    /// Parses each expression, then checks that it survives a round trip
+    /// through both infix printers
+    fn check_infix(txt: &str) -> String {
+        let mut ctx = Context::new();
+        let node = ctx.parse_expr(txt).unwrap();
+        let out = ctx.to_infix(node).unwrap();
+        let shared = ctx.to_infix_with_bindings(node).unwrap();
+        for t in [&out, &shared] {
+            let mut next = Context::new();
+            let n = next.parse_expr(t).unwrap();
+            for (x, y, z) in [(0.5, -1.25, 2.0), (-3.0, 0.75, 0.1)] {
+                let a = ctx.eval_xyz(node, x, y, z).unwrap();
+                let b = next.eval_xyz(n, x, y, z).unwrap();
+                assert!(a == b || (a.is_nan() && b.is_nan()), "{txt} => {t}");
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_to_infix() {
+        assert_eq!(check_infix("(x + y) * z"), "(x + y) * z");
+        assert_eq!(check_infix("x - (y - z)"), "x - (y - z)");
+        assert_eq!(check_infix("(x - y) - z"), "x - y - z");
+        assert_eq!(check_infix("-(x^3)"), "-x^3");
+        assert_eq!(check_infix("(-x)^3"), "(-x)^3");
+        assert_eq!(check_infix("x^(y^z)"), "x^y^z");
+        assert_eq!(check_infix("(x^y)^z"), "(x^y)^z");
+        assert_eq!(check_infix("x^-y"), "x^-y");
+        assert_eq!(check_infix("x * -2.5"), "x * -2.5");
+        assert_eq!(check_infix("(-2)^x"), "(-2)^x");
+        assert_eq!(check_infix("!(x < y)"), "!(x < y)");
+        assert_eq!(check_infix("x < y == (y > z)"), "x < y == (y > z)");
+        assert_eq!(check_infix("x || y && z"), "x || y && z");
+        assert_eq!(check_infix("(x || y) && z"), "(x || y) && z");
+        assert_eq!(check_infix("x % (y * z)"), "x % (y * z)");
+        assert_eq!(
+            check_infix("atan2(y, x + 1) + select(x, y, -z) * recip(y)"),
+            "atan2(y, x + 1) + select(x, y, -z) * recip(y)"
+        );
+        assert_eq!(check_infix("x + 1 / 0"), "x + 1 / 0");
+        assert_eq!(check_infix("x^sqrt(-1)"), "x^sqrt(-1)");
+        assert_eq!(check_infix("0.1 + 0.2 + x"), "0.30000000000000004 + x");
+    }
+
+    #[test]
+    fn test_to_infix_bindings() {
+        let mut ctx = Context::new();
+        let node = ctx
+            .parse_expr(
+                "let a = x + _0; let b = a * sin(a); max(b, -b) + sin(z)",
+            )
+            .unwrap();
+        assert_eq!(
+            ctx.to_infix_with_bindings(node).unwrap(),
+            "let __0 = x + _0;\n\
+             let __1 = __0 * sin(__0);\n\
+             max(__1, -__1) + sin(z)"
+        );
+        assert_eq!(
+            ctx.to_infix(node).unwrap(),
+            "max((x + _0) * sin(x + _0), -((x + _0) * sin(x + _0))) + sin(z)"
+        );
+
+        let bad = ctx.var("let").unwrap();
+        assert!(matches!(ctx.to_infix(bad), Err(Error::BadVarName(..))));
+        let bad = ctx.var("a b").unwrap();
+        assert!(matches!(ctx.to_infix(bad), Err(Error::BadVarName(..))));
+    }
+
+    #[test]
+    fn test_to_infix_deep() {
+        // Printing a long chain must take linear time and memory, and must not
+        // overflow the stack
+        let mut ctx = Context::new();
+        let mut node = ctx.x();
+        for _ in 0..200_000 {
+            node = ctx.sub(node, 1.0).unwrap();
+        }
+        node = ctx.sin(node).unwrap();
+        let s = ctx.to_infix(node).unwrap();
+        assert_eq!(s.len(), "sin(x)".len() + 200_000 * " - 1".len());
+        assert!(s.starts_with("sin(x - 1 - 1"));
+        assert_eq!(ctx.to_infix_with_bindings(node).unwrap(), s);
+    }
+
+    #[test]
+    fn test_to_infix_prospero() {
+        let (ctx, root) = Context::from_text(
+            include_str!("../../../../models/prospero.vm").as_bytes(),
+        )
+        .unwrap();
+        let txt = ctx.to_infix_with_bindings(root).unwrap();
+        assert!(txt.len() < ctx.len() * 64, "output is too long");
+
+        let mut next = Context::new();
+        let node = next.parse_expr(&txt).unwrap();
+        for (x, y) in [(0.0, 0.0), (0.25, -0.5), (-0.75, 0.8)] {
+            let a = ctx.eval_xyz(root, x, y, 0.0).unwrap();
+            let b = next.eval_xyz(node, x, y, 0.0).unwrap();
+            assert_eq!(a, b);
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! Container types with strongly-typed indexes.
 use crate::Error;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 /// Stores a set of `(V, I)` tuples, with lookup in both directions.
 ///
@@ -52,6 +52,9 @@ where
     pub fn get_by_index(&self, i: I) -> Option<&V> {
         self.data.get(i.get())
     }
+    pub fn get_by_value(&self, v: &V) -> Option<I> {
+        self.map.get(v).cloned()
+    }
     /// Insert the given value into the map, returning a handle.
     ///
     /// If the value is already in the map, the handle will be to the existing
@@ -80,6 +83,30 @@ where
     pub fn keys(&self) -> impl Iterator<Item = I> {
         (0..self.data.len()).map(I::new)
     }
+
+    /// Rebuilds the container, keeping only the values at the given indexes
+    ///
+    /// Kept values are visited in index order and may be rewritten by `f`,
+    /// which receives the mapping from old to new indexes built so far (so
+    /// values may refer to earlier values in the same container).  Returns the
+    /// complete mapping from old to new indexes.
+    ///
+    /// `f` must not map two distinct values to the same value.
+    pub fn compact<F>(&mut self, keep: &BTreeSet<I>, mut f: F) -> BTreeMap<I, I>
+    where
+        I: Ord,
+        F: FnMut(&V, &BTreeMap<I, I>) -> V,
+    {
+        let mut out = Self::default();
+        let mut remap = BTreeMap::new();
+        for &i in keep {
+            let v = f(&self.data[i.get()], &remap);
+            let j = out.insert(v);
+            remap.insert(i, j);
+        }
+        *self = out;
+        remap
+    }
     pub fn size_of(&self) -> usize {
         use std::mem::size_of;
         size_of::<V>() * self.data.len()
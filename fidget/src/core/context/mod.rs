@@ -1,6 +1,9 @@
 //! Infrastructure for representing math expressions as graphs
+mod binary;
+mod expr;
 mod indexed;
 mod op;
+mod text;
 
 #[cfg(test)]
 pub(crate) mod bound;
@@ -9,14 +12,14 @@ use indexed::{define_index, Index, IndexMap, IndexVec};
 pub use op::{BinaryOpcode, Op, UnaryOpcode};
 
 use crate::{
-    eval::{Family, Tape},
+    eval::{types::Interval, Choice, Family, Tape},
     ssa::Builder,
+    vm::{SoundInterval, TracingValue, Value},
     Error,
 };
 
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Write;
-use std::io::{BufRead, BufReader, Read};
 
 use ordered_float::OrderedFloat;
 
@@ -57,6 +60,59 @@ impl Context {
         self.vars.clear();
     }
 
+    /// Removes every node which isn't reachable from the given roots
+    ///
+    /// Returns new handles for each of the roots, in the same order.  All
+    /// other [`Node`](Node) and [`VarNode`](VarNode) handles from this context
+    /// are invalidated; variables which are no longer used are also removed.
+    ///
+    /// ```
+    /// # use fidget::context::Context;
+    /// let mut ctx = Context::new();
+    /// let x = ctx.x();
+    /// let y = ctx.y();
+    /// let sum = ctx.add(x, y).unwrap();
+    /// let op = ctx.mul(x, 2.0).unwrap();
+    /// assert_eq!(ctx.len(), 5);
+    ///
+    /// let roots = ctx.compact(&[op]).unwrap();
+    /// assert_eq!(ctx.len(), 3);
+    /// let v = ctx.eval_xyz(roots[0], 3.0, 0.0, 0.0).unwrap();
+    /// assert_eq!(v, 6.0);
+    /// ```
+    pub fn compact(&mut self, roots: &[Node]) -> Result<Vec<Node>, Error> {
+        roots.iter().try_for_each(|r| self.check_node(*r))?;
+
+        let mut keep = BTreeSet::new();
+        let mut keep_vars = BTreeSet::new();
+        let mut todo = roots.to_vec();
+        while let Some(node) = todo.pop() {
+            if !keep.insert(node) {
+                continue;
+            }
+            let op = self.get_op(node).unwrap();
+            if let Op::Var(v) | Op::Input(v) = op {
+                keep_vars.insert(*v);
+            }
+            todo.extend(op.iter_children());
+        }
+
+        let vars = self.vars.compact(&keep_vars, |v, _| v.clone());
+
+        // Children are always created before their parents, so they'll have
+        // already been remapped by the time we reach the parent.  Nodes keep
+        // their relative order, so commutative operations remain sorted.
+        let nodes = self.ops.compact(&keep, |op, nodes| match *op {
+            Op::Input(v) => Op::Input(vars[&v]),
+            Op::Var(v) => Op::Var(vars[&v]),
+            Op::Const(c) => Op::Const(c),
+            Op::Binary(op, a, b) => Op::Binary(op, nodes[&a], nodes[&b]),
+            Op::Unary(op, a) => Op::Unary(op, nodes[&a]),
+            Op::Select(c, a, b) => Op::Select(nodes[&c], nodes[&a], nodes[&b]),
+        });
+        Ok(roots.iter().map(|r| nodes[r]).collect())
+    }
+
     /// Returns the number of [`Op`] nodes in the context
     ///
     /// ```
@@ -158,6 +214,25 @@ impl Context {
         self.ops.insert(Op::Input(v))
     }
 
+    /// Constructs or finds a variable node named "W"
+    ///
+    /// `W` is a fourth input axis (e.g. time, or a blending parameter).  Like
+    /// X, Y, and Z, it may vary between points in bulk evaluation; evaluation
+    /// functions which don't take a `w` argument use a value of 0.
+    /// ```
+    /// # use fidget::context::Context;
+    /// let mut ctx = Context::new();
+    /// let x = ctx.x();
+    /// let w = ctx.w();
+    /// let sum = ctx.add(x, w).unwrap();
+    /// let v = ctx.eval_xyzw(sum, 1.0, 0.0, 0.0, 2.0).unwrap();
+    /// assert_eq!(v, 3.0);
+    /// ```
+    pub fn w(&mut self) -> Node {
+        let v = self.vars.insert(String::from("W"));
+        self.ops.insert(Op::Input(v))
+    }
+
     /// Returns a variable with the provided name.
     ///
     /// If a variable already exists with this name, then it is returned.
@@ -168,7 +243,7 @@ impl Context {
     }
 
     fn check_var_name(&self, name: &str) -> Result<String, Error> {
-        if matches!(name, "X" | "Y" | "Z") {
+        if matches!(name, "X" | "Y" | "Z" | "W") {
             return Err(Error::ReservedName);
         }
         Ok(String::from(name))
@@ -202,12 +277,23 @@ impl Context {
     }
     /// Find or create a [Node] for the given binary operation, with constant
     /// folding.
+    ///
+    /// A constant left-hand argument to `and` or `or` decides which argument is
+    /// returned, so those operations are always folded; tapes can't represent
+    /// them with an immediate on the left.
     fn op_binary(
         &mut self,
         a: Node,
         b: Node,
         op: BinaryOpcode,
     ) -> Result<Node, Error> {
+        if let Some(v) = self.const_value(a)? {
+            match op {
+                BinaryOpcode::And => return Ok(if v == 0.0 { a } else { b }),
+                BinaryOpcode::Or => return Ok(if v != 0.0 { a } else { b }),
+                _ => (),
+            }
+        }
         self.op_binary_f(a, b, |lhs, rhs| Op::Binary(op, lhs, rhs))
     }
 
@@ -245,6 +331,33 @@ impl Context {
         self.op_binary(a.min(b), a.max(b), op)
     }
 
+    /// Builds `op(a, b)` using the matching constructor (e.g. [`Context::add`]
+    /// for [`BinaryOpcode::Add`]), so that its folding and canonical argument
+    /// order are applied
+    fn build_binary(
+        &mut self,
+        op: BinaryOpcode,
+        a: Node,
+        b: Node,
+    ) -> Result<Node, Error> {
+        match op {
+            BinaryOpcode::Add => self.add(a, b),
+            BinaryOpcode::Sub => self.sub(a, b),
+            BinaryOpcode::Mul => self.mul(a, b),
+            BinaryOpcode::Div => self.div(a, b),
+            BinaryOpcode::Min => self.min(a, b),
+            BinaryOpcode::Max => self.max(a, b),
+            BinaryOpcode::Atan2 => self.atan2(a, b),
+            BinaryOpcode::Pow => self.pow(a, b),
+            BinaryOpcode::Mod => self.modulo(a, b),
+            BinaryOpcode::Lt => self.lt(a, b),
+            BinaryOpcode::Gt => self.gt(a, b),
+            BinaryOpcode::Eq => self.eq(a, b),
+            BinaryOpcode::And => self.and(a, b),
+            BinaryOpcode::Or => self.or(a, b),
+        }
+    }
+
     /// Builds an addition node
     /// ```
     /// # let mut ctx = fidget::context::Context::new();
@@ -374,6 +487,57 @@ impl Context {
         self.op_unary(a, UnaryOpcode::Cos)
     }
     
+    /// Builds a tangent node
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let op = ctx.tan(x).unwrap();
+    /// let v = ctx.eval_xyz(op, 0.0, 0.0, 0.0).unwrap();
+    /// assert_eq!(v, 0.0);
+    /// ```
+    pub fn tan<A: IntoNode>(&mut self, a: A) -> Result<Node, Error> {
+        let a = a.into_node(self)?;
+        self.op_unary(a, UnaryOpcode::Tan)
+    }
+
+    /// Builds an arcsine node
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let op = ctx.asin(x).unwrap();
+    /// let v = ctx.eval_xyz(op, 1.0, 0.0, 0.0).unwrap();
+    /// assert_eq!(v, std::f64::consts::FRAC_PI_2);
+    /// ```
+    pub fn asin<A: IntoNode>(&mut self, a: A) -> Result<Node, Error> {
+        let a = a.into_node(self)?;
+        self.op_unary(a, UnaryOpcode::Asin)
+    }
+
+    /// Builds an arccosine node
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let op = ctx.acos(x).unwrap();
+    /// let v = ctx.eval_xyz(op, 1.0, 0.0, 0.0).unwrap();
+    /// assert_eq!(v, 0.0);
+    /// ```
+    pub fn acos<A: IntoNode>(&mut self, a: A) -> Result<Node, Error> {
+        let a = a.into_node(self)?;
+        self.op_unary(a, UnaryOpcode::Acos)
+    }
+
+    /// Builds an arctangent node
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let op = ctx.atan(x).unwrap();
+    /// let v = ctx.eval_xyz(op, 1.0, 0.0, 0.0).unwrap();
+    /// assert_eq!(v, std::f64::consts::FRAC_PI_4);
+    /// ```
+    pub fn atan<A: IntoNode>(&mut self, a: A) -> Result<Node, Error> {
+        let a = a.into_node(self)?;
+        self.op_unary(a, UnaryOpcode::Atan)
+    }
 
     /// Builds a reciprocal node
     /// ```
@@ -480,170 +644,1296 @@ impl Context {
         }
     }
 
-    /// Flattens a subtree of the graph into straight-line code.
-    ///
-    /// The resulting tape uses `E::REG_LIMIT` registers; if more memory is
-    /// required, it includes
-    /// [`vm::Op::Load` / `vm::Op::Store`](crate::vm::Op) operations.
-    ///
-    /// This should always succeed unless the `root` is from a different
-    /// `Context`, in which case `Error::BadNode` will be returned.
-    pub fn get_tape<E: Family>(&self, root: Node) -> Result<Tape<E>, Error> {
-        let mut parent_count: BTreeMap<Node, usize> = BTreeMap::new();
-        let mut seen = BTreeSet::new();
-        let mut todo = vec![root];
-        let mut builder = Builder::new();
-
-        // Accumulate parent counts and declare all the nodes into the builder
-        while let Some(node) = todo.pop() {
-            if !seen.insert(node) {
-                continue;
-            }
-            let op = self.get_op(node).ok_or(Error::BadNode)?;
-            builder.declare_node(node, *op);
-            for child in op.iter_children() {
-                *parent_count.entry(child).or_default() += 1;
-                todo.push(child);
-            }
-        }
+    /// Builds a natural logarithm node
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let op = ctx.ln(x).unwrap();
+    /// let v = ctx.eval_xyz(op, 1.0, 0.0, 0.0).unwrap();
+    /// assert_eq!(v, 0.0);
+    /// ```
+    pub fn ln<A: IntoNode>(&mut self, a: A) -> Result<Node, Error> {
+        let a = a.into_node(self)?;
+        self.op_unary(a, UnaryOpcode::Ln)
+    }
 
-        // Now that we've populated our parents, flatten the graph
-        let mut todo = vec![root];
-        let mut seen = BTreeSet::new();
-        while let Some(node) = todo.pop() {
-            if *parent_count.get(&node).unwrap_or(&0) > 0 || !seen.insert(node)
-            {
-                continue;
-            }
-            let op = self.get_op(node).unwrap();
-            for child in op.iter_children() {
-                todo.push(child);
-                *parent_count.get_mut(&child).unwrap() -= 1;
-            }
-            builder.step(node, *op, self);
-        }
-        let mut ssa_tape = builder.finish();
+    /// Builds a base-2 logarithm node
+    ///
+    /// There's no dedicated opcode; this is built as `ln(a) / ln(2)`.
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let op = ctx.log2(x).unwrap();
+    /// let v = ctx.eval_xyz(op, 8.0, 0.0, 0.0).unwrap();
+    /// assert_eq!(v, 3.0);
+    /// ```
+    pub fn log2<A: IntoNode>(&mut self, a: A) -> Result<Node, Error> {
+        let ln = self.ln(a)?;
+        self.div(ln, std::f64::consts::LN_2)
+    }
 
-        // Special case if the Node is a single constant, which isn't usually
-        // recorded in the tape
-        if ssa_tape.tape.is_empty() {
-            let c = self.const_value(root).unwrap().unwrap() as f32;
-            ssa_tape.tape.push(crate::ssa::Op::CopyImm(0, c));
+    /// Builds a node which raises `a` to the power `b`
+    ///
+    /// This follows the semantics of [`f64::powf`]: a negative base produces
+    /// `NaN` unless the exponent is an integer.
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let y = ctx.y();
+    /// let op = ctx.pow(x, y).unwrap();
+    /// let v = ctx.eval_xyz(op, 4.0, 0.5, 0.0).unwrap();
+    /// assert_eq!(v, 2.0);
+    /// let v = ctx.eval_xyz(op, -2.0, 3.0, 0.0).unwrap();
+    /// assert_eq!(v, -8.0);
+    /// ```
+    pub fn pow<A: IntoNode, B: IntoNode>(
+        &mut self,
+        a: A,
+        b: B,
+    ) -> Result<Node, Error> {
+        let a = a.into_node(self)?;
+        let b = b.into_node(self)?;
+        match self.const_value(b)? {
+            Some(one) if one == 1.0 => Ok(a),
+            Some(two) if two == 2.0 => self.square(a),
+            _ => self.op_binary(a, b, BinaryOpcode::Pow),
         }
-        Ok(Tape::from_ssa(ssa_tape))
     }
 
-    ////////////////////////////////////////////////////////////////////////////
+    /// Builds a node which raises `a` to an integer power
+    ///
+    /// This is equivalent to [`Context::pow`] with a constant exponent; when
+    /// building a tape, integer exponents are lowered to a dedicated opcode,
+    /// which is cheaper to evaluate and accepts negative bases.
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let op = ctx.powi(x, 3).unwrap();
+    /// let v = ctx.eval_xyz(op, -2.0, 0.0, 0.0).unwrap();
+    /// assert_eq!(v, -8.0);
+    /// ```
+    pub fn powi<A: IntoNode>(&mut self, a: A, n: i32) -> Result<Node, Error> {
+        self.pow(a, n as f64)
+    }
 
-    /// Remaps the X, Y, Z nodes to the given values
-    pub fn remap_xyz(
+    /// Builds a node which calculates the four-quadrant arctangent of `y / x`
+    ///
+    /// Following the convention of [`f64::atan2`], the first argument is the
+    /// `y` coordinate and the result is in the range `[-π, π]`.
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let y = ctx.y();
+    /// let op = ctx.atan2(y, x).unwrap();
+    /// let v = ctx.eval_xyz(op, -1.0, 0.0, 0.0).unwrap();
+    /// assert_eq!(v, std::f64::consts::PI);
+    /// let v = ctx.eval_xyz(op, 0.0, -1.0, 0.0).unwrap();
+    /// assert_eq!(v, -std::f64::consts::FRAC_PI_2);
+    /// ```
+    pub fn atan2<A: IntoNode, B: IntoNode>(
         &mut self,
-        root: Node,
-        xyz: [Node; 3],
+        y: A,
+        x: B,
     ) -> Result<Node, Error> {
-        self.check_node(root)?;
-        xyz.iter().try_for_each(|x| self.check_node(*x))?;
-
-        let mut done = [self.x(), self.y(), self.z()]
-            .into_iter()
-            .zip(xyz)
-            .collect::<BTreeMap<_, _>>();
+        let y = y.into_node(self)?;
+        let x = x.into_node(self)?;
+        self.op_binary(y, x, BinaryOpcode::Atan2)
+    }
 
-        // Depth-first recursion on the heap, to protect against stack overflows
-        enum Action {
-            Down,
-            Up,
-        }
+    /// Builds a node which rounds its input down to the nearest integer
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let op = ctx.floor(x).unwrap();
+    /// let v = ctx.eval_xyz(op, -1.5, 0.0, 0.0).unwrap();
+    /// assert_eq!(v, -2.0);
+    /// ```
+    pub fn floor<A: IntoNode>(&mut self, a: A) -> Result<Node, Error> {
+        let a = a.into_node(self)?;
+        self.op_unary(a, UnaryOpcode::Floor)
+    }
 
-        let mut todo = vec![(Action::Down, root)];
-        let mut seen = BTreeSet::new();
-        while let Some((action, node)) = todo.pop() {
-            match action {
-                Action::Down => {
-                    if !seen.insert(node) {
-                        continue;
-                    }
-                    todo.push((Action::Up, node));
-                    todo.extend(
-                        self.get_op(node)
-                            .unwrap()
-                            .iter_children()
-                            .map(|c| (Action::Down, c)),
-                    );
-                }
-                Action::Up => {
-                    let r = match self.get_op(node).unwrap() {
-                        Op::Binary(op, lhs, rhs) => {
-                            let a = done.get(lhs).unwrap();
-                            let b = done.get(rhs).unwrap();
-                            self.op_binary(*a, *b, *op).unwrap()
-                        }
-                        Op::Unary(op, arg) => {
-                            let a = done.get(arg).unwrap();
-                            self.op_unary(*a, *op).unwrap()
-                        }
-                        Op::Var(..) | Op::Const(..) => node,
-                        Op::Input(..) => *done.get(&node).unwrap_or(&node),
-                    };
-                    done.insert(node, r);
-                }
-            }
-        }
-        Ok(*done.get(&root).unwrap())
+    /// Builds a node which rounds its input up to the nearest integer
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let op = ctx.ceil(x).unwrap();
+    /// let v = ctx.eval_xyz(op, -1.5, 0.0, 0.0).unwrap();
+    /// assert_eq!(v, -1.0);
+    /// ```
+    pub fn ceil<A: IntoNode>(&mut self, a: A) -> Result<Node, Error> {
+        let a = a.into_node(self)?;
+        self.op_unary(a, UnaryOpcode::Ceil)
     }
 
-    ////////////////////////////////////////////////////////////////////////////
-    /// Evaluates the given node with the provided values for X, Y, and Z.
-    ///
-    /// This is extremely inefficient; consider calling
-    /// [`get_tape`](Self::get_tape) and building an evaluator instead.
+    /// Builds a node which rounds its input to the nearest integer
     ///
+    /// Like [`f64::round`], half-way cases are rounded away from zero.
     /// ```
     /// # let mut ctx = fidget::context::Context::new();
     /// let x = ctx.x();
-    /// let y = ctx.y();
-    /// let z = ctx.z();
-    /// let op = ctx.mul(x, y).unwrap();
-    /// let op = ctx.div(op, z).unwrap();
-    /// let v = ctx.eval_xyz(op, 3.0, 5.0, 2.0).unwrap();
-    /// assert_eq!(v, 7.5); // (3.0 * 5.0) / 2.0
+    /// let op = ctx.round(x).unwrap();
+    /// let v = ctx.eval_xyz(op, -1.5, 0.0, 0.0).unwrap();
+    /// assert_eq!(v, -2.0);
+    /// let v = ctx.eval_xyz(op, 1.25, 0.0, 0.0).unwrap();
+    /// assert_eq!(v, 1.0);
     /// ```
-    pub fn eval_xyz(
-        &self,
-        root: Node,
-        x: f64,
-        y: f64,
-        z: f64,
-    ) -> Result<f64, Error> {
-        let vars = [("X", x), ("Y", y), ("Z", z)]
-            .into_iter()
-            .map(|(a, b)| (a.to_string(), b))
-            .collect();
-        self.eval(root, &vars)
+    pub fn round<A: IntoNode>(&mut self, a: A) -> Result<Node, Error> {
+        let a = a.into_node(self)?;
+        self.op_unary(a, UnaryOpcode::Round)
     }
 
-    /// Evaluates the given node with a generic set of variables
+    /// Builds a node which computes the Euclidean remainder of `a / b`
     ///
-    /// This is extremely inefficient; consider calling
-    /// [`get_tape`](Self::get_tape) and building an evaluator instead.
-    pub fn eval(
-        &self,
-        root: Node,
-        vars: &BTreeMap<String, f64>,
-    ) -> Result<f64, Error> {
-        let mut cache = vec![None; self.ops.len()].into();
-        self.eval_inner(root, vars, &mut cache)
+    /// This follows the semantics of [`f64::rem_euclid`]: the result is
+    /// always in the range `[0, |b|)`, which makes it suitable for repeating
+    /// a shape across space.
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let op = ctx.modulo(x, 2.0).unwrap();
+    /// let v = ctx.eval_xyz(op, 3.5, 0.0, 0.0).unwrap();
+    /// assert_eq!(v, 1.5);
+    /// let v = ctx.eval_xyz(op, -0.5, 0.0, 0.0).unwrap();
+    /// assert_eq!(v, 1.5);
+    /// ```
+    pub fn modulo<A: IntoNode, B: IntoNode>(
+        &mut self,
+        a: A,
+        b: B,
+    ) -> Result<Node, Error> {
+        let a = a.into_node(self)?;
+        let b = b.into_node(self)?;
+        self.op_binary(a, b, BinaryOpcode::Mod)
     }
 
-    fn eval_inner(
-        &self,
-        node: Node,
-        vars: &BTreeMap<String, f64>,
-        cache: &mut IndexVec<Option<f64>, Node>,
-    ) -> Result<f64, Error> {
-        if node.0 >= cache.len() {
-            return Err(Error::BadNode);
-        }
+    /// Builds a node which returns 1 if `a < b`, and 0 otherwise
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let y = ctx.y();
+    /// let op = ctx.lt(x, y).unwrap();
+    /// let v = ctx.eval_xyz(op, 1.0, 2.0, 0.0).unwrap();
+    /// assert_eq!(v, 1.0);
+    /// let v = ctx.eval_xyz(op, 2.0, 2.0, 0.0).unwrap();
+    /// assert_eq!(v, 0.0);
+    /// ```
+    pub fn lt<A: IntoNode, B: IntoNode>(
+        &mut self,
+        a: A,
+        b: B,
+    ) -> Result<Node, Error> {
+        let a = a.into_node(self)?;
+        let b = b.into_node(self)?;
+        self.op_binary(a, b, BinaryOpcode::Lt)
+    }
+
+    /// Builds a node which returns 1 if `a > b`, and 0 otherwise
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let y = ctx.y();
+    /// let op = ctx.gt(x, y).unwrap();
+    /// let v = ctx.eval_xyz(op, 3.0, 2.0, 0.0).unwrap();
+    /// assert_eq!(v, 1.0);
+    /// let v = ctx.eval_xyz(op, 2.0, 2.0, 0.0).unwrap();
+    /// assert_eq!(v, 0.0);
+    /// ```
+    pub fn gt<A: IntoNode, B: IntoNode>(
+        &mut self,
+        a: A,
+        b: B,
+    ) -> Result<Node, Error> {
+        let a = a.into_node(self)?;
+        let b = b.into_node(self)?;
+        self.op_binary(a, b, BinaryOpcode::Gt)
+    }
+
+    /// Builds a node which returns 1 if `a == b`, and 0 otherwise
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let op = ctx.eq(x, 2.0).unwrap();
+    /// let v = ctx.eval_xyz(op, 2.0, 0.0, 0.0).unwrap();
+    /// assert_eq!(v, 1.0);
+    /// let v = ctx.eval_xyz(op, 3.0, 0.0, 0.0).unwrap();
+    /// assert_eq!(v, 0.0);
+    /// ```
+    pub fn eq<A: IntoNode, B: IntoNode>(
+        &mut self,
+        a: A,
+        b: B,
+    ) -> Result<Node, Error> {
+        let a = a.into_node(self)?;
+        let b = b.into_node(self)?;
+        self.op_binary_commutative(a, b, BinaryOpcode::Eq)
+    }
+
+    /// Builds a logical AND node
+    ///
+    /// If `a` is zero, returns `a`; otherwise, returns `b`.  When used with
+    /// comparisons (which return 0 or 1), this is the usual logical AND.
+    ///
+    /// Like `min` and `max`, this operation records a choice during tracing
+    /// evaluation, so tapes can be simplified if one branch is always taken.
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let y = ctx.y();
+    /// let op = ctx.and(x, y).unwrap();
+    /// let v = ctx.eval_xyz(op, 1.0, 2.0, 0.0).unwrap();
+    /// assert_eq!(v, 2.0);
+    /// let v = ctx.eval_xyz(op, 0.0, 2.0, 0.0).unwrap();
+    /// assert_eq!(v, 0.0);
+    /// ```
+    pub fn and<A: IntoNode, B: IntoNode>(
+        &mut self,
+        a: A,
+        b: B,
+    ) -> Result<Node, Error> {
+        let a = a.into_node(self)?;
+        let b = b.into_node(self)?;
+        self.op_binary(a, b, BinaryOpcode::And)
+    }
+
+    /// Builds a logical OR node
+    ///
+    /// If `a` is non-zero, returns `a`; otherwise, returns `b`.  When used
+    /// with comparisons (which return 0 or 1), this is the usual logical OR.
+    ///
+    /// Like [`Context::and`], this operation records a choice during tracing
+    /// evaluation.
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let y = ctx.y();
+    /// let op = ctx.or(x, y).unwrap();
+    /// let v = ctx.eval_xyz(op, 1.0, 2.0, 0.0).unwrap();
+    /// assert_eq!(v, 1.0);
+    /// let v = ctx.eval_xyz(op, 0.0, 2.0, 0.0).unwrap();
+    /// assert_eq!(v, 2.0);
+    /// ```
+    pub fn or<A: IntoNode, B: IntoNode>(
+        &mut self,
+        a: A,
+        b: B,
+    ) -> Result<Node, Error> {
+        let a = a.into_node(self)?;
+        let b = b.into_node(self)?;
+        self.op_binary(a, b, BinaryOpcode::Or)
+    }
+
+    /// Builds a logical NOT node, which returns 1 if `a` is zero and 0
+    /// otherwise
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let op = ctx.not(x).unwrap();
+    /// let v = ctx.eval_xyz(op, 0.0, 0.0, 0.0).unwrap();
+    /// assert_eq!(v, 1.0);
+    /// let v = ctx.eval_xyz(op, 2.0, 0.0, 0.0).unwrap();
+    /// assert_eq!(v, 0.0);
+    /// ```
+    pub fn not<A: IntoNode>(&mut self, a: A) -> Result<Node, Error> {
+        let a = a.into_node(self)?;
+        self.op_unary(a, UnaryOpcode::Not)
+    }
+
+    /// Builds a node which returns `a` if `cond < 0`, and `b` otherwise
+    ///
+    /// Like `min` and `max`, this operation records a choice during tracing
+    /// evaluation, so the untaken branch can be pruned when simplifying a
+    /// tape.  A constant condition is folded immediately.
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let y = ctx.y();
+    /// let op = ctx.select(x, y, 2.0).unwrap();
+    /// let v = ctx.eval_xyz(op, -1.0, 3.0, 0.0).unwrap();
+    /// assert_eq!(v, 3.0);
+    /// let v = ctx.eval_xyz(op, 1.0, 3.0, 0.0).unwrap();
+    /// assert_eq!(v, 2.0);
+    /// ```
+    pub fn select<C: IntoNode, A: IntoNode, B: IntoNode>(
+        &mut self,
+        cond: C,
+        a: A,
+        b: B,
+    ) -> Result<Node, Error> {
+        let cond = cond.into_node(self)?;
+        let a = a.into_node(self)?;
+        let b = b.into_node(self)?;
+        self.check_node(a)?;
+        self.check_node(b)?;
+        match self.const_value(cond)? {
+            Some(v) if v < 0.0 => Ok(a),
+            Some(_) => Ok(b),
+            None if a == b => Ok(a),
+            None => Ok(self.ops.insert(Op::Select(cond, a, b))),
+        }
+    }
+
+    /// Flattens a subtree of the graph into straight-line code.
+    ///
+    /// The resulting tape uses `E::REG_LIMIT` registers; if more memory is
+    /// required, it includes
+    /// [`vm::Op::Load` / `vm::Op::Store`](crate::vm::Op) operations.
+    ///
+    /// This should always succeed unless the `root` is from a different
+    /// `Context`, in which case `Error::BadNode` will be returned.
+    pub fn get_tape<E: Family>(&self, root: Node) -> Result<Tape<E>, Error> {
+        self.get_tape_multi(&[root])
+    }
+
+    /// Flattens multiple subtrees of the graph into a single tape.
+    ///
+    /// The resulting tape has one output per root, in order; shared
+    /// subexpressions are only evaluated once.  Bulk evaluators write every
+    /// output (see [`BulkEvaluator`](crate::eval::bulk::BulkEvaluator)),
+    /// while other evaluators only return the first output.
+    ///
+    /// Outputs are kept in registers, so there may be at most `E::REG_LIMIT`
+    /// roots; otherwise, `Error::BadOutputCount` will be returned.
+    pub fn get_tape_multi<E: Family>(
+        &self,
+        roots: &[Node],
+    ) -> Result<Tape<E>, Error> {
+        if roots.is_empty() || roots.len() > E::REG_LIMIT as usize {
+            return Err(Error::BadOutputCount(roots.len(), E::REG_LIMIT));
+        }
+        let mut parent_count: BTreeMap<Node, usize> = BTreeMap::new();
+        let mut seen = BTreeSet::new();
+        let mut builder = Builder::new();
+
+        // Outputs are declared first, so that they're assigned the first slots
+        let outputs = roots
+            .iter()
+            .map(|&r| self.get_op(r).map(|op| (r, *op)).ok_or(Error::BadNode))
+            .collect::<Result<Vec<_>, _>>()?;
+        builder.declare_outputs(&outputs);
+
+        let mut todo = roots.to_vec();
+
+        // Accumulate parent counts and declare all the nodes into the builder
+        while let Some(node) = todo.pop() {
+            if !seen.insert(node) {
+                continue;
+            }
+            let op = self.get_op(node).ok_or(Error::BadNode)?;
+            builder.declare_node(node, *op);
+            for child in op.iter_children() {
+                *parent_count.entry(child).or_default() += 1;
+                todo.push(child);
+            }
+        }
+
+        // Now that we've populated our parents, flatten the graph.  Roots
+        // which are used by other roots are skipped here, then pushed once all
+        // of their parents have been flattened.
+        let mut todo = roots.iter().rev().cloned().collect::<Vec<_>>();
+        let mut seen = BTreeSet::new();
+        while let Some(node) = todo.pop() {
+            if *parent_count.get(&node).unwrap_or(&0) > 0 || !seen.insert(node)
+            {
+                continue;
+            }
+            let op = self.get_op(node).unwrap();
+            for child in op.iter_children() {
+                todo.push(child);
+                *parent_count.get_mut(&child).unwrap() -= 1;
+            }
+            builder.step(node, *op, self);
+        }
+        // Constant roots aren't recorded in the tape, but the builder inserts a
+        // copy into their output slot
+        Ok(Tape::from_ssa(builder.finish()))
+    }
+
+    ////////////////////////////////////////////////////////////////////////////
+
+    /// Copies the subgraph rooted at `root` from another context into this one,
+    /// returning the equivalent node in this context
+    ///
+    /// Nodes are deduplicated against those already in this context, and
+    /// variables (including X, Y, Z) are matched by name.
+    /// ```
+    /// # use fidget::context::Context;
+    /// let mut a = Context::new();
+    /// let x = a.x();
+    /// let sphere = a.square(x).unwrap();
+    ///
+    /// let mut b = Context::new();
+    /// let x = b.x();
+    /// let x2 = b.square(x).unwrap();
+    /// let op = b.import(&a, sphere).unwrap();
+    /// assert_eq!(op, x2);
+    /// ```
+    pub fn import(
+        &mut self,
+        other: &Context,
+        root: Node,
+    ) -> Result<Node, Error> {
+        other.check_node(root)?;
+
+        // Map from a node in `other` to a node in `self`
+        let mut done = BTreeMap::new();
+
+        // Depth-first recursion on the heap, to protect against stack overflows
+        enum Action {
+            Down,
+            Up,
+        }
+
+        let mut todo = vec![(Action::Down, root)];
+        let mut seen = BTreeSet::new();
+        while let Some((action, node)) = todo.pop() {
+            match action {
+                Action::Down => {
+                    if !seen.insert(node) {
+                        continue;
+                    }
+                    todo.push((Action::Up, node));
+                    todo.extend(
+                        other
+                            .get_op(node)
+                            .unwrap()
+                            .iter_children()
+                            .map(|c| (Action::Down, c)),
+                    );
+                }
+                Action::Up => {
+                    let r = match *other.get_op(node).unwrap() {
+                        Op::Input(v) => {
+                            let name = other.get_var_by_index(v)?;
+                            let v = self.vars.insert(name.to_owned());
+                            self.ops.insert(Op::Input(v))
+                        }
+                        Op::Var(v) => {
+                            let name = other.get_var_by_index(v)?;
+                            let v = self.vars.insert(name.to_owned());
+                            self.ops.insert(Op::Var(v))
+                        }
+                        Op::Const(c) => self.constant(c.0),
+                        Op::Binary(op, a, b) => {
+                            self.build_binary(op, done[&a], done[&b])?
+                        }
+                        Op::Unary(op, a) => self.op_unary(done[&a], op)?,
+                        Op::Select(c, a, b) => {
+                            self.select(done[&c], done[&a], done[&b])?
+                        }
+                    };
+                    done.insert(node, r);
+                }
+            }
+        }
+        Ok(done[&root])
+    }
+
+    /// Remaps the X, Y, Z nodes to the given values
+    ///
+    /// This is a special case of [`Context::substitute`].
+    pub fn remap_xyz(
+        &mut self,
+        root: Node,
+        xyz: [Node; 3],
+    ) -> Result<Node, Error> {
+        let map = [self.x(), self.y(), self.z()]
+            .into_iter()
+            .zip(xyz)
+            .collect();
+        self.substitute(root, &map)
+    }
+
+    /// Rebuilds the graph rooted at `root`, replacing nodes with new values
+    ///
+    /// Keys in `map` may be any node, e.g. an input, a named variable, or an
+    /// arbitrary subexpression.  Substitution is simultaneous: the replacement
+    /// nodes are not themselves rewritten.  Nodes along the way are rebuilt
+    /// with constant folding, so substituting a constant may shrink the graph.
+    /// ```
+    /// # use fidget::context::Context;
+    /// # use std::collections::BTreeMap;
+    /// let mut ctx = Context::new();
+    /// let x = ctx.x();
+    /// let r = ctx.var("r").unwrap();
+    /// let op = ctx.sub(x, r).unwrap();
+    ///
+    /// // Bake in a value for `r`
+    /// let two = ctx.constant(2.0);
+    /// let baked = ctx.substitute(op, &BTreeMap::from([(r, two)])).unwrap();
+    /// assert_eq!(ctx.eval_xyz(baked, 5.0, 0.0, 0.0).unwrap(), 3.0);
+    /// ```
+    pub fn substitute(
+        &mut self,
+        root: Node,
+        map: &BTreeMap<Node, Node>,
+    ) -> Result<Node, Error> {
+        self.check_node(root)?;
+        map.iter().try_for_each(|(k, v)| {
+            self.check_node(*k)?;
+            self.check_node(*v)
+        })?;
+
+        // Map from an original node to its rebuilt equivalent
+        let mut done = BTreeMap::new();
+
+        // Depth-first recursion on the heap, to protect against stack overflows
+        enum Action {
+            Down,
+            Up,
+        }
+
+        let mut todo = vec![(Action::Down, root)];
+        let mut seen = BTreeSet::new();
+        while let Some((action, node)) = todo.pop() {
+            match action {
+                Action::Down => {
+                    if !seen.insert(node) {
+                        continue;
+                    }
+                    // Substituted nodes don't need their children rebuilt
+                    if let Some(r) = map.get(&node) {
+                        done.insert(node, *r);
+                        continue;
+                    }
+                    todo.push((Action::Up, node));
+                    todo.extend(
+                        self.get_op(node)
+                            .unwrap()
+                            .iter_children()
+                            .map(|c| (Action::Down, c)),
+                    );
+                }
+                Action::Up => {
+                    let r = match *self.get_op(node).unwrap() {
+                        Op::Binary(op, a, b) => {
+                            self.build_binary(op, done[&a], done[&b])?
+                        }
+                        Op::Unary(op, a) => self.op_unary(done[&a], op)?,
+                        Op::Select(c, a, b) => {
+                            self.select(done[&c], done[&a], done[&b])?
+                        }
+                        Op::Var(..) | Op::Const(..) | Op::Input(..) => node,
+                    };
+                    done.insert(node, r);
+                }
+            }
+        }
+        Ok(done[&root])
+    }
+
+    /// Rebuilds the graph rooted at `root`, treating the given variables as
+    /// constants
+    ///
+    /// This re-runs constant folding, so operations which depend only on bound
+    /// variables are collapsed and `select`, `and`, and `or` nodes with a
+    /// constant condition are pruned to a single branch.  Then, `min`, `max`,
+    /// and logical operations are pruned if interval arithmetic (with every
+    /// input and unbound variable unbounded) shows that one branch is never
+    /// taken.  The resulting node typically produces a shorter tape than the
+    /// original, at the cost of rebuilding it whenever a value changes.
+    ///
+    /// Returns [`Error::UnknownVariable`] if a name isn't a variable in this
+    /// context.
+    /// ```
+    /// # use fidget::context::Context;
+    /// # use std::collections::BTreeMap;
+    /// let mut ctx = Context::new();
+    /// let x = ctx.x();
+    /// let r = ctx.var("r").unwrap();
+    /// let r2 = ctx.mul(r, 2.0).unwrap();
+    /// let op = ctx.sub(x, r2).unwrap();
+    ///
+    /// let baked = ctx.bind_vars(op, &BTreeMap::from([("r".to_owned(), 1.5)]));
+    /// let baked = baked.unwrap();
+    /// assert_eq!(baked, ctx.sub(x, 3.0).unwrap());
+    /// ```
+    pub fn bind_vars(
+        &mut self,
+        root: Node,
+        values: &BTreeMap<String, f64>,
+    ) -> Result<Node, Error> {
+        let mut map = BTreeMap::new();
+        for (name, value) in values {
+            let node = self
+                .vars
+                .get_by_value(name)
+                .and_then(|v| self.ops.get_by_value(&Op::Var(v)))
+                .ok_or_else(|| Error::UnknownVariable(name.clone()))?;
+            map.insert(node, self.constant(*value));
+        }
+        let root = self.substitute(root, &map)?;
+        self.prune(root)
+    }
+
+    /// Rebuilds the graph rooted at `root`, removing branches which are never
+    /// taken
+    ///
+    /// Choices for `min`, `max`, `select`, `and`, and `or` nodes are found with
+    /// (outward-rounded) interval arithmetic, treating every input and
+    /// variable as unbounded, so only branches which aren't taken anywhere are
+    /// removed.
+    fn prune(&mut self, root: Node) -> Result<Node, Error> {
+        self.check_node(root)?;
+
+        // Map from an original node to its bounds and rebuilt equivalent
+        let mut done: BTreeMap<Node, (SoundInterval, Node)> = BTreeMap::new();
+
+        // Depth-first recursion on the heap, to protect against stack overflows
+        enum Action {
+            Down,
+            Up,
+        }
+
+        let unbounded = Interval::new(f32::NEG_INFINITY, f32::INFINITY).into();
+        let mut todo = vec![(Action::Down, root)];
+        let mut seen = BTreeSet::new();
+        while let Some((action, node)) = todo.pop() {
+            match action {
+                Action::Down => {
+                    if !seen.insert(node) {
+                        continue;
+                    }
+                    todo.push((Action::Up, node));
+                    todo.extend(
+                        self.get_op(node)
+                            .unwrap()
+                            .iter_children()
+                            .map(|c| (Action::Down, c)),
+                    );
+                }
+                Action::Up => {
+                    // Returns the branch to use in place of a choice node
+                    let pick = |choice, a, b| match choice {
+                        Choice::Left => Some(a),
+                        Choice::Right => Some(b),
+                        _ => None,
+                    };
+                    let out = match *self.get_op(node).unwrap() {
+                        Op::Input(..) | Op::Var(..) => (unbounded, node),
+                        Op::Const(c) => (c.0.into(), node),
+                        Op::Unary(op, a) => {
+                            let (i, a) = done[&a];
+                            let i = match op {
+                                UnaryOpcode::Neg => -i,
+                                UnaryOpcode::Abs => i.abs(),
+                                UnaryOpcode::Recip => i.recip(),
+                                UnaryOpcode::Sqrt => i.sqrt(),
+                                UnaryOpcode::Square => i.square(),
+                                UnaryOpcode::Exp => i.exp(),
+                                UnaryOpcode::Sin => i.sin(),
+                                UnaryOpcode::Cos => i.cos(),
+                                UnaryOpcode::Tan => i.tan(),
+                                UnaryOpcode::Asin => i.asin(),
+                                UnaryOpcode::Acos => i.acos(),
+                                UnaryOpcode::Atan => i.atan(),
+                                UnaryOpcode::Ln => i.ln(),
+                                UnaryOpcode::Floor => i.floor(),
+                                UnaryOpcode::Ceil => i.ceil(),
+                                UnaryOpcode::Round => i.round(),
+                                UnaryOpcode::Not => i.not(),
+                            };
+                            (i, self.op_unary(a, op)?)
+                        }
+                        Op::Binary(op, a, b) => {
+                            let (a, b) = (done[&a], done[&b]);
+                            let (ia, ib) = (a.0, b.0);
+                            let (i, choice) = match op {
+                                BinaryOpcode::Add => (ia + ib, Choice::Both),
+                                BinaryOpcode::Sub => (ia - ib, Choice::Both),
+                                BinaryOpcode::Mul => (ia * ib, Choice::Both),
+                                BinaryOpcode::Div => (ia / ib, Choice::Both),
+                                BinaryOpcode::Atan2 => {
+                                    (ia.atan2(ib), Choice::Both)
+                                }
+                                BinaryOpcode::Pow => (ia.pow(ib), Choice::Both),
+                                BinaryOpcode::Mod => {
+                                    (ia.modulo(ib), Choice::Both)
+                                }
+                                BinaryOpcode::Lt => (ia.lt(ib), Choice::Both),
+                                BinaryOpcode::Gt => (ib.lt(ia), Choice::Both),
+                                BinaryOpcode::Eq => (ia.eq(ib), Choice::Both),
+                                BinaryOpcode::Min => ia.min_choice(ib),
+                                BinaryOpcode::Max => ia.max_choice(ib),
+                                BinaryOpcode::And => ia.and_choice(ib),
+                                BinaryOpcode::Or => ia.or_choice(ib),
+                            };
+                            let r = match pick(choice, a.1, b.1) {
+                                Some(r) => r,
+                                None => self.build_binary(op, a.1, b.1)?,
+                            };
+                            (i, r)
+                        }
+                        Op::Select(c, a, b) => {
+                            let (c, a, b) = (done[&c], done[&a], done[&b]);
+                            let (i, choice) = c.0.select_choice(a.0, b.0);
+                            let r = match pick(choice, a.1, b.1) {
+                                Some(r) => r,
+                                None => self.select(c.1, a.1, b.1)?,
+                            };
+                            (i, r)
+                        }
+                    };
+                    done.insert(node, out);
+                }
+            }
+        }
+        Ok(done[&root].1)
+    }
+
+    /// Builds a node which is the partial derivative of `root` with respect to
+    /// the variable `var`
+    ///
+    /// `var` must be an input (e.g. from [`Context::x`]) or a variable (from
+    /// [`Context::var`]); otherwise, this returns [`Error::NotAVar`].
+    ///
+    /// The derivatives of `min`, `max`, `abs`, and the logical operations pick
+    /// the derivative of the active branch using [`Context::select`].
+    /// Piecewise-constant operations (comparisons and rounding) have a
+    /// derivative of zero everywhere.
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let y = ctx.y();
+    /// let x2 = ctx.square(x).unwrap();
+    /// let op = ctx.mul(x2, y).unwrap();
+    /// let dx = ctx.deriv(op, x).unwrap();
+    /// let v = ctx.eval_xyz(dx, 3.0, 2.0, 0.0).unwrap();
+    /// assert_eq!(v, 12.0); // 2 * x * y
+    /// ```
+    pub fn deriv(&mut self, root: Node, var: Node) -> Result<Node, Error> {
+        self.check_node(root)?;
+        match self.get_op(var).ok_or(Error::BadNode)? {
+            Op::Var(..) | Op::Input(..) => (),
+            _ => return Err(Error::NotAVar),
+        }
+
+        // Map from a node to its partial derivative
+        let mut done = BTreeMap::new();
+
+        // Depth-first recursion on the heap, to protect against stack overflows
+        enum Action {
+            Down,
+            Up,
+        }
+
+        let mut todo = vec![(Action::Down, root)];
+        let mut seen = BTreeSet::new();
+        while let Some((action, node)) = todo.pop() {
+            match action {
+                Action::Down => {
+                    if !seen.insert(node) {
+                        continue;
+                    }
+                    todo.push((Action::Up, node));
+                    todo.extend(
+                        self.get_op(node)
+                            .unwrap()
+                            .iter_children()
+                            .map(|c| (Action::Down, c)),
+                    );
+                }
+                Action::Up => {
+                    let op = *self.get_op(node).unwrap();
+                    let d = match op {
+                        Op::Var(..) | Op::Input(..) => {
+                            self.constant(if node == var { 1.0 } else { 0.0 })
+                        }
+                        Op::Const(..) => self.constant(0.0),
+                        Op::Binary(op, a, b) => {
+                            let da = done[&a];
+                            let db = done[&b];
+                            self.deriv_binary(node, op, a, b, da, db)?
+                        }
+                        Op::Unary(op, a) => {
+                            let da = done[&a];
+                            self.deriv_unary(node, op, a, da)?
+                        }
+                        Op::Select(c, a, b) => {
+                            let da = done[&a];
+                            let db = done[&b];
+                            self.select(c, da, db)?
+                        }
+                    };
+                    done.insert(node, d);
+                }
+            }
+        }
+        Ok(done[&root])
+    }
+
+    /// Builds the derivative of `n = op(a)`, given the derivative `da`
+    fn deriv_unary(
+        &mut self,
+        n: Node,
+        op: UnaryOpcode,
+        a: Node,
+        da: Node,
+    ) -> Result<Node, Error> {
+        let out = match op {
+            UnaryOpcode::Neg => self.neg(da)?,
+            UnaryOpcode::Abs => {
+                let neg = self.neg(da)?;
+                self.select(a, neg, da)?
+            }
+            UnaryOpcode::Recip => {
+                let a2 = self.square(a)?;
+                let d = self.div(da, a2)?;
+                self.neg(d)?
+            }
+            UnaryOpcode::Sqrt => {
+                let n2 = self.mul(n, 2.0)?;
+                self.div(da, n2)?
+            }
+            UnaryOpcode::Square => {
+                let a2 = self.mul(a, 2.0)?;
+                self.mul(a2, da)?
+            }
+            UnaryOpcode::Exp => self.mul(n, da)?,
+            UnaryOpcode::Sin => {
+                let c = self.cos(a)?;
+                self.mul(c, da)?
+            }
+            UnaryOpcode::Cos => {
+                let s = self.sin(a)?;
+                let d = self.mul(s, da)?;
+                self.neg(d)?
+            }
+            UnaryOpcode::Tan => {
+                let c = self.cos(a)?;
+                let c2 = self.square(c)?;
+                self.div(da, c2)?
+            }
+            UnaryOpcode::Asin | UnaryOpcode::Acos => {
+                let a2 = self.square(a)?;
+                let r = self.sub(1.0, a2)?;
+                let r = self.sqrt(r)?;
+                let d = self.div(da, r)?;
+                if op == UnaryOpcode::Acos {
+                    self.neg(d)?
+                } else {
+                    d
+                }
+            }
+            UnaryOpcode::Atan => {
+                let a2 = self.square(a)?;
+                let r = self.add(a2, 1.0)?;
+                self.div(da, r)?
+            }
+            UnaryOpcode::Ln => self.div(da, a)?,
+            UnaryOpcode::Floor
+            | UnaryOpcode::Ceil
+            | UnaryOpcode::Round
+            | UnaryOpcode::Not => self.constant(0.0),
+        };
+        Ok(out)
+    }
+
+    /// Builds the derivative of `n = op(a, b)`, given the derivatives `da` and
+    /// `db`
+    fn deriv_binary(
+        &mut self,
+        n: Node,
+        op: BinaryOpcode,
+        a: Node,
+        b: Node,
+        da: Node,
+        db: Node,
+    ) -> Result<Node, Error> {
+        let out = match op {
+            BinaryOpcode::Add => self.add(da, db)?,
+            BinaryOpcode::Sub => self.sub(da, db)?,
+            BinaryOpcode::Mul => {
+                let l = self.mul(a, db)?;
+                let r = self.mul(b, da)?;
+                self.add(l, r)?
+            }
+            BinaryOpcode::Div => {
+                // (da * b - a * db) / b^2
+                let l = self.mul(da, b)?;
+                let r = self.mul(a, db)?;
+                let num = self.sub(l, r)?;
+                let b2 = self.square(b)?;
+                self.div(num, b2)?
+            }
+            BinaryOpcode::Min => {
+                let c = self.sub(a, b)?;
+                self.select(c, da, db)?
+            }
+            BinaryOpcode::Max => {
+                let c = self.sub(b, a)?;
+                self.select(c, da, db)?
+            }
+            BinaryOpcode::Atan2 => {
+                // For atan2(y, x), this is (x * dy - y * dx) / (x^2 + y^2)
+                let l = self.mul(b, da)?;
+                let r = self.mul(a, db)?;
+                let num = self.sub(l, r)?;
+                let a2 = self.square(a)?;
+                let b2 = self.square(b)?;
+                let den = self.add(a2, b2)?;
+                self.div(num, den)?
+            }
+            BinaryOpcode::Pow => {
+                if self.const_value(db)? == Some(0.0) {
+                    // Power rule, which is well-behaved for negative `a`
+                    let e = self.sub(b, 1.0)?;
+                    let p = self.pow(a, e)?;
+                    let p = self.mul(b, p)?;
+                    self.mul(p, da)?
+                } else {
+                    // a^b * (db * ln(a) + b * da / a)
+                    let ln = self.ln(a)?;
+                    let l = self.mul(db, ln)?;
+                    let r = self.mul(b, da)?;
+                    let r = self.div(r, a)?;
+                    let s = self.add(l, r)?;
+                    self.mul(n, s)?
+                }
+            }
+            BinaryOpcode::Mod => {
+                // n = a - q * b for an integer q which is locally constant, so
+                // the derivative is da - q * db
+                let q = self.sub(a, n)?;
+                let q = self.div(q, b)?;
+                let r = self.mul(q, db)?;
+                self.sub(da, r)?
+            }
+            BinaryOpcode::Lt | BinaryOpcode::Gt | BinaryOpcode::Eq => {
+                self.constant(0.0)
+            }
+            BinaryOpcode::And => {
+                // `and` returns `a` where it's zero, and `b` otherwise
+                let z = self.not(a)?;
+                let c = self.neg(z)?;
+                self.select(c, da, db)?
+            }
+            BinaryOpcode::Or => {
+                // `or` returns `b` where `a` is zero, and `a` otherwise
+                let z = self.not(a)?;
+                let c = self.neg(z)?;
+                self.select(c, db, da)?
+            }
+        };
+        Ok(out)
+    }
+
+    /// Applies algebraic rewrite rules to the graph rooted at `root`, returning
+    /// a new (hopefully smaller) root node and the number of nodes removed
+    ///
+    /// Every node is rebuilt through the usual constructors, so the folding
+    /// which they perform (e.g. `x * 1 → x` or `min(x, x) → x`) is applied
+    /// throughout the graph.  In addition, this function applies rules which
+    /// look at more than one level of the graph:
+    ///
+    /// - `neg(neg(x)) → x` and `neg(a - b) → b - a`
+    /// - `abs(neg(x)) → abs(x)`, and `abs(x) → x` when `x` is the result of
+    ///   `abs`, `square`, `sqrt`, or `exp`
+    /// - `square(neg(x))` and `square(abs(x))` become `square(x)`
+    /// - `a - a → 0`, `a - neg(b) → a + b`, and `a + neg(b) → a - b`
+    /// - constant terms are reassociated, e.g. `(x + c₁) - c₂ → x + (c₁ - c₂)`
+    ///   and `(x * c₁) * c₂ → x * (c₁ * c₂)`
+    /// - `x * -1 → neg(x)`
+    ///
+    /// The original nodes are left in the context; the returned count is the
+    /// difference in [`Context::node_count`] between the old and new roots.
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let a = ctx.add(x, 1.0).unwrap();
+    /// let b = ctx.add(a, 2.0).unwrap();
+    /// let n = ctx.neg(b).unwrap();
+    /// let op = ctx.neg(n).unwrap();
+    /// assert_eq!(ctx.node_count(op).unwrap(), 7);
+    ///
+    /// let (s, removed) = ctx.simplify(op).unwrap();
+    /// assert_eq!(ctx.node_count(s).unwrap(), 3); // x + 3
+    /// assert_eq!(removed, 4);
+    /// assert_eq!(ctx.eval_xyz(s, 1.0, 0.0, 0.0).unwrap(), 4.0);
+    /// ```
+    pub fn simplify(&mut self, root: Node) -> Result<(Node, usize), Error> {
+        let before = self.node_count(root)?;
+
+        // Map from an original node to its simplified equivalent
+        let mut done = BTreeMap::new();
+
+        // Depth-first recursion on the heap, to protect against stack overflows
+        enum Action {
+            Down,
+            Up,
+        }
+
+        let mut todo = vec![(Action::Down, root)];
+        let mut seen = BTreeSet::new();
+        while let Some((action, node)) = todo.pop() {
+            match action {
+                Action::Down => {
+                    if !seen.insert(node) {
+                        continue;
+                    }
+                    todo.push((Action::Up, node));
+                    todo.extend(
+                        self.get_op(node)
+                            .unwrap()
+                            .iter_children()
+                            .map(|c| (Action::Down, c)),
+                    );
+                }
+                Action::Up => {
+                    let r = match *self.get_op(node).unwrap() {
+                        Op::Binary(op, a, b) => {
+                            self.simplify_binary(op, done[&a], done[&b])?
+                        }
+                        Op::Unary(op, a) => {
+                            self.simplify_unary(op, done[&a])?
+                        }
+                        Op::Select(c, a, b) => {
+                            self.select(done[&c], done[&a], done[&b])?
+                        }
+                        Op::Var(..) | Op::Const(..) | Op::Input(..) => node,
+                    };
+                    done.insert(node, r);
+                }
+            }
+        }
+        let out = done[&root];
+        let after = self.node_count(out)?;
+        Ok((out, before.saturating_sub(after)))
+    }
+
+    /// Builds `op(a)`, where `a` has already been simplified
+    fn simplify_unary(
+        &mut self,
+        op: UnaryOpcode,
+        a: Node,
+    ) -> Result<Node, Error> {
+        let op_a = *self.get_op(a).ok_or(Error::BadNode)?;
+        match (op, op_a) {
+            (UnaryOpcode::Neg, Op::Unary(UnaryOpcode::Neg, x)) => Ok(x),
+            (UnaryOpcode::Neg, Op::Binary(BinaryOpcode::Sub, x, y)) => {
+                self.simplify_binary(BinaryOpcode::Sub, y, x)
+            }
+            (UnaryOpcode::Abs, Op::Unary(UnaryOpcode::Neg, x)) => {
+                self.simplify_unary(UnaryOpcode::Abs, x)
+            }
+            (
+                UnaryOpcode::Abs,
+                Op::Unary(
+                    UnaryOpcode::Abs
+                    | UnaryOpcode::Square
+                    | UnaryOpcode::Sqrt
+                    | UnaryOpcode::Exp,
+                    _,
+                ),
+            ) => Ok(a),
+            (
+                UnaryOpcode::Square,
+                Op::Unary(UnaryOpcode::Neg | UnaryOpcode::Abs, x),
+            ) => self.simplify_unary(UnaryOpcode::Square, x),
+            _ => self.op_unary(a, op),
+        }
+    }
+
+    /// Builds `op(a, b)`, where `a` and `b` have already been simplified
+    fn simplify_binary(
+        &mut self,
+        op: BinaryOpcode,
+        a: Node,
+        b: Node,
+    ) -> Result<Node, Error> {
+        let op_a = *self.get_op(a).ok_or(Error::BadNode)?;
+        let op_b = *self.get_op(b).ok_or(Error::BadNode)?;
+        match op {
+            BinaryOpcode::Add => {
+                if let Op::Unary(UnaryOpcode::Neg, x) = op_a {
+                    return self.simplify_binary(BinaryOpcode::Sub, b, x);
+                } else if let Op::Unary(UnaryOpcode::Neg, y) = op_b {
+                    return self.simplify_binary(BinaryOpcode::Sub, a, y);
+                }
+                let r = match (op_a, op_b) {
+                    (_, Op::Const(c)) => self.reassociate(a, 1.0, c.0)?,
+                    (Op::Const(c), _) => self.reassociate(b, 1.0, c.0)?,
+                    _ => None,
+                };
+                match r {
+                    Some(r) => Ok(r),
+                    None => self.add(a, b),
+                }
+            }
+            BinaryOpcode::Sub => {
+                if a == b {
+                    return Ok(self.constant(0.0));
+                } else if let Op::Unary(UnaryOpcode::Neg, y) = op_b {
+                    return self.simplify_binary(BinaryOpcode::Add, a, y);
+                }
+                let r = match (op_a, op_b) {
+                    (_, Op::Const(c)) => self.reassociate(a, 1.0, -c.0)?,
+                    (Op::Const(c), _) => self.reassociate(b, -1.0, c.0)?,
+                    _ => None,
+                };
+                match r {
+                    Some(r) => Ok(r),
+                    None => self.sub(a, b),
+                }
+            }
+            BinaryOpcode::Mul => {
+                let (c, other, n) = match (op_a, op_b) {
+                    (_, Op::Const(c)) => (c.0, op_a, a),
+                    (Op::Const(c), _) => (c.0, op_b, b),
+                    _ => return self.mul(a, b),
+                };
+                match other {
+                    Op::Binary(BinaryOpcode::Mul, x, y) => {
+                        if let Some(d) = self.const_value(y)? {
+                            return self.mul(x, c * d);
+                        } else if let Some(d) = self.const_value(x)? {
+                            return self.mul(y, c * d);
+                        }
+                    }
+                    Op::Unary(UnaryOpcode::Neg, x) => {
+                        let c = self.constant(-c);
+                        return self.simplify_binary(BinaryOpcode::Mul, x, c);
+                    }
+                    _ => (),
+                }
+                if c == -1.0 {
+                    self.neg(n)
+                } else {
+                    self.mul(a, b)
+                }
+            }
+            op => self.build_binary(op, a, b),
+        }
+    }
+
+    /// Builds `s * n + k`, if `n` is itself a sum or difference involving a
+    /// constant (so that the two constants can be combined)
+    ///
+    /// `s` must be either 1 or -1.  Returns `None` if `n` doesn't have the
+    /// correct form.
+    fn reassociate(
+        &mut self,
+        n: Node,
+        s: f64,
+        k: f64,
+    ) -> Result<Option<Node>, Error> {
+        // Decompose `n` into `t * x + c`
+        let (t, x, c) = match *self.get_op(n).ok_or(Error::BadNode)? {
+            Op::Binary(BinaryOpcode::Add, a, b) => {
+                match (self.const_value(a)?, self.const_value(b)?) {
+                    (Some(c), None) => (1.0, b, c),
+                    (None, Some(c)) => (1.0, a, c),
+                    _ => return Ok(None),
+                }
+            }
+            Op::Binary(BinaryOpcode::Sub, a, b) => {
+                match (self.const_value(a)?, self.const_value(b)?) {
+                    (Some(c), None) => (-1.0, b, c),
+                    (None, Some(c)) => (1.0, a, -c),
+                    _ => return Ok(None),
+                }
+            }
+            _ => return Ok(None),
+        };
+        let c = s * c + k;
+        let out = if s * t > 0.0 {
+            self.add(x, c)?
+        } else {
+            self.sub(c, x)?
+        };
+        Ok(Some(out))
+    }
+
+    /// Returns the number of unique nodes which are reachable from `root`,
+    /// including `root` itself
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let sum = ctx.add(x, 1.0).unwrap();
+    /// let op = ctx.mul(sum, sum).unwrap(); // square(x + 1)
+    /// assert_eq!(ctx.node_count(op).unwrap(), 4);
+    /// ```
+    pub fn node_count(&self, root: Node) -> Result<usize, Error> {
+        self.check_node(root)?;
+        let mut todo = vec![root];
+        let mut seen = BTreeSet::new();
+        while let Some(node) = todo.pop() {
+            if seen.insert(node) {
+                todo.extend(self.get_op(node).unwrap().iter_children());
+            }
+        }
+        Ok(seen.len())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////
+    /// Evaluates the given node with the provided values for X, Y, and Z.
+    ///
+    /// The W input (if present) is set to 0.
+    ///
+    /// This is extremely inefficient; consider calling
+    /// [`get_tape`](Self::get_tape) and building an evaluator instead.
+    ///
+    /// ```
+    /// # let mut ctx = fidget::context::Context::new();
+    /// let x = ctx.x();
+    /// let y = ctx.y();
+    /// let z = ctx.z();
+    /// let op = ctx.mul(x, y).unwrap();
+    /// let op = ctx.div(op, z).unwrap();
+    /// let v = ctx.eval_xyz(op, 3.0, 5.0, 2.0).unwrap();
+    /// assert_eq!(v, 7.5); // (3.0 * 5.0) / 2.0
+    /// ```
+    pub fn eval_xyz(
+        &self,
+        root: Node,
+        x: f64,
+        y: f64,
+        z: f64,
+    ) -> Result<f64, Error> {
+        self.eval_xyzw(root, x, y, z, 0.0)
+    }
+
+    /// Evaluates the given node with the provided values for X, Y, Z, and W.
+    ///
+    /// This is extremely inefficient; consider calling
+    /// [`get_tape`](Self::get_tape) and building an evaluator instead.
+    pub fn eval_xyzw(
+        &self,
+        root: Node,
+        x: f64,
+        y: f64,
+        z: f64,
+        w: f64,
+    ) -> Result<f64, Error> {
+        let vars = [("X", x), ("Y", y), ("Z", z), ("W", w)]
+            .into_iter()
+            .map(|(a, b)| (a.to_string(), b))
+            .collect();
+        self.eval(root, &vars)
+    }
+
+    /// Evaluates the given node with a generic set of variables
+    ///
+    /// This is extremely inefficient; consider calling
+    /// [`get_tape`](Self::get_tape) and building an evaluator instead.
+    pub fn eval(
+        &self,
+        root: Node,
+        vars: &BTreeMap<String, f64>,
+    ) -> Result<f64, Error> {
+        let mut cache = vec![None; self.ops.len()].into();
+        self.eval_inner(root, vars, &mut cache)
+    }
+
+    fn eval_inner(
+        &self,
+        node: Node,
+        vars: &BTreeMap<String, f64>,
+        cache: &mut IndexVec<Option<f64>, Node>,
+    ) -> Result<f64, Error> {
+        if node.0 >= cache.len() {
+            return Err(Error::BadNode);
+        }
         if let Some(v) = cache[node] {
             return Ok(v);
         }
@@ -665,6 +1955,26 @@ impl Context {
                     BinaryOpcode::Div => a / b,
                     BinaryOpcode::Min => a.min(b),
                     BinaryOpcode::Max => a.max(b),
+                    BinaryOpcode::Atan2 => a.atan2(b),
+                    BinaryOpcode::Pow => a.powf(b),
+                    BinaryOpcode::Mod => a.rem_euclid(b),
+                    BinaryOpcode::Lt => (a < b) as u8 as f64,
+                    BinaryOpcode::Gt => (a > b) as u8 as f64,
+                    BinaryOpcode::Eq => (a == b) as u8 as f64,
+                    BinaryOpcode::And => {
+                        if a == 0.0 {
+                            a
+                        } else {
+                            b
+                        }
+                    }
+                    BinaryOpcode::Or => {
+                        if a != 0.0 {
+                            a
+                        } else {
+                            b
+                        }
+                    }
                 }
             }
 
@@ -680,6 +1990,24 @@ impl Context {
                     UnaryOpcode::Exp => a.exp(),
                     UnaryOpcode::Sin => a.sin(),
                     UnaryOpcode::Cos => a.cos(),
+                    UnaryOpcode::Tan => a.tan(),
+                    UnaryOpcode::Asin => a.asin(),
+                    UnaryOpcode::Acos => a.acos(),
+                    UnaryOpcode::Atan => a.atan(),
+                    UnaryOpcode::Ln => a.ln(),
+                    UnaryOpcode::Floor => a.floor(),
+                    UnaryOpcode::Ceil => a.ceil(),
+                    UnaryOpcode::Round => a.round(),
+                    UnaryOpcode::Not => (a == 0.0) as u8 as f64,
+                }
+            }
+
+            // Only the selected branch is evaluated
+            Op::Select(c, a, b) => {
+                if get(*c)? < 0.0 {
+                    get(*a)?
+                } else {
+                    get(*b)?
                 }
             }
         };
@@ -688,75 +2016,6 @@ impl Context {
         Ok(v)
     }
 
-    /// Parses a flat text representation of a math tree. For example, the
-    /// circle `(- (+ (square x) (square y)) 1)` can be parsed from
-    /// ```
-    /// # use fidget::context::Context;
-    /// let txt = "
-    /// ## This is a comment!
-    /// 0x600000b90000 var-x
-    /// 0x600000b900a0 square 0x600000b90000
-    /// 0x600000b90050 var-y
-    /// 0x600000b900f0 square 0x600000b90050
-    /// 0x600000b90140 add 0x600000b900a0 0x600000b900f0
-    /// 0x600000b90190 sqrt 0x600000b90140
-    /// 0x600000b901e0 const 1
-    /// ";
-    /// let (ctx, _node) = Context::from_text(&mut txt.as_bytes()).unwrap();
-    /// assert_eq!(ctx.len(), 7);
-    /// ```
-    ///
-    /// This representation is loosely defined and only intended for use in
-    /// quick experiments.
-    pub fn from_text<R: Read>(r: R) -> Result<(Self, Node), Error> {
-        let reader = BufReader::new(r);
-        let mut ctx = Self::new();
-        let mut seen = BTreeMap::new();
-        let mut last = None;
-
-        for line in reader.lines().map(|line| line.unwrap()) {
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
-            let mut iter = line.split_whitespace();
-            let i: String = iter.next().unwrap().to_owned();
-            let opcode = iter.next().unwrap();
-
-            let mut pop = || {
-                let txt = iter.next().unwrap();
-                seen.get(txt)
-                    .cloned()
-                    .ok_or_else(|| Error::UnknownVariable(txt.to_string()))
-            };
-            let node = match opcode {
-                "const" => ctx.constant(iter.next().unwrap().parse().unwrap()),
-                "var-x" => ctx.x(),
-                "var-y" => ctx.y(),
-                "var-z" => ctx.z(),
-                "abs" => ctx.abs(pop()?)?,
-                "neg" => ctx.neg(pop()?)?,
-                "sqrt" => ctx.sqrt(pop()?)?,
-                "square" => ctx.square(pop()?)?,
-                "exp" => ctx.exp(pop()?)?,
-                "sin" => ctx.sin(pop()?)?,
-                "cos" => ctx.cos(pop()?)?,
-                "add" => ctx.add(pop()?, pop()?)?,
-                "mul" => ctx.mul(pop()?, pop()?)?,
-                "min" => ctx.min(pop()?, pop()?)?,
-                "max" => ctx.max(pop()?, pop()?)?,
-                "div" => ctx.div(pop()?, pop()?)?,
-                "sub" => ctx.sub(pop()?, pop()?)?,
-                op => return Err(Error::UnknownOpcode(op.to_owned())),
-            };
-            seen.insert(i, node);
-            last = Some(node);
-        }
-        match last {
-            Some(node) => Ok((ctx, node)),
-            None => Err(Error::EmptyFile),
-        }
-    }
-    
     /// Calculates the runtime size of the entire context
     pub fn size_of(&self) -> usize {
         self.ops.size_of() + self.vars.size_of()
@@ -787,24 +2046,9 @@ impl Context {
                 let v = self.vars.get_by_index(*v).unwrap();
                 out += v;
             }
-            Op::Binary(op, ..) => match op {
-                BinaryOpcode::Add => out += "add",
-                BinaryOpcode::Sub => out += "sub",
-                BinaryOpcode::Mul => out += "mul",
-                BinaryOpcode::Div => out += "div",
-                BinaryOpcode::Min => out += "min",
-                BinaryOpcode::Max => out += "max",
-            },
-            Op::Unary(op, ..) => match op {
-                UnaryOpcode::Neg => out += "neg",
-                UnaryOpcode::Abs => out += "abs",
-                UnaryOpcode::Recip => out += "recip",
-                UnaryOpcode::Sqrt => out += "sqrt",
-                UnaryOpcode::Square => out += "square",
-                UnaryOpcode::Exp => out += "exp",
-                UnaryOpcode::Sin => out += "sin",
-                UnaryOpcode::Cos => out += "cos",
-            },
+            Op::Binary(op, ..) => out += op.name(),
+            Op::Unary(op, ..) => out += op.name(),
+            Op::Select(..) => out += "select",
         };
         write!(
             out,
@@ -931,5 +2175,417 @@ mod test {
         let one = ctx.constant(3.0);
         let v = ctx.remap_xyz(s, [one, y, z]).unwrap();
         assert_eq!(ctx.eval_xyz(v, 0.0, 1.0, 0.0).unwrap(), 4.0);
+
+        let a = ctx.and(x, y).unwrap();
+        let v = ctx.remap_xyz(a, [one, y, z]).unwrap();
+        assert_eq!(v, y);
+        ctx.get_tape::<crate::vm::Eval>(v).unwrap();
+    }
+
+    #[test]
+    fn test_constant_lhs_and_or() {
+        // Every path which builds nodes goes through `op_binary`, which folds a
+        // constant left-hand argument (tapes can't represent it)
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let zero = ctx.constant(0.0);
+        let two = ctx.constant(2.0);
+        for (op, lhs, expected) in [
+            (BinaryOpcode::And, zero, zero),
+            (BinaryOpcode::And, two, x),
+            (BinaryOpcode::Or, zero, x),
+            (BinaryOpcode::Or, two, two),
+        ] {
+            assert_eq!(ctx.op_binary(lhs, x, op).unwrap(), expected);
+        }
+
+        let y = ctx.y();
+        let s = ctx.and(y, x).unwrap();
+        let s = ctx.substitute(s, &BTreeMap::from([(y, two)])).unwrap();
+        assert_eq!(s, x);
+        ctx.get_tape::<crate::vm::Eval>(s).unwrap();
+    }
+
+    #[test]
+    fn test_substitute() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let r = ctx.var("r").unwrap();
+
+        let x2 = ctx.square(x).unwrap();
+        let y2 = ctx.square(y).unwrap();
+        let sum = ctx.add(x2, y2).unwrap();
+        let len = ctx.sqrt(sum).unwrap();
+        let circle = ctx.sub(len, r).unwrap();
+
+        // Baking a parameter
+        let one = ctx.constant(1.0);
+        let c = ctx.substitute(circle, &BTreeMap::from([(r, one)])).unwrap();
+        assert_eq!(c, ctx.sub(len, 1.0).unwrap());
+
+        // Swapping a subexpression
+        let ax = ctx.abs(x).unwrap();
+        let ay = ctx.abs(y).unwrap();
+        let m = ctx.max(ax, ay).unwrap();
+        let c = ctx.substitute(circle, &BTreeMap::from([(len, m)])).unwrap();
+        assert_eq!(c, ctx.sub(m, r).unwrap());
+
+        // Substitution is simultaneous, so X and Y can be swapped
+        let d = ctx.sub(x, y).unwrap();
+        let swapped = ctx
+            .substitute(d, &BTreeMap::from([(x, y), (y, x)]))
+            .unwrap();
+        assert_eq!(swapped, ctx.sub(y, x).unwrap());
+
+        // Function composition, i.e. circle(2 * x, y)
+        let x2 = ctx.mul(x, 2.0).unwrap();
+        let c = ctx.substitute(circle, &BTreeMap::from([(x, x2)])).unwrap();
+        let vars = [("X", 1.5), ("Y", 4.0), ("r", 2.0)]
+            .into_iter()
+            .map(|(k, v)| (k.to_owned(), v))
+            .collect();
+        assert_eq!(ctx.eval(c, &vars).unwrap(), 3.0);
+
+        // Substituting constants folds the whole tree
+        let zero = ctx.constant(0.0);
+        let three = ctx.constant(3.0);
+        let c = ctx
+            .substitute(circle, &BTreeMap::from([(x, three), (y, zero)]))
+            .unwrap();
+        let v = ctx.substitute(c, &BTreeMap::from([(r, one)])).unwrap();
+        assert_eq!(ctx.const_value(v).unwrap(), Some(2.0));
+
+        let bad = BTreeMap::from([(x, Node(100))]);
+        assert!(matches!(ctx.substitute(circle, &bad), Err(Error::BadNode)));
+    }
+
+    #[test]
+    fn test_bind_vars() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let a = ctx.var("a").unwrap();
+        let b = ctx.var("b").unwrap();
+
+        // select(a, x * b, min(y, b + 1))
+        let xb = ctx.mul(x, b).unwrap();
+        let b1 = ctx.add(b, 1.0).unwrap();
+        let m = ctx.min(y, b1).unwrap();
+        let op = ctx.select(a, xb, m).unwrap();
+
+        let values = |a: f64, b: f64| {
+            BTreeMap::from([("a".to_owned(), a), ("b".to_owned(), b)])
+        };
+
+        let v = ctx.bind_vars(op, &values(-1.0, 2.0)).unwrap();
+        assert_eq!(v, ctx.mul(x, 2.0).unwrap());
+
+        let v = ctx.bind_vars(op, &values(1.0, 2.0)).unwrap();
+        assert_eq!(v, ctx.min(y, 3.0).unwrap());
+
+        let full = ctx.get_tape::<crate::vm::Eval>(op).unwrap();
+        let baked = ctx.get_tape::<crate::vm::Eval>(v).unwrap();
+        assert!(baked.len() < full.len());
+        assert_eq!(baked.var_count(), 0);
+
+        // Binding a subset of variables leaves the others live
+        let partial = BTreeMap::from([("b".to_owned(), 0.5)]);
+        let v = ctx.bind_vars(op, &partial).unwrap();
+        for (px, py, pa) in [(1.0, 2.0, -1.0), (3.0, -4.0, 1.0)] {
+            let mut vars = values(pa, 0.5);
+            vars.insert("X".to_owned(), px);
+            vars.insert("Y".to_owned(), py);
+            let expected = ctx.eval(op, &vars).unwrap();
+            assert_eq!(ctx.eval(v, &vars).unwrap(), expected);
+        }
+
+        let bad = BTreeMap::from([("c".to_owned(), 1.0)]);
+        assert!(matches!(
+            ctx.bind_vars(op, &bad),
+            Err(Error::UnknownVariable(s)) if s == "c"
+        ));
+        let bad = BTreeMap::from([("X".to_owned(), 1.0)]);
+        assert!(ctx.bind_vars(op, &bad).is_err());
+    }
+
+    #[test]
+    fn test_bind_vars_prune() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let r = ctx.var("r").unwrap();
+        let values = |r: f64| BTreeMap::from([("r".to_owned(), r)]);
+
+        // x² + 1 is always at least 1, so it's never less than r = 0.5
+        let x2 = ctx.square(x).unwrap();
+        let x2 = ctx.add(x2, 1.0).unwrap();
+        let op = ctx.min(x2, r).unwrap();
+        let v = ctx.bind_vars(op, &values(0.5)).unwrap();
+        assert_eq!(v, ctx.constant(0.5));
+        let v = ctx.bind_vars(op, &values(2.0)).unwrap();
+        assert_eq!(v, ctx.min(x2, 2.0).unwrap());
+
+        // |y| - 2 is always at least -2, so the inner `max` is pruned
+        let ay = ctx.abs(y).unwrap();
+        let ry = ctx.sub(ay, r).unwrap();
+        let inner = ctx.max(ry, -3.0).unwrap();
+        let op = ctx.max(x2, inner).unwrap();
+        let v = ctx.bind_vars(op, &values(2.0)).unwrap();
+        let expected = ctx.sub(ay, 2.0).unwrap();
+        assert_eq!(v, ctx.max(x2, expected).unwrap());
+        let v = ctx.bind_vars(op, &values(4.0)).unwrap();
+        let expected = ctx.sub(ay, 4.0).unwrap();
+        let expected = ctx.max(expected, -3.0).unwrap();
+        assert_eq!(v, ctx.max(x2, expected).unwrap());
+
+        // Choices which can't be made everywhere are left alone
+        let op = ctx.min(x, r).unwrap();
+        let v = ctx.bind_vars(op, &values(1.0)).unwrap();
+        assert_eq!(v, ctx.min(x, 1.0).unwrap());
+        let z = ctx.z();
+        let lt = ctx.lt(x, z).unwrap();
+        let op = ctx.and(lt, r).unwrap();
+        let v = ctx.bind_vars(op, &values(1.0)).unwrap();
+        assert_eq!(v, ctx.and(lt, 1.0).unwrap());
+    }
+
+    #[test]
+    fn test_deriv() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+
+        let x2 = ctx.square(x).unwrap();
+        let y2 = ctx.square(y).unwrap();
+        let r = ctx.add(x2, y2).unwrap();
+        let r = ctx.sqrt(r).unwrap();
+        let xy = ctx.mul(x, y).unwrap();
+        let sin_x = ctx.sin(xy).unwrap();
+        let exprs = [
+            r,
+            sin_x,
+            ctx.div(x, y).unwrap(),
+            ctx.atan2(y, x).unwrap(),
+            ctx.pow(x, y).unwrap(),
+            ctx.powi(x, -3).unwrap(),
+            ctx.modulo(x, y).unwrap(),
+            ctx.min(x2, y).unwrap(),
+            ctx.max(x2, y).unwrap(),
+            ctx.abs(xy).unwrap(),
+            ctx.select(x, y2, xy).unwrap(),
+            ctx.and(x, y2).unwrap(),
+            ctx.or(x, y2).unwrap(),
+            ctx.tan(xy).unwrap(),
+            ctx.atan(xy).unwrap(),
+            ctx.asin(sin_x).unwrap(),
+            ctx.ln(r).unwrap(),
+            ctx.exp(sin_x).unwrap(),
+            ctx.recip(r).unwrap(),
+        ];
+        for (i, e) in exprs.into_iter().enumerate() {
+            let dx = ctx.deriv(e, x).unwrap();
+            let dy = ctx.deriv(e, y).unwrap();
+            ctx.get_tape::<crate::vm::Eval>(dx).unwrap();
+            for (px, py) in [(0.7, 1.3), (1.9, 0.4), (-0.3, 0.8)] {
+                let h = 1e-6;
+                let f = |x, y| ctx.eval_xyz(e, x, y, 0.0).unwrap();
+                let fdx = (f(px + h, py) - f(px - h, py)) / (2.0 * h);
+                let fdy = (f(px, py + h) - f(px, py - h)) / (2.0 * h);
+                let vdx = ctx.eval_xyz(dx, px, py, 0.0).unwrap();
+                let vdy = ctx.eval_xyz(dy, px, py, 0.0).unwrap();
+                if fdx.is_nan() {
+                    continue;
+                }
+                assert!((fdx - vdx).abs() < 1e-4, "{i}: {fdx} != {vdx}");
+                assert!((fdy - vdy).abs() < 1e-4, "{i}: {fdy} != {vdy}");
+            }
+        }
+
+        // Derivatives with respect to other variables are constant zero
+        let z = ctx.z();
+        let dz = ctx.deriv(r, z).unwrap();
+        assert_eq!(ctx.const_value(dz).unwrap(), Some(0.0));
+
+        // Derivatives can be taken with respect to a `Var`
+        let a = ctx.var("a").unwrap();
+        let ax = ctx.mul(a, x).unwrap();
+        let e = ctx.mul(ax, a).unwrap();
+        let da = ctx.deriv(e, a).unwrap();
+        let vars = [("X", 3.0), ("a", 2.0)]
+            .into_iter()
+            .map(|(k, v)| (k.to_owned(), v))
+            .collect();
+        assert_eq!(ctx.eval(da, &vars).unwrap(), 12.0);
+
+        assert!(matches!(ctx.deriv(r, sin_x), Err(Error::NotAVar)));
+    }
+
+    #[test]
+    fn test_import() {
+        let mut a = Context::new();
+        let x = a.x();
+        let y = a.y();
+        let r = a.var("r").unwrap();
+        let x2 = a.square(x).unwrap();
+        let y2 = a.square(y).unwrap();
+        let sum = a.add(x2, y2).unwrap();
+        let sum = a.sqrt(sum).unwrap();
+        let circle = a.sub(sum, r).unwrap();
+        let z = a.z();
+        let cyl = a.select(z, circle, 1.0).unwrap();
+
+        // Build part of the same shape in a different order, so that node
+        // indexes don't match between the two contexts
+        let mut b = Context::new();
+        let r_b = b.var("r").unwrap();
+        let y = b.y();
+        let x = b.x();
+        let y2 = b.square(y).unwrap();
+        let x2 = b.square(x).unwrap();
+        let sum = b.add(y2, x2).unwrap();
+        let len = b.len();
+
+        let out = b.import(&a, sum).unwrap();
+        assert_eq!(out, sum);
+        assert_eq!(b.len(), len);
+
+        let out = b.import(&a, circle).unwrap();
+        let sum = b.sqrt(sum).unwrap();
+        assert_eq!(out, b.sub(sum, r_b).unwrap());
+
+        let out = b.import(&a, cyl).unwrap();
+        let vars = [("X", 3.0), ("Y", 4.0), ("Z", -1.0), ("r", 2.0)]
+            .into_iter()
+            .map(|(k, v)| (k.to_owned(), v))
+            .collect();
+        assert_eq!(a.eval(cyl, &vars).unwrap(), 3.0);
+        assert_eq!(b.eval(out, &vars).unwrap(), 3.0);
+
+        assert!(matches!(b.import(&a, Node(100)), Err(Error::BadNode)));
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let a = ctx.var("a").unwrap();
+        let b = ctx.var("b").unwrap();
+        let xa = ctx.mul(x, a).unwrap();
+        ctx.sin(xa).unwrap();
+        let yb = ctx.add(y, b).unwrap();
+        let op = ctx.max(yb, x).unwrap();
+        let op2 = ctx.sub(yb, 1.0).unwrap();
+        assert_eq!(ctx.len(), 10);
+
+        let vars = [("X", 1.5), ("Y", -3.0), ("a", 1.0), ("b", 2.5)]
+            .into_iter()
+            .map(|(k, v)| (k.to_owned(), v))
+            .collect();
+        let before = ctx.eval(op, &vars).unwrap();
+        let before2 = ctx.eval(op2, &vars).unwrap();
+
+        let out = ctx.compact(&[op, op2, op]).unwrap();
+        assert_eq!(out.len(), 3);
+        assert_eq!(out[0], out[2]);
+        assert_eq!(ctx.len(), 7); // x, y, b, yb, op, 1.0, op2
+        assert_eq!(ctx.eval(out[0], &vars).unwrap(), before);
+        assert_eq!(ctx.eval(out[1], &vars).unwrap(), before2);
+
+        // Deduplication still works after compaction
+        let x = ctx.x();
+        let y = ctx.y();
+        let b = ctx.var("b").unwrap();
+        let yb = ctx.add(b, y).unwrap();
+        assert_eq!(ctx.max(x, yb).unwrap(), out[0]);
+        assert_eq!(ctx.len(), 7);
+
+        // The unused variable `a` was removed
+        assert_eq!(ctx.var_name(b).unwrap(), Some("b"));
+        assert_eq!(ctx.vars.len(), 3);
+
+        assert!(matches!(ctx.compact(&[Node(100)]), Err(Error::BadNode)));
+        assert_eq!(ctx.len(), 7);
+    }
+
+    #[test]
+    fn test_simplify() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+
+        let nx = ctx.neg(x).unwrap();
+        let nnx = ctx.neg(nx).unwrap();
+        assert_eq!(ctx.simplify(nnx).unwrap().0, x);
+
+        let sx = ctx.square(nx).unwrap();
+        let asx = ctx.abs(sx).unwrap();
+        let s = ctx.simplify(asx).unwrap().0;
+        assert_eq!(s, ctx.square(x).unwrap());
+
+        let d = ctx.sub(x, x).unwrap();
+        let s = ctx.simplify(d).unwrap().0;
+        assert_eq!(ctx.const_value(s).unwrap(), Some(0.0));
+
+        // x + -x is rewritten to x - x, then to zero
+        let d = ctx.add(x, nx).unwrap();
+        let s = ctx.simplify(d).unwrap().0;
+        assert_eq!(ctx.const_value(s).unwrap(), Some(0.0));
+
+        // neg(x - y) is rewritten to y - x
+        let d = ctx.sub(x, y).unwrap();
+        let nd = ctx.neg(d).unwrap();
+        let (s, removed) = ctx.simplify(nd).unwrap();
+        assert_eq!(s, ctx.sub(y, x).unwrap());
+        assert_eq!(removed, 1);
+
+        let d = ctx.sub(y, nx).unwrap();
+        let s = ctx.simplify(d).unwrap().0;
+        assert_eq!(s, ctx.add(x, y).unwrap());
+
+        let m = ctx.mul(nx, -1.0).unwrap();
+        assert_eq!(ctx.simplify(m).unwrap().0, x);
+
+        let m = ctx.mul(y, -1.0).unwrap();
+        assert_eq!(ctx.simplify(m).unwrap().0, ctx.neg(y).unwrap());
+
+        // Constant reassociation
+        let a = ctx.sub(x, 2.0).unwrap();
+        let a = ctx.add(a, 5.0).unwrap();
+        let s = ctx.simplify(a).unwrap().0;
+        assert_eq!(s, ctx.add(x, 3.0).unwrap());
+
+        let a = ctx.sub(1.0, x).unwrap();
+        let a = ctx.sub(a, 4.0).unwrap();
+        let s = ctx.simplify(a).unwrap().0;
+        assert_eq!(s, ctx.sub(-3.0, x).unwrap());
+
+        let a = ctx.mul(x, 2.0).unwrap();
+        let a = ctx.mul(a, 3.0).unwrap();
+        let s = ctx.simplify(a).unwrap().0;
+        assert_eq!(s, ctx.mul(x, 6.0).unwrap());
+
+        // Rewrites that expose `min(a, a)` are folded by the constructor
+        let nny = ctx.neg(y).unwrap();
+        let nny = ctx.neg(nny).unwrap();
+        let m = ctx.min(y, nny).unwrap();
+        assert_eq!(ctx.simplify(m).unwrap().0, y);
+    }
+
+    #[test]
+    fn test_simplify_prospero() {
+        const PROSPERO: &str = include_str!("../../../../models/prospero.vm");
+        let (mut ctx, root) = Context::from_text(PROSPERO.as_bytes()).unwrap();
+        let (s, removed) = ctx.simplify(root).unwrap();
+        let before = ctx.node_count(root).unwrap();
+        assert!(removed > 0);
+        assert_eq!(ctx.node_count(s).unwrap(), before - removed);
+
+        for (x, y) in [(0.1, 0.2), (-0.5, 0.7), (0.9, -0.3), (-0.2, -0.8)] {
+            let a = ctx.eval_xyz(root, x, y, 0.0).unwrap();
+            let b = ctx.eval_xyz(s, x, y, 0.0).unwrap();
+            assert!((a - b).abs() < 1e-9, "{a} != {b}");
+        }
     }
 }
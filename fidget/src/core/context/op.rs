@@ -13,6 +13,15 @@ pub enum UnaryOpcode {
     Exp,
     Sin,
     Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Ln,
+    Floor,
+    Ceil,
+    Round,
+    Not,
 }
 
 /// A two-argument math operation
@@ -25,6 +34,114 @@ pub enum BinaryOpcode {
     Div,
     Min,
     Max,
+    Atan2,
+    Pow,
+    Mod,
+    Lt,
+    Gt,
+    Eq,
+    And,
+    Or,
+}
+
+impl UnaryOpcode {
+    /// Returns the name of this opcode (e.g. `"sqrt"`)
+    ///
+    /// This is used in GraphViz drawings and the text format read by
+    /// [`Context::from_text`](crate::context::Context::from_text).
+    pub fn name(&self) -> &'static str {
+        match self {
+            UnaryOpcode::Neg => "neg",
+            UnaryOpcode::Abs => "abs",
+            UnaryOpcode::Recip => "recip",
+            UnaryOpcode::Sqrt => "sqrt",
+            UnaryOpcode::Square => "square",
+            UnaryOpcode::Exp => "exp",
+            UnaryOpcode::Sin => "sin",
+            UnaryOpcode::Cos => "cos",
+            UnaryOpcode::Tan => "tan",
+            UnaryOpcode::Asin => "asin",
+            UnaryOpcode::Acos => "acos",
+            UnaryOpcode::Atan => "atan",
+            UnaryOpcode::Ln => "ln",
+            UnaryOpcode::Floor => "floor",
+            UnaryOpcode::Ceil => "ceil",
+            UnaryOpcode::Round => "round",
+            UnaryOpcode::Not => "not",
+        }
+    }
+
+    /// Looks up an opcode by the name returned from [`UnaryOpcode::name`]
+    pub fn from_name(name: &str) -> Option<Self> {
+        let op = match name {
+            "neg" => UnaryOpcode::Neg,
+            "abs" => UnaryOpcode::Abs,
+            "recip" => UnaryOpcode::Recip,
+            "sqrt" => UnaryOpcode::Sqrt,
+            "square" => UnaryOpcode::Square,
+            "exp" => UnaryOpcode::Exp,
+            "sin" => UnaryOpcode::Sin,
+            "cos" => UnaryOpcode::Cos,
+            "tan" => UnaryOpcode::Tan,
+            "asin" => UnaryOpcode::Asin,
+            "acos" => UnaryOpcode::Acos,
+            "atan" => UnaryOpcode::Atan,
+            "ln" => UnaryOpcode::Ln,
+            "floor" => UnaryOpcode::Floor,
+            "ceil" => UnaryOpcode::Ceil,
+            "round" => UnaryOpcode::Round,
+            "not" => UnaryOpcode::Not,
+            _ => return None,
+        };
+        Some(op)
+    }
+}
+
+impl BinaryOpcode {
+    /// Returns the name of this opcode (e.g. `"add"`)
+    ///
+    /// This is used in GraphViz drawings and the text format read by
+    /// [`Context::from_text`](crate::context::Context::from_text).
+    pub fn name(&self) -> &'static str {
+        match self {
+            BinaryOpcode::Add => "add",
+            BinaryOpcode::Sub => "sub",
+            BinaryOpcode::Mul => "mul",
+            BinaryOpcode::Div => "div",
+            BinaryOpcode::Min => "min",
+            BinaryOpcode::Max => "max",
+            BinaryOpcode::Atan2 => "atan2",
+            BinaryOpcode::Pow => "pow",
+            BinaryOpcode::Mod => "mod",
+            BinaryOpcode::Lt => "lt",
+            BinaryOpcode::Gt => "gt",
+            BinaryOpcode::Eq => "eq",
+            BinaryOpcode::And => "and",
+            BinaryOpcode::Or => "or",
+        }
+    }
+
+    /// Looks up an opcode by the name returned from [`BinaryOpcode::name`]
+    pub fn from_name(name: &str) -> Option<Self> {
+        let op = match name {
+            "add" => BinaryOpcode::Add,
+            "sub" => BinaryOpcode::Sub,
+            "mul" => BinaryOpcode::Mul,
+            "div" => BinaryOpcode::Div,
+            "min" => BinaryOpcode::Min,
+            "max" => BinaryOpcode::Max,
+            "atan2" => BinaryOpcode::Atan2,
+            "pow" => BinaryOpcode::Pow,
+            "mod" => BinaryOpcode::Mod,
+            "lt" => BinaryOpcode::Lt,
+            "gt" => BinaryOpcode::Gt,
+            "eq" => BinaryOpcode::Eq,
+            "and" => BinaryOpcode::And,
+            "or" => BinaryOpcode::Or,
+            _ => return None,
+        };
+        Some(op)
+    }
 }
 
 /// An operation in a math expression.
@@ -44,6 +161,9 @@ pub enum Op {
     Const(OrderedFloat<f64>),
     Binary(BinaryOpcode, Node, Node),
     Unary(UnaryOpcode, Node),
+    /// Picks the second node if the first is less than zero, or the third
+    /// node otherwise
+    Select(Node, Node, Node),
 }
 
 fn dot_color_to_rgb(s: &str) -> &'static str {
@@ -62,9 +182,14 @@ impl Op {
         match self {
             Op::Const(..) => "green",
             Op::Var(..) | Op::Input(..) => "red",
-            Op::Binary(BinaryOpcode::Min | BinaryOpcode::Max, ..) => {
-                "dodgerblue"
-            }
+            Op::Binary(
+                BinaryOpcode::Min
+                | BinaryOpcode::Max
+                | BinaryOpcode::And
+                | BinaryOpcode::Or,
+                ..,
+            )
+            | Op::Select(..) => "dodgerblue",
             Op::Binary(..) | Op::Unary(..) => "goldenrod",
         }
     }
@@ -74,16 +199,17 @@ impl Op {
         match self {
             Op::Const(..) => "oval",
             Op::Var(..) | Op::Input(..) => "circle",
-            Op::Binary(..) | Op::Unary(..) => "box",
+            Op::Binary(..) | Op::Unary(..) | Op::Select(..) => "box",
         }
     }
 
-    /// Iterates over children, producing 0, 1, or 2 values
+    /// Iterates over children, producing 0, 1, 2, or 3 values
     pub fn iter_children(&self) -> impl Iterator<Item = Node> {
         let out = match self {
-            Op::Binary(_, a, b) => [Some(*a), Some(*b)],
-            Op::Unary(_, a) => [Some(*a), None],
-            Op::Var(..) | Op::Input(..) | Op::Const(..) => [None, None],
+            Op::Select(c, a, b) => [Some(*c), Some(*a), Some(*b)],
+            Op::Binary(_, a, b) => [Some(*a), Some(*b), None],
+            Op::Unary(_, a) => [Some(*a), None, None],
+            Op::Var(..) | Op::Input(..) | Op::Const(..) => [None, None, None],
         };
         out.into_iter().flatten()
     }
@@ -0,0 +1,404 @@
+//! Reading and writing the flat text format used by `models/*.vm`
+use crate::{
+    context::{BinaryOpcode, Context, Node, Op, UnaryOpcode},
+    Error,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{BufRead, BufReader, Read, Write},
+};
+
+impl Context {
+    /// Parses a flat text representation of a math tree. For example, the
+    /// circle `(- (+ (square x) (square y)) 1)` can be parsed from
+    /// ```
+    /// # use fidget::context::Context;
+    /// let txt = "
+    /// ## This is a comment!
+    /// 0x600000b90000 var-x
+    /// 0x600000b900a0 square 0x600000b90000
+    /// 0x600000b90050 var-y
+    /// 0x600000b900f0 square 0x600000b90050
+    /// 0x600000b90140 add 0x600000b900a0 0x600000b900f0
+    /// 0x600000b90190 sqrt 0x600000b90140
+    /// 0x600000b901e0 const 1
+    /// ";
+    /// let (ctx, _node) = Context::from_text(&mut txt.as_bytes()).unwrap();
+    /// assert_eq!(ctx.len(), 7);
+    /// ```
+    ///
+    /// Each line defines a node, as an identifier followed by an opcode and
+    /// its arguments (which are the identifiers of earlier nodes).  Opcodes
+    /// are `const <value>`, `var-x/y/z/w` for the input axes, `var-<name>` for
+    /// other variables, `select`, `log2` (see [`Context::log2`]), or the names
+    /// of [`UnaryOpcode`](UnaryOpcode::name) and
+    /// [`BinaryOpcode`](BinaryOpcode::name) values.  Blank lines and lines
+    /// starting with `#` are ignored, and the last node is returned.
+    ///
+    /// Malformed input returns an [`Error::TextError`], which wraps the
+    /// underlying error with its (1-indexed) line and column.
+    pub fn from_text<R: Read>(r: R) -> Result<(Self, Node), Error> {
+        let reader = BufReader::new(r);
+        let mut ctx = Self::new();
+        let mut seen = BTreeMap::new();
+        let mut last = None;
+
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let mut line = Line::new(&line, i + 1);
+            let Some((id_col, id)) = line.tokens.first().cloned() else {
+                continue;
+            };
+            if id.starts_with('#') {
+                continue;
+            }
+            line.pos += 1;
+            let node = line.parse_node(&mut ctx, &seen)?;
+            if let Some((col, t)) = line.tokens.get(line.pos) {
+                return Err(
+                    line.error(*col, Error::UnexpectedToken(t.to_string()))
+                );
+            }
+            if seen.insert(id.to_owned(), node).is_some() {
+                return Err(line.error(id_col, Error::DuplicateName));
+            }
+            last = Some(node);
+        }
+        match last {
+            Some(node) => Ok((ctx, node)),
+            None => Err(Error::EmptyFile),
+        }
+    }
+
+    /// Writes the subgraph rooted at `root` in the text format read by
+    /// [`Context::from_text`]
+    ///
+    /// Nodes are named `_0`, `_1`, etc, and the root is written last.
+    /// Constants are written with enough precision to be read back exactly.
+    ///
+    /// Variables with names that can't be represented in the text format
+    /// (which are empty, contain whitespace, or are one of `x/y/z/w`) return
+    /// [`Error::BadVarName`].
+    ///
+    /// ```
+    /// # use fidget::context::Context;
+    /// let mut ctx = Context::new();
+    /// let x = ctx.x();
+    /// let r = ctx.recip(x).unwrap();
+    /// let out = ctx.add(r, 0.5).unwrap();
+    ///
+    /// let mut txt = vec![];
+    /// ctx.write_text(out, &mut txt).unwrap();
+    /// assert_eq!(
+    ///     std::str::from_utf8(&txt).unwrap(),
+    ///     "_0 var-x\n_1 recip _0\n_2 const 0.5\n_3 add _1 _2\n"
+    /// );
+    /// ```
+    pub fn write_text<W: Write>(
+        &self,
+        root: Node,
+        mut w: W,
+    ) -> Result<(), Error> {
+        self.check_node(root)?;
+
+        // Children are always created before their parents, so iterating in
+        // node order writes them first (and finishes with the root).
+        let mut nodes = BTreeSet::new();
+        let mut todo = vec![root];
+        while let Some(node) = todo.pop() {
+            if nodes.insert(node) {
+                todo.extend(self.get_op(node).unwrap().iter_children());
+            }
+        }
+        let ids: BTreeMap<Node, usize> =
+            nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+
+        for (i, node) in nodes.iter().enumerate() {
+            write!(w, "_{i} ")?;
+            match *self.get_op(*node).unwrap() {
+                Op::Input(v) => {
+                    let name = match self.get_var_by_index(v)? {
+                        "X" => "x",
+                        "Y" => "y",
+                        "Z" => "z",
+                        "W" => "w",
+                        name => return Err(Error::BadVarName(name.to_owned())),
+                    };
+                    writeln!(w, "var-{name}")?;
+                }
+                Op::Var(v) => {
+                    let name = self.get_var_by_index(v)?;
+                    if name.is_empty()
+                        || name.contains(char::is_whitespace)
+                        || matches!(name, "x" | "y" | "z" | "w")
+                    {
+                        return Err(Error::BadVarName(name.to_owned()));
+                    }
+                    writeln!(w, "var-{name}")?;
+                }
+                // `f64`'s `Display` implementation round-trips exactly
+                Op::Const(c) => writeln!(w, "const {}", c.0)?,
+                Op::Unary(op, a) => {
+                    writeln!(w, "{} _{}", op.name(), ids[&a])?;
+                }
+                Op::Binary(op, a, b) => {
+                    writeln!(w, "{} _{} _{}", op.name(), ids[&a], ids[&b])?;
+                }
+                Op::Select(c, a, b) => {
+                    writeln!(
+                        w,
+                        "select _{} _{} _{}",
+                        ids[&c], ids[&a], ids[&b]
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A single line of text, split into whitespace-separated tokens
+struct Line<'a> {
+    /// Tokens and their (1-indexed) starting columns
+    tokens: Vec<(usize, &'a str)>,
+    /// Index of the next token to be consumed
+    pos: usize,
+    /// Line number (1-indexed)
+    line: usize,
+    /// Column just past the end of the line, used when a token is missing
+    end: usize,
+}
+
+impl<'a> Line<'a> {
+    fn new(text: &'a str, line: usize) -> Self {
+        let mut tokens = vec![];
+        let mut start = None;
+        let mut end = 1;
+        for (col, (i, c)) in (1..).zip(text.char_indices()) {
+            match (c.is_whitespace(), start) {
+                (false, None) => start = Some((col, i)),
+                (true, Some((col, s))) => {
+                    tokens.push((col, &text[s..i]));
+                    start = None;
+                }
+                _ => (),
+            }
+            end = col + 1;
+        }
+        if let Some((col, s)) = start {
+            tokens.push((col, &text[s..]));
+        }
+        Self {
+            tokens,
+            pos: 0,
+            line,
+            end,
+        }
+    }
+
+    /// Wraps an error with a position in this line
+    fn error(&self, column: usize, error: Error) -> Error {
+        Error::TextError {
+            line: self.line,
+            column,
+            error: Box::new(error),
+        }
+    }
+
+    /// Returns the next token, or an error mentioning `what` was expected
+    fn next(&mut self, what: &'static str) -> Result<(usize, &'a str), Error> {
+        let t =
+            self.tokens.get(self.pos).cloned().ok_or_else(|| {
+                self.error(self.end, Error::ExpectedToken(what))
+            })?;
+        self.pos += 1;
+        Ok(t)
+    }
+
+    /// Reads a node identifier, looking it up in `seen`
+    fn arg(&mut self, seen: &BTreeMap<String, Node>) -> Result<Node, Error> {
+        let (col, txt) = self.next("argument")?;
+        seen.get(txt).cloned().ok_or_else(|| {
+            self.error(col, Error::UnknownVariable(txt.to_owned()))
+        })
+    }
+
+    /// Parses an opcode and its arguments, building a node in `ctx`
+    fn parse_node(
+        &mut self,
+        ctx: &mut Context,
+        seen: &BTreeMap<String, Node>,
+    ) -> Result<Node, Error> {
+        let (col, opcode) = self.next("opcode")?;
+        let out = match opcode {
+            "const" => {
+                let (col, txt) = self.next("constant")?;
+                let v = txt.parse().map_err(|_| {
+                    self.error(col, Error::BadNumber(txt.to_owned()))
+                })?;
+                Ok(ctx.constant(v))
+            }
+            "var-x" => Ok(ctx.x()),
+            "var-y" => Ok(ctx.y()),
+            "var-z" => Ok(ctx.z()),
+            "var-w" => Ok(ctx.w()),
+            "log2" => {
+                let a = self.arg(seen)?;
+                ctx.log2(a)
+            }
+            "select" => {
+                let c = self.arg(seen)?;
+                let a = self.arg(seen)?;
+                let b = self.arg(seen)?;
+                ctx.select(c, a, b)
+            }
+            op => {
+                if let Some(op) = UnaryOpcode::from_name(op) {
+                    let a = self.arg(seen)?;
+                    ctx.op_unary(a, op)
+                } else if let Some(op) = BinaryOpcode::from_name(op) {
+                    let a = self.arg(seen)?;
+                    let b = self.arg(seen)?;
+                    ctx.build_binary(op, a, b)
+                } else {
+                    match op.strip_prefix("var-") {
+                        Some(name) if !name.is_empty() => ctx.var(name),
+                        _ => Err(Error::UnknownOpcode(op.to_owned())),
+                    }
+                }
+            }
+        };
+        out.map_err(|e| self.error(col, e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Checks that a model survives a round-trip through `write_text`
+    fn check_round_trip(txt: &str) {
+        let (ctx, root) = Context::from_text(txt.as_bytes()).unwrap();
+        let mut out = vec![];
+        ctx.write_text(root, &mut out).unwrap();
+
+        let (next, next_root) = Context::from_text(out.as_slice()).unwrap();
+        assert_eq!(next.len(), ctx.len());
+        assert_eq!(
+            next.node_count(next_root).unwrap(),
+            ctx.node_count(root).unwrap()
+        );
+        for (x, y, z) in [(0.1, 0.2, 0.3), (-0.5, 0.7, 0.0), (0.9, -0.3, -2.0)]
+        {
+            let vars = [("X", x), ("Y", y), ("Z", z), ("W", 0.5), ("a", 1.5)]
+                .into_iter()
+                .map(|(a, b)| (a.to_string(), b))
+                .collect();
+            let a = ctx.eval(root, &vars).unwrap();
+            let b = next.eval(next_root, &vars).unwrap();
+            assert_eq!(a, b);
+        }
+
+        // Writing the result again should give the same text
+        let mut again = vec![];
+        next.write_text(next_root, &mut again).unwrap();
+        assert_eq!(out, again);
+    }
+
+    #[test]
+    fn test_round_trip_models() {
+        check_round_trip(include_str!("../../../../models/prospero.vm"));
+        check_round_trip(include_str!("../../../../models/colonnade.vm"));
+    }
+
+    #[test]
+    fn test_round_trip_ops() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let w = ctx.w();
+        let a = ctx.var("a").unwrap();
+        let r = ctx.recip(x).unwrap();
+        let p = ctx.pow(r, a).unwrap();
+        let lt = ctx.lt(x, w).unwrap();
+        let c = ctx.constant(0.1 + 0.2);
+        let sel = ctx.select(lt, p, c).unwrap();
+        let out = ctx.atan2(sel, 1e-300).unwrap();
+
+        let mut txt = vec![];
+        ctx.write_text(out, &mut txt).unwrap();
+        let txt = String::from_utf8(txt).unwrap();
+        assert!(txt.contains("var-a\n"));
+        assert!(txt.contains("var-w\n"));
+        assert!(txt.contains("recip"));
+        check_round_trip(&txt);
+
+        assert!(txt.contains("const 0.30000000000000004\n"));
+
+        let mut ctx = Context::new();
+        let bad = ctx.var("has space").unwrap();
+        assert!(matches!(
+            ctx.write_text(bad, vec![]),
+            Err(Error::BadVarName(..))
+        ));
+        let bad = ctx.var("x").unwrap();
+        assert!(matches!(
+            ctx.write_text(bad, vec![]),
+            Err(Error::BadVarName(..))
+        ));
+    }
+
+    /// Parses the text, expecting an error at the given position
+    fn check_error(txt: &str, line: usize, column: usize) -> Error {
+        match Context::from_text(txt.as_bytes()) {
+            Err(Error::TextError {
+                line: a,
+                column: b,
+                error,
+            }) => {
+                assert_eq!((a, b), (line, column), "bad position for {error}");
+                *error
+            }
+            Err(e) => panic!("unexpected error {e}"),
+            Ok(..) => panic!("expected an error"),
+        }
+    }
+
+    #[test]
+    fn test_log2() {
+        let txt = "_0 var-x\n_1 log2 _0";
+        let (ctx, node) = Context::from_text(txt.as_bytes()).unwrap();
+        assert_eq!(ctx.eval_xyz(node, 0.25, 0.0, 0.0).unwrap(), -2.0);
+    }
+
+    #[test]
+    fn test_text_errors() {
+        let e = check_error("_0 var-x\n_1 frob _0", 2, 4);
+        assert!(matches!(e, Error::UnknownOpcode(s) if s == "frob"));
+
+        let e = check_error("_0 var-x\n\n  _1 add _0 _2", 3, 13);
+        assert!(matches!(e, Error::UnknownVariable(s) if s == "_2"));
+
+        let e = check_error("_0 var-x\n_1 add _0", 2, 10);
+        assert!(matches!(e, Error::ExpectedToken("argument")));
+
+        let e = check_error("_0", 1, 3);
+        assert!(matches!(e, Error::ExpectedToken("opcode")));
+
+        let e = check_error("# comment\n_0 const 1.2.3", 2, 10);
+        assert!(matches!(e, Error::BadNumber(s) if s == "1.2.3"));
+
+        let e = check_error("_0 var-x\n_1 neg _0 _0", 2, 11);
+        assert!(matches!(e, Error::UnexpectedToken(s) if s == "_0"));
+
+        let e = check_error("_0 var-x\n_0 var-y", 2, 1);
+        assert!(matches!(e, Error::DuplicateName));
+
+        let e = check_error("_0 var-X", 1, 4);
+        assert!(matches!(e, Error::ReservedName));
+
+        assert!(matches!(
+            Context::from_text("# nothing here\n".as_bytes()),
+            Err(Error::EmptyFile)
+        ));
+    }
+}
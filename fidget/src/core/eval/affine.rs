@@ -0,0 +1,354 @@
+//! Affine arithmetic evaluation
+use crate::{
+    eval::{
+        tracing::{
+            TracingEval, TracingEvalData, TracingEvaluator,
+            TracingEvaluatorData,
+        },
+        types::Affine,
+        Choice, EvaluatorStorage, Tape,
+    },
+    vm::eval_affine,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Evaluator for [`Affine`] ranges, returning an affine form and capturing a
+/// trace
+///
+/// Affine arithmetic tracks correlations between values, so it often produces
+/// tighter bounds (and makes more decisions at `min` and `max` nodes) than
+/// interval arithmetic, at the cost of slower evaluation.  Use
+/// [`Interval::from`](crate::eval::types::Interval) to convert the result into
+/// an interval.
+///
+/// This evaluator interprets the tape's VM instructions, so it is available
+/// for tapes from any evaluator family (including the JIT).
+pub type AffineEval<F> = TracingEval<Affine, AffineEvaluator<F>, F>;
+
+/// Scratch data used by an [`AffineEval`] from a particular family `F`
+pub type AffineEvalData<F> = TracingEvalData<AffineEvaluatorData, F>;
+
+/// Inner evaluator used by [`AffineEval`]
+#[derive(Clone)]
+pub struct AffineEvaluator<F> {
+    tape: Tape<F>,
+}
+
+impl<F> EvaluatorStorage<F> for AffineEvaluator<F> {
+    type Storage = ();
+    fn new_with_storage(tape: &Tape<F>, _storage: ()) -> Self {
+        Self { tape: tape.clone() }
+    }
+    fn take(self) -> Option<Self::Storage> {
+        Some(())
+    }
+}
+
+/// Scratch data used by an [`AffineEvaluator`]
+#[derive(Default)]
+pub struct AffineEvaluatorData {
+    slots: Vec<Affine>,
+}
+
+impl<F> TracingEvaluatorData<F> for AffineEvaluatorData {
+    fn prepare(&mut self, tape: &Tape<F>) {
+        let slot_count = tape.slot_count();
+        self.slots.resize(slot_count, f32::NAN.into());
+        self.slots.fill(f32::NAN.into());
+    }
+}
+
+impl<F> TracingEvaluator<Affine, F> for AffineEvaluator<F> {
+    type Data = AffineEvaluatorData;
+
+    fn eval_with(
+        &self,
+        x: Affine,
+        y: Affine,
+        z: Affine,
+        w: Affine,
+        vars: &[f32],
+        choices: &mut [Choice],
+        data: &mut Self::Data,
+    ) -> (Affine, bool) {
+        eval_affine(&self.tape, [x, y, z, w], vars, choices, &mut data.slots)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::eval::types::Interval;
+
+    /// Checks that `f` applied to samples of `a` lands within `out`
+    fn check_unary(a: Affine, out: Affine, f: fn(f32) -> f32) {
+        let range = Interval::from(out);
+        let i = Interval::from(a);
+        for k in 0..=64 {
+            let v = i.lower() + i.width() * k as f32 / 64.0;
+            let r = f(v);
+            assert!(
+                r >= range.lower() - 1e-5 && r <= range.upper() + 1e-5,
+                "f({v}) = {r} is outside {range:?}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_affine_unary() {
+        for i in [[-1.0, 3.0], [-3.0, -1.0], [0.5, 2.0], [-2.0, 1.0]] {
+            let a = Affine::x(i.into());
+            check_unary(a, a.abs(), f32::abs);
+            check_unary(a, a.square(), |v| v * v);
+            check_unary(a, a.exp(), f32::exp);
+            check_unary(a, a * a, |v| v * v);
+            if i[0] > 0.0 {
+                check_unary(a, a.sqrt(), f32::sqrt);
+                check_unary(a, a.ln(), f32::ln);
+                check_unary(a, a.recip(), |v| 1.0 / v);
+            }
+        }
+        assert!(Affine::x([-1.0, 1.0].into()).recip().has_nan());
+    }
+
+    #[test]
+    fn test_affine_correlation() {
+        let x = Affine::x([0.0, 2.0].into());
+        let y = Affine::y([0.0, 2.0].into());
+        assert_eq!(Interval::from(x - x), 0.0.into());
+        assert_eq!(Interval::from(x + y - x), [0.0, 2.0].into());
+
+        // The square is tighter than multiplication
+        let sq = Interval::from(x.square());
+        let mul = Interval::from(x * x);
+        assert!(sq.lower() >= mul.lower() && sq.upper() <= mul.upper());
+    }
+
+    #[test]
+    fn test_affine_interval() {
+        let i = Interval::new(-1.0, 3.0);
+        assert_eq!(Interval::from(Affine::from(i)), i);
+        assert_eq!(Interval::from(Affine::x(i)), i);
+        assert_eq!(Interval::from(Affine::from(2.5)), 2.5.into());
+        assert!(Affine::from(Interval::from(f32::NAN)).has_nan());
+
+        // An infinite center and radius gives an unbounded interval
+        let i = Interval::new(f32::NEG_INFINITY, 0.0);
+        let inf = Interval::new(f32::NEG_INFINITY, f32::INFINITY);
+        assert_eq!(Interval::from(Affine::from(i)), inf);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(any(test, feature = "eval-tests"))]
+pub mod eval_tests {
+    use super::*;
+    use crate::{
+        context::{Context, Node},
+        eval::{types::Interval, Family, Vars},
+    };
+
+    pub fn test_a_basic<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let sum = ctx.add(x, y).unwrap();
+        let out = ctx.sub(sum, x).unwrap();
+        let tape = ctx.get_tape::<I>(out).unwrap();
+
+        let eval = tape.new_affine_evaluator();
+        let x = Affine::x([0.0, 1.0].into());
+        let y = Affine::y([2.0, 3.0].into());
+        let (v, _) = eval.eval(x, y, 0.0.into(), &[]).unwrap();
+        assert_eq!(Interval::from(v), [2.0, 3.0].into());
+
+        // Interval arithmetic can't see that the two `x` terms cancel
+        let eval = tape.new_interval_evaluator();
+        let v = eval.eval_xy([0.0, 1.0], [2.0, 3.0]);
+        assert_eq!(v, [1.0, 4.0].into());
+    }
+
+    pub fn test_a_var<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let a = ctx.var("a").unwrap();
+        let out = ctx.mul(x, a).unwrap();
+        let tape = ctx.get_tape::<I>(out).unwrap();
+
+        let eval = tape.new_affine_evaluator();
+        let mut vars = Vars::new(&tape);
+        vars.set("a", 2.0);
+        let x = Affine::x([1.0, 2.0].into());
+        let (v, _) = eval
+            .eval(x, 0.0.into(), 0.0.into(), vars.as_slice())
+            .unwrap();
+        assert_eq!(Interval::from(v), [2.0, 4.0].into());
+        assert_eq!(v.terms(), [1.0, 0.0, 0.0]);
+    }
+
+    pub fn test_a_min_max<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let x1 = ctx.add(x, 1.0).unwrap();
+        let min = ctx.min(x, x1).unwrap();
+        let tape = ctx.get_tape::<I>(min).unwrap();
+
+        // Interval arithmetic can't make a decision here, because the ranges
+        // of `x` and `x + 1` overlap
+        let eval = tape.new_interval_evaluator();
+        let i = Interval::new(0.0, 2.0);
+        let (_, trace) = eval.eval(i, 0.0.into(), 0.0.into(), &[]).unwrap();
+        assert!(trace.is_none());
+
+        let eval = tape.new_affine_evaluator();
+        let ax = Affine::x([0.0, 2.0].into());
+        let (v, trace) = eval.eval(ax, 0.0.into(), 0.0.into(), &[]).unwrap();
+        assert_eq!(Interval::from(v), [0.0, 2.0].into());
+        let trace = trace.unwrap();
+        assert_eq!(trace.choices(), &[Choice::Left]);
+        let next = trace.simplify().unwrap();
+        assert_eq!(next.len(), 1);
+
+        let max = ctx.max(x1, x).unwrap();
+        let tape = ctx.get_tape::<I>(max).unwrap();
+        let eval = tape.new_affine_evaluator();
+        let (v, trace) = eval.eval(ax, 0.0.into(), 0.0.into(), &[]).unwrap();
+        assert_eq!(Interval::from(v), [1.0, 3.0].into());
+        let next = trace.unwrap().simplify().unwrap();
+        assert_eq!(next.len(), 2); // x + 1
+
+        // If the ranges genuinely overlap, both branches are kept
+        let y = ctx.y();
+        let min = ctx.min(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(min).unwrap();
+        let eval = tape.new_affine_evaluator();
+        let ay = Affine::y([1.0, 3.0].into());
+        let (v, trace) = eval.eval(ax, ay, 0.0.into(), &[]).unwrap();
+        assert!(trace.is_none());
+        let v = Interval::from(v);
+        assert!(v.lower() <= 0.0 && v.upper() >= 2.0, "{v:?}");
+    }
+
+    /// Checks that affine results contain the values at sample points
+    pub fn test_a_contains<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let r = ctx.var("r").unwrap();
+        let x2 = ctx.square(x).unwrap();
+        let y2 = ctx.square(y).unwrap();
+        let sum = ctx.add(x2, y2).unwrap();
+        let len = ctx.sqrt(sum).unwrap();
+        let circle = ctx.sub(len, r).unwrap();
+        let xy = ctx.mul(x, y).unwrap();
+        let exp = ctx.exp(xy).unwrap();
+        let abs = ctx.abs(x).unwrap();
+        let div = ctx.div(y, exp).unwrap();
+        let sin = ctx.sin(x).unwrap();
+        let blend = ctx.max(circle, div).unwrap();
+        let nodes: [Node; 6] = [circle, exp, abs, div, sin, blend];
+
+        let boxes = [
+            ([-1.0, 1.0], [-1.0, 1.0]),
+            ([0.5, 1.5], [-2.0, -0.5]),
+            ([-3.0, -1.0], [0.0, 0.25]),
+        ];
+        for n in nodes {
+            let tape = ctx.get_tape::<I>(n).unwrap();
+            let eval = tape.new_affine_evaluator();
+            let point = tape.new_point_evaluator();
+            let mut vars = Vars::new(&tape);
+            vars.set("r", 0.75);
+            for (bx, by) in boxes {
+                let (bx, by): (Interval, Interval) = (bx.into(), by.into());
+                let (v, _) = eval
+                    .eval(
+                        Affine::x(bx),
+                        Affine::y(by),
+                        0.0.into(),
+                        vars.as_slice(),
+                    )
+                    .unwrap();
+                let v = Interval::from(v);
+                for i in 0..=8 {
+                    for j in 0..=8 {
+                        let px = bx.lower() + bx.width() * i as f32 / 8.0;
+                        let py = by.lower() + by.width() * j as f32 / 8.0;
+                        let (p, _) =
+                            point.eval(px, py, 0.0, vars.as_slice()).unwrap();
+                        assert!(
+                            p >= v.lower() - 1e-5 && p <= v.upper() + 1e-5,
+                            "{p} at ({px}, {py}) is outside {v:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks that infinite values don't cause panics
+    pub fn test_a_infinite<I: Family>() {
+        // `ln` of a range touching zero has an infinite lower bound, which
+        // makes the affine center and radius infinite
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let sub = ctx.sub(x, 0.15).unwrap();
+        let ln = ctx.ln(sub).unwrap();
+        let min = ctx.min(ln, y).unwrap();
+        let max = ctx.max(ln, y).unwrap();
+        let lt = ctx.lt(x, 0.2).unwrap();
+        let select = ctx.select(lt, ln, y).unwrap();
+        let abs = ctx.abs(ln).unwrap();
+        for (n, unbounded) in [
+            (ln, true),
+            (min, true),
+            (select, false),
+            (max, false),
+            (abs, false),
+        ] {
+            let tape = ctx.get_tape::<I>(n).unwrap();
+            let eval = tape.new_affine_evaluator();
+            for bx in [[0.11, 0.23], [0.15, 0.23], [0.15, 0.15]] {
+                let (v, _) = eval
+                    .eval(
+                        Affine::x(bx.into()),
+                        Affine::y([0.0, 1.0].into()),
+                        0.0.into(),
+                        &[],
+                    )
+                    .unwrap();
+                let v = Interval::from(v);
+                assert!(
+                    !unbounded || v.has_nan() || v.lower() == f32::NEG_INFINITY,
+                    "bad result {v:?} for {n:?} at {bx:?}"
+                );
+            }
+        }
+    }
+
+    #[macro_export]
+    macro_rules! affine_test {
+        ($i:ident, $t:ty) => {
+            #[test]
+            fn $i() {
+                $crate::eval::affine::eval_tests::$i::<$t>()
+            }
+        };
+    }
+
+    #[macro_export]
+    macro_rules! affine_tests {
+        ($t:ty) => {
+            $crate::affine_test!(test_a_basic, $t);
+            $crate::affine_test!(test_a_var, $t);
+            $crate::affine_test!(test_a_min_max, $t);
+            $crate::affine_test!(test_a_contains, $t);
+            $crate::affine_test!(test_a_infinite, $t);
+        };
+    }
+}
@@ -0,0 +1,466 @@
+//! Evaluates many tracing values in a single call
+//!
+//! A batch evaluator is given **many values** of a tracing type (e.g. many
+//! intervals), and captures a separate trace for each one.  This is useful
+//! when subdividing space: all of the children of a particular cell can be
+//! evaluated at once, then each child's trace is used to simplify the tape
+//! independently.
+//!
+//! Like [bulk evaluation](crate::eval::bulk), this limits the overhead of
+//! instruction dispatch.
+//!
+//! It is unlikely that you'll want to use these traits or types directly;
+//! they're implementation details to minimize code duplication.
+
+use crate::{
+    eval::{
+        bulk::BulkEvaluatorData,
+        tracing::{
+            BorrowedTracingEvalResult, OwnedTracingEvalResult,
+            TracingEvalResult, TracingEvaluator, TracingEvaluatorData,
+        },
+        Choice, EvaluatorStorage, Family, Tape,
+    },
+    Error,
+};
+
+/// Trait for batch evaluation of the given type `T`
+///
+/// It's uncommon to use this trait outside the library itself; it's an
+/// abstraction to reduce code duplication, and is public because it's used as a
+/// constraint on other public APIs.
+pub trait BatchEvaluator<T, F> {
+    /// Data type used during evaluation
+    type Data: BulkEvaluatorData<F> + Default + Send;
+
+    /// Indicates whether evaluating a batch is cheaper than evaluating each of
+    /// its items separately
+    ///
+    /// If this is `false`, callers should pass one item at a time, e.g. so
+    /// that a renderer can skip regions hidden by the previous ones.
+    const BATCHED: bool = true;
+
+    /// Evaluates many values, writing results and traces into `out` and using
+    /// `data` as scratch memory.
+    ///
+    /// # Panics
+    /// This function may assume that the `x`, `y`, `z`, and `w` slices are of
+    /// equal length, that `out` has been prepared for that many items, and that
+    /// `vars` is correctly sized for the number of variables in the tape.
+    #[allow(clippy::too_many_arguments)]
+    fn eval_with(
+        &self,
+        x: &[T],
+        y: &[T],
+        z: &[T],
+        w: &[T],
+        vars: &[f32],
+        out: &mut BatchOutput<T>,
+        data: &mut Self::Data,
+    );
+}
+
+/// Values and traces written by a [`BatchEvaluator`]
+///
+/// Each item has its own array of [`Choice`] values, stored contiguously.
+pub struct BatchOutput<T> {
+    values: Vec<T>,
+    choices: Vec<Choice>,
+    simplify: Vec<bool>,
+    choice_count: usize,
+}
+
+impl<T> Default for BatchOutput<T> {
+    fn default() -> Self {
+        Self {
+            values: vec![],
+            choices: vec![],
+            simplify: vec![],
+            choice_count: 0,
+        }
+    }
+}
+
+impl<T: Clone + From<f32>> BatchOutput<T> {
+    fn prepare<F>(&mut self, tape: &Tape<F>, size: usize) {
+        self.choice_count = tape.choice_count();
+        self.values.resize(size, f32::NAN.into());
+        self.values.fill(f32::NAN.into());
+        self.choices
+            .resize(size * self.choice_count, Choice::Unknown);
+        self.choices.fill(Choice::Unknown);
+        self.simplify.resize(size, false);
+        self.simplify.fill(false);
+    }
+}
+
+impl<T> BatchOutput<T> {
+    /// Returns the number of items in this output
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Checks whether this output is empty
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Records the result of evaluation for item `i`
+    ///
+    /// `simplify` is combined with any choices already recorded for this item,
+    /// which may have also marked it as simplifiable.
+    pub fn set(&mut self, i: usize, value: T, simplify: bool) {
+        self.values[i] = value;
+        self.simplify[i] |= simplify;
+    }
+
+    /// Records a choice at the given choice index for item `i`
+    #[inline]
+    pub fn record(&mut self, i: usize, index: usize, choice: Choice) {
+        self.choices[i * self.choice_count + index] |= choice;
+        self.simplify[i] |= choice != Choice::Both;
+    }
+
+    /// Returns a mutable view into the choice array for item `i`
+    pub fn choices_mut(&mut self, i: usize) -> &mut [Choice] {
+        &mut self.choices[i * self.choice_count..][..self.choice_count]
+    }
+
+    fn choices(&self, i: usize) -> &[Choice] {
+        &self.choices[i * self.choice_count..][..self.choice_count]
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Adapter which implements [`BatchEvaluator`] by evaluating items one at a
+/// time with a [`TracingEvaluator`]
+///
+/// This is **not** a batched kernel: it's used by evaluator families which
+/// don't have one (e.g. the JIT), so that they can still be used wherever a
+/// batch evaluator is required.  It reports itself as
+/// [not batched](BatchEvaluator::BATCHED), so callers pass one item at a time.
+#[derive(Clone)]
+pub struct UnbatchedEvaluator<E>(E);
+
+impl<E: EvaluatorStorage<F>, F> EvaluatorStorage<F> for UnbatchedEvaluator<E> {
+    type Storage = E::Storage;
+    fn new_with_storage(tape: &Tape<F>, storage: E::Storage) -> Self {
+        Self(E::new_with_storage(tape, storage))
+    }
+    fn take(self) -> Option<Self::Storage> {
+        self.0.take()
+    }
+}
+
+/// Scratch data used by an [`UnbatchedEvaluator`]
+#[derive(Default)]
+pub struct UnbatchedEvaluatorData<D>(D);
+
+impl<D: TracingEvaluatorData<F>, F> BulkEvaluatorData<F>
+    for UnbatchedEvaluatorData<D>
+{
+    fn prepare(&mut self, tape: &Tape<F>, _size: usize) {
+        self.0.prepare(tape);
+    }
+}
+
+impl<T, E, F> BatchEvaluator<T, F> for UnbatchedEvaluator<E>
+where
+    T: Copy,
+    E: TracingEvaluator<T, F>,
+{
+    type Data = UnbatchedEvaluatorData<E::Data>;
+
+    const BATCHED: bool = false;
+
+    fn eval_with(
+        &self,
+        x: &[T],
+        y: &[T],
+        z: &[T],
+        w: &[T],
+        vars: &[f32],
+        out: &mut BatchOutput<T>,
+        data: &mut Self::Data,
+    ) {
+        for i in 0..x.len() {
+            let (value, simplify) = self.0.eval_with(
+                x[i],
+                y[i],
+                z[i],
+                w[i],
+                vars,
+                out.choices_mut(i),
+                &mut data.0,
+            );
+            out.set(i, value, simplify);
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Generic batch evaluator container `struct`
+///
+/// This includes an inner type implementing [`BatchEvaluator`] and a stored
+/// [`Tape`].
+///
+/// The internal `tape` is planned with
+/// [`F::REG_LIMIT`](crate::eval::Family::REG_LIMIT) registers.
+#[derive(Clone)]
+pub struct BatchEval<T, E, F> {
+    eval: E,
+    tape: Tape<F>,
+
+    _p: std::marker::PhantomData<fn(T) -> T>,
+}
+
+impl<T, E, F: Family> BatchEval<T, E, F>
+where
+    E: BatchEvaluator<T, F> + EvaluatorStorage<F>,
+    T: Copy + From<f32>,
+{
+    /// Builds a new evaluator for the given tape, allocating new storage
+    pub fn new(tape: &Tape<F>) -> Self {
+        Self::new_with_storage(tape, E::Storage::default())
+    }
+
+    /// Returns a copy of the inner tape
+    pub fn tape(&self) -> Tape<F> {
+        self.tape.clone()
+    }
+
+    /// Checks whether the inner evaluator is faster when given many items at
+    /// once (see [`BatchEvaluator::BATCHED`])
+    pub fn is_batched() -> bool {
+        E::BATCHED
+    }
+
+    /// Builds a new evaluator for the given tape, reusing the given storage
+    pub fn new_with_storage(tape: &Tape<F>, storage: E::Storage) -> Self {
+        let eval = E::new_with_storage(tape, storage);
+        Self {
+            eval,
+            tape: tape.clone(),
+            _p: std::marker::PhantomData,
+        }
+    }
+
+    /// Consumes the evaluator, returning the inner storage type for reuse
+    pub fn take(self) -> Option<E::Storage> {
+        self.eval.take()
+    }
+
+    /// Evaluate using the given `data` as scratch memory
+    ///
+    /// Returns a handle to the results, which borrows from `data`.
+    ///
+    /// The W input is set to 0; use [`eval_xyzw_with`](Self::eval_xyzw_with)
+    /// to provide values for it.
+    pub fn eval_with<'a>(
+        &self,
+        x: &[T],
+        y: &[T],
+        z: &[T],
+        vars: &[f32],
+        data: &'a mut BatchEvalData<E::Data, T, F>,
+    ) -> Result<BatchEvalResult<'a, T, F>, Error> {
+        self.eval_inner(x, y, z, None, vars, data)?;
+        Ok(BatchEvalResult {
+            out: &data.out,
+            tape: self.tape.clone(),
+        })
+    }
+
+    /// Evaluate using the given `data` as scratch memory, with values for the
+    /// W input
+    ///
+    /// Returns a handle to the results, which borrows from `data`.
+    pub fn eval_xyzw_with<'a>(
+        &self,
+        x: &[T],
+        y: &[T],
+        z: &[T],
+        w: &[T],
+        vars: &[f32],
+        data: &'a mut BatchEvalData<E::Data, T, F>,
+    ) -> Result<BatchEvalResult<'a, T, F>, Error> {
+        self.eval_inner(x, y, z, Some(w), vars, data)?;
+        Ok(BatchEvalResult {
+            out: &data.out,
+            tape: self.tape.clone(),
+        })
+    }
+
+    /// Checks slice sizes, then evaluates into `data.out`
+    ///
+    /// If `w` is `None`, then zeros (stored in `data`) are used instead.
+    fn eval_inner(
+        &self,
+        x: &[T],
+        y: &[T],
+        z: &[T],
+        w: Option<&[T]>,
+        vars: &[f32],
+        data: &mut BatchEvalData<E::Data, T, F>,
+    ) -> Result<(), Error> {
+        if x.len() != y.len()
+            || x.len() != z.len()
+            || w.is_some_and(|w| w.len() != x.len())
+        {
+            return Err(Error::MismatchedSlices);
+        } else if vars.len() != self.tape.var_count() {
+            return Err(Error::BadVarSlice(vars.len(), self.tape.var_count()));
+        }
+        data.prepare(&self.tape, x.len());
+        let w = w.unwrap_or(&data.zeros[..x.len()]);
+        self.eval
+            .eval_with(x, y, z, w, vars, &mut data.out, &mut data.data);
+        Ok(())
+    }
+
+    /// Evaluates the given slices, allocating scratch memory
+    ///
+    /// Returns a tuple of the resulting value and an optional (owned) handle to
+    /// simplify the tape for each item.
+    ///
+    /// This function performs allocation; in a hot loop, consider using
+    /// [`eval_with`](Self::eval_with) instead.
+    #[allow(clippy::type_complexity)]
+    pub fn eval(
+        &self,
+        x: &[T],
+        y: &[T],
+        z: &[T],
+        vars: &[f32],
+    ) -> Result<Vec<(T, Option<OwnedTracingEvalResult<T, F>>)>, Error> {
+        let mut data = Default::default();
+        self.eval_inner(x, y, z, None, vars, &mut data)?;
+        Ok(self.owned_results(&data))
+    }
+
+    /// Evaluates the given slices (including W), allocating scratch memory
+    ///
+    /// Returns a tuple of the resulting value and an optional (owned) handle to
+    /// simplify the tape for each item.
+    ///
+    /// This function performs allocation; in a hot loop, consider using
+    /// [`eval_xyzw_with`](Self::eval_xyzw_with) instead.
+    #[allow(clippy::type_complexity)]
+    pub fn eval_xyzw(
+        &self,
+        x: &[T],
+        y: &[T],
+        z: &[T],
+        w: &[T],
+        vars: &[f32],
+    ) -> Result<Vec<(T, Option<OwnedTracingEvalResult<T, F>>)>, Error> {
+        let mut data = Default::default();
+        self.eval_inner(x, y, z, Some(w), vars, &mut data)?;
+        Ok(self.owned_results(&data))
+    }
+
+    /// Copies results out of `data`, converting traces to owned handles
+    fn owned_results(
+        &self,
+        data: &BatchEvalData<E::Data, T, F>,
+    ) -> Vec<(T, Option<OwnedTracingEvalResult<T, F>>)> {
+        let r = BatchEvalResult {
+            out: &data.out,
+            tape: self.tape.clone(),
+        };
+        (0..r.len())
+            .map(|i| {
+                let (value, trace) = r.get(i);
+                let trace = trace.map(|t| {
+                    TracingEvalResult::new(t.choices().to_vec(), r.tape.clone())
+                });
+                (value, trace)
+            })
+            .collect()
+    }
+}
+
+/// Generic data associated with a batch evaluator
+///
+/// - `D` is the scratch (mutable) data type used by the evaluator
+/// - `T` is the evaluation value type (e.g. `Interval`)
+/// - `F` is the tape family
+pub struct BatchEvalData<D, T, F> {
+    out: BatchOutput<T>,
+
+    /// Zeros, used as the W input when it isn't provided
+    zeros: Vec<T>,
+
+    /// Inner data
+    data: D,
+
+    _p: std::marker::PhantomData<*const F>,
+}
+
+impl<D: Default, T, F> Default for BatchEvalData<D, T, F> {
+    fn default() -> Self {
+        Self {
+            out: BatchOutput::default(),
+            zeros: vec![],
+            data: D::default(),
+            _p: std::marker::PhantomData,
+        }
+    }
+}
+
+// SAFETY: this can't be derived because of Rust limitations, but we're sending
+// around a BatchOutput<T> and a D, which should be fine.
+unsafe impl<D: Send, T: Send, F> Send for BatchEvalData<D, T, F> {}
+
+impl<D: BulkEvaluatorData<F>, T, F> BatchEvalData<D, T, F>
+where
+    T: Clone + From<f32>,
+{
+    fn prepare(&mut self, tape: &Tape<F>, size: usize) {
+        self.out.prepare(tape, size);
+        if self.zeros.len() < size {
+            self.zeros.resize(size, 0.0.into());
+        }
+        self.data.prepare(tape, size);
+    }
+}
+
+/// Results from a batch evaluation, borrowed from a [`BatchEvalData`]
+pub struct BatchEvalResult<'a, T, F> {
+    out: &'a BatchOutput<T>,
+    tape: Tape<F>,
+}
+
+impl<'a, T: Copy, F: Family> BatchEvalResult<'a, T, F> {
+    /// Returns the number of evaluated items
+    pub fn len(&self) -> usize {
+        self.out.len()
+    }
+
+    /// Checks whether there are no evaluated items
+    pub fn is_empty(&self) -> bool {
+        self.out.is_empty()
+    }
+
+    /// Returns the values from every item
+    pub fn values(&self) -> &'a [T] {
+        &self.out.values
+    }
+
+    /// Returns the value of item `i`, and a handle to simplify the tape based
+    /// on that item's trace (if simplification is possible).
+    pub fn get(
+        &self,
+        i: usize,
+    ) -> (T, Option<BorrowedTracingEvalResult<'a, T, F>>) {
+        let out: &'a BatchOutput<T> = self.out;
+        let trace = if out.simplify[i] {
+            Some(TracingEvalResult::new(out.choices(i), self.tape.clone()))
+        } else {
+            None
+        };
+        (out.values[i], trace)
+    }
+}
@@ -4,13 +4,18 @@
 //! can take advantage of SIMD.
 //!
 //! A bulk evaluator expects to be given **many single points**, i.e. the X, Y,
-//! Z inputs are always `&[f32]`.  The output may be of a different type, e.g.
-//! partial derivatives with respect to X/Y/Z
+//! Z, and W inputs are always `&[f32]`.  The output may be of a different
+//! type, e.g. partial derivatives with respect to X/Y/Z
 //! ([`GradEval`](crate::eval::GradSliceEval)).
 //!
 //! Bulk evaluators are typically named `XSliceEval`, where `X` is the output
 //! type.
 //!
+//! If the tape has multiple outputs (see
+//! [`Context::get_tape_multi`](crate::context::Context::get_tape_multi)), then
+//! results are written for every output, one after the other: given `n` points,
+//! output `k` for point `i` is at index `k * n + i`.
+//!
 //! It is unlikely that you'll want to use these traits or types directly;
 //! they're implementation details to minimize code duplication.
 
@@ -35,17 +40,20 @@ pub trait BulkEvaluator<T, F> {
     /// scratch memory.
     ///
     /// # Panics
-    /// This function may assume that the `x`, `y`, `z`, and `out` slices are of
-    /// equal length and panic otherwise; higher-level calls should maintain
+    /// This function may assume that the `x`, `y`, `z`, and `w` slices are of
+    /// equal length (`n`) and that `out` has length `n` times the tape's
+    /// output count, and panic otherwise; higher-level calls should maintain
     /// that invariant.
     ///
     /// This function may also assume that `vars` is correctly sized for the
     /// number of variables in the tape.
+    #[allow(clippy::too_many_arguments)]
     fn eval_with(
         &self,
         x: &[f32],
         y: &[f32],
         z: &[f32],
+        w: &[f32],
         vars: &[f32],
         out: &mut [T],
         data: &mut Self::Data,
@@ -122,6 +130,9 @@ where
     /// Evaluate using the given `data` as scratch memory
     ///
     /// Returns a slice of results borrowed from `data.out`.
+    ///
+    /// The W input is set to 0; use [`eval_xyzw_with`](Self::eval_xyzw_with)
+    /// to provide values for it.
     pub fn eval_with<'a>(
         &self,
         x: &[f32],
@@ -130,15 +141,52 @@ where
         vars: &[f32],
         data: &'a mut BulkEvalData<E::Data, T, F>,
     ) -> Result<&'a [T], Error> {
-        if x.len() != y.len() || x.len() != z.len() {
+        self.eval_inner(x, y, z, None, vars, data)?;
+        Ok(&data.out)
+    }
+
+    /// Evaluate using the given `data` as scratch memory, with values for the
+    /// W input
+    ///
+    /// Returns a slice of results borrowed from `data.out`.
+    pub fn eval_xyzw_with<'a>(
+        &self,
+        x: &[f32],
+        y: &[f32],
+        z: &[f32],
+        w: &[f32],
+        vars: &[f32],
+        data: &'a mut BulkEvalData<E::Data, T, F>,
+    ) -> Result<&'a [T], Error> {
+        self.eval_inner(x, y, z, Some(w), vars, data)?;
+        Ok(&data.out)
+    }
+
+    /// Checks slice sizes, then evaluates into `data.out`
+    ///
+    /// If `w` is `None`, then zeros (stored in `data`) are used instead.
+    fn eval_inner(
+        &self,
+        x: &[f32],
+        y: &[f32],
+        z: &[f32],
+        w: Option<&[f32]>,
+        vars: &[f32],
+        data: &mut BulkEvalData<E::Data, T, F>,
+    ) -> Result<(), Error> {
+        if x.len() != y.len()
+            || x.len() != z.len()
+            || w.is_some_and(|w| w.len() != x.len())
+        {
             return Err(Error::MismatchedSlices);
         } else if vars.len() != self.tape.var_count() {
             return Err(Error::BadVarSlice(vars.len(), self.tape.var_count()));
         }
         data.prepare(&self.tape, x.len());
+        let w = w.unwrap_or(&data.zeros[..x.len()]);
         self.eval
-            .eval_with(x, y, z, vars, &mut data.out, &mut data.data);
-        Ok(&data.out)
+            .eval_with(x, y, z, w, vars, &mut data.out, &mut data.data);
+        Ok(())
     }
 
     /// Evaluates the given slices, returning a fresh `Vec<T>`
@@ -156,6 +204,23 @@ where
         self.eval_with(x, y, z, vars, &mut data)?;
         Ok(data.out)
     }
+
+    /// Evaluates the given slices (including W), returning a fresh `Vec<T>`
+    ///
+    /// This function performs allocation; in a hot loop, consider using
+    /// [`eval_xyzw_with`](Self::eval_xyzw_with) instead.
+    pub fn eval_xyzw(
+        &self,
+        x: &[f32],
+        y: &[f32],
+        z: &[f32],
+        w: &[f32],
+        vars: &[f32],
+    ) -> Result<Vec<T>, Error> {
+        let mut data = Default::default();
+        self.eval_xyzw_with(x, y, z, w, vars, &mut data)?;
+        Ok(data.out)
+    }
 }
 
 /// Generic data associated with a bulk evaluator
@@ -168,6 +233,9 @@ where
 pub struct BulkEvalData<D, T, F> {
     out: Vec<T>,
 
+    /// Zeros, used as the W input when it isn't provided
+    zeros: Vec<f32>,
+
     /// Inner data
     data: D,
 
@@ -178,6 +246,7 @@ impl<D: Default, T, F> Default for BulkEvalData<D, T, F> {
     fn default() -> Self {
         Self {
             out: vec![],
+            zeros: vec![],
             data: D::default(),
             _p: std::marker::PhantomData,
         }
@@ -193,8 +262,11 @@ where
     T: Clone + From<f32>,
 {
     fn prepare(&mut self, tape: &Tape<F>, size: usize) {
-        self.out.resize(size, std::f32::NAN.into());
+        self.out.resize(size * tape.output_count(), f32::NAN.into());
         self.out.fill(std::f32::NAN.into());
+        if self.zeros.len() < size {
+            self.zeros.resize(size, 0.0);
+        }
         self.data.prepare(tape, size);
     }
 }
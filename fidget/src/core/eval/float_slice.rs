@@ -25,7 +25,7 @@ pub type FloatSliceEvalStorage<F> =
 #[cfg(any(test, feature = "eval-tests"))]
 pub mod eval_tests {
     use super::*;
-    use crate::{context::Context, eval::Vars};
+    use crate::{context::Context, eval::Vars, Error};
 
     pub fn test_give_take<I: Family>() {
         let mut ctx = Context::new();
@@ -174,6 +174,329 @@ pub mod eval_tests {
         );
     }
 
+    pub fn test_f_w<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let w = ctx.w();
+        let s = ctx.sin(w).unwrap();
+        let sum = ctx.add(x, s).unwrap();
+        let tape = ctx.get_tape::<I>(sum).unwrap();
+        let eval = tape.new_float_slice_evaluator();
+
+        // Use an odd length, to check the remainder handling
+        let xs: Vec<f32> = (0..11).map(|i| i as f32).collect();
+        let ws: Vec<f32> = (0..11).map(|i| i as f32 * 0.5 - 2.0).collect();
+        let zs = vec![0.0; xs.len()];
+        let out = eval.eval_xyzw(&xs, &zs, &zs, &ws, &[]).unwrap();
+        for i in 0..xs.len() {
+            assert_eq!(out[i], xs[i] + ws[i].sin(), "mismatch at {i}");
+        }
+
+        let out = eval.eval(&xs, &zs, &zs, &[]).unwrap();
+        assert_eq!(out, xs);
+
+        assert!(matches!(
+            eval.eval_xyzw(&xs, &zs, &zs, &ws[1..], &[]),
+            Err(Error::MismatchedSlices)
+        ));
+    }
+
+    pub fn test_f_multi<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let x2 = ctx.square(x).unwrap();
+        let y2 = ctx.square(y).unwrap();
+        let r = ctx.add(x2, y2).unwrap();
+        let circle = ctx.sub(r, 1.0).unwrap();
+        let min = ctx.min(x2, y).unwrap();
+        let c = ctx.constant(2.5);
+
+        // Roots may be shared subexpressions, duplicates, or constants
+        let roots = [circle, x2, min, x2, c, y];
+        let tape = ctx.get_tape_multi::<I>(&roots).unwrap();
+        assert_eq!(tape.output_count(), roots.len());
+        let eval = tape.new_float_slice_evaluator();
+
+        // Check a variety of lengths, to exercise remainder handling
+        for n in [0, 1, 3, 4, 8, 11, 17] {
+            let xs: Vec<f32> = (0..n).map(|i| i as f32 * 0.5 - 2.0).collect();
+            let ys: Vec<f32> = (0..n).map(|i| 1.5 - i as f32 * 0.25).collect();
+            let zs = vec![0.0; n];
+            let out = eval.eval(&xs, &ys, &zs, &[]).unwrap();
+            assert_eq!(out.len(), n * roots.len());
+            for i in 0..n {
+                let (x, y) = (xs[i], ys[i]);
+                let expected =
+                    [x * x + y * y - 1.0, x * x, (x * x).min(y), x * x, 2.5, y];
+                for (k, e) in expected.iter().enumerate() {
+                    assert_eq!(out[k * n + i], *e, "mismatch at {i}, {k}");
+                }
+            }
+        }
+
+        // Simplification applies to every output
+        let max = ctx.max(x, y).unwrap();
+        let tape = ctx.get_tape_multi::<I>(&[min, max]).unwrap();
+        let (_, trace) = tape
+            .new_interval_evaluator()
+            .eval([0.0, 1.0], [2.0, 3.0], [0.0, 0.0], &[])
+            .unwrap();
+        let next = trace.unwrap().simplify().unwrap();
+        assert!(next.len() < tape.len());
+        assert_eq!(next.output_count(), 2);
+        let out = next
+            .new_float_slice_evaluator()
+            .eval(&[0.5, 1.0], &[2.0, 2.5], &[0.0; 2], &[])
+            .unwrap();
+        assert_eq!(out, [0.25, 1.0, 2.0, 2.5]);
+
+        assert!(matches!(
+            ctx.get_tape_multi::<I>(&[]),
+            Err(Error::BadOutputCount(0, _))
+        ));
+    }
+
+    pub fn test_f_exp_sin_cos<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let z = ctx.z();
+        let xs = [-2.0, -1.0, -0.5, 0.0, 0.25, 0.5, 1.0, 2.0, 3.0];
+
+        let exp_x = ctx.exp(x).unwrap();
+        let sin_x = ctx.sin(x).unwrap();
+        let cos_x = ctx.cos(x).unwrap();
+        for (node, f) in [
+            (exp_x, f32::exp as fn(f32) -> f32),
+            (sin_x, f32::sin),
+            (cos_x, f32::cos),
+        ] {
+            let tape = ctx.get_tape::<I>(node).unwrap();
+            let eval = tape.new_float_slice_evaluator();
+            let out = eval.eval(&xs, &[0.0; 9], &[0.0; 9], &[]).unwrap();
+            for (v, o) in xs.iter().zip(out) {
+                assert_eq!(o, f(*v));
+            }
+        }
+
+        // Check that other values are preserved across the call
+        let sin_y = ctx.sin(y).unwrap();
+        let cos_z = ctx.cos(z).unwrap();
+        let prod = ctx.mul(sin_y, cos_z).unwrap();
+        let sum = ctx.add(x, prod).unwrap();
+        let tape = ctx.get_tape::<I>(sum).unwrap();
+        let eval = tape.new_float_slice_evaluator();
+        let ys = xs.map(|v| v * 2.0);
+        let zs = xs.map(|v| v - 1.0);
+        let out = eval.eval(&xs, &ys, &zs, &[]).unwrap();
+        for i in 0..xs.len() {
+            assert_eq!(out[i], xs[i] + ys[i].sin() * zs[i].cos());
+        }
+    }
+
+    pub fn test_f_inverse_trig<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let xs = [-1.0, -0.75, -0.5, 0.0, 0.25, 0.5, 0.75, 1.0, 2.0];
+
+        let tan_x = ctx.tan(x).unwrap();
+        let asin_x = ctx.asin(x).unwrap();
+        let acos_x = ctx.acos(x).unwrap();
+        let atan_x = ctx.atan(x).unwrap();
+        for (node, f) in [
+            (tan_x, f32::tan as fn(f32) -> f32),
+            (asin_x, f32::asin),
+            (acos_x, f32::acos),
+            (atan_x, f32::atan),
+        ] {
+            let tape = ctx.get_tape::<I>(node).unwrap();
+            let eval = tape.new_float_slice_evaluator();
+            let out = eval.eval(&xs, &[0.0; 9], &[0.0; 9], &[]).unwrap();
+            for (v, o) in xs.iter().zip(out) {
+                if v.abs() > 1.0 && node != tan_x && node != atan_x {
+                    assert!(o.is_nan());
+                } else {
+                    assert_eq!(o, f(*v));
+                }
+            }
+        }
+
+        let ys = xs.map(|v| 1.0 - v * 2.0);
+        let atan2 = ctx.atan2(y, x).unwrap();
+        let tape = ctx.get_tape::<I>(atan2).unwrap();
+        let eval = tape.new_float_slice_evaluator();
+        let out = eval.eval(&xs, &ys, &[0.0; 9], &[]).unwrap();
+        for i in 0..xs.len() {
+            // Allow for slight differences if atan2 is constant-folded here
+            assert!((out[i] - ys[i].atan2(xs[i])).abs() < 1e-6);
+        }
+
+        let atan2 = ctx.atan2(y, -0.5).unwrap();
+        let tape = ctx.get_tape::<I>(atan2).unwrap();
+        let eval = tape.new_float_slice_evaluator();
+        let out = eval.eval(&xs, &ys, &[0.0; 9], &[]).unwrap();
+        for i in 0..xs.len() {
+            assert!((out[i] - ys[i].atan2(-0.5)).abs() < 1e-6);
+        }
+
+        let atan2 = ctx.atan2(-0.5, x).unwrap();
+        let sum = ctx.add(atan2, y).unwrap();
+        let tape = ctx.get_tape::<I>(sum).unwrap();
+        let eval = tape.new_float_slice_evaluator();
+        let out = eval.eval(&xs, &ys, &[0.0; 9], &[]).unwrap();
+        for i in 0..xs.len() {
+            assert!((out[i] - ((-0.5f32).atan2(xs[i]) + ys[i])).abs() < 1e-6);
+        }
+    }
+
+    pub fn test_f_ln_pow<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let xs = [-2.0, -1.0, -0.5, 0.0, 0.25, 0.5, 1.0, 2.0, 3.0];
+        let ys = [3.0, 2.0, -1.0, 2.0, 0.5, -0.5, 1.5, 3.0, -2.0];
+
+        let ln_x = ctx.ln(x).unwrap();
+        let tape = ctx.get_tape::<I>(ln_x).unwrap();
+        let eval = tape.new_float_slice_evaluator();
+        let out = eval.eval(&xs, &[0.0; 9], &[0.0; 9], &[]).unwrap();
+        for (v, o) in xs.iter().zip(out) {
+            if *v < 0.0 {
+                assert!(o.is_nan());
+            } else {
+                assert_eq!(o, v.ln());
+            }
+        }
+
+        let pow = ctx.pow(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(pow).unwrap();
+        let eval = tape.new_float_slice_evaluator();
+        let out = eval.eval(&xs, &ys, &[0.0; 9], &[]).unwrap();
+        for i in 0..xs.len() {
+            assert_eq!(out[i], xs[i].powf(ys[i]));
+        }
+
+        let pow = ctx.pow(2.0, x).unwrap();
+        let tape = ctx.get_tape::<I>(pow).unwrap();
+        let eval = tape.new_float_slice_evaluator();
+        let out = eval.eval(&xs, &ys, &[0.0; 9], &[]).unwrap();
+        for i in 0..xs.len() {
+            assert_eq!(out[i], 2f32.powf(xs[i]));
+        }
+
+        let cube = ctx.powi(x, 3).unwrap();
+        let sum = ctx.add(cube, y).unwrap();
+        let tape = ctx.get_tape::<I>(sum).unwrap();
+        let eval = tape.new_float_slice_evaluator();
+        let out = eval.eval(&xs, &ys, &[0.0; 9], &[]).unwrap();
+        for i in 0..xs.len() {
+            assert_eq!(out[i], xs[i].powi(3) + ys[i]);
+        }
+    }
+
+    pub fn test_f_floor_mod<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let xs = [-2.5, -1.5, -0.5, -0.25, 0.0, 0.5, 0.75, 1.5, 3.5];
+        let ys = [2.0, 2.0, 1.0, 0.5, 3.0, -2.0, 0.5, 1.5, -1.0];
+
+        let floor = ctx.floor(x).unwrap();
+        let ceil = ctx.ceil(y).unwrap();
+        let sum = ctx.add(floor, ceil).unwrap();
+        let round = ctx.round(x).unwrap();
+        let sum = ctx.add(sum, round).unwrap();
+        let tape = ctx.get_tape::<I>(sum).unwrap();
+        let eval = tape.new_float_slice_evaluator();
+        let out = eval.eval(&xs, &ys, &[0.0; 9], &[]).unwrap();
+        for i in 0..xs.len() {
+            let v = xs[i].floor() + ys[i].ceil() + xs[i].round();
+            assert_eq!(out[i], v);
+        }
+
+        let m = ctx.modulo(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(m).unwrap();
+        let eval = tape.new_float_slice_evaluator();
+        let out = eval.eval(&xs, &ys, &[0.0; 9], &[]).unwrap();
+        for i in 0..xs.len() {
+            assert_eq!(out[i], xs[i].rem_euclid(ys[i]));
+        }
+    }
+
+    pub fn test_f_cmp_logic<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let xs = [0.0, 1.0, 2.0, -1.0, 0.0, f32::NAN, 3.0, 0.0, 5.0];
+        let ys = [1.0, 1.0, 1.0, 0.0, 0.0, 1.0, 4.0, -2.0, f32::NAN];
+
+        let lt = ctx.lt(x, y).unwrap();
+        let gt = ctx.gt(x, y).unwrap();
+        let eq = ctx.eq(x, y).unwrap();
+        let not = ctx.not(x).unwrap();
+        for (node, f) in [
+            (lt, (|a, b| a < b) as fn(f32, f32) -> bool),
+            (gt, |a, b| a > b),
+            (eq, |a, b| a == b),
+            (not, |a, _b| a == 0.0),
+        ] {
+            let tape = ctx.get_tape::<I>(node).unwrap();
+            let eval = tape.new_float_slice_evaluator();
+            let out = eval.eval(&xs, &ys, &[0.0; 9], &[]).unwrap();
+            for i in 0..xs.len() {
+                assert_eq!(out[i], f(xs[i], ys[i]) as u8 as f32);
+            }
+        }
+
+        let and = ctx.and(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(and).unwrap();
+        let eval = tape.new_float_slice_evaluator();
+        let out = eval.eval(&xs, &ys, &[0.0; 9], &[]).unwrap();
+        for i in 0..xs.len() {
+            let v = if xs[i] == 0.0 { xs[i] } else { ys[i] };
+            assert_eq!(out[i].to_bits(), v.to_bits());
+        }
+
+        let or = ctx.or(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(or).unwrap();
+        let eval = tape.new_float_slice_evaluator();
+        let out = eval.eval(&xs, &ys, &[0.0; 9], &[]).unwrap();
+        for i in 0..xs.len() {
+            let v = if xs[i] != 0.0 { xs[i] } else { ys[i] };
+            assert_eq!(out[i].to_bits(), v.to_bits());
+        }
+    }
+
+    pub fn test_f_select<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let z = ctx.z();
+        let xs = [-1.0, 0.0, -0.0, 1.0, f32::NAN, -2.0, 3.0, -0.5, 0.5];
+        let ys = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0];
+        let zs = [-1.0, -2.0, -3.0, -4.0, -5.0, -6.0, -7.0, -8.0, f32::NAN];
+
+        let select = ctx.select(x, y, z).unwrap();
+        let tape = ctx.get_tape::<I>(select).unwrap();
+        let eval = tape.new_float_slice_evaluator();
+        let out = eval.eval(&xs, &ys, &zs, &[]).unwrap();
+        for i in 0..xs.len() {
+            let v = if xs[i] < 0.0 { ys[i] } else { zs[i] };
+            assert_eq!(out[i].to_bits(), v.to_bits());
+        }
+
+        let select = ctx.select(x, 10.0, y).unwrap();
+        let tape = ctx.get_tape::<I>(select).unwrap();
+        let eval = tape.new_float_slice_evaluator();
+        let out = eval.eval(&xs, &ys, &zs, &[]).unwrap();
+        for i in 0..xs.len() {
+            let v = if xs[i] < 0.0 { 10.0 } else { ys[i] };
+            assert_eq!(out[i], v);
+        }
+    }
+
     #[macro_export]
     macro_rules! float_slice_test {
         ($i:ident, $t:ty) => {
@@ -190,6 +513,14 @@ pub mod eval_tests {
             $crate::float_slice_test!(test_give_take, $t);
             $crate::float_slice_test!(test_vectorized, $t);
             $crate::float_slice_test!(test_f_var, $t);
+            $crate::float_slice_test!(test_f_w, $t);
+            $crate::float_slice_test!(test_f_multi, $t);
+            $crate::float_slice_test!(test_f_exp_sin_cos, $t);
+            $crate::float_slice_test!(test_f_inverse_trig, $t);
+            $crate::float_slice_test!(test_f_ln_pow, $t);
+            $crate::float_slice_test!(test_f_floor_mod, $t);
+            $crate::float_slice_test!(test_f_cmp_logic, $t);
+            $crate::float_slice_test!(test_f_select, $t);
         };
     }
 }
@@ -269,6 +269,64 @@ pub mod eval_tests {
         );
     }
 
+    pub fn test_g_w<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let w = ctx.w();
+        let prod = ctx.mul(x, w).unwrap();
+        let tape = ctx.get_tape::<I>(prod).unwrap();
+
+        // W contributes to the value, but has no spatial gradient
+        let eval = tape.new_grad_slice_evaluator();
+        assert_eq!(
+            eval.eval_xyzw(
+                &[2.0, 1.0],
+                &[0.0; 2],
+                &[0.0; 2],
+                &[3.0, -1.0],
+                &[]
+            )
+            .unwrap(),
+            [
+                Grad::new(6.0, 3.0, 0.0, 0.0),
+                Grad::new(-1.0, -1.0, 0.0, 0.0)
+            ]
+        );
+    }
+
+    pub fn test_g_multi<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let z = ctx.z();
+        let xy = ctx.mul(x, y).unwrap();
+        let sum = ctx.add(xy, z).unwrap();
+        let c = ctx.constant(1.5);
+        let tape = ctx.get_tape_multi::<I>(&[sum, xy, c, y]).unwrap();
+        let eval = tape.new_grad_slice_evaluator();
+
+        let out = eval
+            .eval(&[2.0, 1.0, 0.5], &[3.0, -1.0, 2.0], &[1.0, 0.0, 2.0], &[])
+            .unwrap();
+        assert_eq!(
+            out,
+            [
+                Grad::new(7.0, 3.0, 2.0, 1.0),
+                Grad::new(-1.0, -1.0, 1.0, 1.0),
+                Grad::new(3.0, 2.0, 0.5, 1.0),
+                Grad::new(6.0, 3.0, 2.0, 0.0),
+                Grad::new(-1.0, -1.0, 1.0, 0.0),
+                Grad::new(1.0, 2.0, 0.5, 0.0),
+                Grad::new(1.5, 0.0, 0.0, 0.0),
+                Grad::new(1.5, 0.0, 0.0, 0.0),
+                Grad::new(1.5, 0.0, 0.0, 0.0),
+                Grad::new(3.0, 0.0, 1.0, 0.0),
+                Grad::new(-1.0, 0.0, 1.0, 0.0),
+                Grad::new(2.0, 0.0, 1.0, 0.0),
+            ]
+        );
+    }
+
     pub fn test_g_var<I: Family>() {
         let mut ctx = Context::new();
         let a = ctx.var("a").unwrap();
@@ -340,6 +398,226 @@ pub mod eval_tests {
         );
     }
 
+    pub fn test_g_exp_sin_cos<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+
+        let exp_x = ctx.exp(x).unwrap();
+        let sin_x = ctx.sin(x).unwrap();
+        let cos_x = ctx.cos(x).unwrap();
+        for (node, f) in [
+            (exp_x, Grad::exp as fn(Grad) -> Grad),
+            (sin_x, Grad::sin),
+            (cos_x, Grad::cos),
+        ] {
+            let tape = ctx.get_tape::<I>(node).unwrap();
+            let eval = tape.new_grad_slice_evaluator();
+            for v in [-1.0, 0.0, 0.5, 2.0] {
+                assert_eq!(
+                    eval.eval(&[v], &[0.0], &[0.0], &[]).unwrap()[0],
+                    f(Grad::new(v, 1.0, 0.0, 0.0))
+                );
+            }
+        }
+
+        // Check that other values are preserved across the call
+        let sin_y = ctx.sin(y).unwrap();
+        let sum = ctx.add(x, sin_y).unwrap();
+        let tape = ctx.get_tape::<I>(sum).unwrap();
+        let eval = tape.new_grad_slice_evaluator();
+        let out = eval.eval(&[2.0, 3.0], &[1.0, 4.0], &[0.0; 2], &[]).unwrap();
+        assert_eq!(out[0], Grad::new(2.0 + 1f32.sin(), 1.0, 1f32.cos(), 0.0));
+        assert_eq!(out[1], Grad::new(3.0 + 4f32.sin(), 1.0, 4f32.cos(), 0.0));
+    }
+
+    pub fn test_g_inverse_trig<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+
+        let tan_x = ctx.tan(x).unwrap();
+        let asin_x = ctx.asin(x).unwrap();
+        let acos_x = ctx.acos(x).unwrap();
+        let atan_x = ctx.atan(x).unwrap();
+        for (node, f) in [
+            (tan_x, Grad::tan as fn(Grad) -> Grad),
+            (asin_x, Grad::asin),
+            (acos_x, Grad::acos),
+            (atan_x, Grad::atan),
+        ] {
+            let tape = ctx.get_tape::<I>(node).unwrap();
+            let eval = tape.new_grad_slice_evaluator();
+            for v in [-0.5, 0.0, 0.25, 0.75] {
+                assert_eq!(
+                    eval.eval(&[v], &[0.0], &[0.0], &[]).unwrap()[0],
+                    f(Grad::new(v, 1.0, 0.0, 0.0))
+                );
+            }
+        }
+
+        let atan2 = ctx.atan2(y, x).unwrap();
+        let tape = ctx.get_tape::<I>(atan2).unwrap();
+        let eval = tape.new_grad_slice_evaluator();
+        let out = eval
+            .eval(&[1.0, -2.0], &[1.0, 0.5], &[0.0; 2], &[])
+            .unwrap();
+        assert_eq!(out[0], Grad::new(1f32.atan2(1.0), -0.5, 0.5, 0.0));
+        assert_eq!(
+            out[1],
+            Grad::new(0.5f32.atan2(-2.0), -0.5 / 4.25, -2.0 / 4.25, 0.0)
+        );
+
+        // Check immediates in both positions
+        let atan2 = ctx.atan2(y, 2.0).unwrap();
+        let tape = ctx.get_tape::<I>(atan2).unwrap();
+        let eval = tape.new_grad_slice_evaluator();
+        let out = eval.eval(&[0.0], &[2.0], &[0.0], &[]).unwrap();
+        assert_eq!(out[0], Grad::new(2f32.atan2(2.0), 0.0, 0.25, 0.0));
+
+        let atan2 = ctx.atan2(2.0, x).unwrap();
+        let sum = ctx.add(atan2, y).unwrap();
+        let tape = ctx.get_tape::<I>(sum).unwrap();
+        let eval = tape.new_grad_slice_evaluator();
+        let out = eval.eval(&[2.0], &[3.0], &[0.0], &[]).unwrap();
+        assert_eq!(out[0], Grad::new(2f32.atan2(2.0) + 3.0, -0.25, 1.0, 0.0));
+    }
+
+    pub fn test_g_ln_pow<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+
+        let ln_x = ctx.ln(x).unwrap();
+        let tape = ctx.get_tape::<I>(ln_x).unwrap();
+        let eval = tape.new_grad_slice_evaluator();
+        let out = eval.eval(&[2.0], &[0.0], &[0.0], &[]).unwrap();
+        assert_eq!(out[0], Grad::new(2f32.ln(), 0.5, 0.0, 0.0));
+
+        let pow = ctx.pow(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(pow).unwrap();
+        let eval = tape.new_grad_slice_evaluator();
+        let out = eval.eval(&[2.0], &[3.0], &[0.0], &[]).unwrap();
+        assert_eq!(out[0], Grad::new(8.0, 12.0, 8.0 * 2f32.ln(), 0.0));
+
+        // A negative base is fine with a constant integer exponent
+        let cube = ctx.powi(x, 3).unwrap();
+        let tape = ctx.get_tape::<I>(cube).unwrap();
+        let eval = tape.new_grad_slice_evaluator();
+        let out = eval.eval(&[-2.0], &[0.0], &[0.0], &[]).unwrap();
+        assert_eq!(out[0], Grad::new(-8.0, 12.0, 0.0, 0.0));
+
+        let pow = ctx.pow(x, 1.5).unwrap();
+        let tape = ctx.get_tape::<I>(pow).unwrap();
+        let eval = tape.new_grad_slice_evaluator();
+        let out = eval.eval(&[4.0], &[0.0], &[0.0], &[]).unwrap();
+        assert_eq!(out[0], Grad::new(8.0, 3.0, 0.0, 0.0));
+
+        let pow = ctx.pow(2.0, x).unwrap();
+        let sum = ctx.add(pow, y).unwrap();
+        let tape = ctx.get_tape::<I>(sum).unwrap();
+        let eval = tape.new_grad_slice_evaluator();
+        let out = eval.eval(&[3.0], &[1.0], &[0.0], &[]).unwrap();
+        assert_eq!(out[0], Grad::new(9.0, 8.0 * 2f32.ln(), 1.0, 0.0));
+    }
+
+    pub fn test_g_floor_mod<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+
+        for (node, v) in [
+            (ctx.floor(x).unwrap(), 1.0),
+            (ctx.ceil(x).unwrap(), 2.0),
+            (ctx.round(x).unwrap(), 2.0),
+        ] {
+            let tape = ctx.get_tape::<I>(node).unwrap();
+            let eval = tape.new_grad_slice_evaluator();
+            let out = eval.eval(&[1.75], &[0.0], &[0.0], &[]).unwrap();
+            assert_eq!(out[0], Grad::new(v, 0.0, 0.0, 0.0));
+        }
+
+        let m = ctx.modulo(x, 2.0).unwrap();
+        let tape = ctx.get_tape::<I>(m).unwrap();
+        let eval = tape.new_grad_slice_evaluator();
+        let out = eval.eval(&[-1.5], &[0.0], &[0.0], &[]).unwrap();
+        assert_eq!(out[0], Grad::new(0.5, 1.0, 0.0, 0.0));
+
+        let m = ctx.modulo(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(m).unwrap();
+        let eval = tape.new_grad_slice_evaluator();
+        let out = eval.eval(&[5.5], &[2.0], &[0.0], &[]).unwrap();
+        assert_eq!(out[0], Grad::new(1.5, 1.0, -2.0, 0.0));
+    }
+
+    pub fn test_g_cmp_logic<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+
+        // Comparisons have no partial derivatives
+        let lt = ctx.lt(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(lt).unwrap();
+        let eval = tape.new_grad_slice_evaluator();
+        let out = eval.eval(&[1.0, 3.0], &[2.0, 2.0], &[0.0; 2], &[]).unwrap();
+        assert_eq!(out[0], Grad::new(1.0, 0.0, 0.0, 0.0));
+        assert_eq!(out[1], Grad::new(0.0, 0.0, 0.0, 0.0));
+
+        let eq = ctx.eq(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(eq).unwrap();
+        let eval = tape.new_grad_slice_evaluator();
+        let out = eval.eval(&[2.0, 3.0], &[2.0, 2.0], &[0.0; 2], &[]).unwrap();
+        assert_eq!(out[0], Grad::new(1.0, 0.0, 0.0, 0.0));
+        assert_eq!(out[1], Grad::new(0.0, 0.0, 0.0, 0.0));
+
+        let not = ctx.not(x).unwrap();
+        let tape = ctx.get_tape::<I>(not).unwrap();
+        let eval = tape.new_grad_slice_evaluator();
+        let out = eval.eval(&[0.0, 3.0], &[0.0; 2], &[0.0; 2], &[]).unwrap();
+        assert_eq!(out[0], Grad::new(1.0, 0.0, 0.0, 0.0));
+        assert_eq!(out[1], Grad::new(0.0, 0.0, 0.0, 0.0));
+
+        // Logical operations pass through the selected gradient
+        let and = ctx.and(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(and).unwrap();
+        let eval = tape.new_grad_slice_evaluator();
+        let out = eval.eval(&[0.0, 3.0], &[2.0; 2], &[0.0; 2], &[]).unwrap();
+        assert_eq!(out[0], Grad::new(0.0, 1.0, 0.0, 0.0));
+        assert_eq!(out[1], Grad::new(2.0, 0.0, 1.0, 0.0));
+
+        let or = ctx.or(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(or).unwrap();
+        let eval = tape.new_grad_slice_evaluator();
+        let out = eval.eval(&[0.0, 3.0], &[2.0; 2], &[0.0; 2], &[]).unwrap();
+        assert_eq!(out[0], Grad::new(2.0, 0.0, 1.0, 0.0));
+        assert_eq!(out[1], Grad::new(3.0, 1.0, 0.0, 0.0));
+    }
+
+    pub fn test_g_select<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let z = ctx.z();
+
+        // The gradient of the selected branch is passed through
+        let select = ctx.select(x, y, z).unwrap();
+        let tape = ctx.get_tape::<I>(select).unwrap();
+        let eval = tape.new_grad_slice_evaluator();
+        let out = eval
+            .eval(&[-1.0, 0.0, f32::NAN], &[2.0; 3], &[3.0; 3], &[])
+            .unwrap();
+        assert_eq!(out[0], Grad::new(2.0, 0.0, 1.0, 0.0));
+        assert_eq!(out[1], Grad::new(3.0, 0.0, 0.0, 1.0));
+        assert_eq!(out[2], Grad::new(3.0, 0.0, 0.0, 1.0));
+
+        let select = ctx.select(x, y, 4.0).unwrap();
+        let tape = ctx.get_tape::<I>(select).unwrap();
+        let eval = tape.new_grad_slice_evaluator();
+        let out = eval.eval(&[-1.0, 1.0], &[2.0; 2], &[0.0; 2], &[]).unwrap();
+        assert_eq!(out[0], Grad::new(2.0, 0.0, 1.0, 0.0));
+        assert_eq!(out[1], Grad::new(4.0, 0.0, 0.0, 0.0));
+    }
+
     #[macro_export]
     macro_rules! grad_test {
         ($i:ident, $t:ty) => {
@@ -357,6 +635,8 @@ pub mod eval_tests {
             $crate::grad_test!(test_g_x, $t);
             $crate::grad_test!(test_g_y, $t);
             $crate::grad_test!(test_g_z, $t);
+            $crate::grad_test!(test_g_w, $t);
+            $crate::grad_test!(test_g_multi, $t);
             $crate::grad_test!(test_g_abs, $t);
             $crate::grad_test!(test_g_square, $t);
             $crate::grad_test!(test_g_sqrt, $t);
@@ -367,6 +647,12 @@ pub mod eval_tests {
             $crate::grad_test!(test_g_div, $t);
             $crate::grad_test!(test_g_recip, $t);
             $crate::grad_test!(test_g_var, $t);
+            $crate::grad_test!(test_g_exp_sin_cos, $t);
+            $crate::grad_test!(test_g_inverse_trig, $t);
+            $crate::grad_test!(test_g_ln_pow, $t);
+            $crate::grad_test!(test_g_floor_mod, $t);
+            $crate::grad_test!(test_g_cmp_logic, $t);
+            $crate::grad_test!(test_g_select, $t);
         };
     }
 }
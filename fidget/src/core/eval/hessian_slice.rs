@@ -0,0 +1,334 @@
+//! Evaluation of first and second partial derivatives
+use crate::{
+    eval::{types::Hessian, Tape},
+    vm::eval_hessian_slice,
+    Error,
+};
+
+/// Evaluator for many points, calculating first and second partial
+/// derivatives with respect to X, Y, Z
+///
+/// Results can be passed to [`Hessian::mean_curvature`] and
+/// [`Hessian::gaussian_curvature`] to find the curvature of the isosurface at
+/// each point.
+///
+/// This evaluator interprets the tape's VM instructions, so it is available
+/// for tapes from any evaluator family (including the JIT).
+#[derive(Clone)]
+pub struct HessianSliceEval<F> {
+    tape: Tape<F>,
+}
+
+/// Scratch data used by a [`HessianSliceEval`]
+#[derive(Default)]
+pub struct HessianSliceEvalData {
+    out: Vec<Hessian>,
+    slots: Vec<Vec<Hessian>>,
+}
+
+impl HessianSliceEvalData {
+    fn prepare<F>(&mut self, tape: &Tape<F>, size: usize) {
+        let nan = Hessian::from(f32::NAN);
+        self.slots.resize_with(tape.slot_count(), Vec::new);
+        for s in self.slots.iter_mut() {
+            s.resize(size.max(s.len()), nan);
+        }
+        self.out.resize(size, nan);
+    }
+}
+
+impl<F> HessianSliceEval<F> {
+    /// Builds a new evaluator for the given tape
+    pub fn new(tape: &Tape<F>) -> Self {
+        Self { tape: tape.clone() }
+    }
+
+    /// Returns a copy of the inner tape
+    pub fn tape(&self) -> Tape<F> {
+        self.tape.clone()
+    }
+
+    /// Evaluate using the given `data` as scratch memory
+    ///
+    /// Returns a slice of results borrowed from `data`.  The W input is set to
+    /// 0.
+    pub fn eval_with<'a>(
+        &self,
+        x: &[f32],
+        y: &[f32],
+        z: &[f32],
+        vars: &[f32],
+        data: &'a mut HessianSliceEvalData,
+    ) -> Result<&'a [Hessian], Error> {
+        if x.len() != y.len() || x.len() != z.len() {
+            return Err(Error::MismatchedSlices);
+        } else if vars.len() != self.tape.var_count() {
+            return Err(Error::BadVarSlice(vars.len(), self.tape.var_count()));
+        }
+        let size = x.len();
+        data.prepare(&self.tape, size);
+
+        let zero = [[0.0; 3]; 3];
+        eval_hessian_slice(
+            &self.tape,
+            size,
+            |j, i| match j {
+                0 => Hessian::new(x[i], [1.0, 0.0, 0.0], zero),
+                1 => Hessian::new(y[i], [0.0, 1.0, 0.0], zero),
+                2 => Hessian::new(z[i], [0.0, 0.0, 1.0], zero),
+                3 => Hessian::from(0.0),
+                _ => panic!("Invalid input: {}", j),
+            },
+            |j| vars[j as usize].into(),
+            &mut data.slots,
+        );
+        data.out.copy_from_slice(&data.slots[0][0..size]);
+        Ok(&data.out)
+    }
+
+    /// Evaluates the given slices, returning a fresh `Vec<Hessian>`
+    ///
+    /// This function performs allocation; in a hot loop, consider using
+    /// [`eval_with`](Self::eval_with) instead.
+    pub fn eval(
+        &self,
+        x: &[f32],
+        y: &[f32],
+        z: &[f32],
+        vars: &[f32],
+    ) -> Result<Vec<Hessian>, Error> {
+        let mut data = Default::default();
+        self.eval_with(x, y, z, vars, &mut data)?;
+        Ok(data.out)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(any(test, feature = "eval-tests"))]
+pub mod eval_tests {
+    use super::*;
+    use crate::{
+        context::{Context, Node},
+        eval::{Family, Vars},
+    };
+
+    pub fn test_h_basic<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let z = ctx.z();
+        let xy = ctx.mul(x, y).unwrap();
+        let z2 = ctx.square(z).unwrap();
+        let sum = ctx.add(xy, z2).unwrap();
+        let tape = ctx.get_tape::<I>(sum).unwrap();
+
+        let eval = tape.new_hessian_slice_evaluator();
+        let out = eval.eval(&[2.0], &[3.0], &[4.0], &[]).unwrap();
+        assert_eq!(
+            out[0],
+            Hessian::new(
+                22.0,
+                [3.0, 2.0, 8.0],
+                [[0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 2.0]]
+            )
+        );
+        let grad = tape.new_grad_slice_evaluator();
+        let g = grad.eval(&[2.0], &[3.0], &[4.0], &[]).unwrap();
+        assert_eq!(out[0].grad(), g[0]);
+    }
+
+    pub fn test_h_var<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let a = ctx.var("a").unwrap();
+        let ax = ctx.mul(a, x).unwrap();
+        let s = ctx.square(ax).unwrap();
+        let tape = ctx.get_tape::<I>(s).unwrap();
+
+        let mut vars = Vars::new(&tape);
+        vars.set("a", 3.0);
+        let eval = tape.new_hessian_slice_evaluator();
+        let out = eval
+            .eval(&[1.0, 2.0], &[0.0; 2], &[0.0; 2], vars.as_slice())
+            .unwrap();
+        // (a * x)^2 = 9 x^2
+        assert_eq!(out[0].v, 9.0);
+        assert_eq!(out[1].v, 36.0);
+        assert_eq!(out[0].g, [18.0, 0.0, 0.0]);
+        assert_eq!(out[1].g, [36.0, 0.0, 0.0]);
+        assert_eq!(out[0].h[0], [18.0, 0.0, 0.0]);
+        assert_eq!(out[1].h[0], [18.0, 0.0, 0.0]);
+
+        assert!(matches!(
+            eval.eval(&[1.0], &[0.0], &[0.0], &[]),
+            Err(Error::BadVarSlice(0, 1))
+        ));
+        assert!(matches!(
+            eval.eval(&[1.0, 2.0], &[0.0], &[0.0], vars.as_slice()),
+            Err(Error::MismatchedSlices)
+        ));
+    }
+
+    pub fn test_h_curvature<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let z = ctx.z();
+        let x2 = ctx.square(x).unwrap();
+        let y2 = ctx.square(y).unwrap();
+        let z2 = ctx.square(z).unwrap();
+        let xy = ctx.add(x2, y2).unwrap();
+        let xyz = ctx.add(xy, z2).unwrap();
+        let r = ctx.sqrt(xyz).unwrap();
+        let sphere = ctx.sub(r, 1.0).unwrap();
+        let r = ctx.sqrt(xy).unwrap();
+        let cylinder = ctx.sub(r, 1.0).unwrap();
+
+        let xs = [1.0, 0.0, -0.5, 2.0];
+        let ys = [0.0, 2.0, 0.3, -1.0];
+        let zs = [0.0, 0.5, 0.7, 3.0];
+
+        let tape = ctx.get_tape::<I>(sphere).unwrap();
+        let eval = tape.new_hessian_slice_evaluator();
+        let out = eval.eval(&xs, &ys, &zs, &[]).unwrap();
+        for (i, h) in out.iter().enumerate() {
+            let r = (xs[i].powi(2) + ys[i].powi(2) + zs[i].powi(2)).sqrt();
+            let mean = h.mean_curvature();
+            let gauss = h.gaussian_curvature();
+            assert!((mean - 1.0 / r).abs() < 1e-5, "{mean} != 1 / {r}");
+            assert!((gauss - 1.0 / r.powi(2)).abs() < 1e-5, "{gauss}");
+        }
+
+        let tape = ctx.get_tape::<I>(cylinder).unwrap();
+        let eval = tape.new_hessian_slice_evaluator();
+        let out = eval.eval(&xs, &ys, &zs, &[]).unwrap();
+        for (i, h) in out.iter().enumerate() {
+            let r = (xs[i].powi(2) + ys[i].powi(2)).sqrt();
+            let mean = h.mean_curvature();
+            let gauss = h.gaussian_curvature();
+            assert!((mean - 0.5 / r).abs() < 1e-5, "{mean} != 1 / 2{r}");
+            assert!(gauss.abs() < 1e-5, "{gauss}");
+        }
+
+        // Flipping the sign of the field flips the mean curvature, but not the
+        // Gaussian curvature
+        let neg = ctx.neg(sphere).unwrap();
+        let tape = ctx.get_tape::<I>(neg).unwrap();
+        let eval = tape.new_hessian_slice_evaluator();
+        let out = eval.eval(&[0.0], &[0.0], &[2.0], &[]).unwrap();
+        assert!((out[0].mean_curvature() + 0.5).abs() < 1e-6);
+        assert!((out[0].gaussian_curvature() - 0.25).abs() < 1e-6);
+    }
+
+    /// Compares second derivatives against finite differences of the gradient
+    pub fn test_h_finite_difference<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let z = ctx.z();
+
+        // Arguments which are nonlinear in x, y, z, and stay within (0, 1)
+        // over the sample points
+        let xy = ctx.mul(x, y).unwrap();
+        let z2 = ctx.square(z).unwrap();
+        let a = ctx.add(xy, z2).unwrap();
+        let a = ctx.mul(a, 0.2).unwrap();
+        let a = ctx.add(a, 0.3).unwrap();
+        let yz = ctx.mul(y, z).unwrap();
+        let b = ctx.sin(yz).unwrap();
+        let b = ctx.mul(b, 0.2).unwrap();
+        let b = ctx.add(b, 0.5).unwrap();
+        let bx = ctx.mul(b, x).unwrap();
+
+        let mut nodes: Vec<Node> = vec![
+            ctx.neg(a).unwrap(),
+            ctx.abs(bx).unwrap(),
+            ctx.sqrt(a).unwrap(),
+            ctx.square(bx).unwrap(),
+            ctx.recip(a).unwrap(),
+            ctx.exp(a).unwrap(),
+            ctx.sin(bx).unwrap(),
+            ctx.cos(bx).unwrap(),
+            ctx.tan(a).unwrap(),
+            ctx.asin(a).unwrap(),
+            ctx.acos(b).unwrap(),
+            ctx.atan(bx).unwrap(),
+            ctx.ln(a).unwrap(),
+            ctx.powi(a, 3).unwrap(),
+            ctx.powi(b, -2).unwrap(),
+            ctx.pow(a, 1.5).unwrap(),
+            ctx.atan2(a, bx).unwrap(),
+            ctx.pow(a, b).unwrap(),
+            ctx.div(a, b).unwrap(),
+            ctx.mul(a, bx).unwrap(),
+            ctx.sub(a, bx).unwrap(),
+        ];
+        nodes.push(ctx.min(a, bx).unwrap());
+        nodes.push(ctx.max(a, bx).unwrap());
+
+        let xs = [0.5, -0.75, 1.0];
+        let ys = [0.25, 0.5, -1.0];
+        let zs = [-0.5, 0.75, 0.5];
+        let eps = 1e-3;
+        for n in nodes {
+            let tape = ctx.get_tape::<I>(n).unwrap();
+            let eval = tape.new_hessian_slice_evaluator();
+            let grad = tape.new_grad_slice_evaluator();
+            let out = eval.eval(&xs, &ys, &zs, &[]).unwrap();
+            for (i, h) in out.iter().enumerate() {
+                let g = grad.eval(&[xs[i]], &[ys[i]], &[zs[i]], &[]).unwrap();
+                let g = g[0];
+                assert!((h.v - g.v).abs() < 1e-6);
+                for (a, b) in h.g.iter().zip([g.dx, g.dy, g.dz]) {
+                    assert!((a - b).abs() < 1e-5, "{:?}: {a} != {b}", n);
+                }
+
+                // Central differences along each axis
+                for j in 0..3 {
+                    let mut p = [[xs[i]], [ys[i]], [zs[i]]];
+                    p[j][0] += eps;
+                    let hi = grad.eval(&p[0], &p[1], &p[2], &[]).unwrap();
+                    p[j][0] -= 2.0 * eps;
+                    let lo = grad.eval(&p[0], &p[1], &p[2], &[]).unwrap();
+                    let d = [
+                        hi[0].dx - lo[0].dx,
+                        hi[0].dy - lo[0].dy,
+                        hi[0].dz - lo[0].dz,
+                    ];
+                    for k in 0..3 {
+                        let fd = d[k] / (2.0 * eps);
+                        let err = (h.h[j][k] - fd).abs();
+                        assert!(
+                            err < 1e-2 * fd.abs().max(1.0),
+                            "{:?} at {i}: h[{j}][{k}] = {} != {fd}",
+                            n,
+                            h.h[j][k],
+                        );
+                        assert_eq!(h.h[j][k], h.h[k][j]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[macro_export]
+    macro_rules! hessian_test {
+        ($i:ident, $t:ty) => {
+            #[test]
+            fn $i() {
+                $crate::eval::hessian_slice::eval_tests::$i::<$t>()
+            }
+        };
+    }
+
+    #[macro_export]
+    macro_rules! hessian_slice_tests {
+        ($t:ty) => {
+            $crate::hessian_test!(test_h_basic, $t);
+            $crate::hessian_test!(test_h_var, $t);
+            $crate::hessian_test!(test_h_curvature, $t);
+            $crate::hessian_test!(test_h_finite_difference, $t);
+        };
+    }
+}
@@ -523,6 +523,601 @@ pub mod eval_tests {
         );
     }
 
+    pub fn test_i_w<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let w = ctx.w();
+        let diff = ctx.sub(w, x).unwrap();
+        let tape = ctx.get_tape::<I>(diff).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(
+            eval.eval_xyzw([0.0, 1.0], [0.0; 2], [0.0; 2], [2.0, 5.0], &[])
+                .unwrap()
+                .0,
+            [1.0, 5.0].into()
+        );
+        assert_eq!(eval.eval_xy([0.0, 1.0], [2.0, 5.0]), [-1.0, 0.0].into());
+
+        let m = ctx.max(x, w).unwrap();
+        let tape = ctx.get_tape::<I>(m).unwrap();
+        let eval = tape.new_interval_evaluator();
+        let (v, trace) = eval
+            .eval_xyzw([0.0, 1.0], [0.0; 2], [0.0; 2], [2.0, 3.0], &[])
+            .unwrap();
+        assert_eq!(v, [2.0, 3.0].into());
+        assert_eq!(trace.unwrap().choices(), &[Choice::Right]);
+    }
+
+    pub fn test_i_exp_sin_cos<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+
+        let exp_x = ctx.exp(x).unwrap();
+        let tape = ctx.get_tape::<I>(exp_x).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([0.0, 1.0]), [1.0, 1f32.exp()].into());
+        assert_eq!(eval.eval_x([-1.0, 0.0]), [(-1f32).exp(), 1.0].into());
+
+        let sin_x = ctx.sin(x).unwrap();
+        let tape = ctx.get_tape::<I>(sin_x).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([0.0, 1.0]), [0.0, 1f32.sin()].into());
+
+        let cos_x = ctx.cos(x).unwrap();
+        let tape = ctx.get_tape::<I>(cos_x).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([0.0, 1.0]), [1f32.cos(), 1.0].into());
+
+        // Check that values (and choices) are preserved across the call
+        let y = ctx.y();
+        let z = ctx.z();
+        let sin_y = ctx.sin(y).unwrap();
+        let min = ctx.min(x, sin_y).unwrap();
+        let cos_z = ctx.cos(z).unwrap();
+        let sum = ctx.add(min, cos_z).unwrap();
+        let tape = ctx.get_tape::<I>(sum).unwrap();
+        let eval = tape.new_interval_evaluator();
+        let (v, data) = eval
+            .eval([-2.0, -1.0], [0.0, 1.0], [0.0, 1.0], &[])
+            .unwrap();
+        assert_eq!(v, [-2.0 + 1f32.cos(), 0.0].into());
+        assert_eq!(data.unwrap().choices(), &[Choice::Left]);
+    }
+
+    pub fn test_i_inverse_trig<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+
+        let tan_x = ctx.tan(x).unwrap();
+        let tape = ctx.get_tape::<I>(tan_x).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([0.0, 1.0]), [0.0, 1f32.tan()].into());
+        assert_eq!(
+            eval.eval_x([-1.0, 1.0]),
+            [(-1f32).tan(), 1f32.tan()].into()
+        );
+        let nanan = eval.eval_x([1.0, 2.0]);
+        assert!(nanan.lower().is_nan());
+        assert!(nanan.upper().is_nan());
+
+        let asin_x = ctx.asin(x).unwrap();
+        let tape = ctx.get_tape::<I>(asin_x).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([0.0, 0.5]), [0.0, 0.5f32.asin()].into());
+        assert_eq!(
+            eval.eval_x([-2.0, 0.5]),
+            [(-1f32).asin(), 0.5f32.asin()].into()
+        );
+        let nanan = eval.eval_x([2.0, 3.0]);
+        assert!(nanan.lower().is_nan());
+        assert!(nanan.upper().is_nan());
+
+        let acos_x = ctx.acos(x).unwrap();
+        let tape = ctx.get_tape::<I>(acos_x).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([0.0, 2.0]), [0.0, 0f32.acos()].into());
+        assert_eq!(
+            eval.eval_x([-0.5, 0.5]),
+            [0.5f32.acos(), (-0.5f32).acos()].into()
+        );
+
+        let atan_x = ctx.atan(x).unwrap();
+        let tape = ctx.get_tape::<I>(atan_x).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(
+            eval.eval_x([-1.0, 2.0]),
+            [(-1f32).atan(), 2f32.atan()].into()
+        );
+    }
+
+    pub fn test_i_atan2<I: Family>() {
+        use std::f32::consts::{FRAC_PI_2, PI};
+
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let a = ctx.atan2(y, x).unwrap();
+        let tape = ctx.get_tape::<I>(a).unwrap();
+        let eval = tape.new_interval_evaluator();
+
+        assert_eq!(
+            eval.eval_xy([1.0, 2.0], [1.0, 2.0]),
+            [1f32.atan2(2.0), 2f32.atan2(1.0)].into()
+        );
+        assert_eq!(
+            eval.eval_xy([-2.0, -1.0], [1.0, 2.0]),
+            [2f32.atan2(-1.0), 1f32.atan2(-2.0)].into()
+        );
+        assert_eq!(
+            eval.eval_xy([0.0, 1.0], [-2.0, -1.0]),
+            [-FRAC_PI_2, (-1f32).atan2(1.0)].into()
+        );
+        // Crossing the positive X axis is fine
+        assert_eq!(
+            eval.eval_xy([1.0, 2.0], [-1.0, 1.0]),
+            [(-1f32).atan2(1.0), 1f32.atan2(1.0)].into()
+        );
+        // Crossing the negative X axis or containing the origin isn't
+        assert_eq!(eval.eval_xy([-2.0, -1.0], [-1.0, 1.0]), [-PI, PI].into());
+        assert_eq!(eval.eval_xy([-1.0, 1.0], [-1.0, 1.0]), [-PI, PI].into());
+
+        let (v, _) =
+            eval.eval([f32::NAN; 2], [0.0, 1.0], [0.0; 2], &[]).unwrap();
+        assert!(v.lower().is_nan());
+        assert!(v.upper().is_nan());
+
+        let a = ctx.atan2(y, -1.0).unwrap();
+        let tape = ctx.get_tape::<I>(a).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(
+            eval.eval_xy([0.0, 0.0], [0.5, 1.0]),
+            [1f32.atan2(-1.0), 0.5f32.atan2(-1.0)].into()
+        );
+
+        let a = ctx.atan2(1.0, x).unwrap();
+        let tape = ctx.get_tape::<I>(a).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(
+            eval.eval_x([1.0, 2.0]),
+            [1f32.atan2(2.0), 1f32.atan2(1.0)].into()
+        );
+    }
+
+    pub fn test_i_ln_pow<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+
+        let ln_x = ctx.ln(x).unwrap();
+        let tape = ctx.get_tape::<I>(ln_x).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([1.0, 2.0]), [0.0, 2f32.ln()].into());
+        assert_eq!(
+            eval.eval_x([-1.0, 2.0]),
+            [-f32::INFINITY, 2f32.ln()].into()
+        );
+        let nanan = eval.eval_x([-2.0, -1.0]);
+        assert!(nanan.lower().is_nan());
+        assert!(nanan.upper().is_nan());
+
+        // Integer powers are tight, even with negative inputs
+        let cube = ctx.powi(x, 3).unwrap();
+        let tape = ctx.get_tape::<I>(cube).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([-2.0, 1.0]), [-8.0, 1.0].into());
+
+        let fourth = ctx.powi(x, 4).unwrap();
+        let tape = ctx.get_tape::<I>(fourth).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([-2.0, 1.0]), [0.0, 16.0].into());
+        assert_eq!(eval.eval_x([-2.0, -1.0]), [1.0, 16.0].into());
+
+        let inv = ctx.powi(x, -2).unwrap();
+        let tape = ctx.get_tape::<I>(inv).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([-2.0, -1.0]), [0.25, 1.0].into());
+        assert_eq!(eval.eval_x([1.0, 2.0]), [0.25, 1.0].into());
+        let nanan = eval.eval_x([-1.0, 1.0]);
+        assert!(nanan.lower().is_nan());
+        assert!(nanan.upper().is_nan());
+
+        // Non-integer powers are only valid for positive inputs
+        let sqrt = ctx.pow(x, 0.5).unwrap();
+        let tape = ctx.get_tape::<I>(sqrt).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([1.0, 4.0]), [1.0, 2.0].into());
+        assert_eq!(eval.eval_x([-1.0, 4.0]), [0.0, 2.0].into());
+        let nanan = eval.eval_x([-4.0, -1.0]);
+        assert!(nanan.lower().is_nan());
+        assert!(nanan.upper().is_nan());
+
+        let exp2 = ctx.pow(2.0, x).unwrap();
+        let tape = ctx.get_tape::<I>(exp2).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([-1.0, 3.0]), [0.5, 8.0].into());
+
+        let pow = ctx.pow(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(pow).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_xy([0.5, 2.0], [-1.0, 2.0]), [0.25, 4.0].into());
+        assert_eq!(eval.eval_xy([-2.0, 1.0], [3.0, 3.0]), [-8.0, 1.0].into());
+        let nanan = eval.eval_xy([-2.0, 1.0], [1.0, 2.0]);
+        assert!(nanan.lower().is_nan());
+        assert!(nanan.upper().is_nan());
+    }
+
+    pub fn test_i_floor_mod<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+
+        let floor = ctx.floor(x).unwrap();
+        let tape = ctx.get_tape::<I>(floor).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([0.25, 0.75]), [0.0, 0.0].into());
+        assert_eq!(eval.eval_x([-1.5, 2.5]), [-2.0, 2.0].into());
+
+        let ceil = ctx.ceil(x).unwrap();
+        let tape = ctx.get_tape::<I>(ceil).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([0.25, 0.75]), [1.0, 1.0].into());
+        assert_eq!(eval.eval_x([-1.5, 2.5]), [-1.0, 3.0].into());
+
+        let round = ctx.round(x).unwrap();
+        let tape = ctx.get_tape::<I>(round).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([0.25, 0.75]), [0.0, 1.0].into());
+        assert_eq!(eval.eval_x([-2.5, 2.5]), [-3.0, 3.0].into());
+
+        // Within a single period, the remainder is tight
+        let m = ctx.modulo(x, 2.0).unwrap();
+        let tape = ctx.get_tape::<I>(m).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([2.5, 3.5]), [0.5, 1.5].into());
+        assert_eq!(eval.eval_x([-1.5, -0.5]), [0.5, 1.5].into());
+
+        // Crossing a discontinuity gives the full range
+        assert_eq!(eval.eval_x([1.5, 2.5]), [0.0, 2.0].into());
+        assert_eq!(eval.eval_x([-10.0, 10.0]), [0.0, 2.0].into());
+
+        let m = ctx.modulo(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(m).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_xy([0.5, 1.0], [2.0, 3.0]), [0.5, 1.0].into());
+        assert_eq!(eval.eval_xy([0.5, 2.5], [-3.0, -2.0]), [0.0, 3.0].into());
+        let nanan = eval.eval_xy([0.5, 1.0], [-1.0, 1.0]);
+        assert!(nanan.lower().is_nan());
+        assert!(nanan.upper().is_nan());
+    }
+
+    pub fn test_i_infinite_bounds<I: Family>() {
+        // `ln` of a range touching zero has an infinite lower bound, which
+        // periodic functions and remainders can't handle (and which gives NaN
+        // when multiplied by zero)
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let ln = ctx.ln(x).unwrap();
+
+        let sin = ctx.sin(ln).unwrap();
+        let cos = ctx.cos(ln).unwrap();
+        let m = ctx.modulo(ln, 1.5).unwrap();
+        let mul = ctx.mul(ln, y).unwrap();
+        for node in [sin, cos, m, mul] {
+            let tape = ctx.get_tape::<I>(node).unwrap();
+            let eval = tape.new_interval_evaluator();
+            let nanan = eval.eval_x([0.0, 0.0]);
+            assert!(nanan.lower().is_nan());
+            assert!(nanan.upper().is_nan());
+        }
+    }
+
+    pub fn test_i_cmp_logic<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+
+        let lt = ctx.lt(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(lt).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_xy([0.0, 1.0], [2.0, 3.0]), [1.0, 1.0].into());
+        assert_eq!(eval.eval_xy([1.0, 2.0], [0.0, 1.0]), [0.0, 0.0].into());
+        assert_eq!(eval.eval_xy([0.0, 2.0], [1.0, 3.0]), [0.0, 1.0].into());
+
+        let gt = ctx.gt(x, 1.0).unwrap();
+        let tape = ctx.get_tape::<I>(gt).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([2.0, 3.0]), [1.0, 1.0].into());
+        assert_eq!(eval.eval_x([0.0, 1.0]), [0.0, 0.0].into());
+        assert_eq!(eval.eval_x([0.0, 3.0]), [0.0, 1.0].into());
+
+        let eq = ctx.eq(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(eq).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_xy([1.0, 1.0], [1.0, 1.0]), [1.0, 1.0].into());
+        assert_eq!(eval.eval_xy([0.0, 1.0], [2.0, 3.0]), [0.0, 0.0].into());
+        assert_eq!(eval.eval_xy([0.0, 2.0], [1.0, 3.0]), [0.0, 1.0].into());
+
+        let not = ctx.not(x).unwrap();
+        let tape = ctx.get_tape::<I>(not).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([0.0, 0.0]), [1.0, 1.0].into());
+        assert_eq!(eval.eval_x([1.0, 2.0]), [0.0, 0.0].into());
+        assert_eq!(eval.eval_x([-1.0, 2.0]), [0.0, 1.0].into());
+
+        let and = ctx.and(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(and).unwrap();
+        let eval = tape.new_interval_evaluator();
+        let (r, data) =
+            eval.eval([0.0, 0.0], [2.0, 3.0], [0.0; 2], &[]).unwrap();
+        assert_eq!(r, [0.0, 0.0].into());
+        assert_eq!(data.unwrap().choices(), &[Choice::Left]);
+
+        let (r, data) =
+            eval.eval([-2.0, -1.0], [2.0, 3.0], [0.0; 2], &[]).unwrap();
+        assert_eq!(r, [2.0, 3.0].into());
+        assert_eq!(data.unwrap().choices(), &[Choice::Right]);
+
+        let (r, data) =
+            eval.eval([-1.0, 1.0], [2.0, 3.0], [0.0; 2], &[]).unwrap();
+        assert_eq!(r, [0.0, 3.0].into());
+        assert!(data.is_none());
+
+        let (r, data) =
+            eval.eval([f32::NAN; 2], [2.0, 3.0], [0.0; 2], &[]).unwrap();
+        assert!(r.lower().is_nan());
+        assert!(r.upper().is_nan());
+        assert!(data.is_none());
+
+        let or = ctx.or(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(or).unwrap();
+        let eval = tape.new_interval_evaluator();
+        let (r, data) =
+            eval.eval([0.0, 0.0], [2.0, 3.0], [0.0; 2], &[]).unwrap();
+        assert_eq!(r, [2.0, 3.0].into());
+        assert_eq!(data.unwrap().choices(), &[Choice::Right]);
+
+        let (r, data) =
+            eval.eval([1.0, 2.0], [-3.0, 3.0], [0.0; 2], &[]).unwrap();
+        assert_eq!(r, [1.0, 2.0].into());
+        assert_eq!(data.unwrap().choices(), &[Choice::Left]);
+
+        let (r, data) =
+            eval.eval([-1.0, 1.0], [2.0, 3.0], [0.0; 2], &[]).unwrap();
+        assert_eq!(r, [-1.0, 3.0].into());
+        assert!(data.is_none());
+
+        let (r, data) =
+            eval.eval([0.0, 1.0], [f32::NAN; 2], [0.0; 2], &[]).unwrap();
+        assert!(r.lower().is_nan());
+        assert!(r.upper().is_nan());
+        assert!(data.is_none());
+
+        // Immediates are broadcast into both sides of the interval
+        let and = ctx.and(x, 2.0).unwrap();
+        let tape = ctx.get_tape::<I>(and).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([-1.0, 1.0]), [0.0, 2.0].into());
+        assert_eq!(eval.eval_x([1.0, 3.0]), [2.0, 2.0].into());
+    }
+
+    pub fn test_i_select<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let z = ctx.z();
+
+        let select = ctx.select(x, y, z).unwrap();
+        let tape = ctx.get_tape::<I>(select).unwrap();
+        let eval = tape.new_interval_evaluator();
+        let (r, data) = eval
+            .eval([-2.0, -1.0], [1.0, 2.0], [3.0, 4.0], &[])
+            .unwrap();
+        assert_eq!(r, [1.0, 2.0].into());
+        assert_eq!(data.unwrap().choices(), &[Choice::Left]);
+
+        let (r, data) =
+            eval.eval([0.0, 1.0], [1.0, 2.0], [3.0, 4.0], &[]).unwrap();
+        assert_eq!(r, [3.0, 4.0].into());
+        let data = data.unwrap();
+        assert_eq!(data.choices(), &[Choice::Right]);
+        let next = data.simplify().unwrap();
+        assert_eq!(next.len(), 1);
+        let eval_next = next.new_interval_evaluator();
+        let (r, _) = eval_next
+            .eval([-2.0, -1.0], [1.0, 2.0], [3.0, 4.0], &[])
+            .unwrap();
+        assert_eq!(r, [3.0, 4.0].into());
+
+        let (r, data) =
+            eval.eval([-1.0, 1.0], [1.0, 2.0], [3.0, 5.0], &[]).unwrap();
+        assert_eq!(r, [1.0, 5.0].into());
+        assert!(data.is_none());
+
+        for (c, a) in
+            [([f32::NAN; 2], [1.0, 2.0]), ([-2.0, -1.0], [f32::NAN; 2])]
+        {
+            let (r, data) = eval.eval(c, a, [3.0, 4.0], &[]).unwrap();
+            assert!(r.lower().is_nan());
+            assert!(r.upper().is_nan());
+            assert!(data.is_none());
+        }
+
+        // Immediates are copied into registers
+        let select = ctx.select(x, 1.0, y).unwrap();
+        let tape = ctx.get_tape::<I>(select).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_xy([-1.0, 1.0], [3.0, 4.0]), [1.0, 4.0].into());
+        assert_eq!(eval.eval_xy([-2.0, -1.0], [3.0, 4.0]), [1.0, 1.0].into());
+    }
+
+    /// Checks that interval results contain the true range of the function,
+    /// for interval families which round outwards
+    ///
+    /// The reference values are computed by sampling in double precision.
+    pub fn test_i_sound<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+
+        let mut nodes = vec![];
+        let a = ctx.mul(x, 0.1).unwrap();
+        let b = ctx.div(y, 3.0).unwrap();
+        nodes.push(ctx.add(a, b).unwrap());
+
+        let a = ctx.sub(x, 0.1).unwrap();
+        let b = ctx.add(x, 0.1).unwrap();
+        nodes.push(ctx.mul(a, b).unwrap());
+
+        let x2 = ctx.square(x).unwrap();
+        let y2 = ctx.square(y).unwrap();
+        let r = ctx.add(x2, y2).unwrap();
+        let r = ctx.sqrt(r).unwrap();
+        nodes.push(ctx.sub(r, 0.3).unwrap());
+
+        let a = ctx.mul(x, 0.7).unwrap();
+        let a = ctx.exp(a).unwrap();
+        let b = ctx.sin(y).unwrap();
+        let xy = ctx.mul(x, y).unwrap();
+        let c = ctx.cos(xy).unwrap();
+        let d = ctx.sub(a, b).unwrap();
+        nodes.push(ctx.add(d, c).unwrap());
+
+        nodes.push(ctx.atan2(y, x).unwrap());
+
+        let a = ctx.add(x2, 0.1).unwrap();
+        nodes.push(ctx.ln(a).unwrap());
+
+        let a = ctx.add(x, 2.5).unwrap();
+        let a = ctx.recip(a).unwrap();
+        let b = ctx.add(y, 3.1).unwrap();
+        let b = ctx.div(x, b).unwrap();
+        nodes.push(ctx.sub(a, b).unwrap());
+
+        let a = ctx.powi(x, 3).unwrap();
+        let b = ctx.mul(y, 0.45).unwrap();
+        let b = ctx.atan(b).unwrap();
+        nodes.push(ctx.add(a, b).unwrap());
+
+        let a = ctx.sub(0.2, x).unwrap();
+        nodes.push(ctx.max(a, y2).unwrap());
+
+        // Simple linear congruential generator, so the test is deterministic
+        let mut seed = 0x1234_5678u32;
+        let mut rand = move || {
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            (seed >> 8) as f32 / (1 << 24) as f32
+        };
+        let mut boxes = vec![];
+        for _ in 0..32 {
+            let (x, y) = (rand() * 4.0 - 2.0, rand() * 4.0 - 2.0);
+            // Use a mix of zero-width and wider boxes
+            let (w, h) = if boxes.len() % 4 == 0 {
+                (0.0, 0.0)
+            } else {
+                (rand(), rand())
+            };
+            boxes.push((Interval::new(x, x + w), Interval::new(y, y + h)));
+        }
+        boxes.push(([3.0, 3.0].into(), [0.0, 0.0].into()));
+        boxes.push(([0.1, 0.1].into(), [1.0 / 3.0, 1.0 / 3.0].into()));
+
+        for node in nodes {
+            let tape = ctx.get_tape::<I>(node).unwrap();
+            let eval = tape.new_interval_evaluator();
+            let tape64 = ctx.get_tape::<crate::vm::Eval64>(node).unwrap();
+            let point64 = crate::vm::PointEval64::new(&tape64);
+
+            for &(bx, by) in &boxes {
+                let (out, _) = eval.eval(bx, by, 0.0.into(), &[]).unwrap();
+                for i in 0..=8 {
+                    for j in 0..=8 {
+                        let px = bx.lower() + bx.width() * i as f32 / 8.0;
+                        let py = by.lower() + by.width() * j as f32 / 8.0;
+                        let (v, _) = point64.eval(px, py, 0.0, &[]).unwrap();
+                        if v.is_nan() {
+                            continue;
+                        }
+                        assert!(
+                            out.lower() as f64 <= v && v <= out.upper() as f64,
+                            "{v} at ({px}, {py}) is outside {out:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks that constants which aren't representable as an `f32` are
+    /// loaded as intervals that contain them
+    pub fn test_i_sound_imm<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let a = ctx.mul(x, 0.1).unwrap();
+        let tape = ctx.get_tape::<I>(a).unwrap();
+        let eval = tape.new_interval_evaluator();
+
+        // A regular evaluator would return `0.3f32`, which is above 0.3
+        let out = eval.eval_x([3.0, 3.0]);
+        assert!((out.lower() as f64) < 0.3, "{out:?}");
+        assert!((out.upper() as f64) > 0.3, "{out:?}");
+
+        let c = ctx.constant(0.1);
+        let tape = ctx.get_tape::<I>(c).unwrap();
+        let eval = tape.new_interval_evaluator();
+        let out = eval.eval_x([0.0, 0.0]);
+        assert!((out.lower() as f64) < 0.1, "{out:?}");
+        assert!((out.upper() as f64) > 0.1, "{out:?}");
+
+        // Exactly representable constants are unchanged
+        let c = ctx.constant(0.5);
+        let tape = ctx.get_tape::<I>(c).unwrap();
+        let eval = tape.new_interval_evaluator();
+        assert_eq!(eval.eval_x([0.0, 0.0]), [0.5, 0.5].into());
+    }
+
+    pub fn test_i_sound_non_negative<I: Family>() {
+        // Rounding outwards must keep zero in the result, without pushing the
+        // lower bound of a non-negative function below it
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+
+        let d = ctx.sub(y, y).unwrap();
+        let a = ctx.abs(d).unwrap();
+        let tape = ctx.get_tape::<I>(a).unwrap();
+        let eval = tape.new_interval_evaluator();
+        let out = eval.eval_xy([0.0, 0.0], [0.5, 0.6]);
+        assert_eq!(out.lower(), 0.0, "{out:?}");
+        assert!(out.upper() >= 0.1, "{out:?}");
+
+        let d = ctx.sub(x, x).unwrap();
+        let s = ctx.sqrt(d).unwrap();
+        let tape = ctx.get_tape::<I>(s).unwrap();
+        let eval = tape.new_interval_evaluator();
+        for v in [[0.0, 0.0], [0.5, 0.6]] {
+            let out = eval.eval_x(v);
+            assert_eq!(out.lower(), 0.0, "{out:?}");
+            assert!(out.upper() >= 0.0, "{out:?}");
+        }
+
+        let s = ctx.sqrt(x).unwrap();
+        let tape = ctx.get_tape::<I>(s).unwrap();
+        let eval = tape.new_interval_evaluator();
+        let out = eval.eval_x([0.0, 0.0]);
+        assert_eq!(out.lower(), 0.0, "{out:?}");
+        assert!(out.upper() >= 0.0, "{out:?}");
+
+        let c = ctx.lt(x, y).unwrap();
+        let s = ctx.sqrt(c).unwrap();
+        let a = ctx.abs(s).unwrap();
+        let tape = ctx.get_tape::<I>(a).unwrap();
+        let eval = tape.new_interval_evaluator();
+        let out = eval.eval_xy([0.0, 1.0], [0.5, 0.5]);
+        assert_eq!(out.lower(), 0.0, "{out:?}");
+        assert!(out.upper() >= 1.0, "{out:?}");
+    }
+
     #[macro_export]
     macro_rules! interval_test {
         ($i:ident, $t:ty) => {
@@ -553,6 +1148,24 @@ pub mod eval_tests {
             $crate::interval_test!(test_i_max_imm, $t);
             $crate::interval_test!(test_i_simplify, $t);
             $crate::interval_test!(test_i_var, $t);
+            $crate::interval_test!(test_i_w, $t);
+            $crate::interval_test!(test_i_exp_sin_cos, $t);
+            $crate::interval_test!(test_i_inverse_trig, $t);
+            $crate::interval_test!(test_i_atan2, $t);
+            $crate::interval_test!(test_i_ln_pow, $t);
+            $crate::interval_test!(test_i_floor_mod, $t);
+            $crate::interval_test!(test_i_infinite_bounds, $t);
+            $crate::interval_test!(test_i_cmp_logic, $t);
+            $crate::interval_test!(test_i_select, $t);
+        };
+    }
+
+    #[macro_export]
+    macro_rules! sound_interval_tests {
+        ($t:ty) => {
+            $crate::interval_test!(test_i_sound, $t);
+            $crate::interval_test!(test_i_sound_imm, $t);
+            $crate::interval_test!(test_i_sound_non_negative, $t);
         };
     }
 }
@@ -0,0 +1,200 @@
+//! Batched interval evaluation (i.e. `&[Interval]`)
+use crate::eval::{
+    batch::{BatchEval, BatchEvalData, BatchEvaluator},
+    types::Interval,
+    EvaluatorStorage, Family,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Evaluator for many intervals, returning an interval and capturing a trace
+/// for each one
+pub type IntervalSliceEval<F> =
+    BatchEval<Interval, <F as Family>::IntervalSliceEval, F>;
+
+/// Scratch data used by a batched interval evaluator from a particular family
+/// `F`
+pub type IntervalSliceEvalData<F> = BatchEvalData<
+    <<F as Family>::IntervalSliceEval as BatchEvaluator<Interval, F>>::Data,
+    Interval,
+    F,
+>;
+
+/// Immutable data used by a batched interval evaluator from a particular
+/// family `F`
+pub type IntervalSliceEvalStorage<F> =
+    <<F as Family>::IntervalSliceEval as EvaluatorStorage<F>>::Storage;
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(any(test, feature = "eval-tests"))]
+pub mod eval_tests {
+    use super::*;
+    use crate::{
+        context::{Context, Node},
+        eval::Vars,
+        Error,
+    };
+
+    /// Checks that every item in a batch matches a single tracing evaluation
+    fn check_matches_tracing<I: Family>(ctx: &Context, node: Node) {
+        let tape = ctx.get_tape::<I>(node).unwrap();
+        let eval = tape.new_interval_slice_evaluator();
+        let single = tape.new_interval_evaluator();
+
+        let mut xs = vec![];
+        let mut ys = vec![];
+        for i in -4i32..4 {
+            for j in -4i32..4 {
+                let (x, y) = (i as f32 * 0.75, j as f32 * 0.5);
+                xs.push(Interval::new(x, x + 0.5 + j.abs() as f32 * 0.25));
+                ys.push(Interval::new(y, y + 0.5));
+            }
+        }
+        xs.push(Interval::new(-10.0, 10.0));
+        ys.push(Interval::new(1.0, 1.0));
+        let zs = vec![Interval::new(0.0, 0.0); xs.len()];
+
+        let mut data = Default::default();
+        let out = eval.eval_with(&xs, &ys, &zs, &[], &mut data).unwrap();
+        assert_eq!(out.len(), xs.len());
+        for i in 0..xs.len() {
+            let (v, trace) = out.get(i);
+            let (expected, expected_trace) =
+                single.eval(xs[i], ys[i], zs[i], &[]).unwrap();
+            for (a, b) in
+                [(v.lower(), expected.lower()), (v.upper(), expected.upper())]
+            {
+                assert!(
+                    a == b || (a.is_nan() && b.is_nan()),
+                    "mismatch at {i}: {v:?} != {expected:?}"
+                );
+            }
+            assert_eq!(
+                trace.as_ref().map(|t| t.choices()),
+                expected_trace.as_ref().map(|t| t.choices()),
+                "mismatched trace at {i}"
+            );
+        }
+    }
+
+    pub fn test_is_matches_tracing<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+
+        let max = ctx.max(x, y).unwrap();
+        check_matches_tracing::<I>(&ctx, max);
+
+        let x2 = ctx.square(x).unwrap();
+        let y2 = ctx.square(y).unwrap();
+        let r = ctx.add(x2, y2).unwrap();
+        let r = ctx.sqrt(r).unwrap();
+        let circle = ctx.sub(r, 1.0).unwrap();
+        let min = ctx.min(circle, x).unwrap();
+        let shape = ctx.max(min, 0.5).unwrap();
+        let shape = ctx.min(shape, y).unwrap();
+        check_matches_tracing::<I>(&ctx, shape);
+
+        let s = ctx.sin(x).unwrap();
+        let e = ctx.exp(y).unwrap();
+        let d = ctx.div(s, e).unwrap();
+        let a = ctx.atan2(d, x).unwrap();
+        check_matches_tracing::<I>(&ctx, a);
+
+        let lt = ctx.lt(x, y).unwrap();
+        let and = ctx.and(lt, x).unwrap();
+        let or = ctx.or(and, y).unwrap();
+        let sel = ctx.select(x, or, circle).unwrap();
+        check_matches_tracing::<I>(&ctx, sel);
+    }
+
+    pub fn test_is_simplify<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let min = ctx.min(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(min).unwrap();
+        let eval = tape.new_interval_slice_evaluator();
+
+        let xs = [[0.0, 1.0], [2.0, 3.0], [0.0, 1.0]].map(Interval::from);
+        let ys = [[2.0, 3.0], [0.0, 1.0], [0.5, 1.5]].map(Interval::from);
+        let zs = [Interval::from(0.0); 3];
+        let out = eval.eval(&xs, &ys, &zs, &[]).unwrap();
+        assert_eq!(out.len(), 3);
+
+        let (v, trace) = &out[0];
+        assert_eq!(*v, [0.0, 1.0].into());
+        let t = trace.as_ref().unwrap().simplify().unwrap();
+        assert_eq!(t.len(), 1);
+        let e = t.new_interval_evaluator();
+        assert_eq!(e.eval_xy([0.0, 1.0], [5.0, 6.0]), [0.0, 1.0].into());
+
+        let (v, trace) = &out[1];
+        assert_eq!(*v, [0.0, 1.0].into());
+        let t = trace.as_ref().unwrap().simplify().unwrap();
+        assert_eq!(t.len(), 1);
+        let e = t.new_interval_evaluator();
+        assert_eq!(e.eval_xy([5.0, 6.0], [0.0, 1.0]), [0.0, 1.0].into());
+
+        let (v, trace) = &out[2];
+        assert_eq!(*v, [0.0, 1.0].into());
+        assert!(trace.is_none());
+
+        // Evaluating an empty batch is allowed
+        let out = eval.eval(&[], &[], &[], &[]).unwrap();
+        assert!(out.is_empty());
+    }
+
+    pub fn test_is_var<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let a = ctx.var("a").unwrap();
+        let sum = ctx.add(x, a).unwrap();
+        let tape = ctx.get_tape::<I>(sum).unwrap();
+        let eval = tape.new_interval_slice_evaluator();
+
+        let mut vars = Vars::new(&tape);
+        let xs = [[0.0, 1.0], [2.0, 3.0]].map(Interval::from);
+        let zs = [Interval::from(0.0); 2];
+        let mut data = Default::default();
+        let out = eval
+            .eval_with(
+                &xs,
+                &zs,
+                &zs,
+                vars.bind([("a", 1.0)].into_iter()),
+                &mut data,
+            )
+            .unwrap();
+        assert_eq!(out.values(), &[[1.0, 2.0].into(), [3.0, 4.0].into()]);
+
+        assert!(matches!(
+            eval.eval(&xs, &zs, &zs, &[]),
+            Err(Error::BadVarSlice(0, 1))
+        ));
+        assert!(matches!(
+            eval.eval(&xs, &zs[..1], &zs, &[1.0]),
+            Err(Error::MismatchedSlices)
+        ));
+    }
+
+    #[macro_export]
+    macro_rules! interval_slice_test {
+        ($i:ident, $t:ty) => {
+            #[test]
+            fn $i() {
+                $crate::eval::interval_slice::eval_tests::$i::<$t>()
+            }
+        };
+    }
+
+    #[macro_export]
+    macro_rules! interval_slice_tests {
+        ($t:ty) => {
+            $crate::interval_slice_test!(test_is_matches_tracing, $t);
+            $crate::interval_slice_test!(test_is_simplify, $t);
+            $crate::interval_slice_test!(test_is_var, $t);
+        };
+    }
+}
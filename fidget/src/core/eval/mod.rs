@@ -20,11 +20,18 @@
 // Bulk evaluators
 pub mod float_slice;
 pub mod grad_slice;
+pub mod hessian_slice;
+pub mod var_grad_slice;
 
 // Tracing evaluators
+pub mod affine;
 pub mod interval;
 pub mod point;
 
+// Batch evaluators
+pub mod interval_slice;
+
+pub mod batch;
 pub mod bulk;
 pub mod tape;
 pub mod tracing;
@@ -33,14 +40,19 @@ pub mod types;
 mod vars;
 
 // Re-export a few things
+pub use affine::AffineEval;
 pub use float_slice::FloatSliceEval;
 pub use grad_slice::GradSliceEval;
+pub use hessian_slice::HessianSliceEval;
 pub use interval::IntervalEval;
+pub use interval_slice::IntervalSliceEval;
 pub use point::PointEval;
 pub use tape::Tape;
 pub use tracing::Choice;
+pub use var_grad_slice::VarGradSliceEval;
 pub use vars::Vars;
 
+use batch::BatchEvaluator;
 use bulk::BulkEvaluator;
 use tracing::TracingEvaluator;
 
@@ -61,6 +73,16 @@ pub trait Family: Clone {
         + Clone
         + Send
         + Sync;
+    /// Interval evaluator for many regions at once
+    ///
+    /// Families without a batched kernel use
+    /// [`UnbatchedEvaluator`](batch::UnbatchedEvaluator), which evaluates one
+    /// region at a time.
+    type IntervalSliceEval: BatchEvaluator<types::Interval, Self>
+        + EvaluatorStorage<Self>
+        + Clone
+        + Send
+        + Sync;
 
     /// Bulk point evaluator
     type FloatSliceEval: BulkEvaluator<f32, Self>
@@ -266,6 +266,460 @@ pub mod eval_tests {
         );
     }
 
+    pub fn test_p_w<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let w = ctx.w();
+        let diff = ctx.sub(w, x).unwrap();
+        let tape = ctx.get_tape::<I>(diff).unwrap();
+        let eval = tape.new_point_evaluator();
+        assert_eq!(eval.eval_xyzw(1.0, 2.0, 3.0, 5.0, &[]).unwrap().0, 4.0);
+        assert_eq!(eval.eval(1.0, 2.0, 3.0, &[]).unwrap().0, -1.0);
+
+        // W must survive calls into transcendental functions
+        let s = ctx.sin(x).unwrap();
+        let sum = ctx.add(s, w).unwrap();
+        let tape = ctx.get_tape::<I>(sum).unwrap();
+        let eval = tape.new_point_evaluator();
+        let (v, _) = eval.eval_xyzw(1.0, 0.0, 0.0, 2.0, &[]).unwrap();
+        assert_eq!(v, 1f32.sin() + 2.0);
+    }
+
+    pub fn test_p_exp_sin_cos<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let exp_x = ctx.exp(x).unwrap();
+        let sin_x = ctx.sin(x).unwrap();
+        let cos_x = ctx.cos(x).unwrap();
+
+        for (node, f) in [
+            (exp_x, f32::exp as fn(f32) -> f32),
+            (sin_x, f32::sin),
+            (cos_x, f32::cos),
+        ] {
+            let tape = ctx.get_tape::<I>(node).unwrap();
+            let eval = tape.new_point_evaluator();
+            for v in [-2.0, -0.5, 0.0, 0.5, 1.0, 3.0] {
+                assert_eq!(eval.eval(v, 0.0, 0.0, &[]).unwrap().0, f(v));
+            }
+        }
+
+        // Check that values (and choices) are preserved across the call
+        let y = ctx.y();
+        let z = ctx.z();
+        let sin_y = ctx.sin(y).unwrap();
+        let min = ctx.min(x, sin_y).unwrap();
+        let cos_z = ctx.cos(z).unwrap();
+        let sum = ctx.add(min, cos_z).unwrap();
+        let tape = ctx.get_tape::<I>(sum).unwrap();
+        let eval = tape.new_point_evaluator();
+
+        let (v, data) = eval.eval(-1.0, 0.5, 2.0, &[]).unwrap();
+        assert_eq!(v, -1.0 + 2.0f32.cos());
+        let next = data.unwrap().simplify().unwrap();
+        let eval_next = next.new_point_evaluator();
+        let (v, _) = eval_next.eval(1.0, 0.5, 2.0, &[]).unwrap();
+        assert_eq!(v, 1.0 + 2.0f32.cos());
+
+        let (v, _) = eval.eval(1.0, 0.5, 2.0, &[]).unwrap();
+        assert_eq!(v, 0.5f32.sin() + 2.0f32.cos());
+    }
+
+    pub fn test_p_inverse_trig<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let tan_x = ctx.tan(x).unwrap();
+        let asin_x = ctx.asin(x).unwrap();
+        let acos_x = ctx.acos(x).unwrap();
+        let atan_x = ctx.atan(x).unwrap();
+
+        for (node, f) in [
+            (tan_x, f32::tan as fn(f32) -> f32),
+            (asin_x, f32::asin),
+            (acos_x, f32::acos),
+            (atan_x, f32::atan),
+        ] {
+            let tape = ctx.get_tape::<I>(node).unwrap();
+            let eval = tape.new_point_evaluator();
+            for v in [-1.0, -0.5, 0.0, 0.25, 1.0] {
+                assert_eq!(eval.eval(v, 0.0, 0.0, &[]).unwrap().0, f(v));
+            }
+        }
+
+        let tape = ctx.get_tape::<I>(asin_x).unwrap();
+        let eval = tape.new_point_evaluator();
+        assert!(eval.eval(2.0, 0.0, 0.0, &[]).unwrap().0.is_nan());
+    }
+
+    pub fn test_p_atan2<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let a = ctx.atan2(y, x).unwrap();
+        let tape = ctx.get_tape::<I>(a).unwrap();
+        let eval = tape.new_point_evaluator();
+        for (x, y) in [(1.0, 0.0), (0.0, 1.0), (-1.0, 0.5), (-1.0, -0.5)] {
+            let v = eval.eval(x, y, 0.0, &[]).unwrap().0;
+            assert_eq!(v, f32::atan2(y, x));
+        }
+
+        // Check both argument orders with an immediate
+        let a = ctx.atan2(y, 2.0).unwrap();
+        let tape = ctx.get_tape::<I>(a).unwrap();
+        let eval = tape.new_point_evaluator();
+        assert_eq!(
+            eval.eval(0.0, -3.0, 0.0, &[]).unwrap().0,
+            (-3.0f32).atan2(2.0)
+        );
+
+        let a = ctx.atan2(2.0, x).unwrap();
+        let tape = ctx.get_tape::<I>(a).unwrap();
+        let eval = tape.new_point_evaluator();
+        assert_eq!(
+            eval.eval(-3.0, 0.0, 0.0, &[]).unwrap().0,
+            2.0f32.atan2(-3.0)
+        );
+
+        // Check that other values are preserved across the call
+        let z = ctx.z();
+        let a = ctx.atan2(y, x).unwrap();
+        let sum = ctx.add(a, z).unwrap();
+        let sum = ctx.add(sum, x).unwrap();
+        let tape = ctx.get_tape::<I>(sum).unwrap();
+        let eval = tape.new_point_evaluator();
+        let v = eval.eval(-1.0, 1.0, 3.0, &[]).unwrap().0;
+        assert_eq!(v, 1.0f32.atan2(-1.0) + 3.0 - 1.0);
+    }
+
+    pub fn test_p_ln_pow<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+
+        let ln_x = ctx.ln(x).unwrap();
+        let tape = ctx.get_tape::<I>(ln_x).unwrap();
+        let eval = tape.new_point_evaluator();
+        for v in [0.5, 1.0, 2.0, 10.0] {
+            assert_eq!(eval.eval(v, 0.0, 0.0, &[]).unwrap().0, v.ln());
+        }
+        assert!(eval.eval(-1.0, 0.0, 0.0, &[]).unwrap().0.is_nan());
+
+        let pow = ctx.pow(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(pow).unwrap();
+        let eval = tape.new_point_evaluator();
+        for (x, y) in [(2.0, 3.0), (4.0, 0.5), (-2.0, 3.0), (0.5, -2.0)] {
+            assert_eq!(eval.eval(x, y, 0.0, &[]).unwrap().0, x.powf(y));
+        }
+        assert!(eval.eval(-2.0, 0.5, 0.0, &[]).unwrap().0.is_nan());
+
+        // Immediates in both positions, including integer exponents
+        let pow = ctx.pow(x, 0.5).unwrap();
+        let tape = ctx.get_tape::<I>(pow).unwrap();
+        let eval = tape.new_point_evaluator();
+        assert_eq!(eval.eval(9.0, 0.0, 0.0, &[]).unwrap().0, 3.0);
+
+        let pow = ctx.pow(2.0, x).unwrap();
+        let tape = ctx.get_tape::<I>(pow).unwrap();
+        let eval = tape.new_point_evaluator();
+        assert_eq!(eval.eval(-3.0, 0.0, 0.0, &[]).unwrap().0, 0.125);
+
+        for n in [-3, -2, 0, 3, 4] {
+            let pow = ctx.powi(x, n).unwrap();
+            let tape = ctx.get_tape::<I>(pow).unwrap();
+            let eval = tape.new_point_evaluator();
+            for v in [-1.5, 0.5, 2.0] {
+                let out = eval.eval(v, 0.0, 0.0, &[]).unwrap().0;
+                assert_eq!(out, v.powi(n), "{v}^{n}");
+            }
+        }
+
+        // Check that other values are preserved across the call
+        let z = ctx.z();
+        let pow = ctx.pow(x, y).unwrap();
+        let sum = ctx.add(pow, z).unwrap();
+        let sum = ctx.add(sum, x).unwrap();
+        let tape = ctx.get_tape::<I>(sum).unwrap();
+        let eval = tape.new_point_evaluator();
+        let v = eval.eval(3.0, 2.0, 0.5, &[]).unwrap().0;
+        assert_eq!(v, 9.0 + 0.5 + 3.0);
+    }
+
+    pub fn test_p_floor_mod<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+
+        let floor = ctx.floor(x).unwrap();
+        let ceil = ctx.ceil(x).unwrap();
+        let round = ctx.round(x).unwrap();
+        for (node, f) in [
+            (floor, f32::floor as fn(f32) -> f32),
+            (ceil, f32::ceil),
+            (round, f32::round),
+        ] {
+            let tape = ctx.get_tape::<I>(node).unwrap();
+            let eval = tape.new_point_evaluator();
+            for v in [-2.5, -1.5, -0.5, -0.25, 0.0, 0.5, 0.75, 1.5, 2.5] {
+                assert_eq!(eval.eval(v, 0.0, 0.0, &[]).unwrap().0, f(v));
+            }
+        }
+
+        let m = ctx.modulo(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(m).unwrap();
+        let eval = tape.new_point_evaluator();
+        for (x, y) in [(3.5, 2.0), (-0.5, 2.0), (-4.0, 2.0), (1.0, -3.0)] {
+            assert_eq!(eval.eval(x, y, 0.0, &[]).unwrap().0, x.rem_euclid(y));
+        }
+
+        let m = ctx.modulo(x, 1.5).unwrap();
+        let tape = ctx.get_tape::<I>(m).unwrap();
+        let eval = tape.new_point_evaluator();
+        assert_eq!(eval.eval(-1.0, 0.0, 0.0, &[]).unwrap().0, 0.5);
+
+        let m = ctx.modulo(5.0, x).unwrap();
+        let tape = ctx.get_tape::<I>(m).unwrap();
+        let eval = tape.new_point_evaluator();
+        assert_eq!(eval.eval(3.0, 0.0, 0.0, &[]).unwrap().0, 2.0);
+    }
+
+    pub fn test_p_cmp_logic<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let z = ctx.z();
+
+        let lt = ctx.lt(x, y).unwrap();
+        let gt = ctx.gt(x, y).unwrap();
+        let eq = ctx.eq(x, y).unwrap();
+        for (node, f) in [
+            (lt, (|a, b| a < b) as fn(f32, f32) -> bool),
+            (gt, |a, b| a > b),
+            (eq, |a, b| a == b),
+        ] {
+            let tape = ctx.get_tape::<I>(node).unwrap();
+            let eval = tape.new_point_evaluator();
+            for (a, b) in [(0.0, 1.0), (1.0, 0.0), (1.0, 1.0), (f32::NAN, 1.0)]
+            {
+                let v = eval.eval(a, b, 0.0, &[]).unwrap().0;
+                assert_eq!(v, f(a, b) as u8 as f32, "{a}, {b}");
+            }
+        }
+
+        let lt = ctx.lt(x, 1.0).unwrap();
+        let tape = ctx.get_tape::<I>(lt).unwrap();
+        let eval = tape.new_point_evaluator();
+        assert_eq!(eval.eval(0.5, 0.0, 0.0, &[]).unwrap().0, 1.0);
+        assert_eq!(eval.eval(1.5, 0.0, 0.0, &[]).unwrap().0, 0.0);
+
+        let lt = ctx.lt(1.0, x).unwrap();
+        let tape = ctx.get_tape::<I>(lt).unwrap();
+        let eval = tape.new_point_evaluator();
+        assert_eq!(eval.eval(0.5, 0.0, 0.0, &[]).unwrap().0, 0.0);
+        assert_eq!(eval.eval(1.5, 0.0, 0.0, &[]).unwrap().0, 1.0);
+
+        let not = ctx.not(x).unwrap();
+        let tape = ctx.get_tape::<I>(not).unwrap();
+        let eval = tape.new_point_evaluator();
+        for (v, r) in [(-1.0, 0.0), (0.0, 1.0), (2.0, 0.0), (f32::NAN, 0.0)] {
+            assert_eq!(eval.eval(v, 0.0, 0.0, &[]).unwrap().0, r);
+        }
+
+        let and = ctx.and(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(and).unwrap();
+        let eval = tape.new_point_evaluator();
+        let (r, data) = eval.eval(0.0, 5.0, 0.0, &[]).unwrap();
+        assert_eq!(r, 0.0);
+        assert_eq!(data.unwrap().choices(), &[Choice::Left]);
+        let (r, data) = eval.eval(2.0, 5.0, 0.0, &[]).unwrap();
+        assert_eq!(r, 5.0);
+        assert_eq!(data.unwrap().choices(), &[Choice::Right]);
+        let (r, data) = eval.eval(f32::NAN, 5.0, 0.0, &[]).unwrap();
+        assert_eq!(r, 5.0);
+        assert_eq!(data.unwrap().choices(), &[Choice::Right]);
+
+        let or = ctx.or(x, y).unwrap();
+        let tape = ctx.get_tape::<I>(or).unwrap();
+        let eval = tape.new_point_evaluator();
+        let (r, data) = eval.eval(0.0, 5.0, 0.0, &[]).unwrap();
+        assert_eq!(r, 5.0);
+        assert_eq!(data.unwrap().choices(), &[Choice::Right]);
+        let (r, data) = eval.eval(2.0, 5.0, 0.0, &[]).unwrap();
+        assert_eq!(r, 2.0);
+        assert_eq!(data.unwrap().choices(), &[Choice::Left]);
+        let (r, data) = eval.eval(f32::NAN, 5.0, 0.0, &[]).unwrap();
+        assert!(r.is_nan());
+        assert_eq!(data.unwrap().choices(), &[Choice::Left]);
+
+        let t = tape.simplify(&[Choice::Left]).unwrap();
+        let eval = t.new_point_evaluator();
+        assert_eq!(eval.eval(0.0, 5.0, 0.0, &[]).unwrap().0, 0.0);
+
+        // Immediates on the left-hand side are resolved when building the tape
+        let and = ctx.and(0.0, x).unwrap();
+        let tape = ctx.get_tape::<I>(and).unwrap();
+        assert_eq!(tape.choice_count(), 0);
+        let eval = tape.new_point_evaluator();
+        assert_eq!(eval.eval(3.0, 0.0, 0.0, &[]).unwrap().0, 0.0);
+
+        let or = ctx.or(0.0, x).unwrap();
+        let tape = ctx.get_tape::<I>(or).unwrap();
+        assert_eq!(tape.choice_count(), 0);
+        let eval = tape.new_point_evaluator();
+        assert_eq!(eval.eval(3.0, 0.0, 0.0, &[]).unwrap().0, 3.0);
+
+        let and = ctx.and(x, 2.0).unwrap();
+        let tape = ctx.get_tape::<I>(and).unwrap();
+        let eval = tape.new_point_evaluator();
+        assert_eq!(eval.eval(0.0, 0.0, 0.0, &[]).unwrap().0, 0.0);
+        assert_eq!(eval.eval(3.0, 0.0, 0.0, &[]).unwrap().0, 2.0);
+
+        // Check that multiple choices are written in order
+        let min = ctx.min(x, y).unwrap();
+        let and = ctx.and(min, z).unwrap();
+        let tape = ctx.get_tape::<I>(and).unwrap();
+        let eval = tape.new_point_evaluator();
+        let (r, data) = eval.eval(0.0, 2.0, 3.0, &[]).unwrap();
+        assert_eq!(r, 0.0);
+        let data = data.unwrap();
+        assert_eq!(data.choices(), &[Choice::Left, Choice::Left]);
+
+        let (r, data) = eval.eval(1.0, 2.0, 3.0, &[]).unwrap();
+        assert_eq!(r, 3.0);
+        let data = data.unwrap();
+        assert_eq!(data.choices(), &[Choice::Left, Choice::Right]);
+        let next = data.simplify().unwrap();
+        let eval_next = next.new_point_evaluator();
+        assert_eq!(eval_next.eval(0.0, 2.0, 3.0, &[]).unwrap().0, 3.0);
+    }
+
+    pub fn test_p_select<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let z = ctx.z();
+
+        let select = ctx.select(x, y, z).unwrap();
+        let tape = ctx.get_tape::<I>(select).unwrap();
+        let eval = tape.new_point_evaluator();
+        let (r, data) = eval.eval(-1.0, 2.0, 3.0, &[]).unwrap();
+        assert_eq!(r, 2.0);
+        assert_eq!(data.unwrap().choices(), &[Choice::Left]);
+        for c in [0.0, 1.0, f32::NAN] {
+            let (r, data) = eval.eval(c, 2.0, 3.0, &[]).unwrap();
+            assert_eq!(r, 3.0);
+            assert_eq!(data.unwrap().choices(), &[Choice::Right]);
+        }
+
+        let t = tape.simplify(&[Choice::Left]).unwrap();
+        assert_eq!(t.len(), 1);
+        let eval = t.new_point_evaluator();
+        assert_eq!(eval.eval(1.0, 2.0, 3.0, &[]).unwrap().0, 2.0);
+
+        // Constant conditions and identical branches are folded
+        let s = ctx.select(-1.0, y, z).unwrap();
+        assert_eq!(s, y);
+        let s = ctx.select(x, y, y).unwrap();
+        assert_eq!(s, y);
+
+        // Immediates are copied into registers
+        for (a, b) in [(1.0, Some(y)), (2.0, None)] {
+            let select = match b {
+                Some(b) => ctx.select(x, a, b).unwrap(),
+                None => ctx.select(x, y, a).unwrap(),
+            };
+            let tape = ctx.get_tape::<I>(select).unwrap();
+            let eval = tape.new_point_evaluator();
+            let expected = if b.is_some() { 1.0 } else { 5.0 };
+            assert_eq!(eval.eval(-1.0, 5.0, 0.0, &[]).unwrap().0, expected);
+            let expected = if b.is_some() { 5.0 } else { 2.0 };
+            assert_eq!(eval.eval(1.0, 5.0, 0.0, &[]).unwrap().0, expected);
+        }
+        let select = ctx.select(x, 1.0, 2.0).unwrap();
+        let tape = ctx.get_tape::<I>(select).unwrap();
+        let eval = tape.new_point_evaluator();
+        assert_eq!(eval.eval(-1.0, 0.0, 0.0, &[]).unwrap().0, 1.0);
+        assert_eq!(eval.eval(1.0, 0.0, 0.0, &[]).unwrap().0, 2.0);
+
+        // The condition may also be one of the branches
+        let select = ctx.select(x, x, y).unwrap();
+        let tape = ctx.get_tape::<I>(select).unwrap();
+        let eval = tape.new_point_evaluator();
+        assert_eq!(eval.eval(-1.0, 5.0, 0.0, &[]).unwrap().0, -1.0);
+        assert_eq!(eval.eval(1.0, 5.0, 0.0, &[]).unwrap().0, 5.0);
+
+        // Build a model where many values are live at the same time, so that
+        // the arguments to `select` are spilled to memory
+        let terms = (1..32)
+            .map(|i| ctx.mul(x, i as f64).unwrap())
+            .collect::<Vec<_>>();
+        let mut sum = ctx.constant(0.0);
+        for (i, &t) in terms.iter().enumerate() {
+            let c = ctx.sub(t, 10.0).unwrap();
+            let s = ctx.select(c, t, terms[terms.len() - 1 - i]).unwrap();
+            sum = ctx.add(sum, s).unwrap();
+        }
+        let mut total = ctx.constant(0.0);
+        for &t in terms.iter().rev() {
+            total = ctx.add(total, t).unwrap();
+        }
+        let out = ctx.mul(sum, total).unwrap();
+        let tape = ctx.get_tape::<I>(out).unwrap();
+        let eval = tape.new_point_evaluator();
+        for v in [0.5, 1.0, -0.7] {
+            let terms = (1..32).map(|i| v * i as f32).collect::<Vec<_>>();
+            let sum: f32 = (0..terms.len())
+                .map(|i| {
+                    if terms[i] - 10.0 < 0.0 {
+                        terms[i]
+                    } else {
+                        terms[terms.len() - 1 - i]
+                    }
+                })
+                .sum();
+            let expected = sum * terms.iter().sum::<f32>();
+            let actual = eval.eval(v, 0.0, 0.0, &[]).unwrap().0;
+            assert!(
+                (actual - expected).abs() < 1e-3 * expected.abs(),
+                "mismatch at {v}: {actual} != {expected}"
+            );
+        }
+    }
+
+    pub fn test_p_call_spill<I: Family>() {
+        // Build a model where many values are live at the same time, so that
+        // function calls are interleaved with register spills
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let terms = (1..32)
+            .map(|i| ctx.mul(x, i as f64).unwrap())
+            .collect::<Vec<_>>();
+        let mut lhs = ctx.constant(0.0);
+        for &t in &terms {
+            lhs = ctx.add(lhs, t).unwrap();
+        }
+        let mut rhs = ctx.constant(0.0);
+        for &t in terms.iter().rev() {
+            let s = ctx.sin(t).unwrap();
+            rhs = ctx.add(rhs, s).unwrap();
+        }
+        let out = ctx.mul(lhs, rhs).unwrap();
+        let tape = ctx.get_tape::<I>(out).unwrap();
+        let eval = tape.new_point_evaluator();
+
+        for v in [0.0, 0.1, -0.7] {
+            let (lhs, rhs) = (1..32).fold((0.0, 0.0), |(a, b), i| {
+                let t = v * i as f32;
+                (a + t, b + t.sin())
+            });
+            let expected = lhs * rhs;
+            let actual = eval.eval(v, 0.0, 0.0, &[]).unwrap().0;
+            assert!(
+                (actual - expected).abs() < 1e-3,
+                "mismatch at {v}: {actual} != {expected}"
+            );
+        }
+    }
+
     #[macro_export]
     macro_rules! point_test {
         ($i:ident, $t:ty) => {
@@ -287,7 +741,16 @@ pub mod eval_tests {
             $crate::point_test!(basic_interpreter, $t);
             $crate::point_test!(test_push, $t);
             $crate::point_test!(test_var, $t);
+            $crate::point_test!(test_p_w, $t);
             $crate::point_test!(test_basic, $t);
+            $crate::point_test!(test_p_exp_sin_cos, $t);
+            $crate::point_test!(test_p_call_spill, $t);
+            $crate::point_test!(test_p_inverse_trig, $t);
+            $crate::point_test!(test_p_atan2, $t);
+            $crate::point_test!(test_p_ln_pow, $t);
+            $crate::point_test!(test_p_floor_mod, $t);
+            $crate::point_test!(test_p_cmp_logic, $t);
+            $crate::point_test!(test_p_select, $t);
         };
     }
 }
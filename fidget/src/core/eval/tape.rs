@@ -83,6 +83,27 @@ impl<E: Family> Tape<E> {
         eval::interval::IntervalEval::new_with_storage(self, storage)
     }
 
+    /// Builds a batched interval evaluator from the given `Tape`
+    pub fn new_interval_slice_evaluator(
+        &self,
+    ) -> eval::interval_slice::IntervalSliceEval<E> {
+        eval::interval_slice::IntervalSliceEval::new(self)
+    }
+
+    /// Builds a batched interval evaluator from the given `Tape`, reusing
+    /// storage
+    pub fn new_interval_slice_evaluator_with_storage(
+        &self,
+        storage: eval::interval_slice::IntervalSliceEvalStorage<E>,
+    ) -> eval::interval_slice::IntervalSliceEval<E> {
+        eval::interval_slice::IntervalSliceEval::new_with_storage(self, storage)
+    }
+
+    /// Builds an affine arithmetic evaluator from the given `Tape`
+    pub fn new_affine_evaluator(&self) -> eval::affine::AffineEval<E> {
+        eval::affine::AffineEval::new(self)
+    }
+
     /// Builds a float evaluator from the given `Tape`
     pub fn new_float_slice_evaluator(
         &self,
@@ -112,6 +133,20 @@ impl<E: Family> Tape<E> {
     ) -> eval::grad_slice::GradSliceEval<E> {
         eval::grad_slice::GradSliceEval::new_with_storage(self, storage)
     }
+
+    /// Builds an evaluator for first and second partial derivatives
+    pub fn new_hessian_slice_evaluator(
+        &self,
+    ) -> eval::hessian_slice::HessianSliceEval<E> {
+        eval::hessian_slice::HessianSliceEval::new(self)
+    }
+
+    /// Builds an evaluator for partial derivatives with respect to variables
+    pub fn new_var_grad_slice_evaluator(
+        &self,
+    ) -> eval::var_grad_slice::VarGradSliceEval<E> {
+        eval::var_grad_slice::VarGradSliceEval::new(self)
+    }
 }
 
 impl<E> std::ops::Deref for Tape<E> {
@@ -159,6 +194,17 @@ impl Data {
         self.asm.is_empty()
     }
 
+    /// Returns the number of outputs written by the tape
+    ///
+    /// Tapes built with [`Context::get_tape`] have a single output; tapes built
+    /// with [`Context::get_tape_multi`] have one output per root.
+    ///
+    /// [`Context::get_tape`]: crate::context::Context::get_tape
+    /// [`Context::get_tape_multi`]: crate::context::Context::get_tape_multi
+    pub fn output_count(&self) -> usize {
+        self.ssa.output_count
+    }
+
     /// Returns the number of choice (min/max) nodes in the tape.
     ///
     /// This is required because some evaluators pre-allocate spaces for the
@@ -213,18 +259,22 @@ impl Data {
 
         // Steal `tape.asm` and hand it to the workspace for use in allocator
         workspace.reset_with_storage(reg_limit, self.ssa.tape.len(), tape.asm);
+        workspace.alloc.bind_outputs(self.output_count());
 
         let mut choice_count = 0;
 
-        // The tape is constructed so that the output slot is first
-        assert_eq!(self.ssa.tape[0].output(), 0);
-        workspace.set_active(self.ssa.tape[0].output(), 0);
-        workspace.count += 1;
+        // The tape is constructed so that outputs occupy the first slots, which
+        // are preserved in the simplified tape
+        for i in 0..self.output_count() as u32 {
+            workspace.set_active(i, i);
+        }
+        workspace.count += self.output_count() as u32;
 
         // Other iterators to consume various arrays in order
         let mut choice_iter = choices.iter().rev();
 
         let mut ops_out = tape.ssa.tape;
+        let mut imm64 = tape.ssa.imm64;
 
         for mut op in self.ssa.tape.iter().cloned() {
             let index = op.output();
@@ -254,7 +304,17 @@ impl Data {
                 | SsaOp::SquareReg(index, arg)
                 | SsaOp::ExpReg(index, arg)
                 | SsaOp::SineReg(index, arg)
-                | SsaOp::CosineReg(index, arg) => {
+                | SsaOp::CosineReg(index, arg)
+                | SsaOp::TanReg(index, arg)
+                | SsaOp::AsinReg(index, arg)
+                | SsaOp::AcosReg(index, arg)
+                | SsaOp::AtanReg(index, arg)
+                | SsaOp::LnReg(index, arg)
+                | SsaOp::PowiReg(index, arg, _)
+                | SsaOp::FloorReg(index, arg)
+                | SsaOp::CeilReg(index, arg)
+                | SsaOp::RoundReg(index, arg)
+                | SsaOp::NotReg(index, arg) => {
                     *index = new_index;
                     *arg = workspace.get_or_insert_active(*arg);
                 }
@@ -276,7 +336,9 @@ impl Data {
                     }
                 }
                 SsaOp::MinRegImm(index, arg, imm)
-                | SsaOp::MaxRegImm(index, arg, imm) => {
+                | SsaOp::MaxRegImm(index, arg, imm)
+                | SsaOp::AndRegImm(index, arg, imm)
+                | SsaOp::OrRegImm(index, arg, imm) => {
                     match choice_iter.next().unwrap() {
                         Choice::Left => match workspace.active(*arg) {
                             Some(new_arg) => {
@@ -299,7 +361,9 @@ impl Data {
                     }
                 }
                 SsaOp::MinRegReg(index, lhs, rhs)
-                | SsaOp::MaxRegReg(index, lhs, rhs) => {
+                | SsaOp::MaxRegReg(index, lhs, rhs)
+                | SsaOp::AndRegReg(index, lhs, rhs)
+                | SsaOp::OrRegReg(index, lhs, rhs) => {
                     match choice_iter.next().unwrap() {
                         Choice::Left => match workspace.active(*lhs) {
                             Some(new_lhs) => {
@@ -328,10 +392,45 @@ impl Data {
                         Choice::Unknown => panic!("oh no"),
                     }
                 }
+                SsaOp::Select(index, cond, lhs, rhs) => {
+                    match choice_iter.next().unwrap() {
+                        Choice::Left => match workspace.active(*lhs) {
+                            Some(new_lhs) => {
+                                op = SsaOp::CopyReg(new_index, new_lhs);
+                            }
+                            None => {
+                                workspace.set_active(*lhs, new_index);
+                                continue;
+                            }
+                        },
+                        Choice::Right => match workspace.active(*rhs) {
+                            Some(new_rhs) => {
+                                op = SsaOp::CopyReg(new_index, new_rhs);
+                            }
+                            None => {
+                                workspace.set_active(*rhs, new_index);
+                                continue;
+                            }
+                        },
+                        Choice::Both => {
+                            choice_count += 1;
+                            *index = new_index;
+                            *cond = workspace.get_or_insert_active(*cond);
+                            *lhs = workspace.get_or_insert_active(*lhs);
+                            *rhs = workspace.get_or_insert_active(*rhs);
+                        }
+                        Choice::Unknown => panic!("oh no"),
+                    }
+                }
                 SsaOp::AddRegReg(index, lhs, rhs)
                 | SsaOp::MulRegReg(index, lhs, rhs)
                 | SsaOp::SubRegReg(index, lhs, rhs)
-                | SsaOp::DivRegReg(index, lhs, rhs) => {
+                | SsaOp::DivRegReg(index, lhs, rhs)
+                | SsaOp::Atan2RegReg(index, lhs, rhs)
+                | SsaOp::PowRegReg(index, lhs, rhs)
+                | SsaOp::ModRegReg(index, lhs, rhs)
+                | SsaOp::LtRegReg(index, lhs, rhs)
+                | SsaOp::EqRegReg(index, lhs, rhs) => {
                     *index = new_index;
                     *lhs = workspace.get_or_insert_active(*lhs);
                     *rhs = workspace.get_or_insert_active(*rhs);
@@ -341,12 +440,29 @@ impl Data {
                 | SsaOp::SubRegImm(index, arg, _imm)
                 | SsaOp::SubImmReg(index, arg, _imm)
                 | SsaOp::DivRegImm(index, arg, _imm)
-                | SsaOp::DivImmReg(index, arg, _imm) => {
+                | SsaOp::DivImmReg(index, arg, _imm)
+                | SsaOp::Atan2RegImm(index, arg, _imm)
+                | SsaOp::Atan2ImmReg(index, arg, _imm)
+                | SsaOp::PowRegImm(index, arg, _imm)
+                | SsaOp::PowImmReg(index, arg, _imm)
+                | SsaOp::ModRegImm(index, arg, _imm)
+                | SsaOp::ModImmReg(index, arg, _imm)
+                | SsaOp::LtRegImm(index, arg, _imm)
+                | SsaOp::LtImmReg(index, arg, _imm)
+                | SsaOp::EqRegImm(index, arg, _imm) => {
                     *index = new_index;
                     *arg = workspace.get_or_insert_active(*arg);
                 }
             }
-            workspace.alloc.op(op);
+            // Exact immediates follow their operation, unless it was replaced
+            // by a register copy
+            match self.ssa.imm64.get(&index) {
+                Some(&imm) if !matches!(op, SsaOp::CopyReg(..)) => {
+                    imm64.insert(new_index, imm);
+                    workspace.alloc.op_imm64(op, imm);
+                }
+                _ => workspace.alloc.op(op),
+            }
             ops_out.push(op);
         }
 
@@ -357,7 +473,9 @@ impl Data {
             ssa: SsaTape {
                 tape: ops_out,
                 choice_count,
+                output_count: self.output_count(),
                 vars: self.ssa.vars.clone(),
+                imm64,
             },
             asm: asm_tape,
         })
@@ -369,6 +487,15 @@ impl Data {
         self.asm.iter().cloned().rev()
     }
 
+    /// Produces an iterator that visits [`vm::Op`](crate::vm::Op) values in
+    /// evaluation order, along with the exact value of their immediate if it
+    /// isn't representable as an `f32`
+    pub fn iter_asm_imm64(
+        &self,
+    ) -> impl Iterator<Item = (VmOp, Option<f64>)> + '_ {
+        self.asm.iter_imm64().rev()
+    }
+
     /// Pretty-prints the inner SSA tape
     pub fn pretty_print(&self) {
         self.ssa.pretty_print()
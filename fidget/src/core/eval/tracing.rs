@@ -68,14 +68,18 @@ pub trait TracingEvaluator<T, F> {
 
     /// Evaluates the given value, using `choices` and `data` as scratch memory.
     ///
+    /// `w` is the value of the fourth input axis.
+    ///
     /// # Panics
     /// If `vars` or `choices` is of the incorrect length, this function is
     /// allowed (encouraged, even) to panic.
+    #[allow(clippy::too_many_arguments)]
     fn eval_with(
         &self,
         x: T,
         y: T,
         z: T,
+        w: T,
         vars: &[f32],
         choices: &mut [Choice],
         data: &mut Self::Data,
@@ -116,6 +120,7 @@ pub struct TracingEval<T, E, F> {
 impl<T, E, F: Family> TracingEval<T, E, F>
 where
     E: TracingEvaluator<T, F> + EvaluatorStorage<F>,
+    T: From<f32>,
 {
     /// Builds a new evaluator for the given tape, allocating new storage
     pub fn new(tape: &Tape<F>) -> Self {
@@ -147,6 +152,9 @@ where
     /// Returns a tuple of the resulting value and an optional handle to
     /// simplify the tape, if simplification is possible.  This handle borrows
     /// from the provided `data`.
+    ///
+    /// The W input is set to 0; use [`eval_xyzw_with`](Self::eval_xyzw_with)
+    /// to provide a value for it.
     pub fn eval_with<'a, J: Into<T>>(
         &self,
         x: J,
@@ -154,6 +162,25 @@ where
         z: J,
         vars: &[f32],
         data: &'a mut TracingEvalData<E::Data, F>,
+    ) -> Result<(T, Option<BorrowedTracingEvalResult<'a, T, F>>), Error> {
+        let w = T::from(0.0);
+        self.eval_xyzw_with(x.into(), y.into(), z.into(), w, vars, data)
+    }
+
+    /// Evaluate using (and modifying) the given workspace, with a value for
+    /// the W input
+    ///
+    /// Returns a tuple of the resulting value and an optional handle to
+    /// simplify the tape, if simplification is possible.  This handle borrows
+    /// from the provided `data`.
+    pub fn eval_xyzw_with<'a, J: Into<T>>(
+        &self,
+        x: J,
+        y: J,
+        z: J,
+        w: J,
+        vars: &[f32],
+        data: &'a mut TracingEvalData<E::Data, F>,
     ) -> Result<(T, Option<BorrowedTracingEvalResult<'a, T, F>>), Error> {
         if vars.len() != self.tape.var_count() {
             return Err(Error::BadVarSlice(vars.len(), self.tape.var_count()));
@@ -163,6 +190,7 @@ where
             x.into(),
             y.into(),
             z.into(),
+            w.into(),
             vars,
             &mut data.choices,
             &mut data.data,
@@ -183,15 +211,34 @@ where
     ///
     /// Returns a tuple of the resulting value and an optional (owned) handle to
     /// simplify the tape, if simplification is possible.
+    ///
+    /// The W input is set to 0; use [`eval_xyzw`](Self::eval_xyzw) to provide
+    /// a value for it.
     pub fn eval<J: Into<T>>(
         &self,
         x: J,
         y: J,
         z: J,
         vars: &[f32],
+    ) -> Result<(T, Option<OwnedTracingEvalResult<T, F>>), Error> {
+        self.eval_xyzw(x.into(), y.into(), z.into(), T::from(0.0), vars)
+    }
+
+    /// Evaluates with a value for the W input, allocating scratch memory if
+    /// required.
+    ///
+    /// Returns a tuple of the resulting value and an optional (owned) handle to
+    /// simplify the tape, if simplification is possible.
+    pub fn eval_xyzw<J: Into<T>>(
+        &self,
+        x: J,
+        y: J,
+        z: J,
+        w: J,
+        vars: &[f32],
     ) -> Result<(T, Option<OwnedTracingEvalResult<T, F>>), Error> {
         let mut data = Default::default();
-        let (out, r) = self.eval_with(x, y, z, vars, &mut data)?;
+        let (out, r) = self.eval_xyzw_with(x, y, z, w, vars, &mut data)?;
 
         // Convert from a &[Choice] (borrowed from data above) to returning the
         // Vec<Choice> itself.
@@ -300,6 +347,29 @@ where
         self.simplify_with(&mut Default::default(), Default::default())
     }
 
+    /// Builds a trace from a choice array and the tape that produced it
+    pub(crate) fn new(choices: B, tape: Tape<F>) -> Self {
+        Self {
+            choices,
+            tape,
+            _p: std::marker::PhantomData,
+        }
+    }
+
+    /// Changes the (phantom) evaluation type of this trace
+    ///
+    /// This lets traces from evaluators with different value types (e.g.
+    /// [`Interval`](crate::eval::types::Interval) and
+    /// [`Affine`](crate::eval::types::Affine)) be handled interchangeably.
+    #[cfg(feature = "render")]
+    pub(crate) fn cast<E>(self) -> TracingEvalResult<E, F, B> {
+        TracingEvalResult {
+            choices: self.choices,
+            tape: self.tape,
+            _p: std::marker::PhantomData,
+        }
+    }
+
     /// Returns a read-only view into the [`Choice`](Choice) slice.
     ///
     /// This is a convenience function for unit testing.
@@ -109,6 +109,169 @@ impl Grad {
         }
     }
 
+    /// Tangent
+    pub fn tan(self) -> Self {
+        let v = self.v.tan();
+        let d = 1.0 + v * v;
+        Grad {
+            v,
+            dx: self.dx * d,
+            dy: self.dy * d,
+            dz: self.dz * d,
+        }
+    }
+
+    /// Arcsine
+    pub fn asin(self) -> Self {
+        let d = (1.0 - self.v * self.v).sqrt();
+        Grad {
+            v: self.v.asin(),
+            dx: self.dx / d,
+            dy: self.dy / d,
+            dz: self.dz / d,
+        }
+    }
+
+    /// Arccosine
+    pub fn acos(self) -> Self {
+        let d = -(1.0 - self.v * self.v).sqrt();
+        Grad {
+            v: self.v.acos(),
+            dx: self.dx / d,
+            dy: self.dy / d,
+            dz: self.dz / d,
+        }
+    }
+
+    /// Arctangent
+    pub fn atan(self) -> Self {
+        let d = 1.0 + self.v * self.v;
+        Grad {
+            v: self.v.atan(),
+            dx: self.dx / d,
+            dy: self.dy / d,
+            dz: self.dz / d,
+        }
+    }
+
+    /// Four-quadrant arctangent, treating `self` as `y` and `rhs` as `x`
+    pub fn atan2(self, rhs: Self) -> Self {
+        let d = self.v.powi(2) + rhs.v.powi(2);
+        Grad {
+            v: self.v.atan2(rhs.v),
+            dx: (rhs.v * self.dx - self.v * rhs.dx) / d,
+            dy: (rhs.v * self.dy - self.v * rhs.dy) / d,
+            dz: (rhs.v * self.dz - self.v * rhs.dz) / d,
+        }
+    }
+
+    /// Natural logarithm
+    pub fn ln(self) -> Self {
+        Grad {
+            v: self.v.ln(),
+            dx: self.dx / self.v,
+            dy: self.dy / self.v,
+            dz: self.dz / self.v,
+        }
+    }
+
+    /// Raises `self` to the power `rhs`
+    pub fn pow(self, rhs: Self) -> Self {
+        let v = self.v.powf(rhs.v);
+        let da = rhs.v * self.v.powf(rhs.v - 1.0);
+        // The derivative with respect to the exponent is only well-defined for
+        // a positive base, so skip it entirely if the exponent is constant.
+        let db = |d: f32| if d == 0.0 { 0.0 } else { v * self.v.ln() * d };
+        Grad {
+            v,
+            dx: da * self.dx + db(rhs.dx),
+            dy: da * self.dy + db(rhs.dy),
+            dz: da * self.dz + db(rhs.dz),
+        }
+    }
+
+    /// Raises `self` to an integer power
+    pub fn powi(self, n: i32) -> Self {
+        let d = if n == 0 {
+            0.0
+        } else {
+            n as f32 * self.v.powi(n - 1)
+        };
+        Grad {
+            v: self.v.powi(n),
+            dx: self.dx * d,
+            dy: self.dy * d,
+            dz: self.dz * d,
+        }
+    }
+
+    /// Rounds down to the nearest integer, which has a gradient of zero
+    pub fn floor(self) -> Self {
+        Grad::new(self.v.floor(), 0.0, 0.0, 0.0)
+    }
+
+    /// Rounds up to the nearest integer, which has a gradient of zero
+    pub fn ceil(self) -> Self {
+        Grad::new(self.v.ceil(), 0.0, 0.0, 0.0)
+    }
+
+    /// Rounds to the nearest integer, which has a gradient of zero
+    pub fn round(self) -> Self {
+        Grad::new(self.v.round(), 0.0, 0.0, 0.0)
+    }
+
+    /// Returns 1 if `self < rhs` and 0 otherwise, with a gradient of zero
+    pub fn lt(self, rhs: Self) -> Self {
+        Grad::new((self.v < rhs.v) as u8 as f32, 0.0, 0.0, 0.0)
+    }
+
+    /// Returns 1 if `self == rhs` and 0 otherwise, with a gradient of zero
+    pub fn eq(self, rhs: Self) -> Self {
+        Grad::new((self.v == rhs.v) as u8 as f32, 0.0, 0.0, 0.0)
+    }
+
+    /// Logical AND, returning `self` if it is zero and `rhs` otherwise
+    pub fn and(self, rhs: Self) -> Self {
+        if self.v == 0.0 {
+            self
+        } else {
+            rhs
+        }
+    }
+
+    /// Logical OR, returning `self` if it is non-zero and `rhs` otherwise
+    pub fn or(self, rhs: Self) -> Self {
+        if self.v != 0.0 {
+            self
+        } else {
+            rhs
+        }
+    }
+
+    /// Returns `lhs` if `self < 0` and `rhs` otherwise
+    pub fn select(self, lhs: Self, rhs: Self) -> Self {
+        if self.v < 0.0 {
+            lhs
+        } else {
+            rhs
+        }
+    }
+
+    /// Euclidean remainder of `self / rhs`
+    ///
+    /// Away from discontinuities, this is `self - q * rhs` for some constant
+    /// integer `q`, so the gradient of `self` passes through unchanged.
+    pub fn modulo(self, rhs: Self) -> Self {
+        let v = self.v.rem_euclid(rhs.v);
+        let q = ((self.v - v) / rhs.v).round();
+        Grad {
+            v,
+            dx: self.dx - q * rhs.dx,
+            dy: self.dy - q * rhs.dy,
+            dz: self.dz - q * rhs.dz,
+        }
+    }
+
     /// Minimum of two values
     pub fn min(self, rhs: Self) -> Self {
         if self.v < rhs.v {
@@ -139,6 +302,7 @@ impl From<f32> for Grad {
     }
 }
 
+#[cfg(any(feature = "render", feature = "mesh", feature = "fit"))]
 impl From<Grad> for nalgebra::Vector4<f32> {
     fn from(g: Grad) -> Self {
         nalgebra::Vector4::new(g.dx, g.dy, g.dz, g.v)
@@ -206,6 +370,374 @@ impl std::ops::Neg for Grad {
     }
 }
 
+/// Logical NOT, returning 1 if the value is zero and 0 otherwise, with a
+/// gradient of zero
+impl std::ops::Not for Grad {
+    type Output = Self;
+    fn not(self) -> Self {
+        Grad::new((self.v == 0.0) as u8 as f32, 0.0, 0.0, 0.0)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A point in space with associated first and second partial derivatives
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct Hessian {
+    /// Value of the distance field at this point
+    pub v: f32,
+    /// Partial derivatives with respect to `x`, `y`, and `z`
+    pub g: [f32; 3],
+    /// Second partial derivatives, where `h[i][j]` is the derivative with
+    /// respect to axes `i` and `j` (so the matrix is symmetric)
+    pub h: [[f32; 3]; 3],
+}
+
+impl Hessian {
+    /// Constructs a new value with first and second derivatives
+    pub fn new(v: f32, g: [f32; 3], h: [[f32; 3]; 3]) -> Self {
+        Self { v, g, h }
+    }
+
+    /// Returns the value and first derivatives as a [`Grad`]
+    pub fn grad(&self) -> Grad {
+        Grad::new(self.v, self.g[0], self.g[1], self.g[2])
+    }
+
+    /// Mean curvature of the isosurface passing through this point
+    ///
+    /// This is the average of the two principal curvatures, with the sign
+    /// chosen so that a sphere of radius `r` (positive outside) has a mean
+    /// curvature of `1 / r`.  The result is infinite or NaN where the gradient
+    /// is zero.
+    pub fn mean_curvature(&self) -> f32 {
+        let n2 = self.g.iter().map(|g| g * g).sum::<f32>();
+        let trace = (0..3).map(|i| self.h[i][i]).sum::<f32>();
+        let mut ghg = 0.0;
+        for i in 0..3 {
+            for j in 0..3 {
+                ghg += self.g[i] * self.h[i][j] * self.g[j];
+            }
+        }
+        (n2 * trace - ghg) / (2.0 * n2.powf(1.5))
+    }
+
+    /// Gaussian curvature of the isosurface passing through this point
+    ///
+    /// This is the product of the two principal curvatures, so a sphere of
+    /// radius `r` has a Gaussian curvature of `1 / r²`.  The result is infinite
+    /// or NaN where the gradient is zero.
+    pub fn gaussian_curvature(&self) -> f32 {
+        let n2 = self.g.iter().map(|g| g * g).sum::<f32>();
+        let h = &self.h;
+        let mut gag = 0.0;
+        for i in 0..3 {
+            for j in 0..3 {
+                let (i1, i2) = ((i + 1) % 3, (i + 2) % 3);
+                let (j1, j2) = ((j + 1) % 3, (j + 2) % 3);
+                let adj = h[j1][i1] * h[j2][i2] - h[j1][i2] * h[j2][i1];
+                gag += self.g[i] * adj * self.g[j];
+            }
+        }
+        gag / n2.powi(2)
+    }
+
+    /// Applies a function of one argument, given its value and first and
+    /// second derivatives at `self.v`
+    fn chain(self, v: f32, d1: f32, d2: f32) -> Self {
+        let mut out = Hessian::from(v);
+        for i in 0..3 {
+            out.g[i] = d1 * self.g[i];
+            for j in 0..3 {
+                out.h[i][j] = d1 * self.h[i][j] + d2 * (self.g[i] * self.g[j]);
+            }
+        }
+        out
+    }
+
+    /// Applies a function of two arguments, given its value and partial
+    /// derivatives `d = [da, db]` and `dd = [daa, dab, dbb]`
+    fn chain2(self, rhs: Self, v: f32, d: [f32; 2], dd: [f32; 3]) -> Self {
+        let (a, b) = (&self, &rhs);
+        let mut out = Hessian::from(v);
+        for i in 0..3 {
+            out.g[i] = d[0] * a.g[i] + d[1] * b.g[i];
+            for j in 0..3 {
+                out.h[i][j] = d[0] * a.h[i][j]
+                    + d[1] * b.h[i][j]
+                    + dd[0] * (a.g[i] * a.g[j])
+                    + dd[1] * (a.g[i] * b.g[j] + b.g[i] * a.g[j])
+                    + dd[2] * (b.g[i] * b.g[j]);
+            }
+        }
+        out
+    }
+
+    /// Checks whether first and second derivatives are all zero
+    fn is_constant(&self) -> bool {
+        self.g
+            .iter()
+            .chain(self.h.iter().flatten())
+            .all(|d| *d == 0.0)
+    }
+
+    /// Absolute value
+    pub fn abs(self) -> Self {
+        if self.v < 0.0 {
+            -self
+        } else {
+            self
+        }
+    }
+
+    /// Square root
+    pub fn sqrt(self) -> Self {
+        let v = self.v.sqrt();
+        self.chain(v, 0.5 / v, -0.25 / (v * self.v))
+    }
+
+    /// Reciprocal
+    pub fn recip(self) -> Self {
+        let r = 1.0 / self.v;
+        self.chain(r, -r * r, 2.0 * r * r * r)
+    }
+
+    /// Exponential
+    pub fn exp(self) -> Self {
+        let v = self.v.exp();
+        self.chain(v, v, v)
+    }
+
+    /// Sine
+    pub fn sin(self) -> Self {
+        let v = self.v.sin();
+        self.chain(v, self.v.cos(), -v)
+    }
+
+    /// Cosine
+    pub fn cos(self) -> Self {
+        let v = self.v.cos();
+        self.chain(v, -self.v.sin(), -v)
+    }
+
+    /// Tangent
+    pub fn tan(self) -> Self {
+        let v = self.v.tan();
+        let d = 1.0 + v * v;
+        self.chain(v, d, 2.0 * v * d)
+    }
+
+    /// Arcsine
+    pub fn asin(self) -> Self {
+        let d = 1.0 / (1.0 - self.v * self.v).sqrt();
+        self.chain(self.v.asin(), d, self.v * d.powi(3))
+    }
+
+    /// Arccosine
+    pub fn acos(self) -> Self {
+        let d = 1.0 / (1.0 - self.v * self.v).sqrt();
+        self.chain(self.v.acos(), -d, -self.v * d.powi(3))
+    }
+
+    /// Arctangent
+    pub fn atan(self) -> Self {
+        let d = 1.0 / (1.0 + self.v * self.v);
+        self.chain(self.v.atan(), d, -2.0 * self.v * d * d)
+    }
+
+    /// Four-quadrant arctangent, treating `self` as `y` and `rhs` as `x`
+    pub fn atan2(self, rhs: Self) -> Self {
+        let (y, x) = (self.v, rhs.v);
+        let r2 = x * x + y * y;
+        let r4 = r2 * r2;
+        self.chain2(
+            rhs,
+            y.atan2(x),
+            [x / r2, -y / r2],
+            [-2.0 * x * y / r4, (y * y - x * x) / r4, 2.0 * x * y / r4],
+        )
+    }
+
+    /// Natural logarithm
+    pub fn ln(self) -> Self {
+        let r = 1.0 / self.v;
+        self.chain(self.v.ln(), r, -r * r)
+    }
+
+    /// Raises `self` to the power `rhs`
+    pub fn pow(self, rhs: Self) -> Self {
+        let (a, b) = (self.v, rhs.v);
+        let v = a.powf(b);
+        let da = b * a.powf(b - 1.0);
+        let daa = b * (b - 1.0) * a.powf(b - 2.0);
+        // As with `Grad::pow`, skip derivatives with respect to a constant
+        // exponent, since they're only well-defined for a positive base.
+        if rhs.is_constant() {
+            self.chain(v, da, daa)
+        } else {
+            let ln = a.ln();
+            let dab = a.powf(b - 1.0) * (1.0 + b * ln);
+            self.chain2(rhs, v, [da, v * ln], [daa, dab, v * ln * ln])
+        }
+    }
+
+    /// Raises `self` to an integer power
+    pub fn powi(self, n: i32) -> Self {
+        let d1 = if n == 0 {
+            0.0
+        } else {
+            n as f32 * self.v.powi(n - 1)
+        };
+        let d2 = if n == 0 || n == 1 {
+            0.0
+        } else {
+            (n * (n - 1)) as f32 * self.v.powi(n - 2)
+        };
+        self.chain(self.v.powi(n), d1, d2)
+    }
+
+    /// Rounds down to the nearest integer, which has derivatives of zero
+    pub fn floor(self) -> Self {
+        self.v.floor().into()
+    }
+
+    /// Rounds up to the nearest integer, which has derivatives of zero
+    pub fn ceil(self) -> Self {
+        self.v.ceil().into()
+    }
+
+    /// Rounds to the nearest integer, which has derivatives of zero
+    pub fn round(self) -> Self {
+        self.v.round().into()
+    }
+
+    /// Returns 1 if `self < rhs` and 0 otherwise, with derivatives of zero
+    pub fn lt(self, rhs: Self) -> Self {
+        ((self.v < rhs.v) as u8 as f32).into()
+    }
+
+    /// Returns 1 if `self == rhs` and 0 otherwise, with derivatives of zero
+    pub fn eq(self, rhs: Self) -> Self {
+        ((self.v == rhs.v) as u8 as f32).into()
+    }
+
+    /// Logical AND, returning `self` if it is zero and `rhs` otherwise
+    pub fn and(self, rhs: Self) -> Self {
+        if self.v == 0.0 {
+            self
+        } else {
+            rhs
+        }
+    }
+
+    /// Logical OR, returning `self` if it is non-zero and `rhs` otherwise
+    pub fn or(self, rhs: Self) -> Self {
+        if self.v != 0.0 {
+            self
+        } else {
+            rhs
+        }
+    }
+
+    /// Returns `lhs` if `self < 0` and `rhs` otherwise
+    pub fn select(self, lhs: Self, rhs: Self) -> Self {
+        if self.v < 0.0 {
+            lhs
+        } else {
+            rhs
+        }
+    }
+
+    /// Euclidean remainder of `self / rhs`
+    ///
+    /// Away from discontinuities, this is `self - q * rhs` for some constant
+    /// integer `q`.
+    pub fn modulo(self, rhs: Self) -> Self {
+        let v = self.v.rem_euclid(rhs.v);
+        let q = ((self.v - v) / rhs.v).round();
+        self.chain2(rhs, v, [1.0, -q], [0.0; 3])
+    }
+
+    /// Minimum of two values
+    pub fn min(self, rhs: Self) -> Self {
+        if self.v < rhs.v {
+            self
+        } else {
+            rhs
+        }
+    }
+
+    /// Maximum of two values
+    pub fn max(self, rhs: Self) -> Self {
+        if self.v > rhs.v {
+            self
+        } else {
+            rhs
+        }
+    }
+}
+
+impl From<f32> for Hessian {
+    fn from(v: f32) -> Self {
+        Hessian {
+            v,
+            g: [0.0; 3],
+            h: [[0.0; 3]; 3],
+        }
+    }
+}
+
+impl std::ops::Add<Hessian> for Hessian {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        self.chain2(rhs, self.v + rhs.v, [1.0, 1.0], [0.0; 3])
+    }
+}
+
+impl std::ops::Mul<Hessian> for Hessian {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        self.chain2(rhs, self.v * rhs.v, [rhs.v, self.v], [0.0, 1.0, 0.0])
+    }
+}
+
+impl std::ops::Div<Hessian> for Hessian {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        let (a, r) = (self.v, 1.0 / rhs.v);
+        self.chain2(
+            rhs,
+            a * r,
+            [r, -a * r * r],
+            [0.0, -r * r, 2.0 * a * r * r * r],
+        )
+    }
+}
+
+impl std::ops::Sub<Hessian> for Hessian {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self.chain2(rhs, self.v - rhs.v, [1.0, -1.0], [0.0; 3])
+    }
+}
+
+impl std::ops::Neg for Hessian {
+    type Output = Self;
+    fn neg(self) -> Self {
+        self.chain(-self.v, -1.0, 0.0)
+    }
+}
+
+/// Logical NOT, returning 1 if the value is zero and 0 otherwise, with
+/// derivatives of zero
+impl std::ops::Not for Hessian {
+    type Output = Self;
+    fn not(self) -> Self {
+        ((self.v == 0.0) as u8 as f32).into()
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 /// Stores a range, with conservative calculations to guarantee that it always
@@ -391,13 +923,338 @@ impl Interval {
         return Interval::new(cos_lower.min(cos_upper), cos_lower.max(cos_upper));
     }
     
-    /// Calculates the minimum of two intervals
-    ///
-    /// Returns both the result and a [`Choice`] indicating whether one side is
-    /// always less than the other.
+    /// Calculates the tangent of the interval
     ///
-    /// If either side is `NAN`, returns the `NAN` interval and `Choice::Both`.
-    pub fn min_choice(self, rhs: Self) -> (Self, Choice) {
+    /// If the interval includes one of the asymptotes at `π/2 + kπ`, returns
+    /// the `NAN` interval.
+    pub fn tan(self) -> Self {
+        if self.has_nan() {
+            return f32::NAN.into();
+        }
+        let branch = |v: f32| ((v + PI / 2.0) / PI).floor();
+        if self.upper - self.lower >= PI
+            || branch(self.lower) != branch(self.upper)
+        {
+            f32::NAN.into()
+        } else {
+            Interval::new(self.lower.tan(), self.upper.tan())
+        }
+    }
+
+    /// Calculates the arcsine of the interval
+    ///
+    /// If the entire interval is outside of `[-1, 1]`, returns a `NAN`
+    /// interval; otherwise, returns the arcsine of the valid portion.
+    pub fn asin(self) -> Self {
+        if self.has_nan() || self.lower > 1.0 || self.upper < -1.0 {
+            f32::NAN.into()
+        } else {
+            Interval::new(
+                self.lower.max(-1.0).asin(),
+                self.upper.min(1.0).asin(),
+            )
+        }
+    }
+
+    /// Calculates the arccosine of the interval
+    ///
+    /// If the entire interval is outside of `[-1, 1]`, returns a `NAN`
+    /// interval; otherwise, returns the arccosine of the valid portion.
+    pub fn acos(self) -> Self {
+        if self.has_nan() || self.lower > 1.0 || self.upper < -1.0 {
+            f32::NAN.into()
+        } else {
+            Interval::new(
+                self.upper.min(1.0).acos(),
+                self.lower.max(-1.0).acos(),
+            )
+        }
+    }
+
+    /// Calculates the arctangent of the interval
+    pub fn atan(self) -> Self {
+        if self.has_nan() {
+            f32::NAN.into()
+        } else {
+            Interval::new(self.lower.atan(), self.upper.atan())
+        }
+    }
+
+    /// Calculates the four-quadrant arctangent, treating `self` as `y` and
+    /// `rhs` as `x`
+    ///
+    /// If the region touches the branch cut along the negative X axis (or
+    /// contains the origin), returns the full range `[-π, π]`.
+    pub fn atan2(self, rhs: Self) -> Self {
+        if self.has_nan() || rhs.has_nan() {
+            return f32::NAN.into();
+        }
+        if self.lower <= 0.0 && self.upper >= 0.0 && rhs.lower <= 0.0 {
+            return Interval::new(-PI, PI);
+        }
+        // Otherwise, the angle is continuous over the region, and the region
+        // doesn't contain the origin, so the extrema are found at its corners
+        let mut lower = f32::INFINITY;
+        let mut upper = -f32::INFINITY;
+        for y in [self.lower, self.upper] {
+            for x in [rhs.lower, rhs.upper] {
+                let a = y.atan2(x);
+                lower = lower.min(a);
+                upper = upper.max(a);
+            }
+        }
+        Interval::new(lower, upper)
+    }
+
+    /// Calculates the natural logarithm of the interval
+    ///
+    /// If the entire interval is below 0, returns a `NAN` interval; otherwise,
+    /// returns the logarithm of the valid (positive) interval.
+    pub fn ln(self) -> Self {
+        if self.lower < 0.0 {
+            if self.upper > 0.0 {
+                Interval::new(-f32::INFINITY, self.upper.ln())
+            } else {
+                f32::NAN.into()
+            }
+        } else {
+            Interval::new(self.lower.ln(), self.upper.ln())
+        }
+    }
+
+    /// Raises the interval to an integer power
+    ///
+    /// Negative powers of an interval which includes 0 return the `NAN`
+    /// interval.
+    pub fn powi(self, n: i32) -> Self {
+        if self.has_nan() {
+            f32::NAN.into()
+        } else if n == 0 {
+            Interval::new(1.0, 1.0)
+        } else if self.lower <= 0.0 && self.upper >= 0.0 {
+            if n < 0 {
+                f32::NAN.into()
+            } else if n % 2 == 0 {
+                let m = self.lower.abs().max(self.upper.abs());
+                Interval::new(0.0, m.powi(n))
+            } else {
+                Interval::new(self.lower.powi(n), self.upper.powi(n))
+            }
+        } else {
+            // The function is monotonic if the interval doesn't include 0
+            let a = self.lower.powi(n);
+            let b = self.upper.powi(n);
+            Interval::new(a.min(b), a.max(b))
+        }
+    }
+
+    /// Raises the interval to the power `rhs`
+    ///
+    /// If `rhs` is a single integer, this is equivalent to [`Interval::powi`].
+    /// Otherwise, negative values in `self` produce `NAN`: if `rhs` is a
+    /// single value, the calculation is performed on the valid (positive)
+    /// portion of `self`, and if `rhs` is a true range, any negative values in
+    /// `self` produce the `NAN` interval.
+    pub fn pow(self, rhs: Self) -> Self {
+        if self.has_nan() || rhs.has_nan() {
+            return f32::NAN.into();
+        }
+        let base = if self.lower >= 0.0 {
+            self
+        } else if rhs.lower == rhs.upper && rhs.lower == rhs.lower as i16 as f32
+        {
+            return self.powi(rhs.lower as i32);
+        } else if rhs.lower == rhs.upper && self.upper >= 0.0 {
+            Interval::new(0.0, self.upper)
+        } else {
+            return f32::NAN.into();
+        };
+        // With a non-negative base, `x^y = exp(y * ln(x))` is monotonic in
+        // each argument, so the extrema are found at the corners.
+        let mut lower = f32::INFINITY;
+        let mut upper = -f32::INFINITY;
+        for x in [base.lower, base.upper] {
+            for y in [rhs.lower, rhs.upper] {
+                let v = x.powf(y);
+                lower = lower.min(v);
+                upper = upper.max(v);
+            }
+        }
+        Interval::new(lower, upper)
+    }
+
+    /// Rounds the interval down to the nearest integer
+    ///
+    /// `floor` is monotonic, so the result is tight even if the interval
+    /// spans one or more steps.
+    pub fn floor(self) -> Self {
+        if self.has_nan() {
+            f32::NAN.into()
+        } else {
+            Interval::new(self.lower.floor(), self.upper.floor())
+        }
+    }
+
+    /// Rounds the interval up to the nearest integer
+    pub fn ceil(self) -> Self {
+        if self.has_nan() {
+            f32::NAN.into()
+        } else {
+            Interval::new(self.lower.ceil(), self.upper.ceil())
+        }
+    }
+
+    /// Rounds the interval to the nearest integer
+    pub fn round(self) -> Self {
+        if self.has_nan() {
+            f32::NAN.into()
+        } else {
+            Interval::new(self.lower.round(), self.upper.round())
+        }
+    }
+
+    /// Calculates the Euclidean remainder of `self / rhs`
+    ///
+    /// If `rhs` is a single value and `self` lies within a single period, the
+    /// remainder is continuous and the result is tight.  If `self` crosses a
+    /// discontinuity (or `rhs` is a true range), the result is the full range
+    /// `[0, max(|rhs|)]`.  If `rhs` includes 0, returns the `NAN` interval.
+    pub fn modulo(self, rhs: Self) -> Self {
+        if self.has_nan()
+            || rhs.has_nan()
+            || (rhs.lower <= 0.0 && rhs.upper >= 0.0)
+        {
+            return f32::NAN.into();
+        }
+        let m = rhs.abs();
+        if m.lower == m.upper {
+            let b = m.lower;
+            if (self.lower / b).floor() == (self.upper / b).floor() {
+                let a = self.lower.rem_euclid(b);
+                let c = self.upper.rem_euclid(b);
+                return Interval::new(a.min(c), a.max(c));
+            }
+        } else if self.lower >= 0.0 && self.upper < m.lower {
+            return self;
+        }
+        Interval::new(0.0, m.upper)
+    }
+
+    /// Checks whether `self < rhs`
+    ///
+    /// Returns `[1, 1]` if this is always true, `[0, 0]` if it is never true,
+    /// and `[0, 1]` if it depends on the position within each interval.
+    pub fn lt(self, rhs: Self) -> Self {
+        if self.has_nan() || rhs.has_nan() {
+            f32::NAN.into()
+        } else if self.upper < rhs.lower {
+            Interval::new(1.0, 1.0)
+        } else if self.lower >= rhs.upper {
+            Interval::new(0.0, 0.0)
+        } else {
+            Interval::new(0.0, 1.0)
+        }
+    }
+
+    /// Checks whether `self == rhs`
+    ///
+    /// Returns `[1, 1]` if both intervals are the same single value, `[0, 0]`
+    /// if they are disjoint, and `[0, 1]` otherwise.
+    pub fn eq(self, rhs: Self) -> Self {
+        if self.has_nan() || rhs.has_nan() {
+            f32::NAN.into()
+        } else if self.lower == self.upper
+            && rhs.lower == rhs.upper
+            && self.lower == rhs.lower
+        {
+            Interval::new(1.0, 1.0)
+        } else if self.upper < rhs.lower || rhs.upper < self.lower {
+            Interval::new(0.0, 0.0)
+        } else {
+            Interval::new(0.0, 1.0)
+        }
+    }
+
+    /// Calculates the logical AND of two intervals
+    ///
+    /// At each point, this returns `self` if it is zero and `rhs` otherwise.
+    /// The [`Choice`] is `Left` if `self` is exactly zero, `Right` if `self`
+    /// does not contain zero, and `Both` otherwise.
+    ///
+    /// If either side is `NAN`, returns the `NAN` interval and `Choice::Both`.
+    pub fn and_choice(self, rhs: Self) -> (Self, Choice) {
+        if self.has_nan() || rhs.has_nan() {
+            (f32::NAN.into(), Choice::Both)
+        } else if self.lower == 0.0 && self.upper == 0.0 {
+            (self, Choice::Left)
+        } else if self.lower > 0.0 || self.upper < 0.0 {
+            (rhs, Choice::Right)
+        } else {
+            (
+                Interval::new(rhs.lower.min(0.0), rhs.upper.max(0.0)),
+                Choice::Both,
+            )
+        }
+    }
+
+    /// Picks between two intervals, using `self` as the condition
+    ///
+    /// At each point, this returns `lhs` if `self < 0` and `rhs` otherwise.
+    /// The [`Choice`] is `Left` if `self` is entirely below zero, `Right` if
+    /// it's entirely at or above zero, and `Both` otherwise (in which case the
+    /// result is the union of the two intervals).
+    ///
+    /// If any argument is `NAN`, returns the `NAN` interval and `Choice::Both`.
+    pub fn select_choice(self, lhs: Self, rhs: Self) -> (Self, Choice) {
+        if self.has_nan() || lhs.has_nan() || rhs.has_nan() {
+            (f32::NAN.into(), Choice::Both)
+        } else if self.upper < 0.0 {
+            (lhs, Choice::Left)
+        } else if self.lower >= 0.0 {
+            (rhs, Choice::Right)
+        } else {
+            (
+                Interval::new(
+                    lhs.lower.min(rhs.lower),
+                    lhs.upper.max(rhs.upper),
+                ),
+                Choice::Both,
+            )
+        }
+    }
+
+    /// Calculates the logical OR of two intervals
+    ///
+    /// At each point, this returns `self` if it is non-zero and `rhs`
+    /// otherwise.  The [`Choice`] is `Left` if `self` does not contain zero,
+    /// `Right` if `self` is exactly zero, and `Both` otherwise.
+    ///
+    /// If either side is `NAN`, returns the `NAN` interval and `Choice::Both`.
+    pub fn or_choice(self, rhs: Self) -> (Self, Choice) {
+        if self.has_nan() || rhs.has_nan() {
+            (f32::NAN.into(), Choice::Both)
+        } else if self.lower > 0.0 || self.upper < 0.0 {
+            (self, Choice::Left)
+        } else if self.lower == 0.0 && self.upper == 0.0 {
+            (rhs, Choice::Right)
+        } else {
+            (
+                Interval::new(
+                    self.lower.min(rhs.lower),
+                    self.upper.max(rhs.upper),
+                ),
+                Choice::Both,
+            )
+        }
+    }
+
+    /// Calculates the minimum of two intervals
+    ///
+    /// Returns both the result and a [`Choice`] indicating whether one side is
+    /// always less than the other.
+    ///
+    /// If either side is `NAN`, returns the `NAN` interval and `Choice::Both`.
+    pub fn min_choice(self, rhs: Self) -> (Self, Choice) {
         if self.has_nan() || rhs.has_nan() {
             return (std::f32::NAN.into(), Choice::Both);
         }
@@ -575,3 +1432,536 @@ impl std::ops::Neg for Interval {
         Interval::new(-self.upper, -self.lower)
     }
 }
+
+/// Logical NOT, returning 1 where the input is zero and 0 elsewhere
+impl std::ops::Not for Interval {
+    type Output = Self;
+    fn not(self) -> Self {
+        if self.has_nan() {
+            f32::NAN.into()
+        } else if self.lower == 0.0 && self.upper == 0.0 {
+            Interval::new(1.0, 1.0)
+        } else if self.lower > 0.0 || self.upper < 0.0 {
+            Interval::new(0.0, 0.0)
+        } else {
+            Interval::new(0.0, 1.0)
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A range stored in (reduced) affine form
+///
+/// The value is `center + Σ terms[i] εᵢ ± err`, where each `εᵢ` is a noise
+/// symbol in `[-1, 1]`, shared between every value in a calculation.  The
+/// three noise symbols typically represent the X, Y, and Z axes; because they
+/// are shared, correlated values can cancel out (e.g. `x - x` is exactly 0),
+/// which gives much tighter bounds than [`Interval`] arithmetic on deep tapes.
+///
+/// Nonlinear operations are approximated with a linear function of their
+/// input, plus new error which is accumulated into `err`.  Operations without
+/// a good linear approximation fall back to [`Interval`] arithmetic.
+///
+/// # Warning
+/// Like [`Interval`], this implementation does not set rounding modes, so it
+/// may not be _perfect_.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Affine {
+    center: f32,
+    terms: [f32; 3],
+    err: f32,
+}
+
+impl Affine {
+    /// Builds a new affine form
+    ///
+    /// # Panics
+    /// Panics if `err` is negative
+    pub fn new(center: f32, terms: [f32; 3], err: f32) -> Self {
+        assert!(err >= 0.0 || err.is_nan());
+        Self { center, terms, err }
+    }
+
+    /// Builds an affine form which spans the given interval along noise
+    /// symbol `axis`
+    ///
+    /// # Panics
+    /// Panics if `axis` is not 0, 1, or 2
+    pub fn from_axis(i: Interval, axis: usize) -> Self {
+        let mut terms = [0.0; 3];
+        terms[axis] = i.width() / 2.0;
+        Self::new(i.midpoint(), terms, 0.0)
+    }
+
+    /// Builds an affine form spanning the given interval along the X axis
+    pub fn x(i: Interval) -> Self {
+        Self::from_axis(i, 0)
+    }
+
+    /// Builds an affine form spanning the given interval along the Y axis
+    pub fn y(i: Interval) -> Self {
+        Self::from_axis(i, 1)
+    }
+
+    /// Builds an affine form spanning the given interval along the Z axis
+    pub fn z(i: Interval) -> Self {
+        Self::from_axis(i, 2)
+    }
+
+    /// Returns the central value
+    pub fn center(&self) -> f32 {
+        self.center
+    }
+
+    /// Returns the coefficients of each noise symbol
+    pub fn terms(&self) -> [f32; 3] {
+        self.terms
+    }
+
+    /// Returns the accumulated (uncorrelated) error
+    pub fn err(&self) -> f32 {
+        self.err
+    }
+
+    /// Returns the maximum distance from the center
+    pub fn radius(&self) -> f32 {
+        self.terms.iter().map(|t| t.abs()).sum::<f32>() + self.err
+    }
+
+    /// Returns `true` if any component is `NaN`
+    pub fn has_nan(&self) -> bool {
+        self.center.is_nan()
+            || self.err.is_nan()
+            || self.terms.iter().any(|t| t.is_nan())
+    }
+
+    /// Returns `true` if this is exactly zero
+    fn is_zero(&self) -> bool {
+        self.center == 0.0 && self.radius() == 0.0
+    }
+
+    /// Returns either `self` or `i` (converted to an affine form), whichever
+    /// has the smaller radius
+    ///
+    /// If `self` has a `NaN` component, `i` is always returned.
+    fn tightest(self, i: Interval) -> Self {
+        if self.has_nan() || i.width() / 2.0 < self.radius() {
+            i.into()
+        } else {
+            self
+        }
+    }
+
+    /// Multiplies by a constant
+    fn scale(self, s: f32) -> Self {
+        Self {
+            center: self.center * s,
+            terms: self.terms.map(|t| t * s),
+            err: self.err * s.abs(),
+        }
+    }
+
+    /// Adds a constant offset and additional error
+    fn shift(self, offset: f32, err: f32) -> Self {
+        Self {
+            center: self.center + offset,
+            terms: self.terms,
+            err: self.err + err,
+        }
+    }
+
+    /// Applies a function which is monotonic and either convex or concave
+    /// over the range of `self`, given its derivative
+    ///
+    /// This uses the min-range approximation: the slope is the derivative at
+    /// whichever end of the range has the smaller magnitude, so that the
+    /// residual `f(x) - slope * x` is monotonic and bounded by its values at
+    /// the ends of the range.
+    fn min_range(self, f: fn(f32) -> f32, df: fn(f32) -> f32) -> Self {
+        let i = Interval::from(self);
+        let (lo, hi) = (i.lower(), i.upper());
+        if lo == hi {
+            return f(lo).into();
+        }
+        let (da, db) = (df(lo), df(hi));
+        let slope = if da.abs() < db.abs() { da } else { db };
+        let a = f(lo) - slope * lo;
+        let b = f(hi) - slope * hi;
+        let (a, b) = (a.min(b), a.max(b));
+        let out = self.scale(slope).shift((a + b) / 2.0, (b - a) / 2.0);
+        if out.has_nan() {
+            f32::NAN.into()
+        } else {
+            out
+        }
+    }
+
+    /// Absolute value
+    ///
+    /// If the range includes zero, this uses the chord between the two ends
+    /// of the range, which is the best linear approximation.
+    pub fn abs(self) -> Self {
+        let i = Interval::from(self);
+        let (lo, hi) = (i.lower(), i.upper());
+        if i.has_nan() {
+            f32::NAN.into()
+        } else if lo >= 0.0 {
+            self
+        } else if hi <= 0.0 {
+            -self
+        } else if !(lo.is_finite() && hi.is_finite()) {
+            // The chord would be undefined
+            i.abs().into()
+        } else {
+            // The chord is `slope * x + intercept`, and |x| lies below it by
+            // up to `gap` (at x = 0)
+            let slope = (hi + lo) / (hi - lo);
+            let gap = -2.0 * lo * hi / (hi - lo);
+            let intercept = -lo - slope * lo;
+            self.scale(slope).shift(intercept - gap / 2.0, gap / 2.0)
+        }
+    }
+
+    /// Square
+    ///
+    /// This is tighter than multiplication, because both sides of the
+    /// multiplication are the same value.
+    pub fn square(self) -> Self {
+        // With self = c + L + E (where E is the error term), the result is
+        // c² + 2cL + 2cE + (L + E)², and the last term is in [0, r²]
+        let c = self.center;
+        let r2 = self.radius().powi(2);
+        let out = Self {
+            center: c * c + r2 / 2.0,
+            terms: self.terms.map(|t| 2.0 * c * t),
+            err: 2.0 * c.abs() * self.err + r2 / 2.0,
+        };
+        out.tightest(Interval::from(self).square())
+    }
+
+    /// Square root
+    pub fn sqrt(self) -> Self {
+        if Interval::from(self).lower() > 0.0 {
+            self.min_range(f32::sqrt, |x| 0.5 / x.sqrt())
+        } else {
+            Interval::from(self).sqrt().into()
+        }
+    }
+
+    /// Reciprocal
+    ///
+    /// If the range includes 0, returns `NAN`
+    pub fn recip(self) -> Self {
+        let i = Interval::from(self);
+        if i.lower() > 0.0 || i.upper() < 0.0 {
+            self.min_range(|x| 1.0 / x, |x| -1.0 / (x * x))
+        } else {
+            f32::NAN.into()
+        }
+    }
+
+    /// Exponential
+    pub fn exp(self) -> Self {
+        self.min_range(f32::exp, f32::exp)
+    }
+
+    /// Natural logarithm
+    pub fn ln(self) -> Self {
+        if Interval::from(self).lower() > 0.0 {
+            self.min_range(f32::ln, |x| 1.0 / x)
+        } else {
+            Interval::from(self).ln().into()
+        }
+    }
+
+    /// Sine, calculated with interval arithmetic
+    pub fn sine(self) -> Self {
+        Interval::from(self).sine().into()
+    }
+
+    /// Cosine, calculated with interval arithmetic
+    pub fn cosine(self) -> Self {
+        Interval::from(self).cosine().into()
+    }
+
+    /// Tangent, calculated with interval arithmetic
+    pub fn tan(self) -> Self {
+        Interval::from(self).tan().into()
+    }
+
+    /// Arcsine, calculated with interval arithmetic
+    pub fn asin(self) -> Self {
+        Interval::from(self).asin().into()
+    }
+
+    /// Arccosine, calculated with interval arithmetic
+    pub fn acos(self) -> Self {
+        Interval::from(self).acos().into()
+    }
+
+    /// Arctangent, calculated with interval arithmetic
+    pub fn atan(self) -> Self {
+        Interval::from(self).atan().into()
+    }
+
+    /// Four-quadrant arctangent, calculated with interval arithmetic
+    pub fn atan2(self, rhs: Self) -> Self {
+        Interval::from(self).atan2(rhs.into()).into()
+    }
+
+    /// Raises `self` to the power `rhs`, calculated with interval arithmetic
+    pub fn pow(self, rhs: Self) -> Self {
+        Interval::from(self).pow(rhs.into()).into()
+    }
+
+    /// Raises `self` to an integer power
+    pub fn powi(self, n: i32) -> Self {
+        match n {
+            0 => 1.0.into(),
+            1 => self,
+            2 => self.square(),
+            _ => Interval::from(self).powi(n).into(),
+        }
+    }
+
+    /// Rounds down, calculated with interval arithmetic
+    pub fn floor(self) -> Self {
+        Interval::from(self).floor().into()
+    }
+
+    /// Rounds up, calculated with interval arithmetic
+    pub fn ceil(self) -> Self {
+        Interval::from(self).ceil().into()
+    }
+
+    /// Rounds to the nearest integer, calculated with interval arithmetic
+    pub fn round(self) -> Self {
+        Interval::from(self).round().into()
+    }
+
+    /// Euclidean remainder, calculated with interval arithmetic
+    pub fn modulo(self, rhs: Self) -> Self {
+        Interval::from(self).modulo(rhs.into()).into()
+    }
+
+    /// Checks whether `self < rhs`
+    ///
+    /// The comparison is made on `self - rhs`, so correlated values are
+    /// compared exactly.
+    pub fn lt(self, rhs: Self) -> Self {
+        let d = Interval::from(self - rhs);
+        if d.has_nan() {
+            f32::NAN.into()
+        } else if d.upper() < 0.0 {
+            1.0.into()
+        } else if d.lower() >= 0.0 {
+            0.0.into()
+        } else {
+            Interval::new(0.0, 1.0).into()
+        }
+    }
+
+    /// Checks whether `self == rhs`
+    pub fn eq(self, rhs: Self) -> Self {
+        let d = self - rhs;
+        let i = Interval::from(d);
+        if d.has_nan() {
+            f32::NAN.into()
+        } else if d.is_zero() {
+            1.0.into()
+        } else if i.lower() > 0.0 || i.upper() < 0.0 {
+            0.0.into()
+        } else {
+            Interval::new(0.0, 1.0).into()
+        }
+    }
+
+    /// Calculates the logical AND, using `self` as the condition
+    ///
+    /// The [`Choice`] follows the same rules as [`Interval::and_choice`].
+    pub fn and_choice(self, rhs: Self) -> (Self, Choice) {
+        let (out, choice) = Interval::from(self).and_choice(rhs.into());
+        match choice {
+            Choice::Left => (self, choice),
+            Choice::Right => (rhs, choice),
+            _ => (out.into(), choice),
+        }
+    }
+
+    /// Calculates the logical OR, using `self` as the condition
+    ///
+    /// The [`Choice`] follows the same rules as [`Interval::or_choice`].
+    pub fn or_choice(self, rhs: Self) -> (Self, Choice) {
+        let (out, choice) = Interval::from(self).or_choice(rhs.into());
+        match choice {
+            Choice::Left => (self, choice),
+            Choice::Right => (rhs, choice),
+            _ => (out.into(), choice),
+        }
+    }
+
+    /// Picks between two values, using `self` as the condition
+    ///
+    /// The [`Choice`] follows the same rules as [`Interval::select_choice`].
+    pub fn select_choice(self, lhs: Self, rhs: Self) -> (Self, Choice) {
+        let (out, choice) =
+            Interval::from(self).select_choice(lhs.into(), rhs.into());
+        match choice {
+            Choice::Left => (lhs, choice),
+            Choice::Right => (rhs, choice),
+            _ => (out.into(), choice),
+        }
+    }
+
+    /// Calculates the minimum of two values
+    ///
+    /// The [`Choice`] is based on the range of `self - rhs`, so it may pick a
+    /// side in cases where the equivalent [`Interval::min_choice`] cannot.
+    pub fn min_choice(self, rhs: Self) -> (Self, Choice) {
+        if self.has_nan() || rhs.has_nan() {
+            return (f32::NAN.into(), Choice::Both);
+        }
+        let d = self - rhs;
+        let i = Interval::from(d);
+        if i.upper() < 0.0 {
+            (self, Choice::Left)
+        } else if i.lower() > 0.0 {
+            (rhs, Choice::Right)
+        } else {
+            // min(a, b) = (a + b - |a - b|) / 2, unless the interval
+            // result is tighter
+            let a = (self + rhs - d.abs()).scale(0.5);
+            let (i, _) = Interval::from(self).min_choice(rhs.into());
+            (a.tightest(i), Choice::Both)
+        }
+    }
+
+    /// Calculates the maximum of two values
+    ///
+    /// The [`Choice`] is based on the range of `self - rhs`, so it may pick a
+    /// side in cases where the equivalent [`Interval::max_choice`] cannot.
+    pub fn max_choice(self, rhs: Self) -> (Self, Choice) {
+        if self.has_nan() || rhs.has_nan() {
+            return (f32::NAN.into(), Choice::Both);
+        }
+        let d = self - rhs;
+        let i = Interval::from(d);
+        if i.lower() > 0.0 {
+            (self, Choice::Left)
+        } else if i.upper() < 0.0 {
+            (rhs, Choice::Right)
+        } else {
+            // max(a, b) = (a + b + |a - b|) / 2, unless the interval
+            // result is tighter
+            let a = (self + rhs + d.abs()).scale(0.5);
+            let (i, _) = Interval::from(self).max_choice(rhs.into());
+            (a.tightest(i), Choice::Both)
+        }
+    }
+}
+
+impl From<f32> for Affine {
+    fn from(f: f32) -> Self {
+        Affine::new(f, [0.0; 3], 0.0)
+    }
+}
+
+/// Converts an interval into an affine form with no correlated terms
+impl From<Interval> for Affine {
+    fn from(i: Interval) -> Self {
+        if i.has_nan() {
+            f32::NAN.into()
+        } else {
+            Affine::new(i.midpoint(), [0.0; 3], i.width() / 2.0)
+        }
+    }
+}
+
+/// Converts an affine form into the interval that bounds it
+///
+/// If the center and radius are both infinite, the affine form can't say which
+/// way the range extends, so the result is `[-inf, inf]`.
+impl From<Affine> for Interval {
+    fn from(a: Affine) -> Self {
+        if a.has_nan() {
+            f32::NAN.into()
+        } else {
+            let r = a.radius();
+            let (lower, upper) = (a.center - r, a.center + r);
+            if lower.is_nan() || upper.is_nan() {
+                Interval::new(f32::NEG_INFINITY, f32::INFINITY)
+            } else {
+                Interval::new(lower, upper)
+            }
+        }
+    }
+}
+
+impl std::ops::Add<Affine> for Affine {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            center: self.center + rhs.center,
+            terms: std::array::from_fn(|i| self.terms[i] + rhs.terms[i]),
+            err: self.err + rhs.err,
+        }
+    }
+}
+
+impl std::ops::Sub<Affine> for Affine {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl std::ops::Mul<Affine> for Affine {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        // The product of the two non-central parts is bounded by the product
+        // of their radii, and is added to the error term
+        let (a, b) = (self.center, rhs.center);
+        let err = a.abs() * rhs.err
+            + b.abs() * self.err
+            + self.radius() * rhs.radius();
+        let terms =
+            std::array::from_fn(|i| a * rhs.terms[i] + b * self.terms[i]);
+        let out = Self {
+            center: a * b,
+            terms,
+            err,
+        };
+        out.tightest(Interval::from(self) * Interval::from(rhs))
+    }
+}
+
+impl std::ops::Div<Affine> for Affine {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        if rhs.radius() == 0.0 {
+            self.scale(1.0 / rhs.center)
+        } else {
+            self * rhs.recip()
+        }
+    }
+}
+
+impl std::ops::Neg for Affine {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self {
+            center: -self.center,
+            terms: self.terms.map(|t| -t),
+            err: self.err,
+        }
+    }
+}
+
+/// Logical NOT, calculated with interval arithmetic
+impl std::ops::Not for Affine {
+    type Output = Self;
+    fn not(self) -> Self {
+        (!Interval::from(self)).into()
+    }
+}
@@ -0,0 +1,311 @@
+//! Evaluation of partial derivatives with respect to variables
+use crate::{
+    eval::{types::Grad, Tape, Vars},
+    vm::eval_grad_slice,
+    Error,
+};
+
+/// Values and partial derivatives returned by a [`VarGradSliceEval`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VarGrads {
+    /// Value at each point
+    pub v: Vec<f32>,
+    /// Partial derivatives, where `d[k][i]` is the derivative at the `i`'th
+    /// point with respect to the `k`'th variable passed to
+    /// [`Vars::set_grad_vars`]
+    pub d: Vec<Vec<f32>>,
+}
+
+/// Evaluator for many points, calculating partial derivatives with respect to
+/// variables (rather than X, Y, Z)
+///
+/// The variables to differentiate are selected with
+/// [`Vars::set_grad_vars`].  Derivatives are tracked in the three partial
+/// derivative slots of a [`Grad`], so the tape is evaluated once for every
+/// three variables.
+///
+/// This evaluator interprets the tape's VM instructions, so it is available
+/// for tapes from any evaluator family (including the JIT).
+#[derive(Clone)]
+pub struct VarGradSliceEval<F> {
+    tape: Tape<F>,
+}
+
+/// Scratch data used by a [`VarGradSliceEval`]
+#[derive(Default)]
+pub struct VarGradSliceEvalData {
+    out: VarGrads,
+    slots: Vec<Vec<Grad>>,
+}
+
+impl VarGradSliceEvalData {
+    fn prepare<F>(&mut self, tape: &Tape<F>, size: usize, grads: usize) {
+        let nan = Grad::from(f32::NAN);
+        self.slots.resize_with(tape.slot_count(), Vec::new);
+        for s in self.slots.iter_mut() {
+            s.resize(size.max(s.len()), nan);
+        }
+        self.out.v.resize(size, f32::NAN);
+        self.out.d.resize_with(grads, Vec::new);
+        for d in self.out.d.iter_mut() {
+            d.resize(size, f32::NAN);
+        }
+    }
+}
+
+impl<F> VarGradSliceEval<F> {
+    /// Builds a new evaluator for the given tape
+    pub fn new(tape: &Tape<F>) -> Self {
+        Self { tape: tape.clone() }
+    }
+
+    /// Returns a copy of the inner tape
+    pub fn tape(&self) -> Tape<F> {
+        self.tape.clone()
+    }
+
+    /// Evaluate using the given `data` as scratch memory
+    ///
+    /// Returns results borrowed from `data`.  The W input is set to 0.
+    pub fn eval_with<'a>(
+        &self,
+        x: &[f32],
+        y: &[f32],
+        z: &[f32],
+        vars: &Vars,
+        data: &'a mut VarGradSliceEvalData,
+    ) -> Result<&'a VarGrads, Error> {
+        let values = vars.as_slice();
+        if x.len() != y.len() || x.len() != z.len() {
+            return Err(Error::MismatchedSlices);
+        } else if values.len() != self.tape.var_count() {
+            return Err(Error::BadVarSlice(
+                values.len(),
+                self.tape.var_count(),
+            ));
+        }
+        let size = x.len();
+        let grad = vars.grad_vars();
+        data.prepare(&self.tape, size, grad.len());
+
+        // Inputs are constant with respect to the variables
+        let input = |j: u8, i: usize| match j {
+            0 => Grad::from(x[i]),
+            1 => Grad::from(y[i]),
+            2 => Grad::from(z[i]),
+            3 => Grad::from(0.0),
+            _ => panic!("Invalid input: {}", j),
+        };
+
+        // Always do at least one pass, so that values are populated
+        let passes = grad.len().div_ceil(3).max(1);
+        for p in 0..passes {
+            let chunk = &grad[p * 3..(p * 3 + 3).min(grad.len())];
+            let seed = |j: u32, n: usize| {
+                if chunk.get(n) == Some(&j) {
+                    1.0
+                } else {
+                    0.0
+                }
+            };
+            let var = |j: u32| {
+                let v = values[j as usize];
+                Grad::new(v, seed(j, 0), seed(j, 1), seed(j, 2))
+            };
+            eval_grad_slice(&self.tape, size, input, var, &mut data.slots);
+
+            let out = &data.slots[0][0..size];
+            for (v, g) in data.out.v.iter_mut().zip(out) {
+                *v = g.v;
+            }
+            for n in 0..chunk.len() {
+                for (d, g) in data.out.d[p * 3 + n].iter_mut().zip(out) {
+                    *d = [g.dx, g.dy, g.dz][n];
+                }
+            }
+        }
+        Ok(&data.out)
+    }
+
+    /// Evaluates the given slices, returning fresh values and derivatives
+    ///
+    /// This function performs allocation; in a hot loop, consider using
+    /// [`eval_with`](Self::eval_with) instead.
+    pub fn eval(
+        &self,
+        x: &[f32],
+        y: &[f32],
+        z: &[f32],
+        vars: &Vars,
+    ) -> Result<VarGrads, Error> {
+        let mut data = Default::default();
+        self.eval_with(x, y, z, vars, &mut data)?;
+        Ok(data.out)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(any(test, feature = "eval-tests"))]
+pub mod eval_tests {
+    use crate::{context::Context, eval::Family, eval::Vars, Error};
+
+    pub fn test_vg_basic<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let a = ctx.var("a").unwrap();
+        let b = ctx.var("b").unwrap();
+        let ax = ctx.mul(a, x).unwrap();
+        let b2 = ctx.square(b).unwrap();
+        let sum = ctx.add(ax, b2).unwrap();
+        let tape = ctx.get_tape::<I>(sum).unwrap();
+
+        let mut vars = Vars::new(&tape);
+        vars.bind([("a", 2.0), ("b", 3.0)].into_iter());
+        vars.set_grad_vars(["b", "a"]).unwrap();
+
+        let eval = tape.new_var_grad_slice_evaluator();
+        let out = eval
+            .eval(&[1.0, 2.0], &[0.0, 0.0], &[0.0, 0.0], &vars)
+            .unwrap();
+        assert_eq!(out.v, vec![11.0, 13.0]);
+        assert_eq!(out.d, vec![vec![6.0, 6.0], vec![1.0, 2.0]]);
+
+        // Without any gradient variables, we still get values
+        vars.set_grad_vars([]).unwrap();
+        let out = eval.eval(&[1.0], &[0.0], &[0.0], &vars).unwrap();
+        assert_eq!(out.v, vec![11.0]);
+        assert!(out.d.is_empty());
+    }
+
+    pub fn test_vg_many<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let names = ["a", "b", "c", "d", "e"];
+        let mut sum = ctx.constant(0.0);
+        for (i, name) in names.iter().enumerate() {
+            // sum += name * x^i
+            let v = ctx.var(name).unwrap();
+            let p = ctx.powi(x, i as i32).unwrap();
+            let t = ctx.mul(v, p).unwrap();
+            sum = ctx.add(sum, t).unwrap();
+        }
+        let e = ctx.var("e").unwrap();
+        let sum = ctx.mul(sum, e).unwrap();
+        let tape = ctx.get_tape::<I>(sum).unwrap();
+
+        let mut vars = Vars::new(&tape);
+        vars.bind(names.iter().cloned().zip([1.0, 2.0, 3.0, 4.0, 5.0]));
+        vars.set_grad_vars(["e", "d", "c", "b", "a"]).unwrap();
+
+        let eval = tape.new_var_grad_slice_evaluator();
+        let out = eval.eval(&[2.0], &[0.0], &[0.0], &vars).unwrap();
+        // 1 + 2 * 2 + 3 * 4 + 4 * 8 + 5 * 16 = 129
+        assert_eq!(out.v, vec![129.0 * 5.0]);
+        assert_eq!(
+            out.d,
+            vec![
+                vec![129.0 + 16.0 * 5.0],
+                vec![8.0 * 5.0],
+                vec![4.0 * 5.0],
+                vec![2.0 * 5.0],
+                vec![5.0]
+            ]
+        );
+    }
+
+    pub fn test_vg_spill<I: Family>() {
+        // Many values are live at once, so tapes with few registers will
+        // include loads and stores
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let a = ctx.var("a").unwrap();
+        let ax = ctx.mul(a, x).unwrap();
+        let terms = (1..32)
+            .map(|i| ctx.mul(ax, i as f64).unwrap())
+            .collect::<Vec<_>>();
+        let mut lhs = ctx.constant(0.0);
+        for &t in &terms {
+            lhs = ctx.add(lhs, t).unwrap();
+        }
+        let mut rhs = ctx.constant(0.0);
+        for &t in terms.iter().rev() {
+            let s = ctx.sin(t).unwrap();
+            rhs = ctx.add(rhs, s).unwrap();
+        }
+        let out = ctx.mul(lhs, rhs).unwrap();
+        let tape = ctx.get_tape::<I>(out).unwrap();
+
+        let mut vars = Vars::new(&tape);
+        vars.set_grad_vars(["a"]).unwrap();
+        let eval = tape.new_var_grad_slice_evaluator();
+        for (a, x) in [(0.5, 0.1), (-1.0, 0.3)] {
+            vars.set("a", a);
+            let out = eval.eval(&[x], &[0.0], &[0.0], &vars).unwrap();
+
+            // lhs = a * x * sum(i), rhs = sum(sin(a * x * i))
+            let (lhs, rhs, drhs) = (1..32).fold((0.0, 0.0, 0.0), |acc, i| {
+                let i = i as f32;
+                let t = a * x * i;
+                (acc.0 + t, acc.1 + t.sin(), acc.2 + t.cos() * x * i)
+            });
+            let dlhs = lhs / a;
+            let expected = dlhs * rhs + lhs * drhs;
+            assert!((out.v[0] - lhs * rhs).abs() < 1e-3);
+            assert!(
+                (out.d[0][0] - expected).abs() < 1e-3,
+                "mismatch at {a}, {x}: {} != {expected}",
+                out.d[0][0]
+            );
+        }
+    }
+
+    pub fn test_vg_errors<I: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let a = ctx.var("a").unwrap();
+        let sum = ctx.add(x, a).unwrap();
+        let tape = ctx.get_tape::<I>(sum).unwrap();
+
+        let mut vars = Vars::new(&tape);
+        vars.set_grad_vars(["a"]).unwrap();
+        assert!(matches!(
+            vars.set_grad_vars(["a", "b"]),
+            Err(Error::UnknownVariable(s)) if s == "b"
+        ));
+        assert_eq!(vars.grad_vars(), &[0]);
+
+        let eval = tape.new_var_grad_slice_evaluator();
+        assert!(matches!(
+            eval.eval(&[1.0, 2.0], &[0.0], &[0.0], &vars),
+            Err(Error::MismatchedSlices)
+        ));
+
+        let other = ctx.get_tape::<I>(x).unwrap();
+        let vars = Vars::new(&other);
+        assert!(matches!(
+            eval.eval(&[1.0], &[0.0], &[0.0], &vars),
+            Err(Error::BadVarSlice(0, 1))
+        ));
+    }
+
+    #[macro_export]
+    macro_rules! var_grad_test {
+        ($i:ident, $t:ty) => {
+            #[test]
+            fn $i() {
+                $crate::eval::var_grad_slice::eval_tests::$i::<$t>()
+            }
+        };
+    }
+
+    #[macro_export]
+    macro_rules! var_grad_slice_tests {
+        ($t:ty) => {
+            $crate::var_grad_test!(test_vg_basic, $t);
+            $crate::var_grad_test!(test_vg_many, $t);
+            $crate::var_grad_test!(test_vg_spill, $t);
+            $crate::var_grad_test!(test_vg_errors, $t);
+        };
+    }
+}
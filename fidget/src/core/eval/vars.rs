@@ -1,11 +1,15 @@
-use crate::eval::tape::Data;
+use crate::{eval::tape::Data, Error};
 use std::{collections::BTreeMap, sync::Arc};
 
 /// `Vars` contains the mapping of variable names to indexes, and a `Vec<f32>`
 /// which is suitably sized for use in evaluation.
+///
+/// It also records which variables are being differentiated by a
+/// [`VarGradSliceEval`](crate::eval::VarGradSliceEval).
 pub struct Vars {
     names: Arc<BTreeMap<String, u32>>,
     values: Vec<f32>,
+    grad: Vec<u32>,
 }
 
 impl Vars {
@@ -13,7 +17,11 @@ impl Vars {
     pub fn new(tape: &Data) -> Self {
         let names = tape.vars();
         let values = vec![0.0; names.len()];
-        Self { names, values }
+        Self {
+            names,
+            values,
+            grad: vec![],
+        }
     }
 
     /// Binds variables by name to the given values
@@ -48,4 +56,37 @@ impl Vars {
     pub fn as_slice(&self) -> &[f32] {
         self.values.as_slice()
     }
+
+    /// Returns the inner data slice, for in-place modification
+    pub fn as_mut_slice(&mut self) -> &mut [f32] {
+        self.values.as_mut_slice()
+    }
+
+    /// Selects the variables to be differentiated, by name
+    ///
+    /// Partial derivatives are returned in the same order as `names`.  Unlike
+    /// [`bind`](Self::bind), every name must be present in the tape; otherwise,
+    /// this returns [`Error::UnknownVariable`] and leaves the selection
+    /// unchanged.
+    pub fn set_grad_vars<'a, I: IntoIterator<Item = &'a str>>(
+        &mut self,
+        names: I,
+    ) -> Result<(), Error> {
+        let grad = names
+            .into_iter()
+            .map(|name| {
+                self.names
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| Error::UnknownVariable(name.to_owned()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.grad = grad;
+        Ok(())
+    }
+
+    /// Returns the indexes of variables which are being differentiated
+    pub fn grad_vars(&self) -> &[u32] {
+        self.grad.as_slice()
+    }
 }
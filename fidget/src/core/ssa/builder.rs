@@ -14,14 +14,26 @@ pub(crate) struct Builder {
     mapping: BTreeMap<Node, u32>,
     vars: BTreeMap<VarNode, u32>,
     var_names: BTreeMap<String, u32>,
-    constants: BTreeMap<Node, f32>,
+    constants: BTreeMap<Node, f64>,
     choice_count: usize,
+
+    /// Next unused SSA slot
+    next_slot: u32,
+
+    /// Number of outputs, which occupy the first slots of the tape
+    output_count: usize,
+
+    /// Copies into output slots for roots that are constant or duplicated
+    output_copies: Vec<SsaOp>,
+
+    /// Exact values of immediates which aren't representable as an `f32`
+    imm64: BTreeMap<u32, f64>,
 }
 
 #[derive(Debug)]
 enum Location {
     Slot(u32),
-    Immediate(f32),
+    Immediate(f64),
 }
 
 impl Builder {
@@ -33,14 +45,66 @@ impl Builder {
             var_names: BTreeMap::new(),
             constants: BTreeMap::new(),
             choice_count: 0,
+            next_slot: 0,
+            output_count: 0,
+            output_copies: vec![],
+            imm64: BTreeMap::new(),
         }
     }
 
-    pub fn finish(self) -> Tape {
+    pub fn finish(mut self) -> Tape {
+        // Output copies are evaluated last, so they go at the front of the
+        // (reversed) tape.
+        self.tape.splice(0..0, self.output_copies);
         Tape {
             tape: self.tape,
             choice_count: self.choice_count,
+            output_count: self.output_count,
             vars: Arc::new(self.var_names),
+            imm64: self.imm64,
+        }
+    }
+
+    /// Converts an immediate to `f32` for the operation writing to `slot`
+    ///
+    /// If the conversion loses precision, the exact value is recorded in the
+    /// tape's side table for double-precision evaluators.
+    fn imm(&mut self, slot: u32, imm: f64) -> f32 {
+        let out = imm as f32;
+        if out as f64 != imm && !imm.is_nan() {
+            self.imm64.insert(slot, imm);
+        }
+        out
+    }
+
+    /// Assigns the first `roots.len()` slots to the given roots, in order
+    ///
+    /// Slot `i` is written by root `i`'s own operation if that root is unique
+    /// and non-constant; otherwise, it's written by a copy at the end of the
+    /// tape.
+    ///
+    /// This must be called before any other node is declared.
+    pub fn declare_outputs(&mut self, roots: &[(Node, Op)]) {
+        assert_eq!(self.next_slot, 0);
+        self.output_count = roots.len();
+        self.next_slot = roots.len().try_into().unwrap();
+        for (i, &(node, op)) in roots.iter().enumerate() {
+            let i = i.try_into().unwrap();
+            match op {
+                Op::Const(c) => {
+                    self.constants.insert(node, c.0);
+                    let imm = self.imm(i, c.0);
+                    self.output_copies.push(SsaOp::CopyImm(i, imm));
+                }
+                _ => match self.mapping.entry(node) {
+                    Entry::Vacant(e) => {
+                        e.insert(i);
+                    }
+                    Entry::Occupied(e) => {
+                        self.output_copies.push(SsaOp::CopyReg(i, *e.get()));
+                    }
+                },
+            }
         }
     }
 
@@ -53,6 +117,26 @@ impl Builder {
         }
     }
 
+    /// Returns a slot for the given node, which may be a constant
+    ///
+    /// If the node is a constant, a fresh slot is allocated and the returned
+    /// `CopyImm` operation (which writes the constant to that slot) must be
+    /// pushed to the tape after its consumer.
+    ///
+    /// This must only be called after every node has been declared, since it
+    /// allocates slots past those assigned to declared nodes.
+    fn get_slot_or_copy(&mut self, node: Node) -> (u32, Option<SsaOp>) {
+        match self.get_allocated_value(node) {
+            Location::Slot(r) => (r, None),
+            Location::Immediate(imm) => {
+                let r = self.next_slot;
+                self.next_slot += 1;
+                let imm = self.imm(r, imm);
+                (r, Some(SsaOp::CopyImm(r, imm)))
+            }
+        }
+    }
+
     /// Ensure that the given node is mapped.
     ///
     /// This must be called before `step` uses the node (as either parent or
@@ -60,11 +144,13 @@ impl Builder {
     pub fn declare_node(&mut self, node: Node, op: Op) {
         match op {
             Op::Const(c) => {
-                self.constants.insert(node, c.0 as f32);
+                self.constants.insert(node, c.0);
             }
             _ => {
-                let index: u32 = self.mapping.len().try_into().unwrap();
-                self.mapping.entry(node).or_insert(index);
+                if let Entry::Vacant(e) = self.mapping.entry(node) {
+                    e.insert(self.next_slot);
+                    self.next_slot += 1;
+                }
             }
         }
     }
@@ -77,6 +163,7 @@ impl Builder {
                     "X" => 0,
                     "Y" => 1,
                     "Z" => 2,
+                    "W" => 3,
                     i => panic!("Unexpected input index: {i}"),
                 };
                 Some(SsaOp::Input(index.unwrap(), arg))
@@ -97,7 +184,7 @@ impl Builder {
             Op::Const(c) => {
                 // Skip this (because it's not inserted into the tape),
                 // recording its value for use as an immediate later.
-                self.constants.insert(node, c.0 as f32);
+                self.constants.insert(node, c.0);
                 assert!(index.is_none());
                 None
             }
@@ -127,26 +214,68 @@ impl Builder {
                     BinaryOpcode::Max => {
                         (SsaOp::MaxRegReg, SsaOp::MaxRegImm, SsaOp::MaxRegImm)
                     }
+                    BinaryOpcode::Atan2 => (
+                        SsaOp::Atan2RegReg,
+                        SsaOp::Atan2RegImm,
+                        SsaOp::Atan2ImmReg,
+                    ),
+                    BinaryOpcode::Pow => {
+                        (SsaOp::PowRegReg, SsaOp::PowRegImm, SsaOp::PowImmReg)
+                    }
+                    BinaryOpcode::Mod => {
+                        (SsaOp::ModRegReg, SsaOp::ModRegImm, SsaOp::ModImmReg)
+                    }
+                    BinaryOpcode::Lt => {
+                        (SsaOp::LtRegReg, SsaOp::LtRegImm, SsaOp::LtImmReg)
+                    }
+                    // `a > b` is lowered to `b < a`
+                    BinaryOpcode::Gt => (
+                        |out, lhs, rhs| SsaOp::LtRegReg(out, rhs, lhs),
+                        SsaOp::LtImmReg,
+                        SsaOp::LtRegImm,
+                    ),
+                    BinaryOpcode::Eq => {
+                        (SsaOp::EqRegReg, SsaOp::EqRegImm, SsaOp::EqRegImm)
+                    }
+                    // `Context::op_binary` folds a constant left-hand
+                    // argument, so it never reaches the tape.
+                    BinaryOpcode::And => {
+                        (SsaOp::AndRegReg, SsaOp::AndRegImm, |_, _, _| {
+                            panic!("Cannot handle and(imm, reg)")
+                        })
+                    }
+                    BinaryOpcode::Or => {
+                        (SsaOp::OrRegReg, SsaOp::OrRegImm, |_, _, _| {
+                            panic!("Cannot handle or(imm, reg)")
+                        })
+                    }
                 };
 
-                if matches!(op, BinaryOpcode::Min | BinaryOpcode::Max) {
-                    self.choice_count += 1;
-                }
-
                 let op = match (lhs, rhs) {
+                    // Integer exponents use a dedicated opcode, which is
+                    // cheaper and has simpler interval behavior.  We limit them
+                    // to the `i16` range so that they can be negated safely and
+                    // are exactly representable in any precision.
+                    (Location::Slot(arg), Location::Immediate(imm))
+                        if matches!(op, BinaryOpcode::Pow)
+                            && imm == imm as i16 as f64 =>
+                    {
+                        SsaOp::PowiReg(index, arg, imm as i16 as i32)
+                    }
                     (Location::Slot(lhs), Location::Slot(rhs)) => {
                         f.0(index, lhs, rhs)
                     }
                     (Location::Slot(arg), Location::Immediate(imm)) => {
-                        f.1(index, arg, imm)
+                        f.1(index, arg, self.imm(index, imm))
                     }
                     (Location::Immediate(imm), Location::Slot(arg)) => {
-                        f.2(index, arg, imm)
+                        f.2(index, arg, self.imm(index, imm))
                     }
                     (Location::Immediate(..), Location::Immediate(..)) => {
                         panic!("Cannot handle f(imm, imm)")
                     }
                 };
+                self.choice_count += op.choice_count();
                 Some(op)
             }
             Op::Unary(op, lhs) => {
@@ -166,9 +295,38 @@ impl Builder {
                     UnaryOpcode::Exp => SsaOp::ExpReg,
                     UnaryOpcode::Sin => SsaOp::SineReg,
                     UnaryOpcode::Cos => SsaOp::CosineReg,
+                    UnaryOpcode::Tan => SsaOp::TanReg,
+                    UnaryOpcode::Asin => SsaOp::AsinReg,
+                    UnaryOpcode::Acos => SsaOp::AcosReg,
+                    UnaryOpcode::Atan => SsaOp::AtanReg,
+                    UnaryOpcode::Ln => SsaOp::LnReg,
+                    UnaryOpcode::Floor => SsaOp::FloorReg,
+                    UnaryOpcode::Ceil => SsaOp::CeilReg,
+                    UnaryOpcode::Round => SsaOp::RoundReg,
+                    UnaryOpcode::Not => SsaOp::NotReg,
                 };
                 Some(op(index, lhs))
             }
+            Op::Select(cond, lhs, rhs) => {
+                let cond = match self.get_allocated_value(cond) {
+                    Location::Slot(r) => r,
+                    // `Context::select` folds a constant condition
+                    Location::Immediate(..) => {
+                        panic!("Cannot handle select(imm, ..)")
+                    }
+                };
+                // There's only a register-register form of `Select`, so
+                // constant branches are copied into fresh slots.  The tape is
+                // in reverse evaluation order, so those copies come after the
+                // `Select` itself.
+                let (lhs, lhs_copy) = self.get_slot_or_copy(lhs);
+                let (rhs, rhs_copy) = self.get_slot_or_copy(rhs);
+                let op = SsaOp::Select(index.unwrap(), cond, lhs, rhs);
+                self.choice_count += op.choice_count();
+                self.tape.push(op);
+                self.tape.extend(lhs_copy.into_iter().chain(rhs_copy));
+                None
+            }
         };
 
         if let Some(op) = op {
@@ -1,8 +1,8 @@
 /// Opcode for use in an SSA [`Tape`](super::Tape)
 #[derive(Copy, Clone, Debug)]
 pub enum Op {
-    /// Reads one of the inputs (X, Y, Z).  This is the most flexible variable,
-    /// and may vary between terms in vector / SIMD evaluation.
+    /// Reads one of the inputs (X, Y, Z, W).  This is the most flexible
+    /// variable, and may vary between terms in vector / SIMD evaluation.
     Input(u32, u32),
     /// A single variable.  Unlike `Input`, this node is assumed to remain
     /// constant across all terms in vector / SIMD evaluation, but may be edited
@@ -53,6 +53,58 @@ pub enum Op {
     SineReg(u32, u32),
     /// Compute the cosine of a register
     CosineReg(u32, u32),
+    /// Compute the tangent of a register
+    TanReg(u32, u32),
+    /// Compute the arcsine of a register
+    AsinReg(u32, u32),
+    /// Compute the arccosine of a register
+    AcosReg(u32, u32),
+    /// Compute the arctangent of a register
+    AtanReg(u32, u32),
+    /// Compute the natural logarithm of a register
+    LnReg(u32, u32),
+
+    /// Compute `atan2(reg, imm)`, i.e. the angle of the point `(imm, reg)`
+    Atan2RegImm(u32, u32, f32),
+    /// Compute `atan2(imm, reg)`, i.e. the angle of the point `(reg, imm)`
+    Atan2ImmReg(u32, u32, f32),
+    /// Compute `atan2(lhs, rhs)`, i.e. the angle of the point `(rhs, lhs)`
+    Atan2RegReg(u32, u32, u32),
+
+    /// Raise a register to the power of an immediate
+    PowRegImm(u32, u32, f32),
+    /// Raise an immediate to the power of a register
+    PowImmReg(u32, u32, f32),
+    /// Raise one register to the power of another
+    PowRegReg(u32, u32, u32),
+    /// Raise a register to an integer power
+    PowiReg(u32, u32, i32),
+
+    /// Round a register down to the nearest integer
+    FloorReg(u32, u32),
+    /// Round a register up to the nearest integer
+    CeilReg(u32, u32),
+    /// Round a register to the nearest integer (ties away from zero)
+    RoundReg(u32, u32),
+    /// Compute the Euclidean remainder of a register and an immediate
+    ModRegImm(u32, u32, f32),
+    /// Compute the Euclidean remainder of an immediate and a register
+    ModImmReg(u32, u32, f32),
+    /// Compute the Euclidean remainder of two registers
+    ModRegReg(u32, u32, u32),
+
+    /// Returns 1 if the register is zero, and 0 otherwise
+    NotReg(u32, u32),
+    /// Returns 1 if the register is less than the immediate
+    LtRegImm(u32, u32, f32),
+    /// Returns 1 if the immediate is less than the register
+    LtImmReg(u32, u32, f32),
+    /// Returns 1 if the left register is less than the right register
+    LtRegReg(u32, u32, u32),
+    /// Returns 1 if the register is equal to the immediate
+    EqRegImm(u32, u32, f32),
+    /// Returns 1 if the two registers are equal
+    EqRegReg(u32, u32, u32),
 
     /// Compute the minimum of a register and an immediate
     MinRegImm(u32, u32, f32),
@@ -62,6 +114,27 @@ pub enum Op {
     MinRegReg(u32, u32, u32),
     /// Compute the maximum of two registers
     MaxRegReg(u32, u32, u32),
+    /// Logical AND of a register and an immediate
+    ///
+    /// Returns the register if it is zero, or the immediate otherwise
+    AndRegImm(u32, u32, f32),
+    /// Logical AND of two registers
+    ///
+    /// Returns the left register if it is zero, or the right otherwise
+    AndRegReg(u32, u32, u32),
+    /// Logical OR of a register and an immediate
+    ///
+    /// Returns the register if it is non-zero, or the immediate otherwise
+    OrRegImm(u32, u32, f32),
+    /// Logical OR of two registers
+    ///
+    /// Returns the left register if it is non-zero, or the right otherwise
+    OrRegReg(u32, u32, u32),
+    /// Picks between two registers based on a condition register
+    ///
+    /// Returns the left register if the condition is less than zero, or the
+    /// right register otherwise
+    Select(u32, u32, u32, u32),
 }
 
 impl Op {
@@ -91,11 +164,40 @@ impl Op {
             
             | Op::SineReg(out, ..)
             | Op::CosineReg(out, ..)
+            | Op::TanReg(out, ..)
+            | Op::AsinReg(out, ..)
+            | Op::AcosReg(out, ..)
+            | Op::AtanReg(out, ..)
+            | Op::Atan2RegImm(out, ..)
+            | Op::Atan2ImmReg(out, ..)
+            | Op::Atan2RegReg(out, ..)
+            | Op::LnReg(out, ..)
+            | Op::PowRegImm(out, ..)
+            | Op::PowImmReg(out, ..)
+            | Op::PowRegReg(out, ..)
+            | Op::PowiReg(out, ..)
+            | Op::FloorReg(out, ..)
+            | Op::CeilReg(out, ..)
+            | Op::RoundReg(out, ..)
+            | Op::ModRegImm(out, ..)
+            | Op::ModImmReg(out, ..)
+            | Op::ModRegReg(out, ..)
+            | Op::NotReg(out, ..)
+            | Op::LtRegImm(out, ..)
+            | Op::LtImmReg(out, ..)
+            | Op::LtRegReg(out, ..)
+            | Op::EqRegImm(out, ..)
+            | Op::EqRegReg(out, ..)
             
             | Op::MinRegImm(out, ..)
             | Op::MaxRegImm(out, ..)
             | Op::MinRegReg(out, ..)
-            | Op::MaxRegReg(out, ..) => *out,
+            | Op::MaxRegReg(out, ..)
+            | Op::AndRegImm(out, ..)
+            | Op::AndRegReg(out, ..)
+            | Op::OrRegImm(out, ..)
+            | Op::OrRegReg(out, ..)
+            | Op::Select(out, ..) => *out,
         }
     }
     /// Returns the number of choices made by the given opcode
@@ -124,11 +226,40 @@ impl Op {
             | Op::DivRegImm(..)
             | Op::DivImmReg(..)
             | Op::SineReg(..)
-            | Op::CosineReg(..) => 0,
+            | Op::CosineReg(..)
+            | Op::TanReg(..)
+            | Op::AsinReg(..)
+            | Op::AcosReg(..)
+            | Op::AtanReg(..)
+            | Op::Atan2RegImm(..)
+            | Op::Atan2ImmReg(..)
+            | Op::Atan2RegReg(..)
+            | Op::LnReg(..)
+            | Op::PowRegImm(..)
+            | Op::PowImmReg(..)
+            | Op::PowRegReg(..)
+            | Op::PowiReg(..)
+            | Op::FloorReg(..)
+            | Op::CeilReg(..)
+            | Op::RoundReg(..)
+            | Op::ModRegImm(..)
+            | Op::ModImmReg(..)
+            | Op::ModRegReg(..)
+            | Op::NotReg(..)
+            | Op::LtRegImm(..)
+            | Op::LtImmReg(..)
+            | Op::LtRegReg(..)
+            | Op::EqRegImm(..)
+            | Op::EqRegReg(..) => 0,
             Op::MinRegImm(..)
             | Op::MaxRegImm(..)
             | Op::MinRegReg(..)
-            | Op::MaxRegReg(..) => 1,
+            | Op::MaxRegReg(..)
+            | Op::AndRegImm(..)
+            | Op::AndRegReg(..)
+            | Op::OrRegImm(..)
+            | Op::OrRegReg(..)
+            | Op::Select(..) => 1,
         }
     }
 }
@@ -139,6 +270,7 @@ mod test {
 
     #[test]
     fn test_op_size() {
-        assert_eq!(std::mem::size_of::<Op>(), 16);
+        // `Select` is the only opcode with four arguments
+        assert_eq!(std::mem::size_of::<Op>(), 20);
     }
 }
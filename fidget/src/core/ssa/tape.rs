@@ -23,6 +23,13 @@ pub struct Tape {
     /// Number of choice operations in the tape
     pub choice_count: usize,
 
+    /// Number of outputs, which are written to slots `0..output_count`
+    pub output_count: usize,
+
+    /// Exact values of immediates which aren't representable as an `f32`,
+    /// keyed by the output slot of the operation that uses them
+    pub imm64: BTreeMap<u32, f64>,
+
     /// Mapping from variable names (in the original
     /// [`Context`](crate::context::Context)) to indexes in the variable array
     /// used during evaluation.
@@ -36,6 +43,7 @@ impl Tape {
     /// Resets to an empty tape, preserving allocations
     pub fn reset(&mut self) {
         self.tape.clear();
+        self.imm64.clear();
         self.choice_count = 0;
     }
     /// Pretty-prints the given tape to `stdout`
@@ -56,7 +64,16 @@ impl Tape {
                 | Op::SquareReg(out, arg)
                 | Op::ExpReg(out, arg)
                 | Op::SineReg(out, arg)
-                | Op::CosineReg(out, arg) => {
+                | Op::CosineReg(out, arg)
+                | Op::TanReg(out, arg)
+                | Op::AsinReg(out, arg)
+                | Op::AcosReg(out, arg)
+                | Op::AtanReg(out, arg)
+                | Op::LnReg(out, arg)
+                | Op::FloorReg(out, arg)
+                | Op::CeilReg(out, arg)
+                | Op::RoundReg(out, arg)
+                | Op::NotReg(out, arg) => {
                     let op = match op {
                         Op::NegReg(..) => "NEG",
                         Op::AbsReg(..) => "ABS",
@@ -67,6 +84,15 @@ impl Tape {
                         Op::CopyReg(..) => "COPY",
                         Op::SineReg(..) => "SIN",
                         Op::CosineReg(..) => "COS",
+                        Op::TanReg(..) => "TAN",
+                        Op::AsinReg(..) => "ASIN",
+                        Op::AcosReg(..) => "ACOS",
+                        Op::AtanReg(..) => "ATAN",
+                        Op::LnReg(..) => "LN",
+                        Op::FloorReg(..) => "FLOOR",
+                        Op::CeilReg(..) => "CEIL",
+                        Op::RoundReg(..) => "ROUND",
+                        Op::NotReg(..) => "NOT",
                         _ => unreachable!(),
                     };
                     println!("${out} = {op} ${arg}");
@@ -77,7 +103,14 @@ impl Tape {
                 | Op::DivRegReg(out, lhs, rhs)
                 | Op::SubRegReg(out, lhs, rhs)
                 | Op::MinRegReg(out, lhs, rhs)
-                | Op::MaxRegReg(out, lhs, rhs) => {
+                | Op::MaxRegReg(out, lhs, rhs)
+                | Op::Atan2RegReg(out, lhs, rhs)
+                | Op::PowRegReg(out, lhs, rhs)
+                | Op::ModRegReg(out, lhs, rhs)
+                | Op::LtRegReg(out, lhs, rhs)
+                | Op::EqRegReg(out, lhs, rhs)
+                | Op::AndRegReg(out, lhs, rhs)
+                | Op::OrRegReg(out, lhs, rhs) => {
                     let op = match op {
                         Op::AddRegReg(..) => "ADD",
                         Op::MulRegReg(..) => "MUL",
@@ -85,10 +118,20 @@ impl Tape {
                         Op::SubRegReg(..) => "SUB",
                         Op::MinRegReg(..) => "MIN",
                         Op::MaxRegReg(..) => "MAX",
+                        Op::Atan2RegReg(..) => "ATAN2",
+                        Op::PowRegReg(..) => "POW",
+                        Op::ModRegReg(..) => "MOD",
+                        Op::LtRegReg(..) => "LT",
+                        Op::EqRegReg(..) => "EQ",
+                        Op::AndRegReg(..) => "AND",
+                        Op::OrRegReg(..) => "OR",
                         _ => unreachable!(),
                     };
                     println!("${out} = {op} ${lhs} ${rhs}");
                 }
+                Op::Select(out, cond, lhs, rhs) => {
+                    println!("${out} = SELECT ${cond} ${lhs} ${rhs}");
+                }
 
                 Op::AddRegImm(out, arg, imm)
                 | Op::MulRegImm(out, arg, imm)
@@ -97,7 +140,18 @@ impl Tape {
                 | Op::SubImmReg(out, arg, imm)
                 | Op::SubRegImm(out, arg, imm)
                 | Op::MinRegImm(out, arg, imm)
-                | Op::MaxRegImm(out, arg, imm) => {
+                | Op::MaxRegImm(out, arg, imm)
+                | Op::Atan2RegImm(out, arg, imm)
+                | Op::Atan2ImmReg(out, arg, imm)
+                | Op::PowRegImm(out, arg, imm)
+                | Op::PowImmReg(out, arg, imm)
+                | Op::ModRegImm(out, arg, imm)
+                | Op::ModImmReg(out, arg, imm)
+                | Op::LtRegImm(out, arg, imm)
+                | Op::LtImmReg(out, arg, imm)
+                | Op::EqRegImm(out, arg, imm)
+                | Op::AndRegImm(out, arg, imm)
+                | Op::OrRegImm(out, arg, imm) => {
                     let (op, swap) = match op {
                         Op::AddRegImm(..) => ("ADD", false),
                         Op::MulRegImm(..) => ("MUL", false),
@@ -107,6 +161,17 @@ impl Tape {
                         Op::SubRegImm(..) => ("SUB", false),
                         Op::MinRegImm(..) => ("MIN", false),
                         Op::MaxRegImm(..) => ("MAX", false),
+                        Op::Atan2RegImm(..) => ("ATAN2", false),
+                        Op::Atan2ImmReg(..) => ("ATAN2", true),
+                        Op::PowRegImm(..) => ("POW", false),
+                        Op::PowImmReg(..) => ("POW", true),
+                        Op::ModRegImm(..) => ("MOD", false),
+                        Op::ModImmReg(..) => ("MOD", true),
+                        Op::LtRegImm(..) => ("LT", false),
+                        Op::LtImmReg(..) => ("LT", true),
+                        Op::EqRegImm(..) => ("EQ", false),
+                        Op::AndRegImm(..) => ("AND", false),
+                        Op::OrRegImm(..) => ("OR", false),
                         _ => unreachable!(),
                     };
                     if swap {
@@ -115,6 +180,9 @@ impl Tape {
                         println!("${out} = {op} ${arg} {imm}");
                     }
                 }
+                Op::PowiReg(out, arg, n) => {
+                    println!("${out} = POWI ${arg} {n}");
+                }
                 Op::CopyImm(out, imm) => {
                     println!("${out} = COPY {imm}");
                 }
@@ -129,8 +197,12 @@ impl Tape {
     /// simplifies **and** performs register allocation in a single pass.
     pub fn get_asm(&self, reg_limit: u8) -> VmTape {
         let mut alloc = RegisterAllocator::new(reg_limit, self.tape.len());
+        alloc.bind_outputs(self.output_count);
         for &op in self.tape.iter() {
-            alloc.op(op)
+            match self.imm64.get(&op.output()) {
+                Some(&imm) => alloc.op_imm64(op, imm),
+                None => alloc.op(op),
+            }
         }
         alloc.finalize()
     }
@@ -50,6 +50,10 @@ pub struct RegisterAllocator {
 
     /// Output slots, assembled in reverse order
     out: Tape,
+
+    /// Exact value of the immediate in the operation being lowered, if it
+    /// isn't representable as an `f32`
+    imm64: Option<f64>,
 }
 
 impl RegisterAllocator {
@@ -69,6 +73,7 @@ impl RegisterAllocator {
             spare_memory: Vec::with_capacity(1024),
 
             out: Tape::new(reg_limit),
+            imm64: None,
         };
         out.bind_register(0, 0);
         out
@@ -86,6 +91,7 @@ impl RegisterAllocator {
             spare_memory: vec![],
 
             out: Tape::new(0),
+            imm64: None,
         }
     }
 
@@ -114,6 +120,19 @@ impl RegisterAllocator {
         self.bind_register(0, 0);
     }
 
+    /// Binds SSA registers `1..n` to local registers `1..n`
+    ///
+    /// This is used for tapes with multiple outputs, which must end up in the
+    /// first `n` registers.  SSA register 0 is always bound upon construction
+    /// or reset, so this only needs to be called if `n > 1`.
+    pub fn bind_outputs(&mut self, n: usize) {
+        assert!(n <= self.reg_limit as usize);
+        for i in 1..n {
+            self.bind_register(i as u32, i as u8);
+        }
+        self.out.slot_count = self.out.slot_count.max(n as u32);
+    }
+
     /// Claims the internal `Vec<Op>`, leaving it empty
     #[inline]
     pub fn finalize(&mut self) -> Tape {
@@ -272,11 +291,28 @@ impl RegisterAllocator {
             SsaOp::CopyReg(out, arg) => (out, arg, Op::CopyReg),
             SsaOp::SineReg(out, arg) => (out, arg, Op::SineReg),
             SsaOp::CosineReg(out, arg) => (out, arg, Op::CosineReg),
+            SsaOp::TanReg(out, arg) => (out, arg, Op::TanReg),
+            SsaOp::AsinReg(out, arg) => (out, arg, Op::AsinReg),
+            SsaOp::AcosReg(out, arg) => (out, arg, Op::AcosReg),
+            SsaOp::AtanReg(out, arg) => (out, arg, Op::AtanReg),
+            SsaOp::LnReg(out, arg) => (out, arg, Op::LnReg),
+            SsaOp::FloorReg(out, arg) => (out, arg, Op::FloorReg),
+            SsaOp::CeilReg(out, arg) => (out, arg, Op::CeilReg),
+            SsaOp::RoundReg(out, arg) => (out, arg, Op::RoundReg),
+            SsaOp::NotReg(out, arg) => (out, arg, Op::NotReg),
             _ => panic!("Bad opcode: {op:?}"),
         };
         self.op_reg_fn(out, arg, op);
     }
 
+    /// Lowers an operation whose immediate isn't exactly representable as an
+    /// `f32`, recording its exact value in the output tape
+    pub fn op_imm64(&mut self, op: SsaOp, imm: f64) {
+        self.imm64 = Some(imm);
+        self.op(op);
+        assert!(self.imm64.is_none());
+    }
+
     #[inline(always)]
     pub fn op(&mut self, op: SsaOp) {
         match op {
@@ -292,7 +328,16 @@ impl RegisterAllocator {
             | SsaOp::ExpReg(..)
             | SsaOp::CopyReg(..)
             | SsaOp::SineReg(..)
-            | SsaOp::CosineReg(..) => self.op_reg(op),
+            | SsaOp::CosineReg(..)
+            | SsaOp::TanReg(..)
+            | SsaOp::AsinReg(..)
+            | SsaOp::AcosReg(..)
+            | SsaOp::AtanReg(..)
+            | SsaOp::LnReg(..)
+            | SsaOp::FloorReg(..)
+            | SsaOp::CeilReg(..)
+            | SsaOp::RoundReg(..)
+            | SsaOp::NotReg(..) => self.op_reg(op),
 
             SsaOp::AddRegImm(..)
             | SsaOp::SubRegImm(..)
@@ -301,14 +346,49 @@ impl RegisterAllocator {
             | SsaOp::DivRegImm(..)
             | SsaOp::DivImmReg(..)
             | SsaOp::MinRegImm(..)
-            | SsaOp::MaxRegImm(..) => self.op_reg_imm(op),
+            | SsaOp::MaxRegImm(..)
+            | SsaOp::Atan2RegImm(..)
+            | SsaOp::Atan2ImmReg(..)
+            | SsaOp::PowRegImm(..)
+            | SsaOp::PowImmReg(..)
+            | SsaOp::ModRegImm(..)
+            | SsaOp::ModImmReg(..)
+            | SsaOp::LtRegImm(..)
+            | SsaOp::LtImmReg(..)
+            | SsaOp::EqRegImm(..)
+            | SsaOp::AndRegImm(..)
+            | SsaOp::OrRegImm(..) => self.op_reg_imm(op),
+
+            SsaOp::PowiReg(out, arg, n) => {
+                self.op_reg_fn(out, arg, |out, arg| Op::PowiReg(out, arg, n))
+            }
 
             SsaOp::AddRegReg(..)
             | SsaOp::SubRegReg(..)
             | SsaOp::MulRegReg(..)
             | SsaOp::DivRegReg(..)
             | SsaOp::MinRegReg(..)
-            | SsaOp::MaxRegReg(..) => self.op_reg_reg(op),
+            | SsaOp::MaxRegReg(..)
+            | SsaOp::Atan2RegReg(..)
+            | SsaOp::PowRegReg(..)
+            | SsaOp::ModRegReg(..)
+            | SsaOp::LtRegReg(..)
+            | SsaOp::EqRegReg(..)
+            | SsaOp::AndRegReg(..)
+            | SsaOp::OrRegReg(..) => self.op_reg_reg(op),
+
+            SsaOp::Select(out, cond, lhs, rhs) => {
+                self.op_select(out, cond, lhs, rhs)
+            }
+        }
+    }
+
+    /// Pushes a lowered operation, attaching the exact value of its immediate
+    #[inline(always)]
+    fn push_op(&mut self, op: Op) {
+        match self.imm64.take() {
+            Some(imm) => self.out.push_imm64(op, imm),
+            None => self.out.push(op),
         }
     }
 
@@ -386,17 +466,17 @@ impl RegisterAllocator {
         match self.get_allocation(arg) {
             Allocation::Register(r_y) => {
                 assert!(r_x != r_y);
-                self.out.push(op(r_x, r_y));
+                self.push_op(op(r_x, r_y));
                 self.release_reg(r_x);
             }
             Allocation::Memory(m_y) => {
-                self.out.push(op(r_x, r_x));
+                self.push_op(op(r_x, r_x));
                 self.rebind_register(arg, r_x);
 
                 self.push_store(r_x, m_y);
             }
             Allocation::Unassigned => {
-                self.out.push(op(r_x, r_x));
+                self.push_op(op(r_x, r_x));
                 self.rebind_register(arg, r_x);
             }
         }
@@ -528,6 +608,15 @@ impl RegisterAllocator {
             SsaOp::DivRegReg(out, lhs, rhs) => (out, lhs, rhs, Op::DivRegReg),
             SsaOp::MinRegReg(out, lhs, rhs) => (out, lhs, rhs, Op::MinRegReg),
             SsaOp::MaxRegReg(out, lhs, rhs) => (out, lhs, rhs, Op::MaxRegReg),
+            SsaOp::Atan2RegReg(out, lhs, rhs) => {
+                (out, lhs, rhs, Op::Atan2RegReg)
+            }
+            SsaOp::PowRegReg(out, lhs, rhs) => (out, lhs, rhs, Op::PowRegReg),
+            SsaOp::ModRegReg(out, lhs, rhs) => (out, lhs, rhs, Op::ModRegReg),
+            SsaOp::LtRegReg(out, lhs, rhs) => (out, lhs, rhs, Op::LtRegReg),
+            SsaOp::EqRegReg(out, lhs, rhs) => (out, lhs, rhs, Op::EqRegReg),
+            SsaOp::AndRegReg(out, lhs, rhs) => (out, lhs, rhs, Op::AndRegReg),
+            SsaOp::OrRegReg(out, lhs, rhs) => (out, lhs, rhs, Op::OrRegReg),
             _ => panic!("Bad opcode: {op:?}"),
         };
         let r_x = self.get_out_reg(out);
@@ -607,6 +696,70 @@ impl RegisterAllocator {
         }
     }
 
+    /// Lowers a [`Select`](crate::vm::Op::Select) operation, pushing it to
+    /// the internal tape.
+    ///
+    /// Rather than enumerating every configuration (as in `op_reg_reg`), this
+    /// handles each argument independently.
+    #[inline(always)]
+    fn op_select(&mut self, out: u32, cond: u32, lhs: u32, rhs: u32) {
+        // Each distinct argument is handled as follows:
+        //
+        //   arg | what do?
+        //  ================================================================
+        //   r_y | Use r_y directly
+        //  -----|----------------------------------------------------------
+        //    U  | Bind the argument to r_x (if it's not already taken by an
+        //       | earlier argument) or to a fresh register r_a
+        //  -----|----------------------------------------------------------
+        //   m_y | Same as U, followed by store r_x / r_a -> m_y
+        //
+        // If r_x was not reused by any argument, it's free afterwards.
+        //
+        // All of the arguments are looked up before any registers are
+        // allocated, so that they're marked as recently used and won't be
+        // evicted by `get_register`.
+        let r_x = self.get_out_reg(out);
+        let args = [cond, lhs, rhs];
+        let allocs = args.map(|a| self.get_allocation(a));
+
+        let mut regs = [r_x; 3];
+        let mut stores: ArrayVec<(u8, u32), 3> = ArrayVec::new();
+        let mut out_reused = false;
+        for i in 0..args.len() {
+            if let Some(j) = args[..i].iter().position(|a| *a == args[i]) {
+                regs[i] = regs[j];
+                continue;
+            }
+            regs[i] = match allocs[i] {
+                Allocation::Register(r_y) => r_y,
+                Allocation::Memory(..) | Allocation::Unassigned => {
+                    let r_a = if out_reused {
+                        let r_a = self.get_register();
+                        self.bind_register(args[i], r_a);
+                        r_a
+                    } else {
+                        out_reused = true;
+                        self.rebind_register(args[i], r_x);
+                        r_x
+                    };
+                    if let Allocation::Memory(m_y) = allocs[i] {
+                        stores.push((r_a, m_y));
+                    }
+                    r_a
+                }
+            };
+        }
+
+        self.out.push(Op::Select(r_x, regs[0], regs[1], regs[2]));
+        for (r_a, m_y) in stores {
+            self.push_store(r_a, m_y);
+        }
+        if !out_reused {
+            self.release_reg(r_x);
+        }
+    }
+
     /// Lowers a function taking one register and one immediate into an
     /// [`Op`](crate::vm::Op), pushing it to the internal tape.
     #[inline(always)]
@@ -620,6 +773,21 @@ impl RegisterAllocator {
             SsaOp::DivImmReg(out, arg, imm) => (out, arg, imm, Op::DivImmReg),
             SsaOp::MinRegImm(out, arg, imm) => (out, arg, imm, Op::MinRegImm),
             SsaOp::MaxRegImm(out, arg, imm) => (out, arg, imm, Op::MaxRegImm),
+            SsaOp::Atan2RegImm(out, arg, imm) => {
+                (out, arg, imm, Op::Atan2RegImm)
+            }
+            SsaOp::Atan2ImmReg(out, arg, imm) => {
+                (out, arg, imm, Op::Atan2ImmReg)
+            }
+            SsaOp::PowRegImm(out, arg, imm) => (out, arg, imm, Op::PowRegImm),
+            SsaOp::PowImmReg(out, arg, imm) => (out, arg, imm, Op::PowImmReg),
+            SsaOp::ModRegImm(out, arg, imm) => (out, arg, imm, Op::ModRegImm),
+            SsaOp::ModImmReg(out, arg, imm) => (out, arg, imm, Op::ModImmReg),
+            SsaOp::LtRegImm(out, arg, imm) => (out, arg, imm, Op::LtRegImm),
+            SsaOp::LtImmReg(out, arg, imm) => (out, arg, imm, Op::LtImmReg),
+            SsaOp::EqRegImm(out, arg, imm) => (out, arg, imm, Op::EqRegImm),
+            SsaOp::AndRegImm(out, arg, imm) => (out, arg, imm, Op::AndRegImm),
+            SsaOp::OrRegImm(out, arg, imm) => (out, arg, imm, Op::OrRegImm),
             _ => panic!("Bad opcode: {op:?}"),
         };
         self.op_reg_fn(out, arg, |out, arg| op(out, arg, imm));
@@ -628,7 +796,7 @@ impl RegisterAllocator {
     #[inline(always)]
     fn op_out_only(&mut self, out: u32, op: impl Fn(u8) -> Op) {
         let r_x = self.get_out_reg(out);
-        self.out.push(op(r_x));
+        self.push_op(op(r_x));
         self.release_reg(r_x);
     }
 
@@ -1,8 +1,10 @@
 use crate::{
     eval::{
+        batch::{BatchEvaluator, BatchOutput},
         bulk::{BulkEvaluator, BulkEvaluatorData},
+        tape::Data,
         tracing::{TracingEvaluator, TracingEvaluatorData},
-        types::{Grad, Interval},
+        types::{Affine, Grad, Hessian, Interval},
         Choice, EvaluatorStorage, Family, Tape,
     },
     vm::Op,
@@ -22,6 +24,7 @@ impl Family for Eval {
     type PointEval = AsmEval;
     type FloatSliceEval = AsmEval;
     type GradSliceEval = AsmEval;
+    type IntervalSliceEval = AsmEval;
 
     fn tile_sizes_3d() -> &'static [usize] {
         &[256, 128, 64, 32, 16, 8]
@@ -35,7 +38,7 @@ impl Family for Eval {
 ////////////////////////////////////////////////////////////////////////////////
 
 /// Helper struct to reduce boilerplate conversions
-struct SlotArray<'a, T>(&'a mut [T]);
+pub(super) struct SlotArray<'a, T>(pub(super) &'a mut [T]);
 impl<T> std::ops::Index<u8> for SlotArray<'_, T> {
     type Output = T;
     fn index(&self, i: u8) -> &Self::Output {
@@ -70,7 +73,7 @@ pub struct AsmEval {
 
 /// Generic scratch data a tracing evaluator
 pub struct AsmTracingEvalData<T> {
-    slots: Vec<T>,
+    pub(super) slots: Vec<T>,
 }
 
 impl<T> Default for AsmTracingEvalData<T> {
@@ -79,11 +82,11 @@ impl<T> Default for AsmTracingEvalData<T> {
     }
 }
 
-impl<T> TracingEvaluatorData<Eval> for AsmTracingEvalData<T>
+impl<T, F> TracingEvaluatorData<F> for AsmTracingEvalData<T>
 where
     T: From<f32> + Clone,
 {
-    fn prepare(&mut self, tape: &Tape<Eval>) {
+    fn prepare(&mut self, tape: &Tape<F>) {
         assert!(tape.reg_limit() == u8::MAX);
 
         let slot_count = tape.slot_count();
@@ -112,6 +115,7 @@ impl TracingEvaluator<Interval, Eval> for AsmEval {
         x: Interval,
         y: Interval,
         z: Interval,
+        w: Interval,
         vars: &[f32],
         choices: &mut [Choice],
         data: &mut Self::Data,
@@ -128,6 +132,7 @@ impl TracingEvaluator<Interval, Eval> for AsmEval {
                         0 => x,
                         1 => y,
                         2 => z,
+                        3 => w,
                         _ => panic!("Invalid input: {}", i),
                     }
                 }
@@ -178,6 +183,113 @@ impl TracingEvaluator<Interval, Eval> for AsmEval {
                 Op::CosineReg(out, arg) => {
                     v[out] = v[arg].cosine();
                 }
+                Op::TanReg(out, arg) => {
+                    v[out] = v[arg].tan();
+                }
+                Op::AsinReg(out, arg) => {
+                    v[out] = v[arg].asin();
+                }
+                Op::AcosReg(out, arg) => {
+                    v[out] = v[arg].acos();
+                }
+                Op::AtanReg(out, arg) => {
+                    v[out] = v[arg].atan();
+                }
+                Op::Atan2RegImm(out, arg, imm) => {
+                    v[out] = v[arg].atan2(imm.into());
+                }
+                Op::Atan2ImmReg(out, arg, imm) => {
+                    v[out] = Interval::from(imm).atan2(v[arg]);
+                }
+                Op::Atan2RegReg(out, lhs, rhs) => {
+                    v[out] = v[lhs].atan2(v[rhs]);
+                }
+                Op::LnReg(out, arg) => {
+                    v[out] = v[arg].ln();
+                }
+                Op::PowRegImm(out, arg, imm) => {
+                    v[out] = v[arg].pow(imm.into());
+                }
+                Op::PowImmReg(out, arg, imm) => {
+                    v[out] = Interval::from(imm).pow(v[arg]);
+                }
+                Op::PowRegReg(out, lhs, rhs) => {
+                    v[out] = v[lhs].pow(v[rhs]);
+                }
+                Op::FloorReg(out, arg) => {
+                    v[out] = v[arg].floor();
+                }
+                Op::CeilReg(out, arg) => {
+                    v[out] = v[arg].ceil();
+                }
+                Op::RoundReg(out, arg) => {
+                    v[out] = v[arg].round();
+                }
+                Op::ModRegImm(out, arg, imm) => {
+                    v[out] = v[arg].modulo(imm.into());
+                }
+                Op::ModImmReg(out, arg, imm) => {
+                    v[out] = Interval::from(imm).modulo(v[arg]);
+                }
+                Op::ModRegReg(out, lhs, rhs) => {
+                    v[out] = v[lhs].modulo(v[rhs]);
+                }
+                Op::NotReg(out, arg) => {
+                    v[out] = !v[arg];
+                }
+                Op::LtRegImm(out, arg, imm) => {
+                    v[out] = v[arg].lt(imm.into());
+                }
+                Op::LtImmReg(out, arg, imm) => {
+                    v[out] = Interval::from(imm).lt(v[arg]);
+                }
+                Op::LtRegReg(out, lhs, rhs) => {
+                    v[out] = v[lhs].lt(v[rhs]);
+                }
+                Op::EqRegImm(out, arg, imm) => {
+                    v[out] = v[arg].eq(imm.into());
+                }
+                Op::EqRegReg(out, lhs, rhs) => {
+                    v[out] = v[lhs].eq(v[rhs]);
+                }
+                Op::AndRegImm(out, arg, imm) => {
+                    let (value, choice) = v[arg].and_choice(imm.into());
+                    v[out] = value;
+                    choices[choice_index] |= choice;
+                    choice_index += 1;
+                    simplify |= choice != Choice::Both;
+                }
+                Op::AndRegReg(out, lhs, rhs) => {
+                    let (value, choice) = v[lhs].and_choice(v[rhs]);
+                    v[out] = value;
+                    choices[choice_index] |= choice;
+                    choice_index += 1;
+                    simplify |= choice != Choice::Both;
+                }
+                Op::OrRegImm(out, arg, imm) => {
+                    let (value, choice) = v[arg].or_choice(imm.into());
+                    v[out] = value;
+                    choices[choice_index] |= choice;
+                    choice_index += 1;
+                    simplify |= choice != Choice::Both;
+                }
+                Op::OrRegReg(out, lhs, rhs) => {
+                    let (value, choice) = v[lhs].or_choice(v[rhs]);
+                    v[out] = value;
+                    choices[choice_index] |= choice;
+                    choice_index += 1;
+                    simplify |= choice != Choice::Both;
+                }
+                Op::Select(out, cond, lhs, rhs) => {
+                    let (value, choice) = v[cond].select_choice(v[lhs], v[rhs]);
+                    v[out] = value;
+                    choices[choice_index] |= choice;
+                    choice_index += 1;
+                    simplify |= choice != Choice::Both;
+                }
+                Op::PowiReg(out, arg, n) => {
+                    v[out] = v[arg].powi(n);
+                }
                 Op::MinRegImm(out, arg, imm) => {
                     let (value, choice) = v[arg].min_choice(imm.into());
                     v[out] = value;
@@ -233,6 +345,7 @@ impl TracingEvaluator<f32, Eval> for AsmEval {
         x: f32,
         y: f32,
         z: f32,
+        w: f32,
         vars: &[f32],
         choices: &mut [Choice],
         data: &mut Self::Data,
@@ -248,6 +361,7 @@ impl TracingEvaluator<f32, Eval> for AsmEval {
                         0 => x,
                         1 => y,
                         2 => z,
+                        3 => w,
                         _ => panic!("Invalid input: {}", i),
                     }
                 }
@@ -298,6 +412,137 @@ impl TracingEvaluator<f32, Eval> for AsmEval {
                 Op::CosineReg(out, arg) => {
                     v[out] = v[arg].cos();
                 }
+                Op::TanReg(out, arg) => {
+                    v[out] = v[arg].tan();
+                }
+                Op::AsinReg(out, arg) => {
+                    v[out] = v[arg].asin();
+                }
+                Op::AcosReg(out, arg) => {
+                    v[out] = v[arg].acos();
+                }
+                Op::AtanReg(out, arg) => {
+                    v[out] = v[arg].atan();
+                }
+                Op::Atan2RegImm(out, arg, imm) => {
+                    v[out] = v[arg].atan2(imm);
+                }
+                Op::Atan2ImmReg(out, arg, imm) => {
+                    v[out] = imm.atan2(v[arg]);
+                }
+                Op::Atan2RegReg(out, lhs, rhs) => {
+                    v[out] = v[lhs].atan2(v[rhs]);
+                }
+                Op::LnReg(out, arg) => {
+                    v[out] = v[arg].ln();
+                }
+                Op::PowRegImm(out, arg, imm) => {
+                    v[out] = v[arg].powf(imm);
+                }
+                Op::PowImmReg(out, arg, imm) => {
+                    v[out] = imm.powf(v[arg]);
+                }
+                Op::PowRegReg(out, lhs, rhs) => {
+                    v[out] = v[lhs].powf(v[rhs]);
+                }
+                Op::FloorReg(out, arg) => {
+                    v[out] = v[arg].floor();
+                }
+                Op::CeilReg(out, arg) => {
+                    v[out] = v[arg].ceil();
+                }
+                Op::RoundReg(out, arg) => {
+                    v[out] = v[arg].round();
+                }
+                Op::ModRegImm(out, arg, imm) => {
+                    v[out] = v[arg].rem_euclid(imm);
+                }
+                Op::ModImmReg(out, arg, imm) => {
+                    v[out] = imm.rem_euclid(v[arg]);
+                }
+                Op::ModRegReg(out, lhs, rhs) => {
+                    v[out] = v[lhs].rem_euclid(v[rhs]);
+                }
+                Op::NotReg(out, arg) => {
+                    v[out] = (v[arg] == 0.0) as u8 as f32;
+                }
+                Op::LtRegImm(out, arg, imm) => {
+                    v[out] = (v[arg] < imm) as u8 as f32;
+                }
+                Op::LtImmReg(out, arg, imm) => {
+                    v[out] = (imm < v[arg]) as u8 as f32;
+                }
+                Op::LtRegReg(out, lhs, rhs) => {
+                    v[out] = (v[lhs] < v[rhs]) as u8 as f32;
+                }
+                Op::EqRegImm(out, arg, imm) => {
+                    v[out] = (v[arg] == imm) as u8 as f32;
+                }
+                Op::EqRegReg(out, lhs, rhs) => {
+                    v[out] = (v[lhs] == v[rhs]) as u8 as f32;
+                }
+                Op::AndRegImm(out, arg, imm) => {
+                    let a = v[arg];
+                    v[out] = if a == 0.0 {
+                        choices[choice_index] |= Choice::Left;
+                        a
+                    } else {
+                        choices[choice_index] |= Choice::Right;
+                        imm
+                    };
+                    simplify |= choices[choice_index] != Choice::Both;
+                    choice_index += 1;
+                }
+                Op::AndRegReg(out, lhs, rhs) => {
+                    let a = v[lhs];
+                    v[out] = if a == 0.0 {
+                        choices[choice_index] |= Choice::Left;
+                        a
+                    } else {
+                        choices[choice_index] |= Choice::Right;
+                        v[rhs]
+                    };
+                    simplify |= choices[choice_index] != Choice::Both;
+                    choice_index += 1;
+                }
+                Op::OrRegImm(out, arg, imm) => {
+                    let a = v[arg];
+                    v[out] = if a != 0.0 {
+                        choices[choice_index] |= Choice::Left;
+                        a
+                    } else {
+                        choices[choice_index] |= Choice::Right;
+                        imm
+                    };
+                    simplify |= choices[choice_index] != Choice::Both;
+                    choice_index += 1;
+                }
+                Op::OrRegReg(out, lhs, rhs) => {
+                    let a = v[lhs];
+                    v[out] = if a != 0.0 {
+                        choices[choice_index] |= Choice::Left;
+                        a
+                    } else {
+                        choices[choice_index] |= Choice::Right;
+                        v[rhs]
+                    };
+                    simplify |= choices[choice_index] != Choice::Both;
+                    choice_index += 1;
+                }
+                Op::Select(out, cond, lhs, rhs) => {
+                    v[out] = if v[cond] < 0.0 {
+                        choices[choice_index] |= Choice::Left;
+                        v[lhs]
+                    } else {
+                        choices[choice_index] |= Choice::Right;
+                        v[rhs]
+                    };
+                    simplify |= choices[choice_index] != Choice::Both;
+                    choice_index += 1;
+                }
+                Op::PowiReg(out, arg, n) => {
+                    v[out] = v[arg].powi(n);
+                }
                 Op::MinRegImm(out, arg, imm) => {
                     let a = v[arg];
                     v[out] = if a < imm {
@@ -408,9 +653,9 @@ impl TracingEvaluator<f32, Eval> for AsmEval {
 /// Float-point interpreter-style evaluator for a tape of [`Op`]
 pub struct AsmBulkEvalData<T> {
     /// Workspace for data
-    slots: Vec<Vec<T>>,
+    pub(super) slots: Vec<Vec<T>>,
     /// Current slice size in `self.slots`
-    slice_size: usize,
+    pub(super) slice_size: usize,
 }
 
 impl<T> Default for AsmBulkEvalData<T> {
@@ -422,11 +667,11 @@ impl<T> Default for AsmBulkEvalData<T> {
     }
 }
 
-impl<T> BulkEvaluatorData<Eval> for AsmBulkEvalData<T>
+impl<T, F> BulkEvaluatorData<F> for AsmBulkEvalData<T>
 where
     T: From<f32> + Clone,
 {
-    fn prepare(&mut self, tape: &Tape<Eval>, size: usize) {
+    fn prepare(&mut self, tape: &Tape<F>, size: usize) {
         assert!(tape.reg_limit() == u8::MAX);
         self.slots.resize_with(tape.slot_count(), || {
             vec![std::f32::NAN.into(); size.max(self.slice_size)]
@@ -448,13 +693,15 @@ impl BulkEvaluator<f32, Eval> for AsmEval {
         xs: &[f32],
         ys: &[f32],
         zs: &[f32],
+        ws: &[f32],
         vars: &[f32],
         out: &mut [f32],
         data: &mut Self::Data,
     ) {
         assert_eq!(xs.len(), ys.len());
         assert_eq!(ys.len(), zs.len());
-        assert_eq!(zs.len(), out.len());
+        assert_eq!(zs.len(), ws.len());
+        assert_eq!(ws.len() * self.tape.output_count(), out.len());
         assert_eq!(vars.len(), self.tape.var_count());
         assert_eq!(data.slots.len(), self.tape.slot_count());
 
@@ -468,6 +715,7 @@ impl BulkEvaluator<f32, Eval> for AsmEval {
                     0 => xs,
                     1 => ys,
                     2 => zs,
+                    3 => ws,
                     _ => panic!("Invalid input: {}", i),
                 }),
                 Op::Var(out, i) => v[out][0..size].fill(vars[i as usize]),
@@ -547,200 +795,165 @@ impl BulkEvaluator<f32, Eval> for AsmEval {
                         v[out][i] = v[arg][i].cos();
                     }
                 }
-                Op::MinRegImm(out, arg, imm) => {
+                Op::TanReg(out, arg) => {
                     for i in 0..size {
-                        v[out][i] = v[arg][i].min(imm);
+                        v[out][i] = v[arg][i].tan();
                     }
                 }
-                Op::MaxRegImm(out, arg, imm) => {
+                Op::AsinReg(out, arg) => {
                     for i in 0..size {
-                        v[out][i] = v[arg][i].max(imm);
+                        v[out][i] = v[arg][i].asin();
                     }
                 }
-                Op::AddRegReg(out, lhs, rhs) => {
+                Op::AcosReg(out, arg) => {
                     for i in 0..size {
-                        v[out][i] = v[lhs][i] + v[rhs][i];
+                        v[out][i] = v[arg][i].acos();
                     }
                 }
-                Op::MulRegReg(out, lhs, rhs) => {
+                Op::AtanReg(out, arg) => {
                     for i in 0..size {
-                        v[out][i] = v[lhs][i] * v[rhs][i];
+                        v[out][i] = v[arg][i].atan();
                     }
                 }
-                Op::DivRegReg(out, lhs, rhs) => {
+                Op::Atan2RegImm(out, arg, imm) => {
                     for i in 0..size {
-                        v[out][i] = v[lhs][i] / v[rhs][i];
+                        v[out][i] = v[arg][i].atan2(imm);
                     }
                 }
-                Op::SubRegReg(out, lhs, rhs) => {
+                Op::Atan2ImmReg(out, arg, imm) => {
                     for i in 0..size {
-                        v[out][i] = v[lhs][i] - v[rhs][i];
+                        v[out][i] = imm.atan2(v[arg][i]);
                     }
                 }
-                Op::MinRegReg(out, lhs, rhs) => {
+                Op::Atan2RegReg(out, lhs, rhs) => {
                     for i in 0..size {
-                        v[out][i] = v[lhs][i].min(v[rhs][i]);
+                        v[out][i] = v[lhs][i].atan2(v[rhs][i]);
                     }
                 }
-                Op::MaxRegReg(out, lhs, rhs) => {
+                Op::LnReg(out, arg) => {
                     for i in 0..size {
-                        v[out][i] = v[lhs][i].max(v[rhs][i]);
+                        v[out][i] = v[arg][i].ln();
                     }
                 }
-                Op::CopyImm(out, imm) => {
+                Op::PowRegImm(out, arg, imm) => {
                     for i in 0..size {
-                        v[out][i] = imm;
+                        v[out][i] = v[arg][i].powf(imm);
                     }
                 }
-                Op::Load(out, mem) => {
+                Op::PowImmReg(out, arg, imm) => {
                     for i in 0..size {
-                        v[out][i] = v[mem][i];
+                        v[out][i] = imm.powf(v[arg][i]);
                     }
                 }
-                Op::Store(out, mem) => {
+                Op::PowRegReg(out, lhs, rhs) => {
                     for i in 0..size {
-                        v[mem][i] = v[out][i];
+                        v[out][i] = v[lhs][i].powf(v[rhs][i]);
                     }
                 }
-            }
-        }
-        out[0..size].copy_from_slice(&data.slots[0][0..size])
-    }
-}
-
-////////////////////////////////////////////////////////////////////////////////
-
-impl BulkEvaluator<Grad, Eval> for AsmEval {
-    type Data = AsmBulkEvalData<Grad>;
-
-    fn eval_with(
-        &self,
-        xs: &[f32],
-        ys: &[f32],
-        zs: &[f32],
-        vars: &[f32],
-        out: &mut [Grad],
-        data: &mut Self::Data,
-    ) {
-        assert_eq!(xs.len(), ys.len());
-        assert_eq!(ys.len(), zs.len());
-        assert_eq!(zs.len(), out.len());
-        assert_eq!(vars.len(), self.tape.var_count());
-        assert_eq!(data.slots.len(), self.tape.slot_count());
-
-        let size = xs.len();
-        assert!(data.slice_size >= size);
-
-        let mut v = SlotArray(&mut data.slots);
-        for op in self.tape.iter_asm() {
-            match op {
-                Op::Input(out, j) => {
+                Op::FloorReg(out, arg) => {
                     for i in 0..size {
-                        v[out][i] = match j {
-                            0 => Grad::new(xs[i], 1.0, 0.0, 0.0),
-                            1 => Grad::new(ys[i], 0.0, 1.0, 0.0),
-                            2 => Grad::new(zs[i], 0.0, 0.0, 1.0),
-                            _ => panic!("Invalid input: {}", i),
-                        }
+                        v[out][i] = v[arg][i].floor();
                     }
                 }
-                Op::Var(out, j) => {
-                    // TODO: error handling?
-                    v[out][0..size].fill(Grad::new(
-                        vars[j as usize],
-                        0.0,
-                        0.0,
-                        0.0,
-                    ));
+                Op::CeilReg(out, arg) => {
+                    for i in 0..size {
+                        v[out][i] = v[arg][i].ceil();
+                    }
                 }
-                Op::NegReg(out, arg) => {
+                Op::RoundReg(out, arg) => {
                     for i in 0..size {
-                        v[out][i] = -v[arg][i];
+                        v[out][i] = v[arg][i].round();
                     }
                 }
-                Op::AbsReg(out, arg) => {
+                Op::ModRegImm(out, arg, imm) => {
                     for i in 0..size {
-                        v[out][i] = v[arg][i].abs();
+                        v[out][i] = v[arg][i].rem_euclid(imm);
                     }
                 }
-                Op::RecipReg(out, arg) => {
-                    let one: Grad = 1.0.into();
+                Op::ModImmReg(out, arg, imm) => {
                     for i in 0..size {
-                        v[out][i] = one / v[arg][i];
+                        v[out][i] = imm.rem_euclid(v[arg][i]);
                     }
                 }
-                Op::SqrtReg(out, arg) => {
+                Op::ModRegReg(out, lhs, rhs) => {
                     for i in 0..size {
-                        v[out][i] = v[arg][i].sqrt();
+                        v[out][i] = v[lhs][i].rem_euclid(v[rhs][i]);
                     }
                 }
-                Op::SquareReg(out, arg) => {
+                Op::NotReg(out, arg) => {
                     for i in 0..size {
-                        let s = v[arg][i];
-                        v[out][i] = s * s;
+                        v[out][i] = (v[arg][i] == 0.0) as u8 as f32;
                     }
                 }
-                Op::CopyReg(out, arg) => {
+                Op::LtRegImm(out, arg, imm) => {
                     for i in 0..size {
-                        v[out][i] = v[arg][i];
+                        v[out][i] = (v[arg][i] < imm) as u8 as f32;
                     }
                 }
-                Op::ExpReg(out, arg) => {
+                Op::LtImmReg(out, arg, imm) => {
                     for i in 0..size {
-                        v[out][i] = v[arg][i].exp();
+                        v[out][i] = (imm < v[arg][i]) as u8 as f32;
                     }
                 }
-                Op::AddRegImm(out, arg, imm) => {
+                Op::LtRegReg(out, lhs, rhs) => {
                     for i in 0..size {
-                        v[out][i] = v[arg][i] + imm.into();
+                        v[out][i] = (v[lhs][i] < v[rhs][i]) as u8 as f32;
                     }
                 }
-                Op::MulRegImm(out, arg, imm) => {
+                Op::EqRegImm(out, arg, imm) => {
                     for i in 0..size {
-                        v[out][i] = v[arg][i] * imm.into();
+                        v[out][i] = (v[arg][i] == imm) as u8 as f32;
                     }
                 }
-                Op::DivRegImm(out, arg, imm) => {
+                Op::EqRegReg(out, lhs, rhs) => {
                     for i in 0..size {
-                        v[out][i] = v[arg][i] / imm.into();
+                        v[out][i] = (v[lhs][i] == v[rhs][i]) as u8 as f32;
                     }
                 }
-                Op::DivImmReg(out, arg, imm) => {
-                    let imm: Grad = imm.into();
+                Op::AndRegImm(out, arg, imm) => {
                     for i in 0..size {
-                        v[out][i] = imm / v[arg][i];
+                        let a = v[arg][i];
+                        v[out][i] = if a == 0.0 { a } else { imm };
                     }
                 }
-                Op::SubImmReg(out, arg, imm) => {
-                    let imm: Grad = imm.into();
+                Op::AndRegReg(out, lhs, rhs) => {
                     for i in 0..size {
-                        v[out][i] = imm - v[arg][i];
+                        let a = v[lhs][i];
+                        v[out][i] = if a == 0.0 { a } else { v[rhs][i] };
                     }
                 }
-                Op::SubRegImm(out, arg, imm) => {
-                    let imm: Grad = imm.into();
+                Op::OrRegImm(out, arg, imm) => {
                     for i in 0..size {
-                        v[out][i] = v[arg][i] - imm;
+                        let a = v[arg][i];
+                        v[out][i] = if a != 0.0 { a } else { imm };
                     }
                 }
-                Op::SineReg(out, arg) => {
+                Op::OrRegReg(out, lhs, rhs) => {
                     for i in 0..size {
-                        v[out][i] = v[arg][i].sin();
+                        let a = v[lhs][i];
+                        v[out][i] = if a != 0.0 { a } else { v[rhs][i] };
                     }
                 }
-                Op::CosineReg(out, arg) => {
+                Op::Select(out, cond, lhs, rhs) => {
                     for i in 0..size {
-                        v[out][i] = v[arg][i].cos();
+                        v[out][i] = if v[cond][i] < 0.0 {
+                            v[lhs][i]
+                        } else {
+                            v[rhs][i]
+                        };
+                    }
+                }
+                Op::PowiReg(out, arg, n) => {
+                    for i in 0..size {
+                        v[out][i] = v[arg][i].powi(n);
                     }
                 }
                 Op::MinRegImm(out, arg, imm) => {
-                    let imm: Grad = imm.into();
                     for i in 0..size {
                         v[out][i] = v[arg][i].min(imm);
                     }
                 }
                 Op::MaxRegImm(out, arg, imm) => {
-                    let imm: Grad = imm.into();
                     for i in 0..size {
                         v[out][i] = v[arg][i].max(imm);
                     }
@@ -776,7 +989,6 @@ impl BulkEvaluator<Grad, Eval> for AsmEval {
                     }
                 }
                 Op::CopyImm(out, imm) => {
-                    let imm: Grad = imm.into();
                     for i in 0..size {
                         v[out][i] = imm;
                     }
@@ -793,15 +1005,1249 @@ impl BulkEvaluator<Grad, Eval> for AsmEval {
                 }
             }
         }
-        out.copy_from_slice(&data.slots[0][0..size])
+        for k in 0..self.tape.output_count() {
+            out[k * size..][..size].copy_from_slice(&data.slots[k][0..size])
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+impl BulkEvaluator<Grad, Eval> for AsmEval {
+    type Data = AsmBulkEvalData<Grad>;
+
+    fn eval_with(
+        &self,
+        xs: &[f32],
+        ys: &[f32],
+        zs: &[f32],
+        ws: &[f32],
+        vars: &[f32],
+        out: &mut [Grad],
+        data: &mut Self::Data,
+    ) {
+        assert_eq!(xs.len(), ys.len());
+        assert_eq!(ys.len(), zs.len());
+        assert_eq!(zs.len(), ws.len());
+        assert_eq!(ws.len() * self.tape.output_count(), out.len());
+        assert_eq!(vars.len(), self.tape.var_count());
+        assert_eq!(data.slots.len(), self.tape.slot_count());
+
+        let size = xs.len();
+        assert!(data.slice_size >= size);
+
+        eval_grad_slice(
+            &self.tape,
+            size,
+            |j, i| match j {
+                0 => Grad::new(xs[i], 1.0, 0.0, 0.0),
+                1 => Grad::new(ys[i], 0.0, 1.0, 0.0),
+                2 => Grad::new(zs[i], 0.0, 0.0, 1.0),
+                3 => Grad::new(ws[i], 0.0, 0.0, 0.0),
+                _ => panic!("Invalid input: {}", j),
+            },
+            // TODO: error handling?
+            |j| Grad::new(vars[j as usize], 0.0, 0.0, 0.0),
+            &mut data.slots,
+        );
+        for k in 0..self.tape.output_count() {
+            out[k * size..][..size].copy_from_slice(&data.slots[k][0..size])
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+impl BatchEvaluator<Interval, Eval> for AsmEval {
+    type Data = AsmBulkEvalData<Interval>;
+
+    fn eval_with(
+        &self,
+        xs: &[Interval],
+        ys: &[Interval],
+        zs: &[Interval],
+        ws: &[Interval],
+        vars: &[f32],
+        trace: &mut BatchOutput<Interval>,
+        data: &mut Self::Data,
+    ) {
+        assert_eq!(xs.len(), ys.len());
+        assert_eq!(ys.len(), zs.len());
+        assert_eq!(zs.len(), ws.len());
+        assert_eq!(ws.len(), trace.len());
+        assert_eq!(vars.len(), self.tape.var_count());
+        assert_eq!(data.slots.len(), self.tape.slot_count());
+
+        let size = xs.len();
+        assert!(data.slice_size >= size);
+
+        let mut choice_index = 0;
+        let mut v = SlotArray(&mut data.slots);
+        for op in self.tape.iter_asm() {
+            match op {
+                Op::Input(out, i) => v[out][0..size].copy_from_slice(match i {
+                    0 => xs,
+                    1 => ys,
+                    2 => zs,
+                    3 => ws,
+                    _ => panic!("Invalid input: {}", i),
+                }),
+                Op::Var(out, i) => {
+                    v[out][0..size].fill(vars[i as usize].into())
+                }
+                Op::NegReg(out, arg) => unary(&mut v, out, arg, size, |a| -a),
+                Op::AbsReg(out, arg) => {
+                    unary(&mut v, out, arg, size, Interval::abs);
+                }
+                Op::RecipReg(out, arg) => {
+                    unary(&mut v, out, arg, size, Interval::recip);
+                }
+                Op::SqrtReg(out, arg) => {
+                    unary(&mut v, out, arg, size, Interval::sqrt);
+                }
+                Op::SquareReg(out, arg) => {
+                    unary(&mut v, out, arg, size, Interval::square);
+                }
+                Op::ExpReg(out, arg) => {
+                    unary(&mut v, out, arg, size, Interval::exp);
+                }
+                Op::CopyReg(out, arg) => unary(&mut v, out, arg, size, |a| a),
+                Op::AddRegImm(out, arg, imm) => {
+                    let imm = Interval::from(imm);
+                    unary(&mut v, out, arg, size, |a| a + imm);
+                }
+                Op::MulRegImm(out, arg, imm) => {
+                    let imm = Interval::from(imm);
+                    unary(&mut v, out, arg, size, |a| a * imm);
+                }
+                Op::DivRegImm(out, arg, imm) => {
+                    let imm = Interval::from(imm);
+                    unary(&mut v, out, arg, size, |a| a / imm);
+                }
+                Op::DivImmReg(out, arg, imm) => {
+                    let imm = Interval::from(imm);
+                    unary(&mut v, out, arg, size, |a| imm / a);
+                }
+                Op::SubImmReg(out, arg, imm) => {
+                    let imm = Interval::from(imm);
+                    unary(&mut v, out, arg, size, |a| imm - a);
+                }
+                Op::SubRegImm(out, arg, imm) => {
+                    let imm = Interval::from(imm);
+                    unary(&mut v, out, arg, size, |a| a - imm);
+                }
+                Op::SineReg(out, arg) => {
+                    unary(&mut v, out, arg, size, Interval::sine);
+                }
+                Op::CosineReg(out, arg) => {
+                    unary(&mut v, out, arg, size, Interval::cosine);
+                }
+                Op::TanReg(out, arg) => {
+                    unary(&mut v, out, arg, size, Interval::tan);
+                }
+                Op::AsinReg(out, arg) => {
+                    unary(&mut v, out, arg, size, Interval::asin);
+                }
+                Op::AcosReg(out, arg) => {
+                    unary(&mut v, out, arg, size, Interval::acos);
+                }
+                Op::AtanReg(out, arg) => {
+                    unary(&mut v, out, arg, size, Interval::atan);
+                }
+                Op::Atan2RegImm(out, arg, imm) => {
+                    let imm = Interval::from(imm);
+                    unary(&mut v, out, arg, size, |a| a.atan2(imm));
+                }
+                Op::Atan2ImmReg(out, arg, imm) => {
+                    let imm = Interval::from(imm);
+                    unary(&mut v, out, arg, size, |a| imm.atan2(a));
+                }
+                Op::Atan2RegReg(out, lhs, rhs) => {
+                    binary(&mut v, out, lhs, rhs, size, Interval::atan2);
+                }
+                Op::LnReg(out, arg) => {
+                    unary(&mut v, out, arg, size, Interval::ln);
+                }
+                Op::PowRegImm(out, arg, imm) => {
+                    let imm = Interval::from(imm);
+                    unary(&mut v, out, arg, size, |a| a.pow(imm));
+                }
+                Op::PowImmReg(out, arg, imm) => {
+                    let imm = Interval::from(imm);
+                    unary(&mut v, out, arg, size, |a| imm.pow(a));
+                }
+                Op::PowRegReg(out, lhs, rhs) => {
+                    binary(&mut v, out, lhs, rhs, size, Interval::pow);
+                }
+                Op::PowiReg(out, arg, n) => {
+                    unary(&mut v, out, arg, size, |a| a.powi(n));
+                }
+                Op::FloorReg(out, arg) => {
+                    unary(&mut v, out, arg, size, Interval::floor);
+                }
+                Op::CeilReg(out, arg) => {
+                    unary(&mut v, out, arg, size, Interval::ceil);
+                }
+                Op::RoundReg(out, arg) => {
+                    unary(&mut v, out, arg, size, Interval::round);
+                }
+                Op::ModRegImm(out, arg, imm) => {
+                    let imm = Interval::from(imm);
+                    unary(&mut v, out, arg, size, |a| a.modulo(imm));
+                }
+                Op::ModImmReg(out, arg, imm) => {
+                    let imm = Interval::from(imm);
+                    unary(&mut v, out, arg, size, |a| imm.modulo(a));
+                }
+                Op::ModRegReg(out, lhs, rhs) => {
+                    binary(&mut v, out, lhs, rhs, size, Interval::modulo);
+                }
+                Op::NotReg(out, arg) => unary(&mut v, out, arg, size, |a| !a),
+                Op::LtRegImm(out, arg, imm) => {
+                    let imm = Interval::from(imm);
+                    unary(&mut v, out, arg, size, |a| a.lt(imm));
+                }
+                Op::LtImmReg(out, arg, imm) => {
+                    let imm = Interval::from(imm);
+                    unary(&mut v, out, arg, size, |a| imm.lt(a));
+                }
+                Op::LtRegReg(out, lhs, rhs) => {
+                    binary(&mut v, out, lhs, rhs, size, Interval::lt);
+                }
+                Op::EqRegImm(out, arg, imm) => {
+                    let imm = Interval::from(imm);
+                    unary(&mut v, out, arg, size, |a| a.eq(imm));
+                }
+                Op::EqRegReg(out, lhs, rhs) => {
+                    binary(&mut v, out, lhs, rhs, size, Interval::eq);
+                }
+                Op::AndRegImm(out, arg, imm) => {
+                    let imm = Interval::from(imm);
+                    for i in 0..size {
+                        let (value, choice) = v[arg][i].and_choice(imm);
+                        v[out][i] = value;
+                        trace.record(i, choice_index, choice);
+                    }
+                    choice_index += 1;
+                }
+                Op::AndRegReg(out, lhs, rhs) => {
+                    for i in 0..size {
+                        let (value, choice) = v[lhs][i].and_choice(v[rhs][i]);
+                        v[out][i] = value;
+                        trace.record(i, choice_index, choice);
+                    }
+                    choice_index += 1;
+                }
+                Op::OrRegImm(out, arg, imm) => {
+                    let imm = Interval::from(imm);
+                    for i in 0..size {
+                        let (value, choice) = v[arg][i].or_choice(imm);
+                        v[out][i] = value;
+                        trace.record(i, choice_index, choice);
+                    }
+                    choice_index += 1;
+                }
+                Op::OrRegReg(out, lhs, rhs) => {
+                    for i in 0..size {
+                        let (value, choice) = v[lhs][i].or_choice(v[rhs][i]);
+                        v[out][i] = value;
+                        trace.record(i, choice_index, choice);
+                    }
+                    choice_index += 1;
+                }
+                Op::Select(out, cond, lhs, rhs) => {
+                    for i in 0..size {
+                        let (value, choice) =
+                            v[cond][i].select_choice(v[lhs][i], v[rhs][i]);
+                        v[out][i] = value;
+                        trace.record(i, choice_index, choice);
+                    }
+                    choice_index += 1;
+                }
+                Op::MinRegImm(out, arg, imm) => {
+                    let imm = Interval::from(imm);
+                    for i in 0..size {
+                        let (value, choice) = v[arg][i].min_choice(imm);
+                        v[out][i] = value;
+                        trace.record(i, choice_index, choice);
+                    }
+                    choice_index += 1;
+                }
+                Op::MaxRegImm(out, arg, imm) => {
+                    let imm = Interval::from(imm);
+                    for i in 0..size {
+                        let (value, choice) = v[arg][i].max_choice(imm);
+                        v[out][i] = value;
+                        trace.record(i, choice_index, choice);
+                    }
+                    choice_index += 1;
+                }
+                Op::MinRegReg(out, lhs, rhs) => {
+                    for i in 0..size {
+                        let (value, choice) = v[lhs][i].min_choice(v[rhs][i]);
+                        v[out][i] = value;
+                        trace.record(i, choice_index, choice);
+                    }
+                    choice_index += 1;
+                }
+                Op::MaxRegReg(out, lhs, rhs) => {
+                    for i in 0..size {
+                        let (value, choice) = v[lhs][i].max_choice(v[rhs][i]);
+                        v[out][i] = value;
+                        trace.record(i, choice_index, choice);
+                    }
+                    choice_index += 1;
+                }
+                Op::AddRegReg(out, lhs, rhs) => {
+                    binary(&mut v, out, lhs, rhs, size, |a, b| a + b);
+                }
+                Op::MulRegReg(out, lhs, rhs) => {
+                    binary(&mut v, out, lhs, rhs, size, |a, b| a * b);
+                }
+                Op::DivRegReg(out, lhs, rhs) => {
+                    binary(&mut v, out, lhs, rhs, size, |a, b| a / b);
+                }
+                Op::SubRegReg(out, lhs, rhs) => {
+                    binary(&mut v, out, lhs, rhs, size, |a, b| a - b);
+                }
+                Op::CopyImm(out, imm) => {
+                    v[out][0..size].fill(imm.into());
+                }
+                Op::Load(out, mem) => {
+                    for i in 0..size {
+                        v[out][i] = v[mem][i];
+                    }
+                }
+                Op::Store(out, mem) => {
+                    for i in 0..size {
+                        v[mem][i] = v[out][i];
+                    }
+                }
+            }
+        }
+        for i in 0..size {
+            trace.set(i, data.slots[0][i], false);
+        }
+    }
+}
+
+/// Applies `f` to the first `size` items in slot `arg`, writing to slot `out`
+pub(super) fn unary<T: Copy>(
+    v: &mut SlotArray<Vec<T>>,
+    out: u8,
+    arg: u8,
+    size: usize,
+    f: impl Fn(T) -> T,
+) {
+    for i in 0..size {
+        v[out][i] = f(v[arg][i]);
+    }
+}
+
+/// Applies `f` to the first `size` items in slots `lhs` and `rhs`, writing to
+/// slot `out`
+pub(super) fn binary<T: Copy>(
+    v: &mut SlotArray<Vec<T>>,
+    out: u8,
+    lhs: u8,
+    rhs: u8,
+    size: usize,
+    f: impl Fn(T, T) -> T,
+) {
+    for i in 0..size {
+        v[out][i] = f(v[lhs][i], v[rhs][i]);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Evaluates the given tape on `size` points, using [`Grad`] values
+///
+/// `input(j, i)` returns the value of input `j` at point `i`, and `var(j)`
+/// returns the value of variable `j`; by choosing which of these carry nonzero
+/// partial derivatives, the caller picks what the gradient is taken with
+/// respect to.  The result is written to the first `size` items of `slots[0]`.
+///
+/// This only uses the tape's instructions, so it may be used to interpret a
+/// tape from any evaluator family.
+pub(crate) fn eval_grad_slice<I, V>(
+    tape: &Data,
+    size: usize,
+    input: I,
+    var: V,
+    slots: &mut [Vec<Grad>],
+) where
+    I: Fn(u8, usize) -> Grad,
+    V: Fn(u32) -> Grad,
+{
+    let mut v = SlotArray(slots);
+    for op in tape.iter_asm() {
+        match op {
+            Op::Input(out, j) => {
+                for i in 0..size {
+                    v[out][i] = input(j, i);
+                }
+            }
+            Op::Var(out, j) => {
+                v[out][0..size].fill(var(j));
+            }
+            Op::NegReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = -v[arg][i];
+                }
+            }
+            Op::AbsReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].abs();
+                }
+            }
+            Op::RecipReg(out, arg) => {
+                let one: Grad = 1.0.into();
+                for i in 0..size {
+                    v[out][i] = one / v[arg][i];
+                }
+            }
+            Op::SqrtReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].sqrt();
+                }
+            }
+            Op::SquareReg(out, arg) => {
+                for i in 0..size {
+                    let s = v[arg][i];
+                    v[out][i] = s * s;
+                }
+            }
+            Op::CopyReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i];
+                }
+            }
+            Op::ExpReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].exp();
+                }
+            }
+            Op::AddRegImm(out, arg, imm) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i] + imm.into();
+                }
+            }
+            Op::MulRegImm(out, arg, imm) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i] * imm.into();
+                }
+            }
+            Op::DivRegImm(out, arg, imm) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i] / imm.into();
+                }
+            }
+            Op::DivImmReg(out, arg, imm) => {
+                let imm: Grad = imm.into();
+                for i in 0..size {
+                    v[out][i] = imm / v[arg][i];
+                }
+            }
+            Op::SubImmReg(out, arg, imm) => {
+                let imm: Grad = imm.into();
+                for i in 0..size {
+                    v[out][i] = imm - v[arg][i];
+                }
+            }
+            Op::SubRegImm(out, arg, imm) => {
+                let imm: Grad = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i] - imm;
+                }
+            }
+            Op::SineReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].sin();
+                }
+            }
+            Op::CosineReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].cos();
+                }
+            }
+            Op::TanReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].tan();
+                }
+            }
+            Op::AsinReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].asin();
+                }
+            }
+            Op::AcosReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].acos();
+                }
+            }
+            Op::AtanReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].atan();
+                }
+            }
+            Op::Atan2RegImm(out, arg, imm) => {
+                let imm: Grad = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i].atan2(imm);
+                }
+            }
+            Op::Atan2ImmReg(out, arg, imm) => {
+                let imm: Grad = imm.into();
+                for i in 0..size {
+                    v[out][i] = imm.atan2(v[arg][i]);
+                }
+            }
+            Op::Atan2RegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i].atan2(v[rhs][i]);
+                }
+            }
+            Op::LnReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].ln();
+                }
+            }
+            Op::PowRegImm(out, arg, imm) => {
+                let imm: Grad = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i].pow(imm);
+                }
+            }
+            Op::PowImmReg(out, arg, imm) => {
+                let imm: Grad = imm.into();
+                for i in 0..size {
+                    v[out][i] = imm.pow(v[arg][i]);
+                }
+            }
+            Op::PowRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i].pow(v[rhs][i]);
+                }
+            }
+            Op::FloorReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].floor();
+                }
+            }
+            Op::CeilReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].ceil();
+                }
+            }
+            Op::RoundReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].round();
+                }
+            }
+            Op::ModRegImm(out, arg, imm) => {
+                let imm: Grad = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i].modulo(imm);
+                }
+            }
+            Op::ModImmReg(out, arg, imm) => {
+                let imm: Grad = imm.into();
+                for i in 0..size {
+                    v[out][i] = imm.modulo(v[arg][i]);
+                }
+            }
+            Op::ModRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i].modulo(v[rhs][i]);
+                }
+            }
+            Op::NotReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = !v[arg][i];
+                }
+            }
+            Op::LtRegImm(out, arg, imm) => {
+                let imm: Grad = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i].lt(imm);
+                }
+            }
+            Op::LtImmReg(out, arg, imm) => {
+                let imm: Grad = imm.into();
+                for i in 0..size {
+                    v[out][i] = imm.lt(v[arg][i]);
+                }
+            }
+            Op::LtRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i].lt(v[rhs][i]);
+                }
+            }
+            Op::EqRegImm(out, arg, imm) => {
+                let imm: Grad = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i].eq(imm);
+                }
+            }
+            Op::EqRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i].eq(v[rhs][i]);
+                }
+            }
+            Op::AndRegImm(out, arg, imm) => {
+                let imm: Grad = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i].and(imm);
+                }
+            }
+            Op::AndRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i].and(v[rhs][i]);
+                }
+            }
+            Op::OrRegImm(out, arg, imm) => {
+                let imm: Grad = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i].or(imm);
+                }
+            }
+            Op::OrRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i].or(v[rhs][i]);
+                }
+            }
+            Op::Select(out, cond, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[cond][i].select(v[lhs][i], v[rhs][i]);
+                }
+            }
+            Op::PowiReg(out, arg, n) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].powi(n);
+                }
+            }
+            Op::MinRegImm(out, arg, imm) => {
+                let imm: Grad = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i].min(imm);
+                }
+            }
+            Op::MaxRegImm(out, arg, imm) => {
+                let imm: Grad = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i].max(imm);
+                }
+            }
+            Op::AddRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i] + v[rhs][i];
+                }
+            }
+            Op::MulRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i] * v[rhs][i];
+                }
+            }
+            Op::DivRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i] / v[rhs][i];
+                }
+            }
+            Op::SubRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i] - v[rhs][i];
+                }
+            }
+            Op::MinRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i].min(v[rhs][i]);
+                }
+            }
+            Op::MaxRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i].max(v[rhs][i]);
+                }
+            }
+            Op::CopyImm(out, imm) => {
+                let imm: Grad = imm.into();
+                for i in 0..size {
+                    v[out][i] = imm;
+                }
+            }
+            Op::Load(out, mem) => {
+                for i in 0..size {
+                    v[out][i] = v[mem][i];
+                }
+            }
+            Op::Store(out, mem) => {
+                for i in 0..size {
+                    v[mem][i] = v[out][i];
+                }
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Evaluates the given tape on `size` points, using [`Hessian`] values
+///
+/// This is equivalent to [`eval_grad_slice`], but tracks second derivatives.
+pub(crate) fn eval_hessian_slice<I, V>(
+    tape: &Data,
+    size: usize,
+    input: I,
+    var: V,
+    slots: &mut [Vec<Hessian>],
+) where
+    I: Fn(u8, usize) -> Hessian,
+    V: Fn(u32) -> Hessian,
+{
+    let mut v = SlotArray(slots);
+    for op in tape.iter_asm() {
+        match op {
+            Op::Input(out, j) => {
+                for i in 0..size {
+                    v[out][i] = input(j, i);
+                }
+            }
+            Op::Var(out, j) => {
+                v[out][0..size].fill(var(j));
+            }
+            Op::NegReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = -v[arg][i];
+                }
+            }
+            Op::AbsReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].abs();
+                }
+            }
+            Op::RecipReg(out, arg) => {
+                let one: Hessian = 1.0.into();
+                for i in 0..size {
+                    v[out][i] = one / v[arg][i];
+                }
+            }
+            Op::SqrtReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].sqrt();
+                }
+            }
+            Op::SquareReg(out, arg) => {
+                for i in 0..size {
+                    let s = v[arg][i];
+                    v[out][i] = s * s;
+                }
+            }
+            Op::CopyReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i];
+                }
+            }
+            Op::ExpReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].exp();
+                }
+            }
+            Op::AddRegImm(out, arg, imm) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i] + imm.into();
+                }
+            }
+            Op::MulRegImm(out, arg, imm) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i] * imm.into();
+                }
+            }
+            Op::DivRegImm(out, arg, imm) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i] / imm.into();
+                }
+            }
+            Op::DivImmReg(out, arg, imm) => {
+                let imm: Hessian = imm.into();
+                for i in 0..size {
+                    v[out][i] = imm / v[arg][i];
+                }
+            }
+            Op::SubImmReg(out, arg, imm) => {
+                let imm: Hessian = imm.into();
+                for i in 0..size {
+                    v[out][i] = imm - v[arg][i];
+                }
+            }
+            Op::SubRegImm(out, arg, imm) => {
+                let imm: Hessian = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i] - imm;
+                }
+            }
+            Op::SineReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].sin();
+                }
+            }
+            Op::CosineReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].cos();
+                }
+            }
+            Op::TanReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].tan();
+                }
+            }
+            Op::AsinReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].asin();
+                }
+            }
+            Op::AcosReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].acos();
+                }
+            }
+            Op::AtanReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].atan();
+                }
+            }
+            Op::Atan2RegImm(out, arg, imm) => {
+                let imm: Hessian = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i].atan2(imm);
+                }
+            }
+            Op::Atan2ImmReg(out, arg, imm) => {
+                let imm: Hessian = imm.into();
+                for i in 0..size {
+                    v[out][i] = imm.atan2(v[arg][i]);
+                }
+            }
+            Op::Atan2RegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i].atan2(v[rhs][i]);
+                }
+            }
+            Op::LnReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].ln();
+                }
+            }
+            Op::PowRegImm(out, arg, imm) => {
+                let imm: Hessian = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i].pow(imm);
+                }
+            }
+            Op::PowImmReg(out, arg, imm) => {
+                let imm: Hessian = imm.into();
+                for i in 0..size {
+                    v[out][i] = imm.pow(v[arg][i]);
+                }
+            }
+            Op::PowRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i].pow(v[rhs][i]);
+                }
+            }
+            Op::FloorReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].floor();
+                }
+            }
+            Op::CeilReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].ceil();
+                }
+            }
+            Op::RoundReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].round();
+                }
+            }
+            Op::ModRegImm(out, arg, imm) => {
+                let imm: Hessian = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i].modulo(imm);
+                }
+            }
+            Op::ModImmReg(out, arg, imm) => {
+                let imm: Hessian = imm.into();
+                for i in 0..size {
+                    v[out][i] = imm.modulo(v[arg][i]);
+                }
+            }
+            Op::ModRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i].modulo(v[rhs][i]);
+                }
+            }
+            Op::NotReg(out, arg) => {
+                for i in 0..size {
+                    v[out][i] = !v[arg][i];
+                }
+            }
+            Op::LtRegImm(out, arg, imm) => {
+                let imm: Hessian = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i].lt(imm);
+                }
+            }
+            Op::LtImmReg(out, arg, imm) => {
+                let imm: Hessian = imm.into();
+                for i in 0..size {
+                    v[out][i] = imm.lt(v[arg][i]);
+                }
+            }
+            Op::LtRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i].lt(v[rhs][i]);
+                }
+            }
+            Op::EqRegImm(out, arg, imm) => {
+                let imm: Hessian = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i].eq(imm);
+                }
+            }
+            Op::EqRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i].eq(v[rhs][i]);
+                }
+            }
+            Op::AndRegImm(out, arg, imm) => {
+                let imm: Hessian = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i].and(imm);
+                }
+            }
+            Op::AndRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i].and(v[rhs][i]);
+                }
+            }
+            Op::OrRegImm(out, arg, imm) => {
+                let imm: Hessian = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i].or(imm);
+                }
+            }
+            Op::OrRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i].or(v[rhs][i]);
+                }
+            }
+            Op::Select(out, cond, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[cond][i].select(v[lhs][i], v[rhs][i]);
+                }
+            }
+            Op::PowiReg(out, arg, n) => {
+                for i in 0..size {
+                    v[out][i] = v[arg][i].powi(n);
+                }
+            }
+            Op::MinRegImm(out, arg, imm) => {
+                let imm: Hessian = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i].min(imm);
+                }
+            }
+            Op::MaxRegImm(out, arg, imm) => {
+                let imm: Hessian = imm.into();
+                for i in 0..size {
+                    v[out][i] = v[arg][i].max(imm);
+                }
+            }
+            Op::AddRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i] + v[rhs][i];
+                }
+            }
+            Op::MulRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i] * v[rhs][i];
+                }
+            }
+            Op::DivRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i] / v[rhs][i];
+                }
+            }
+            Op::SubRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i] - v[rhs][i];
+                }
+            }
+            Op::MinRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i].min(v[rhs][i]);
+                }
+            }
+            Op::MaxRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[lhs][i].max(v[rhs][i]);
+                }
+            }
+            Op::CopyImm(out, imm) => {
+                let imm: Hessian = imm.into();
+                for i in 0..size {
+                    v[out][i] = imm;
+                }
+            }
+            Op::Load(out, mem) => {
+                for i in 0..size {
+                    v[out][i] = v[mem][i];
+                }
+            }
+            Op::Store(out, mem) => {
+                for i in 0..size {
+                    v[mem][i] = v[out][i];
+                }
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Evaluates the given tape using [`Affine`] arithmetic, recording choices
+///
+/// Like [`eval_grad_slice`], this only uses the tape's instructions, so it may
+/// be used to interpret a tape from any evaluator family.
+pub(crate) fn eval_affine(
+    tape: &Data,
+    inputs: [Affine; 4],
+    vars: &[f32],
+    choices: &mut [Choice],
+    slots: &mut [Affine],
+) -> (Affine, bool) {
+    let mut simplify = false;
+    assert_eq!(vars.len(), tape.var_count());
+
+    let mut choice_index = 0;
+    let mut v = SlotArray(slots);
+    for op in tape.iter_asm() {
+        match op {
+            Op::Input(out, i) => {
+                v[out] = match i {
+                    0..=3 => inputs[i as usize],
+                    _ => panic!("Invalid input: {}", i),
+                }
+            }
+            Op::Var(out, i) => {
+                v[out] = vars[i as usize].into();
+            }
+            Op::NegReg(out, arg) => {
+                v[out] = -v[arg];
+            }
+            Op::AbsReg(out, arg) => {
+                v[out] = v[arg].abs();
+            }
+            Op::RecipReg(out, arg) => {
+                v[out] = v[arg].recip();
+            }
+            Op::SqrtReg(out, arg) => {
+                v[out] = v[arg].sqrt();
+            }
+            Op::SquareReg(out, arg) => {
+                v[out] = v[arg].square();
+            }
+            Op::ExpReg(out, arg) => {
+                v[out] = v[arg].exp();
+            }
+            Op::CopyReg(out, arg) => v[out] = v[arg],
+            Op::AddRegImm(out, arg, imm) => {
+                v[out] = v[arg] + imm.into();
+            }
+            Op::MulRegImm(out, arg, imm) => {
+                v[out] = v[arg] * imm.into();
+            }
+            Op::DivRegImm(out, arg, imm) => {
+                v[out] = v[arg] / imm.into();
+            }
+            Op::DivImmReg(out, arg, imm) => {
+                let imm: Affine = imm.into();
+                v[out] = imm / v[arg];
+            }
+            Op::SubImmReg(out, arg, imm) => {
+                v[out] = Affine::from(imm) - v[arg];
+            }
+            Op::SubRegImm(out, arg, imm) => {
+                v[out] = v[arg] - imm.into();
+            }
+            Op::SineReg(out, arg) => {
+                v[out] = v[arg].sine();
+            }
+            Op::CosineReg(out, arg) => {
+                v[out] = v[arg].cosine();
+            }
+            Op::TanReg(out, arg) => {
+                v[out] = v[arg].tan();
+            }
+            Op::AsinReg(out, arg) => {
+                v[out] = v[arg].asin();
+            }
+            Op::AcosReg(out, arg) => {
+                v[out] = v[arg].acos();
+            }
+            Op::AtanReg(out, arg) => {
+                v[out] = v[arg].atan();
+            }
+            Op::Atan2RegImm(out, arg, imm) => {
+                v[out] = v[arg].atan2(imm.into());
+            }
+            Op::Atan2ImmReg(out, arg, imm) => {
+                v[out] = Affine::from(imm).atan2(v[arg]);
+            }
+            Op::Atan2RegReg(out, lhs, rhs) => {
+                v[out] = v[lhs].atan2(v[rhs]);
+            }
+            Op::LnReg(out, arg) => {
+                v[out] = v[arg].ln();
+            }
+            Op::PowRegImm(out, arg, imm) => {
+                v[out] = v[arg].pow(imm.into());
+            }
+            Op::PowImmReg(out, arg, imm) => {
+                v[out] = Affine::from(imm).pow(v[arg]);
+            }
+            Op::PowRegReg(out, lhs, rhs) => {
+                v[out] = v[lhs].pow(v[rhs]);
+            }
+            Op::FloorReg(out, arg) => {
+                v[out] = v[arg].floor();
+            }
+            Op::CeilReg(out, arg) => {
+                v[out] = v[arg].ceil();
+            }
+            Op::RoundReg(out, arg) => {
+                v[out] = v[arg].round();
+            }
+            Op::ModRegImm(out, arg, imm) => {
+                v[out] = v[arg].modulo(imm.into());
+            }
+            Op::ModImmReg(out, arg, imm) => {
+                v[out] = Affine::from(imm).modulo(v[arg]);
+            }
+            Op::ModRegReg(out, lhs, rhs) => {
+                v[out] = v[lhs].modulo(v[rhs]);
+            }
+            Op::NotReg(out, arg) => {
+                v[out] = !v[arg];
+            }
+            Op::LtRegImm(out, arg, imm) => {
+                v[out] = v[arg].lt(imm.into());
+            }
+            Op::LtImmReg(out, arg, imm) => {
+                v[out] = Affine::from(imm).lt(v[arg]);
+            }
+            Op::LtRegReg(out, lhs, rhs) => {
+                v[out] = v[lhs].lt(v[rhs]);
+            }
+            Op::EqRegImm(out, arg, imm) => {
+                v[out] = v[arg].eq(imm.into());
+            }
+            Op::EqRegReg(out, lhs, rhs) => {
+                v[out] = v[lhs].eq(v[rhs]);
+            }
+            Op::AndRegImm(out, arg, imm) => {
+                let (value, choice) = v[arg].and_choice(imm.into());
+                v[out] = value;
+                choices[choice_index] |= choice;
+                choice_index += 1;
+                simplify |= choice != Choice::Both;
+            }
+            Op::AndRegReg(out, lhs, rhs) => {
+                let (value, choice) = v[lhs].and_choice(v[rhs]);
+                v[out] = value;
+                choices[choice_index] |= choice;
+                choice_index += 1;
+                simplify |= choice != Choice::Both;
+            }
+            Op::OrRegImm(out, arg, imm) => {
+                let (value, choice) = v[arg].or_choice(imm.into());
+                v[out] = value;
+                choices[choice_index] |= choice;
+                choice_index += 1;
+                simplify |= choice != Choice::Both;
+            }
+            Op::OrRegReg(out, lhs, rhs) => {
+                let (value, choice) = v[lhs].or_choice(v[rhs]);
+                v[out] = value;
+                choices[choice_index] |= choice;
+                choice_index += 1;
+                simplify |= choice != Choice::Both;
+            }
+            Op::Select(out, cond, lhs, rhs) => {
+                let (value, choice) = v[cond].select_choice(v[lhs], v[rhs]);
+                v[out] = value;
+                choices[choice_index] |= choice;
+                choice_index += 1;
+                simplify |= choice != Choice::Both;
+            }
+            Op::PowiReg(out, arg, n) => {
+                v[out] = v[arg].powi(n);
+            }
+            Op::MinRegImm(out, arg, imm) => {
+                let (value, choice) = v[arg].min_choice(imm.into());
+                v[out] = value;
+                choices[choice_index] |= choice;
+                choice_index += 1;
+                simplify |= choice != Choice::Both;
+            }
+            Op::MaxRegImm(out, arg, imm) => {
+                let (value, choice) = v[arg].max_choice(imm.into());
+                v[out] = value;
+                choices[choice_index] |= choice;
+                choice_index += 1;
+                simplify |= choice != Choice::Both;
+            }
+            Op::AddRegReg(out, lhs, rhs) => v[out] = v[lhs] + v[rhs],
+            Op::MulRegReg(out, lhs, rhs) => v[out] = v[lhs] * v[rhs],
+            Op::DivRegReg(out, lhs, rhs) => v[out] = v[lhs] / v[rhs],
+            Op::SubRegReg(out, lhs, rhs) => v[out] = v[lhs] - v[rhs],
+            Op::MinRegReg(out, lhs, rhs) => {
+                let (value, choice) = v[lhs].min_choice(v[rhs]);
+                v[out] = value;
+                choices[choice_index] |= choice;
+                simplify |= choice != Choice::Both;
+                choice_index += 1;
+            }
+            Op::MaxRegReg(out, lhs, rhs) => {
+                let (value, choice) = v[lhs].max_choice(v[rhs]);
+                v[out] = value;
+                choices[choice_index] |= choice;
+                simplify |= choice != Choice::Both;
+                choice_index += 1;
+            }
+            Op::CopyImm(out, imm) => {
+                v[out] = imm.into();
+            }
+            Op::Load(out, mem) => {
+                v[out] = v[mem];
+            }
+            Op::Store(out, mem) => {
+                v[mem] = v[out];
+            }
+        }
     }
+    (slots[0], simplify)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     crate::grad_slice_tests!(Eval);
+    crate::var_grad_slice_tests!(Eval);
+    crate::hessian_slice_tests!(Eval);
     crate::interval_tests!(Eval);
+    crate::interval_slice_tests!(Eval);
+    crate::affine_tests!(Eval);
     crate::float_slice_tests!(Eval);
     crate::point_tests!(Eval);
 }
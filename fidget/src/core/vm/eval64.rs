@@ -0,0 +1,1092 @@
+use super::{
+    eval::{binary, unary, AsmBulkEvalData, AsmTracingEvalData, SlotArray},
+    types64::{BulkValue, Grad64, Interval64, SoundInterval, TracingValue},
+};
+use crate::{
+    eval::{
+        batch::{BatchEvaluator, BatchOutput},
+        bulk::BulkEvaluator,
+        tape::Data,
+        tracing::TracingEvaluator,
+        types::{Grad, Interval},
+        Choice, EvaluatorStorage, Family, Tape,
+    },
+    vm::Op,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Family of evaluators that use a local interpreter in double precision
+///
+/// Inputs and variables are converted from `f32` on entry, and every operation
+/// (along with every constant in the tape) uses `f64`.  Results are converted
+/// back to `f32` for the standard evaluator types; intervals are rounded
+/// outwards during that conversion.  Use [`SoundEval`] if interval results
+/// must be guaranteed to contain the true result.
+///
+/// In addition, [`PointEval64`](super::PointEval64) and
+/// [`FloatSliceEval64`](super::FloatSliceEval64) return `f64` values directly.
+#[derive(Clone)]
+pub enum Eval64 {}
+
+impl Family for Eval64 {
+    /// This is interpreted, so we can use the maximum number of registers
+    const REG_LIMIT: u8 = u8::MAX;
+
+    type IntervalEval = AsmEval64;
+    type PointEval = AsmEval64;
+    type FloatSliceEval = AsmEval64;
+    type GradSliceEval = AsmEval64;
+    type IntervalSliceEval = AsmEval64;
+
+    fn tile_sizes_3d() -> &'static [usize] {
+        &[256, 128, 64, 32, 16, 8]
+    }
+
+    fn tile_sizes_2d() -> &'static [usize] {
+        &[256, 128, 64, 32, 16, 8]
+    }
+}
+
+/// Family of double-precision evaluators with sound interval arithmetic
+///
+/// This is identical to [`Eval64`], except that every interval operation rounds
+/// outwards (rather than only the final conversion to `f32`).  As a result,
+/// interval results are guaranteed to contain the true range of the expression
+/// over the input intervals, even when intermediate values aren't exactly
+/// representable.  This makes interval evaluation slightly slower and its
+/// results slightly wider.
+#[derive(Clone)]
+pub enum SoundEval {}
+
+impl Family for SoundEval {
+    const REG_LIMIT: u8 = Eval64::REG_LIMIT;
+
+    type IntervalEval = AsmEval64<SoundEval>;
+    type PointEval = AsmEval64<SoundEval>;
+    type FloatSliceEval = AsmEval64<SoundEval>;
+    type GradSliceEval = AsmEval64<SoundEval>;
+    type IntervalSliceEval = AsmEval64<SoundEval>;
+
+    fn tile_sizes_3d() -> &'static [usize] {
+        Eval64::tile_sizes_3d()
+    }
+
+    fn tile_sizes_2d() -> &'static [usize] {
+        Eval64::tile_sizes_2d()
+    }
+}
+
+/// Double-precision tracing and bulk evaluator
+#[derive(Clone)]
+pub struct AsmEval64<F = Eval64> {
+    /// Instruction tape, in reverse-evaluation order
+    tape: Tape<F>,
+}
+
+impl<F> EvaluatorStorage<F> for AsmEval64<F> {
+    type Storage = ();
+    fn new_with_storage(tape: &Tape<F>, _storage: ()) -> Self {
+        Self { tape: tape.clone() }
+    }
+    fn take(self) -> Option<Self::Storage> {
+        Some(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+impl TracingEvaluator<Interval, Eval64> for AsmEval64 {
+    type Data = AsmTracingEvalData<Interval64>;
+
+    fn eval_with(
+        &self,
+        x: Interval,
+        y: Interval,
+        z: Interval,
+        w: Interval,
+        vars: &[f32],
+        choices: &mut [Choice],
+        data: &mut Self::Data,
+    ) -> (Interval, bool) {
+        let (out, simplify) = eval_tracing(
+            &self.tape,
+            [x.into(), y.into(), z.into(), w.into()],
+            vars,
+            choices,
+            &mut data.slots,
+        );
+        (out.into(), simplify)
+    }
+}
+
+impl TracingEvaluator<Interval, SoundEval> for AsmEval64<SoundEval> {
+    type Data = AsmTracingEvalData<SoundInterval>;
+
+    fn eval_with(
+        &self,
+        x: Interval,
+        y: Interval,
+        z: Interval,
+        w: Interval,
+        vars: &[f32],
+        choices: &mut [Choice],
+        data: &mut Self::Data,
+    ) -> (Interval, bool) {
+        let (out, simplify) = eval_tracing(
+            &self.tape,
+            [x.into(), y.into(), z.into(), w.into()],
+            vars,
+            choices,
+            &mut data.slots,
+        );
+        (out.into(), simplify)
+    }
+}
+
+impl<F> TracingEvaluator<f32, F> for AsmEval64<F> {
+    type Data = AsmTracingEvalData<f64>;
+
+    fn eval_with(
+        &self,
+        x: f32,
+        y: f32,
+        z: f32,
+        w: f32,
+        vars: &[f32],
+        choices: &mut [Choice],
+        data: &mut Self::Data,
+    ) -> (f32, bool) {
+        let (out, simplify) = eval_tracing(
+            &self.tape,
+            [x as f64, y as f64, z as f64, w as f64],
+            vars,
+            choices,
+            &mut data.slots,
+        );
+        (out as f32, simplify)
+    }
+}
+
+impl<F> TracingEvaluator<f64, F> for AsmEval64<F> {
+    type Data = AsmTracingEvalData<f64>;
+
+    fn eval_with(
+        &self,
+        x: f64,
+        y: f64,
+        z: f64,
+        w: f64,
+        vars: &[f32],
+        choices: &mut [Choice],
+        data: &mut Self::Data,
+    ) -> (f64, bool) {
+        eval_tracing(&self.tape, [x, y, z, w], vars, choices, &mut data.slots)
+    }
+}
+
+/// Evaluates the tape with a single value of type `T`
+///
+/// This is shared between the point and interval evaluators, which differ
+/// only in their value type.
+fn eval_tracing<T: TracingValue>(
+    tape: &Data,
+    inputs: [T; 4],
+    vars: &[f32],
+    choices: &mut [Choice],
+    slots: &mut [T],
+) -> (T, bool) {
+    assert_eq!(vars.len(), tape.var_count());
+    let mut choice_index = 0;
+    let mut simplify = false;
+    let mut v = SlotArray(slots);
+    let mut choose = |(value, choice): (T, Choice)| {
+        choices[choice_index] |= choice;
+        simplify |= choice != Choice::Both;
+        choice_index += 1;
+        value
+    };
+    for (op, imm64) in tape.iter_asm_imm64() {
+        // Immediates are stored as `f32`, with exact values in a side table
+        let exact = |imm: f32| T::from(imm64.unwrap_or(imm.into()));
+        match op {
+            Op::Input(out, i) => {
+                v[out] = match i {
+                    0..=3 => inputs[i as usize],
+                    _ => panic!("Invalid input: {}", i),
+                }
+            }
+            Op::Var(out, i) => v[out] = (vars[i as usize] as f64).into(),
+            Op::NegReg(out, arg) => v[out] = -v[arg],
+            Op::AbsReg(out, arg) => v[out] = v[arg].abs(),
+            Op::RecipReg(out, arg) => v[out] = v[arg].recip(),
+            Op::SqrtReg(out, arg) => v[out] = v[arg].sqrt(),
+            Op::SquareReg(out, arg) => v[out] = v[arg].square(),
+            Op::ExpReg(out, arg) => v[out] = v[arg].exp(),
+            Op::CopyReg(out, arg) => v[out] = v[arg],
+            Op::AddRegImm(out, arg, imm) => v[out] = v[arg] + exact(imm),
+            Op::MulRegImm(out, arg, imm) => v[out] = v[arg] * exact(imm),
+            Op::DivRegImm(out, arg, imm) => v[out] = v[arg] / exact(imm),
+            Op::DivImmReg(out, arg, imm) => v[out] = exact(imm) / v[arg],
+            Op::SubImmReg(out, arg, imm) => v[out] = exact(imm) - v[arg],
+            Op::SubRegImm(out, arg, imm) => v[out] = v[arg] - exact(imm),
+            Op::SineReg(out, arg) => v[out] = v[arg].sin(),
+            Op::CosineReg(out, arg) => v[out] = v[arg].cos(),
+            Op::TanReg(out, arg) => v[out] = v[arg].tan(),
+            Op::AsinReg(out, arg) => v[out] = v[arg].asin(),
+            Op::AcosReg(out, arg) => v[out] = v[arg].acos(),
+            Op::AtanReg(out, arg) => v[out] = v[arg].atan(),
+            Op::Atan2RegImm(out, arg, imm) => {
+                v[out] = v[arg].atan2(exact(imm));
+            }
+            Op::Atan2ImmReg(out, arg, imm) => {
+                v[out] = exact(imm).atan2(v[arg]);
+            }
+            Op::Atan2RegReg(out, lhs, rhs) => v[out] = v[lhs].atan2(v[rhs]),
+            Op::LnReg(out, arg) => v[out] = v[arg].ln(),
+            Op::PowRegImm(out, arg, imm) => v[out] = v[arg].pow(exact(imm)),
+            Op::PowImmReg(out, arg, imm) => v[out] = exact(imm).pow(v[arg]),
+            Op::PowRegReg(out, lhs, rhs) => v[out] = v[lhs].pow(v[rhs]),
+            Op::PowiReg(out, arg, n) => v[out] = v[arg].powi(n),
+            Op::FloorReg(out, arg) => v[out] = v[arg].floor(),
+            Op::CeilReg(out, arg) => v[out] = v[arg].ceil(),
+            Op::RoundReg(out, arg) => v[out] = v[arg].round(),
+            Op::ModRegImm(out, arg, imm) => {
+                v[out] = v[arg].modulo(exact(imm));
+            }
+            Op::ModImmReg(out, arg, imm) => {
+                v[out] = exact(imm).modulo(v[arg]);
+            }
+            Op::ModRegReg(out, lhs, rhs) => v[out] = v[lhs].modulo(v[rhs]),
+            Op::NotReg(out, arg) => v[out] = v[arg].not(),
+            Op::LtRegImm(out, arg, imm) => v[out] = v[arg].lt(exact(imm)),
+            Op::LtImmReg(out, arg, imm) => v[out] = exact(imm).lt(v[arg]),
+            Op::LtRegReg(out, lhs, rhs) => v[out] = v[lhs].lt(v[rhs]),
+            Op::EqRegImm(out, arg, imm) => v[out] = v[arg].eq(exact(imm)),
+            Op::EqRegReg(out, lhs, rhs) => v[out] = v[lhs].eq(v[rhs]),
+            Op::AndRegImm(out, arg, imm) => {
+                v[out] = choose(v[arg].and_choice(exact(imm)));
+            }
+            Op::AndRegReg(out, lhs, rhs) => {
+                v[out] = choose(v[lhs].and_choice(v[rhs]));
+            }
+            Op::OrRegImm(out, arg, imm) => {
+                v[out] = choose(v[arg].or_choice(exact(imm)));
+            }
+            Op::OrRegReg(out, lhs, rhs) => {
+                v[out] = choose(v[lhs].or_choice(v[rhs]));
+            }
+            Op::Select(out, cond, lhs, rhs) => {
+                v[out] = choose(v[cond].select_choice(v[lhs], v[rhs]));
+            }
+            Op::MinRegImm(out, arg, imm) => {
+                v[out] = choose(v[arg].min_choice(exact(imm)));
+            }
+            Op::MaxRegImm(out, arg, imm) => {
+                v[out] = choose(v[arg].max_choice(exact(imm)));
+            }
+            Op::MinRegReg(out, lhs, rhs) => {
+                v[out] = choose(v[lhs].min_choice(v[rhs]));
+            }
+            Op::MaxRegReg(out, lhs, rhs) => {
+                v[out] = choose(v[lhs].max_choice(v[rhs]));
+            }
+            Op::AddRegReg(out, lhs, rhs) => v[out] = v[lhs] + v[rhs],
+            Op::MulRegReg(out, lhs, rhs) => v[out] = v[lhs] * v[rhs],
+            Op::DivRegReg(out, lhs, rhs) => v[out] = v[lhs] / v[rhs],
+            Op::SubRegReg(out, lhs, rhs) => v[out] = v[lhs] - v[rhs],
+            Op::CopyImm(out, imm) => v[out] = exact(imm),
+            Op::Load(out, mem) => v[out] = v[mem],
+            Op::Store(out, mem) => v[mem] = v[out],
+        }
+    }
+    (slots[0], simplify)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+impl<F> BulkEvaluator<f32, F> for AsmEval64<F> {
+    type Data = AsmBulkEvalData<f64>;
+
+    fn eval_with(
+        &self,
+        xs: &[f32],
+        ys: &[f32],
+        zs: &[f32],
+        ws: &[f32],
+        vars: &[f32],
+        out: &mut [f32],
+        data: &mut Self::Data,
+    ) {
+        let size = xs.len();
+        eval_bulk(
+            &self.tape,
+            size,
+            |j, i| match j {
+                0 => xs[i] as f64,
+                1 => ys[i] as f64,
+                2 => zs[i] as f64,
+                3 => ws[i] as f64,
+                _ => panic!("Invalid input: {}", j),
+            },
+            vars,
+            data,
+        );
+        for k in 0..self.tape.output_count() {
+            for (o, v) in out[k * size..].iter_mut().zip(&data.slots[k][..size])
+            {
+                *o = *v as f32;
+            }
+        }
+    }
+}
+
+impl<F> BulkEvaluator<f64, F> for AsmEval64<F> {
+    type Data = AsmBulkEvalData<f64>;
+
+    fn eval_with(
+        &self,
+        xs: &[f32],
+        ys: &[f32],
+        zs: &[f32],
+        ws: &[f32],
+        vars: &[f32],
+        out: &mut [f64],
+        data: &mut Self::Data,
+    ) {
+        let size = xs.len();
+        eval_bulk(
+            &self.tape,
+            size,
+            |j, i| match j {
+                0 => xs[i] as f64,
+                1 => ys[i] as f64,
+                2 => zs[i] as f64,
+                3 => ws[i] as f64,
+                _ => panic!("Invalid input: {}", j),
+            },
+            vars,
+            data,
+        );
+        for k in 0..self.tape.output_count() {
+            out[k * size..][..size].copy_from_slice(&data.slots[k][..size]);
+        }
+    }
+}
+
+impl<F> BulkEvaluator<Grad, F> for AsmEval64<F> {
+    type Data = AsmBulkEvalData<Grad64>;
+
+    fn eval_with(
+        &self,
+        xs: &[f32],
+        ys: &[f32],
+        zs: &[f32],
+        ws: &[f32],
+        vars: &[f32],
+        out: &mut [Grad],
+        data: &mut Self::Data,
+    ) {
+        let size = xs.len();
+        eval_bulk(
+            &self.tape,
+            size,
+            |j, i| match j {
+                0 => Grad64::new(xs[i] as f64, 1.0, 0.0, 0.0),
+                1 => Grad64::new(ys[i] as f64, 0.0, 1.0, 0.0),
+                2 => Grad64::new(zs[i] as f64, 0.0, 0.0, 1.0),
+                3 => Grad64::new(ws[i] as f64, 0.0, 0.0, 0.0),
+                _ => panic!("Invalid input: {}", j),
+            },
+            vars,
+            data,
+        );
+        for k in 0..self.tape.output_count() {
+            for (o, v) in out[k * size..].iter_mut().zip(&data.slots[k][..size])
+            {
+                *o = (*v).into();
+            }
+        }
+    }
+}
+
+/// Evaluates the tape on `size` points, using values of type `T`
+///
+/// `input(j, i)` returns the value of input `j` at point `i`.  Output `k` is
+/// written to the first `size` items of `data.slots[k]`.
+fn eval_bulk<T: BulkValue, I: Fn(u8, usize) -> T>(
+    tape: &Data,
+    size: usize,
+    input: I,
+    vars: &[f32],
+    data: &mut AsmBulkEvalData<T>,
+) {
+    assert_eq!(vars.len(), tape.var_count());
+    assert_eq!(data.slots.len(), tape.slot_count());
+    assert!(data.slice_size >= size);
+
+    let mut v = SlotArray(&mut data.slots);
+    for (op, imm64) in tape.iter_asm_imm64() {
+        let exact = |imm: f32| T::from(imm64.unwrap_or(imm.into()));
+        match op {
+            Op::Input(out, j) => {
+                for i in 0..size {
+                    v[out][i] = input(j, i);
+                }
+            }
+            Op::Var(out, j) => {
+                v[out][0..size].fill((vars[j as usize] as f64).into());
+            }
+            Op::NegReg(out, arg) => unary(&mut v, out, arg, size, |a| -a),
+            Op::AbsReg(out, arg) => unary(&mut v, out, arg, size, T::abs),
+            Op::RecipReg(out, arg) => unary(&mut v, out, arg, size, T::recip),
+            Op::SqrtReg(out, arg) => unary(&mut v, out, arg, size, T::sqrt),
+            Op::SquareReg(out, arg) => {
+                unary(&mut v, out, arg, size, T::square);
+            }
+            Op::ExpReg(out, arg) => unary(&mut v, out, arg, size, T::exp),
+            Op::CopyReg(out, arg) => unary(&mut v, out, arg, size, |a| a),
+            Op::AddRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a + imm);
+            }
+            Op::MulRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a * imm);
+            }
+            Op::DivRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a / imm);
+            }
+            Op::DivImmReg(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| imm / a);
+            }
+            Op::SubImmReg(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| imm - a);
+            }
+            Op::SubRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a - imm);
+            }
+            Op::SineReg(out, arg) => unary(&mut v, out, arg, size, T::sin),
+            Op::CosineReg(out, arg) => unary(&mut v, out, arg, size, T::cos),
+            Op::TanReg(out, arg) => unary(&mut v, out, arg, size, T::tan),
+            Op::AsinReg(out, arg) => unary(&mut v, out, arg, size, T::asin),
+            Op::AcosReg(out, arg) => unary(&mut v, out, arg, size, T::acos),
+            Op::AtanReg(out, arg) => unary(&mut v, out, arg, size, T::atan),
+            Op::Atan2RegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a.atan2(imm));
+            }
+            Op::Atan2ImmReg(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| imm.atan2(a));
+            }
+            Op::Atan2RegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, T::atan2);
+            }
+            Op::LnReg(out, arg) => unary(&mut v, out, arg, size, T::ln),
+            Op::PowRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a.pow(imm));
+            }
+            Op::PowImmReg(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| imm.pow(a));
+            }
+            Op::PowRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, T::pow);
+            }
+            Op::PowiReg(out, arg, n) => {
+                unary(&mut v, out, arg, size, |a| a.powi(n));
+            }
+            Op::FloorReg(out, arg) => unary(&mut v, out, arg, size, T::floor),
+            Op::CeilReg(out, arg) => unary(&mut v, out, arg, size, T::ceil),
+            Op::RoundReg(out, arg) => unary(&mut v, out, arg, size, T::round),
+            Op::ModRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a.modulo(imm));
+            }
+            Op::ModImmReg(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| imm.modulo(a));
+            }
+            Op::ModRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, T::modulo);
+            }
+            Op::NotReg(out, arg) => unary(&mut v, out, arg, size, T::not),
+            Op::LtRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a.lt(imm));
+            }
+            Op::LtImmReg(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| imm.lt(a));
+            }
+            Op::LtRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, T::lt);
+            }
+            Op::EqRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a.eq(imm));
+            }
+            Op::EqRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, T::eq);
+            }
+            Op::AndRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a.and(imm));
+            }
+            Op::AndRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, T::and);
+            }
+            Op::OrRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a.or(imm));
+            }
+            Op::OrRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, T::or);
+            }
+            Op::Select(out, cond, lhs, rhs) => {
+                for i in 0..size {
+                    v[out][i] = v[cond][i].select(v[lhs][i], v[rhs][i]);
+                }
+            }
+            Op::MinRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a.min(imm));
+            }
+            Op::MaxRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a.max(imm));
+            }
+            Op::MinRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, T::min);
+            }
+            Op::MaxRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, T::max);
+            }
+            Op::AddRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, |a, b| a + b);
+            }
+            Op::MulRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, |a, b| a * b);
+            }
+            Op::DivRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, |a, b| a / b);
+            }
+            Op::SubRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, |a, b| a - b);
+            }
+            Op::CopyImm(out, imm) => v[out][0..size].fill(exact(imm)),
+            Op::Load(out, mem) => {
+                for i in 0..size {
+                    v[out][i] = v[mem][i];
+                }
+            }
+            Op::Store(out, mem) => {
+                for i in 0..size {
+                    v[mem][i] = v[out][i];
+                }
+            }
+        }
+    }
+}
+
+impl BatchEvaluator<Interval, Eval64> for AsmEval64 {
+    type Data = AsmBulkEvalData<Interval64>;
+
+    fn eval_with(
+        &self,
+        xs: &[Interval],
+        ys: &[Interval],
+        zs: &[Interval],
+        ws: &[Interval],
+        vars: &[f32],
+        out: &mut BatchOutput<Interval>,
+        data: &mut Self::Data,
+    ) {
+        eval_batch(&self.tape, [xs, ys, zs, ws], vars, out, data);
+    }
+}
+
+impl BatchEvaluator<Interval, SoundEval> for AsmEval64<SoundEval> {
+    type Data = AsmBulkEvalData<SoundInterval>;
+
+    fn eval_with(
+        &self,
+        xs: &[Interval],
+        ys: &[Interval],
+        zs: &[Interval],
+        ws: &[Interval],
+        vars: &[f32],
+        out: &mut BatchOutput<Interval>,
+        data: &mut Self::Data,
+    ) {
+        eval_batch(&self.tape, [xs, ys, zs, ws], vars, out, data);
+    }
+}
+
+/// Evaluates the tape on many intervals, using values of type `T`
+///
+/// This is equivalent to [`eval_bulk`], but records a separate trace for each
+/// item in `trace`.
+fn eval_batch<T>(
+    tape: &Data,
+    inputs: [&[Interval]; 4],
+    vars: &[f32],
+    trace: &mut BatchOutput<Interval>,
+    data: &mut AsmBulkEvalData<T>,
+) where
+    T: TracingValue + From<Interval>,
+    Interval: From<T>,
+{
+    let size = inputs[0].len();
+    assert_eq!(vars.len(), tape.var_count());
+    assert_eq!(data.slots.len(), tape.slot_count());
+    assert!(data.slice_size >= size);
+
+    let mut choice_index = 0;
+    let mut v = SlotArray(&mut data.slots);
+    for (op, imm64) in tape.iter_asm_imm64() {
+        let exact = |imm: f32| T::from(imm64.unwrap_or(imm.into()));
+        match op {
+            Op::Input(out, j) => {
+                let input = match j {
+                    0..=3 => inputs[j as usize],
+                    _ => panic!("Invalid input: {}", j),
+                };
+                for i in 0..size {
+                    v[out][i] = input[i].into();
+                }
+            }
+            Op::Var(out, j) => {
+                v[out][0..size].fill((vars[j as usize] as f64).into());
+            }
+            Op::NegReg(out, arg) => unary(&mut v, out, arg, size, |a| -a),
+            Op::AbsReg(out, arg) => unary(&mut v, out, arg, size, T::abs),
+            Op::RecipReg(out, arg) => unary(&mut v, out, arg, size, T::recip),
+            Op::SqrtReg(out, arg) => unary(&mut v, out, arg, size, T::sqrt),
+            Op::SquareReg(out, arg) => {
+                unary(&mut v, out, arg, size, T::square);
+            }
+            Op::ExpReg(out, arg) => unary(&mut v, out, arg, size, T::exp),
+            Op::CopyReg(out, arg) => unary(&mut v, out, arg, size, |a| a),
+            Op::AddRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a + imm);
+            }
+            Op::MulRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a * imm);
+            }
+            Op::DivRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a / imm);
+            }
+            Op::DivImmReg(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| imm / a);
+            }
+            Op::SubImmReg(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| imm - a);
+            }
+            Op::SubRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a - imm);
+            }
+            Op::SineReg(out, arg) => unary(&mut v, out, arg, size, T::sin),
+            Op::CosineReg(out, arg) => unary(&mut v, out, arg, size, T::cos),
+            Op::TanReg(out, arg) => unary(&mut v, out, arg, size, T::tan),
+            Op::AsinReg(out, arg) => unary(&mut v, out, arg, size, T::asin),
+            Op::AcosReg(out, arg) => unary(&mut v, out, arg, size, T::acos),
+            Op::AtanReg(out, arg) => unary(&mut v, out, arg, size, T::atan),
+            Op::Atan2RegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a.atan2(imm));
+            }
+            Op::Atan2ImmReg(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| imm.atan2(a));
+            }
+            Op::Atan2RegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, T::atan2);
+            }
+            Op::LnReg(out, arg) => unary(&mut v, out, arg, size, T::ln),
+            Op::PowRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a.pow(imm));
+            }
+            Op::PowImmReg(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| imm.pow(a));
+            }
+            Op::PowRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, T::pow);
+            }
+            Op::PowiReg(out, arg, n) => {
+                unary(&mut v, out, arg, size, |a| a.powi(n));
+            }
+            Op::FloorReg(out, arg) => unary(&mut v, out, arg, size, T::floor),
+            Op::CeilReg(out, arg) => unary(&mut v, out, arg, size, T::ceil),
+            Op::RoundReg(out, arg) => unary(&mut v, out, arg, size, T::round),
+            Op::ModRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a.modulo(imm));
+            }
+            Op::ModImmReg(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| imm.modulo(a));
+            }
+            Op::ModRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, T::modulo);
+            }
+            Op::NotReg(out, arg) => unary(&mut v, out, arg, size, T::not),
+            Op::LtRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a.lt(imm));
+            }
+            Op::LtImmReg(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| imm.lt(a));
+            }
+            Op::LtRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, T::lt);
+            }
+            Op::EqRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                unary(&mut v, out, arg, size, |a| a.eq(imm));
+            }
+            Op::EqRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, T::eq);
+            }
+            Op::AndRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                for i in 0..size {
+                    let (value, choice) = v[arg][i].and_choice(imm);
+                    v[out][i] = value;
+                    trace.record(i, choice_index, choice);
+                }
+                choice_index += 1;
+            }
+            Op::AndRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    let (value, choice) = v[lhs][i].and_choice(v[rhs][i]);
+                    v[out][i] = value;
+                    trace.record(i, choice_index, choice);
+                }
+                choice_index += 1;
+            }
+            Op::OrRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                for i in 0..size {
+                    let (value, choice) = v[arg][i].or_choice(imm);
+                    v[out][i] = value;
+                    trace.record(i, choice_index, choice);
+                }
+                choice_index += 1;
+            }
+            Op::OrRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    let (value, choice) = v[lhs][i].or_choice(v[rhs][i]);
+                    v[out][i] = value;
+                    trace.record(i, choice_index, choice);
+                }
+                choice_index += 1;
+            }
+            Op::Select(out, cond, lhs, rhs) => {
+                for i in 0..size {
+                    let (value, choice) =
+                        v[cond][i].select_choice(v[lhs][i], v[rhs][i]);
+                    v[out][i] = value;
+                    trace.record(i, choice_index, choice);
+                }
+                choice_index += 1;
+            }
+            Op::MinRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                for i in 0..size {
+                    let (value, choice) = v[arg][i].min_choice(imm);
+                    v[out][i] = value;
+                    trace.record(i, choice_index, choice);
+                }
+                choice_index += 1;
+            }
+            Op::MaxRegImm(out, arg, imm) => {
+                let imm = exact(imm);
+                for i in 0..size {
+                    let (value, choice) = v[arg][i].max_choice(imm);
+                    v[out][i] = value;
+                    trace.record(i, choice_index, choice);
+                }
+                choice_index += 1;
+            }
+            Op::MinRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    let (value, choice) = v[lhs][i].min_choice(v[rhs][i]);
+                    v[out][i] = value;
+                    trace.record(i, choice_index, choice);
+                }
+                choice_index += 1;
+            }
+            Op::MaxRegReg(out, lhs, rhs) => {
+                for i in 0..size {
+                    let (value, choice) = v[lhs][i].max_choice(v[rhs][i]);
+                    v[out][i] = value;
+                    trace.record(i, choice_index, choice);
+                }
+                choice_index += 1;
+            }
+            Op::AddRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, |a, b| a + b);
+            }
+            Op::MulRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, |a, b| a * b);
+            }
+            Op::DivRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, |a, b| a / b);
+            }
+            Op::SubRegReg(out, lhs, rhs) => {
+                binary(&mut v, out, lhs, rhs, size, |a, b| a - b);
+            }
+            Op::CopyImm(out, imm) => v[out][0..size].fill(exact(imm)),
+            Op::Load(out, mem) => {
+                for i in 0..size {
+                    v[out][i] = v[mem][i];
+                }
+            }
+            Op::Store(out, mem) => {
+                for i in 0..size {
+                    v[mem][i] = v[out][i];
+                }
+            }
+        }
+    }
+    for i in 0..size {
+        trace.set(i, data.slots[0][i].into(), false);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        context::{Context, Node},
+        eval::{FloatSliceEval, PointEval},
+        vm::{FloatSliceEval64, PointEval64},
+    };
+    use ieee754::Ieee754;
+
+    // The shared tests compare transcendental functions against their `f32`
+    // implementations exactly, which double-precision evaluation doesn't
+    // match to the last bit; those are checked in `test_transcendental`.
+    crate::point_test!(test_constant, Eval64);
+    crate::point_test!(test_constant_push, Eval64);
+    crate::point_test!(test_circle, Eval64);
+    crate::point_test!(test_p_max, Eval64);
+    crate::point_test!(test_p_min, Eval64);
+    crate::point_test!(basic_interpreter, Eval64);
+    crate::point_test!(test_push, Eval64);
+    crate::point_test!(test_var, Eval64);
+    crate::point_test!(test_basic, Eval64);
+    crate::point_test!(test_p_call_spill, Eval64);
+    crate::point_test!(test_p_inverse_trig, Eval64);
+    crate::point_test!(test_p_atan2, Eval64);
+    crate::point_test!(test_p_ln_pow, Eval64);
+    crate::point_test!(test_p_floor_mod, Eval64);
+    crate::point_test!(test_p_cmp_logic, Eval64);
+    crate::point_test!(test_p_select, Eval64);
+    crate::affine_tests!(Eval64);
+    crate::interval_test!(test_interval, Eval64);
+    crate::interval_test!(test_i_abs, Eval64);
+    crate::interval_test!(test_i_sqrt, Eval64);
+    crate::interval_test!(test_i_square, Eval64);
+    crate::interval_test!(test_i_neg, Eval64);
+    crate::interval_test!(test_i_mul, Eval64);
+    crate::interval_test!(test_i_mul_imm, Eval64);
+    crate::interval_test!(test_i_sub, Eval64);
+    crate::interval_test!(test_i_sub_imm, Eval64);
+    crate::interval_test!(test_i_recip, Eval64);
+    crate::interval_test!(test_i_div, Eval64);
+    crate::interval_test!(test_i_min, Eval64);
+    crate::interval_test!(test_i_min_imm, Eval64);
+    crate::interval_test!(test_i_max, Eval64);
+    crate::interval_test!(test_i_max_imm, Eval64);
+    crate::interval_test!(test_i_simplify, Eval64);
+    crate::interval_test!(test_i_var, Eval64);
+    crate::interval_test!(test_i_ln_pow, Eval64);
+    crate::interval_test!(test_i_floor_mod, Eval64);
+    crate::interval_test!(test_i_infinite_bounds, Eval64);
+    crate::interval_test!(test_i_cmp_logic, Eval64);
+    crate::interval_test!(test_i_select, Eval64);
+    crate::sound_interval_tests!(SoundEval);
+    crate::interval_slice_tests!(Eval64);
+    crate::float_slice_test!(test_give_take, Eval64);
+    crate::float_slice_test!(test_vectorized, Eval64);
+    crate::float_slice_test!(test_f_var, Eval64);
+    crate::float_slice_test!(test_f_ln_pow, Eval64);
+    crate::float_slice_test!(test_f_floor_mod, Eval64);
+    crate::float_slice_test!(test_f_cmp_logic, Eval64);
+    crate::float_slice_test!(test_f_select, Eval64);
+    crate::grad_test!(test_g_circle, Eval64);
+    crate::grad_test!(test_g_x, Eval64);
+    crate::grad_test!(test_g_y, Eval64);
+    crate::grad_test!(test_g_z, Eval64);
+    crate::grad_test!(test_g_abs, Eval64);
+    crate::grad_test!(test_g_square, Eval64);
+    crate::grad_test!(test_g_sqrt, Eval64);
+    crate::grad_test!(test_g_mul, Eval64);
+    crate::grad_test!(test_g_min, Eval64);
+    crate::grad_test!(test_g_max, Eval64);
+    crate::grad_test!(test_g_min_max, Eval64);
+    crate::grad_test!(test_g_div, Eval64);
+    crate::grad_test!(test_g_recip, Eval64);
+    crate::grad_test!(test_g_var, Eval64);
+    crate::grad_test!(test_g_exp_sin_cos, Eval64);
+    crate::grad_test!(test_g_ln_pow, Eval64);
+    crate::grad_test!(test_g_floor_mod, Eval64);
+    crate::grad_test!(test_g_cmp_logic, Eval64);
+    crate::grad_test!(test_g_select, Eval64);
+
+    #[test]
+    fn test_transcendental() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let ops: [(Node, fn(f64) -> f64); 8] = [
+            (ctx.exp(x).unwrap(), f64::exp),
+            (ctx.sin(x).unwrap(), f64::sin),
+            (ctx.cos(x).unwrap(), f64::cos),
+            (ctx.tan(x).unwrap(), f64::tan),
+            (ctx.asin(x).unwrap(), f64::asin),
+            (ctx.acos(x).unwrap(), f64::acos),
+            (ctx.atan(x).unwrap(), f64::atan),
+            (ctx.atan2(x, y).unwrap(), |x| x.atan2(0.5)),
+        ];
+        let xs = [-0.75, -0.5, -0.125, 0.0, 0.25, 0.5, 0.875];
+        for (node, f) in ops {
+            let tape = ctx.get_tape::<Eval64>(node).unwrap();
+            let point = PointEval::new(&tape);
+            let float = FloatSliceEval::new(&tape);
+            let grad = tape.new_grad_slice_evaluator();
+            let interval = tape.new_interval_evaluator();
+
+            let ys = [0.5; 7];
+            let zs = [0.0; 7];
+            let fs = float.eval(&xs, &ys, &zs, &[]).unwrap();
+            let gs = grad.eval(&xs, &ys, &zs, &[]).unwrap();
+            for (i, x) in xs.iter().enumerate() {
+                let expected = f(*x as f64) as f32;
+                let (p, _) = point.eval(*x, 0.5, 0.0, &[]).unwrap();
+                assert_eq!(p, expected);
+                assert_eq!(fs[i], expected);
+                assert_eq!(gs[i].v, expected);
+            }
+
+            // Intervals must contain every sample in double precision
+            let i = Interval::new(xs[0], xs[6]);
+            let (out, _) =
+                interval.eval(i, 0.5.into(), 0.0.into(), &[]).unwrap();
+            for x in xs {
+                let v = f(x as f64);
+                assert!(out.lower() as f64 <= v && v <= out.upper() as f64);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cancellation() {
+        // (x + 1e8) - 1e8 loses every bit of x in single precision
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let a = ctx.add(x, 1e8).unwrap();
+        let b = ctx.sub(a, 1e8).unwrap();
+
+        let tape = ctx.get_tape::<crate::vm::Eval>(b).unwrap();
+        let eval = PointEval::new(&tape);
+        assert_eq!(eval.eval(0.25, 0.0, 0.0, &[]).unwrap().0, 0.0);
+
+        let tape = ctx.get_tape::<Eval64>(b).unwrap();
+        let eval = PointEval::new(&tape);
+        assert_eq!(eval.eval(0.25, 0.0, 0.0, &[]).unwrap().0, 0.25);
+        let eval = PointEval64::new(&tape);
+        assert_eq!(eval.eval(0.25, 0.0, 0.0, &[]).unwrap().0, 0.25);
+
+        let eval = FloatSliceEval::new(&tape);
+        let out = eval.eval(&[0.25, 0.5], &[0.0; 2], &[0.0; 2], &[]);
+        assert_eq!(out.unwrap(), [0.25, 0.5]);
+        let eval = FloatSliceEval64::new(&tape);
+        let out = eval.eval(&[0.25, 0.5], &[0.0; 2], &[0.0; 2], &[]);
+        assert_eq!(out.unwrap(), [0.25, 0.5]);
+
+        let eval = tape.new_interval_evaluator();
+        let x = Interval::new(0.25, 0.5);
+        let (out, _) = eval.eval(x, 0.0.into(), 0.0.into(), &[]).unwrap();
+        assert_eq!(out, x);
+    }
+
+    #[test]
+    fn test_constant_precision() {
+        // 0.1 isn't exactly representable, and the f64 value is closer
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let a = ctx.mul(x, 0.1).unwrap();
+        let tape = ctx.get_tape::<Eval64>(a).unwrap();
+        let eval = PointEval64::new(&tape);
+        assert_eq!(eval.eval(3.0, 0.0, 0.0, &[]).unwrap().0, 3.0 * 0.1);
+
+        let c = ctx.constant(0.1);
+        let tape = ctx.get_tape::<Eval64>(c).unwrap();
+        let eval = PointEval64::new(&tape);
+        assert_eq!(eval.eval(0.0, 0.0, 0.0, &[]).unwrap().0, 0.1);
+    }
+
+    #[test]
+    fn test_i_sound_infinite_bounds() {
+        crate::eval::interval::eval_tests::test_i_infinite_bounds::<SoundEval>()
+    }
+
+    #[test]
+    fn test_simplify_precision() {
+        // Exact immediates must follow their operations through simplification
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let a = ctx.mul(x, 0.1).unwrap();
+        let b = ctx.min(a, 0.2).unwrap();
+        let tape = ctx.get_tape::<Eval64>(b).unwrap();
+
+        let t = tape.simplify(&[Choice::Both]).unwrap();
+        let eval = PointEval64::new(&t);
+        assert_eq!(eval.eval(1.0, 0.0, 0.0, &[]).unwrap().0, 0.1);
+        assert_eq!(eval.eval(3.0, 0.0, 0.0, &[]).unwrap().0, 0.2);
+
+        let t = tape.simplify(&[Choice::Left]).unwrap();
+        let eval = PointEval64::new(&t);
+        assert_eq!(eval.eval(3.0, 0.0, 0.0, &[]).unwrap().0, 3.0 * 0.1);
+
+        let t = tape.simplify(&[Choice::Right]).unwrap();
+        let eval = PointEval64::new(&t);
+        assert_eq!(eval.eval(3.0, 0.0, 0.0, &[]).unwrap().0, 0.2);
+    }
+
+    #[test]
+    fn test_interval_rounding() {
+        // The true result isn't representable as an f32, so the interval must
+        // be widened to contain it
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let a = ctx.mul(x, 0.1).unwrap();
+        let tape = ctx.get_tape::<Eval64>(a).unwrap();
+        let eval = tape.new_interval_evaluator();
+        let (out, _) = eval.eval(3.0, 0.0, 0.0, &[]).unwrap();
+        assert!(out.lower() < out.upper());
+        assert!((out.lower() as f64) < 0.3);
+        assert!((out.upper() as f64) > 0.3);
+        assert_eq!(out.upper(), out.lower().next());
+    }
+}
@@ -1,12 +1,26 @@
 //! Instruction tapes in the form of assembly for a simple virtual machine
 mod alloc;
 mod eval;
+mod eval64;
 mod lru;
 mod op;
 mod tape;
+mod types64;
 
 pub(super) use alloc::RegisterAllocator;
+pub(crate) use eval::{eval_affine, eval_grad_slice, eval_hessian_slice};
+#[cfg(feature = "jit")]
+pub(crate) use types64::{SoundInterval, TracingValue, Value};
 
 pub use eval::Eval;
+pub use eval64::{AsmEval64, Eval64, SoundEval};
 pub use op::Op;
 pub use tape::Tape;
+
+use crate::eval::{bulk::BulkEval, tracing::TracingEval};
+
+/// Double-precision point evaluator, returning `f64` results
+pub type PointEval64 = TracingEval<f64, AsmEval64, Eval64>;
+
+/// Double-precision bulk evaluator, returning `f64` results
+pub type FloatSliceEval64 = BulkEval<f64, AsmEval64, Eval64>;
@@ -9,7 +9,7 @@
 /// - RHS register (or immediate for `*Imm`)
 #[derive(Copy, Clone, Debug)]
 pub enum Op {
-    /// Read one of the inputs (X, Y, Z)
+    /// Read one of the inputs (X, Y, Z, W)
     Input(u8, u8),
 
     /// Reads one of the variables
@@ -66,11 +66,76 @@ pub enum Op {
     SineReg(u8, u8),
     /// Compute the cosine of a register
     CosineReg(u8, u8),
+    /// Compute the tangent of a register
+    TanReg(u8, u8),
+    /// Compute the arcsine of a register
+    AsinReg(u8, u8),
+    /// Compute the arccosine of a register
+    AcosReg(u8, u8),
+    /// Compute the arctangent of a register
+    AtanReg(u8, u8),
+    /// Compute the natural logarithm of a register
+    LnReg(u8, u8),
+
+    /// Compute `atan2(reg, imm)`
+    Atan2RegImm(u8, u8, f32),
+    /// Compute `atan2(imm, reg)`
+    Atan2ImmReg(u8, u8, f32),
+    /// Compute `atan2(lhs, rhs)`
+    Atan2RegReg(u8, u8, u8),
+
+    /// Raise a register to the power of an immediate
+    PowRegImm(u8, u8, f32),
+    /// Raise an immediate to the power of a register
+    PowImmReg(u8, u8, f32),
+    /// Raise one register to the power of another
+    PowRegReg(u8, u8, u8),
+    /// Raise a register to an integer power
+    PowiReg(u8, u8, i32),
+
+    /// Round a register down to the nearest integer
+    FloorReg(u8, u8),
+    /// Round a register up to the nearest integer
+    CeilReg(u8, u8),
+    /// Round a register to the nearest integer (ties away from zero)
+    RoundReg(u8, u8),
+    /// Compute the Euclidean remainder of a register and an immediate
+    ModRegImm(u8, u8, f32),
+    /// Compute the Euclidean remainder of an immediate and a register
+    ModImmReg(u8, u8, f32),
+    /// Compute the Euclidean remainder of two registers
+    ModRegReg(u8, u8, u8),
+
+    /// Returns 1 if the register is zero, and 0 otherwise
+    NotReg(u8, u8),
+    /// Returns 1 if the register is less than the immediate
+    LtRegImm(u8, u8, f32),
+    /// Returns 1 if the immediate is less than the register
+    LtImmReg(u8, u8, f32),
+    /// Returns 1 if the left register is less than the right register
+    LtRegReg(u8, u8, u8),
+    /// Returns 1 if the register is equal to the immediate
+    EqRegImm(u8, u8, f32),
+    /// Returns 1 if the two registers are equal
+    EqRegReg(u8, u8, u8),
     
     /// Take the minimum of two registers
     MinRegReg(u8, u8, u8),
     /// Take the maximum of two registers
     MaxRegReg(u8, u8, u8),
+    /// Logical AND of a register and an immediate
+    AndRegImm(u8, u8, f32),
+    /// Logical AND of two registers
+    AndRegReg(u8, u8, u8),
+    /// Logical OR of a register and an immediate
+    OrRegImm(u8, u8, f32),
+    /// Logical OR of two registers
+    OrRegReg(u8, u8, u8),
+    /// Picks the left register if the condition register is less than zero,
+    /// or the right register otherwise
+    ///
+    /// Arguments are output, condition, left, and right registers
+    Select(u8, u8, u8, u8),
 
     /// Copy an immediate to a register
     CopyImm(u8, f32),
@@ -1,5 +1,6 @@
 //! Tape used for evaluation
 use crate::vm::Op;
+use std::collections::BTreeMap;
 
 /// Low-level tape for use with the Fidget virtual machine (or to be lowered
 /// further into machine instructions).
@@ -12,6 +13,13 @@ pub struct Tape {
 
     /// Number of registers, before we fall back to Load/Store operations
     reg_limit: u8,
+
+    /// Exact values of immediates which aren't representable as an `f32`,
+    /// keyed by the index of their operation in `tape`
+    ///
+    /// This is only read by double-precision evaluators; it's usually empty,
+    /// so it doesn't make the tape larger for everyone else.
+    imm64: BTreeMap<usize, f64>,
 }
 
 impl Tape {
@@ -21,11 +29,13 @@ impl Tape {
             tape: vec![],
             slot_count: 1,
             reg_limit,
+            imm64: BTreeMap::new(),
         }
     }
     /// Resets this tape, retaining its allocations
     pub fn reset(&mut self, reg_limit: u8) {
         self.tape.clear();
+        self.imm64.clear();
         self.slot_count = 1;
         self.reg_limit = reg_limit;
     }
@@ -57,10 +67,25 @@ impl Tape {
     pub fn iter(&self) -> std::slice::Iter<'_, Op> {
         self.into_iter()
     }
+    /// Returns a front-to-back iterator, pairing each operation with the exact
+    /// value of its immediate (if that value isn't representable as an `f32`)
+    pub fn iter_imm64(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (Op, Option<f64>)> + '_ {
+        self.tape
+            .iter()
+            .enumerate()
+            .map(|(i, op)| (*op, self.imm64.get(&i).cloned()))
+    }
     #[inline]
     pub(crate) fn push(&mut self, op: Op) {
         self.tape.push(op)
     }
+    /// Pushes an operation, recording the exact value of its immediate
+    pub(crate) fn push_imm64(&mut self, op: Op, imm: f64) {
+        self.imm64.insert(self.tape.len(), imm);
+        self.tape.push(op)
+    }
 }
 
 impl<'a> IntoIterator for &'a Tape {
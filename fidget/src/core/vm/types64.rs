@@ -0,0 +1,1084 @@
+//! Double-precision value types used by the [`Eval64`](super::Eval64) family
+//!
+//! These mirror [`Interval`] and [`Grad`], and are converted to them (with
+//! outward rounding, in the case of intervals) at the end of evaluation.
+//!
+//! When its `SOUND` parameter is set (as in the [`SoundEval`](super::SoundEval)
+//! family), every [`Interval64`] operation also rounds outwards, so that
+//! interval results are guaranteed to contain the true range of the function.
+//! Basic arithmetic uses error-free transformations to round by exactly one ulp
+//! in the correct direction (and only when the result is inexact); functions
+//! from the math library are widened by a small relative error instead.
+use crate::eval::{
+    types::{Grad, Interval},
+    Choice,
+};
+use ieee754::Ieee754;
+use std::f64::consts::PI;
+
+/// Operations shared by every value type in the double-precision interpreter
+pub(crate) trait Value:
+    Copy
+    + From<f64>
+    + std::ops::Add<Output = Self>
+    + std::ops::Sub<Output = Self>
+    + std::ops::Mul<Output = Self>
+    + std::ops::Div<Output = Self>
+    + std::ops::Neg<Output = Self>
+{
+    fn abs(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn recip(self) -> Self;
+    fn square(self) -> Self;
+    fn exp(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn tan(self) -> Self;
+    fn asin(self) -> Self;
+    fn acos(self) -> Self;
+    fn atan(self) -> Self;
+    fn atan2(self, rhs: Self) -> Self;
+    fn ln(self) -> Self;
+    fn pow(self, rhs: Self) -> Self;
+    fn powi(self, n: i32) -> Self;
+    fn floor(self) -> Self;
+    fn ceil(self) -> Self;
+    fn round(self) -> Self;
+    fn modulo(self, rhs: Self) -> Self;
+    fn lt(self, rhs: Self) -> Self;
+    fn eq(self, rhs: Self) -> Self;
+    fn not(self) -> Self;
+}
+
+/// Branching operations for tracing evaluation, which also return a [`Choice`]
+pub(crate) trait TracingValue: Value {
+    fn and_choice(self, rhs: Self) -> (Self, Choice);
+    fn or_choice(self, rhs: Self) -> (Self, Choice);
+    fn select_choice(self, lhs: Self, rhs: Self) -> (Self, Choice);
+    fn min_choice(self, rhs: Self) -> (Self, Choice);
+    fn max_choice(self, rhs: Self) -> (Self, Choice);
+}
+
+/// Branching operations for bulk evaluation
+pub(super) trait BulkValue: Value {
+    fn and(self, rhs: Self) -> Self;
+    fn or(self, rhs: Self) -> Self;
+    fn select(self, lhs: Self, rhs: Self) -> Self;
+    fn min(self, rhs: Self) -> Self;
+    fn max(self, rhs: Self) -> Self;
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+impl Value for f64 {
+    fn abs(self) -> Self {
+        f64::abs(self)
+    }
+    fn sqrt(self) -> Self {
+        f64::sqrt(self)
+    }
+    fn recip(self) -> Self {
+        1.0 / self
+    }
+    fn square(self) -> Self {
+        self * self
+    }
+    fn exp(self) -> Self {
+        f64::exp(self)
+    }
+    fn sin(self) -> Self {
+        f64::sin(self)
+    }
+    fn cos(self) -> Self {
+        f64::cos(self)
+    }
+    fn tan(self) -> Self {
+        f64::tan(self)
+    }
+    fn asin(self) -> Self {
+        f64::asin(self)
+    }
+    fn acos(self) -> Self {
+        f64::acos(self)
+    }
+    fn atan(self) -> Self {
+        f64::atan(self)
+    }
+    fn atan2(self, rhs: Self) -> Self {
+        f64::atan2(self, rhs)
+    }
+    fn ln(self) -> Self {
+        f64::ln(self)
+    }
+    fn pow(self, rhs: Self) -> Self {
+        self.powf(rhs)
+    }
+    fn powi(self, n: i32) -> Self {
+        f64::powi(self, n)
+    }
+    fn floor(self) -> Self {
+        f64::floor(self)
+    }
+    fn ceil(self) -> Self {
+        f64::ceil(self)
+    }
+    fn round(self) -> Self {
+        f64::round(self)
+    }
+    fn modulo(self, rhs: Self) -> Self {
+        self.rem_euclid(rhs)
+    }
+    fn lt(self, rhs: Self) -> Self {
+        (self < rhs) as u8 as f64
+    }
+    fn eq(self, rhs: Self) -> Self {
+        (self == rhs) as u8 as f64
+    }
+    fn not(self) -> Self {
+        (self == 0.0) as u8 as f64
+    }
+}
+
+impl TracingValue for f64 {
+    fn and_choice(self, rhs: Self) -> (Self, Choice) {
+        if self == 0.0 {
+            (self, Choice::Left)
+        } else {
+            (rhs, Choice::Right)
+        }
+    }
+    fn or_choice(self, rhs: Self) -> (Self, Choice) {
+        if self != 0.0 {
+            (self, Choice::Left)
+        } else {
+            (rhs, Choice::Right)
+        }
+    }
+    fn select_choice(self, lhs: Self, rhs: Self) -> (Self, Choice) {
+        if self < 0.0 {
+            (lhs, Choice::Left)
+        } else {
+            (rhs, Choice::Right)
+        }
+    }
+    fn min_choice(self, rhs: Self) -> (Self, Choice) {
+        if self < rhs {
+            (self, Choice::Left)
+        } else if rhs < self {
+            (rhs, Choice::Right)
+        } else if self.is_nan() || rhs.is_nan() {
+            (f64::NAN, Choice::Both)
+        } else {
+            (rhs, Choice::Both)
+        }
+    }
+    fn max_choice(self, rhs: Self) -> (Self, Choice) {
+        if self > rhs {
+            (self, Choice::Left)
+        } else if rhs > self {
+            (rhs, Choice::Right)
+        } else if self.is_nan() || rhs.is_nan() {
+            (f64::NAN, Choice::Both)
+        } else {
+            (rhs, Choice::Both)
+        }
+    }
+}
+
+impl BulkValue for f64 {
+    fn and(self, rhs: Self) -> Self {
+        self.and_choice(rhs).0
+    }
+    fn or(self, rhs: Self) -> Self {
+        self.or_choice(rhs).0
+    }
+    fn select(self, lhs: Self, rhs: Self) -> Self {
+        self.select_choice(lhs, rhs).0
+    }
+    fn min(self, rhs: Self) -> Self {
+        f64::min(self, rhs)
+    }
+    fn max(self, rhs: Self) -> Self {
+        f64::max(self, rhs)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Double-precision equivalent of [`Interval`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Interval64<const SOUND: bool = false> {
+    lower: f64,
+    upper: f64,
+}
+
+impl<const SOUND: bool> Interval64<SOUND> {
+    fn new(lower: f64, upper: f64) -> Self {
+        assert!(upper >= lower || (lower.is_nan() && upper.is_nan()));
+        Self { lower, upper }
+    }
+
+    /// Builds an interval from bounds which may be NaN (e.g. from adding
+    /// opposite infinities), returning NaN if either of them is
+    fn new_or_nan(lower: f64, upper: f64) -> Self {
+        if lower.is_nan() || upper.is_nan() {
+            f64::NAN.into()
+        } else {
+            Interval64::new(lower, upper)
+        }
+    }
+
+    fn has_nan(&self) -> bool {
+        self.lower.is_nan() || self.upper.is_nan()
+    }
+
+    fn is_finite(&self) -> bool {
+        self.lower.is_finite() && self.upper.is_finite()
+    }
+
+    /// Builds the tightest interval containing every value in `vs`
+    ///
+    /// NaN values are skipped, unless every value is NaN.
+    fn hull<const N: usize>(vs: [f64; N]) -> Self {
+        let mut lower = vs[0];
+        let mut upper = vs[0];
+        for v in vs {
+            lower = lower.min(v);
+            upper = upper.max(v);
+        }
+        Interval64::new(lower, upper)
+    }
+
+    /// Returns the lower bound for a rounded result
+    ///
+    /// If `SOUND` is set, this is less than or equal to the exact result
+    fn down((v, err): Rounded) -> f64 {
+        if SOUND && err < 0.0 && v.is_finite() {
+            v.prev()
+        } else {
+            v
+        }
+    }
+
+    /// Returns the upper bound for a rounded result
+    ///
+    /// If `SOUND` is set, this is greater than or equal to the exact result
+    fn up((v, err): Rounded) -> f64 {
+        if SOUND && err > 0.0 && v.is_finite() {
+            v.next()
+        } else {
+            v
+        }
+    }
+
+    /// Builds the tightest interval containing the exact result of every
+    /// operation in `vs`
+    fn hull_rounded<const N: usize>(vs: [Rounded; N]) -> Self {
+        let lower = Self::hull(vs.map(Self::down)).lower;
+        let upper = Self::hull(vs.map(Self::up)).upper;
+        Interval64::new(lower, upper)
+    }
+
+    /// Widens the interval to account for error in the math library, if
+    /// `SOUND` is set
+    fn widen(self) -> Self {
+        if !SOUND {
+            return self;
+        }
+        let down = |v: f64| {
+            if v.is_finite() {
+                v - v.abs() * LIBM_ERROR - f64::from_bits(1)
+            } else {
+                v
+            }
+        };
+        let up = |v: f64| -down(-v);
+        Interval64::new(down(self.lower), up(self.upper))
+    }
+
+    /// Returns a range for the output of a function which is periodic with
+    /// period 2π and has its maximum at `peak` (and its minimum half a period
+    /// later), e.g. sine or cosine
+    fn periodic(self, peak: f64, f: fn(f64) -> f64) -> Self {
+        // Infinite bounds would make the remainders below NaN
+        if !self.is_finite() {
+            return f64::NAN.into();
+        }
+        if self.upper - self.lower >= 2.0 * PI {
+            return Interval64::new(-1.0, 1.0);
+        }
+        // Find the first peak and trough at or above the lower bound
+        let two_pi = 2.0 * PI;
+        let first = |p: f64| self.lower + (p - self.lower).rem_euclid(two_pi);
+        let has_peak = first(peak) <= self.upper;
+        let has_trough = first(peak + PI) <= self.upper;
+        let v = Self::hull([f(self.lower), f(self.upper)]).widen();
+        Interval64::new(
+            if has_trough { -1.0 } else { v.lower.max(-1.0) },
+            if has_peak { 1.0 } else { v.upper.min(1.0) },
+        )
+    }
+}
+
+/// Double-precision interval with outward rounding
+///
+/// This is used by the [`SoundEval`](super::SoundEval) family, and by the JIT's
+/// sound interval evaluator for functions which are implemented in Rust.
+pub(crate) type SoundInterval = Interval64<true>;
+
+/// Relative error allowed for results from the system's math library
+///
+/// This is about 8 ulp; most `libm` functions are accurate to within 1 ulp, so
+/// it leaves plenty of headroom.
+const LIBM_ERROR: f64 = 1.0 / (1u64 << 49) as f64;
+
+/// Result of a floating-point operation, along with the rounding error
+///
+/// The second value has the same sign as `exact - rounded`, and is zero if the
+/// operation was exact (or if the error can't be computed, e.g. on overflow).
+type Rounded = (f64, f64);
+
+/// Adds two values, returning the sum and its rounding error (Knuth's TwoSum)
+fn two_sum(a: f64, b: f64) -> Rounded {
+    let s = a + b;
+    let bb = s - a;
+    (s, (a - (s - bb)) + (b - bb))
+}
+
+/// Multiplies two values, returning the product and its rounding error
+fn two_prod(a: f64, b: f64) -> Rounded {
+    let p = a * b;
+    (p, a.mul_add(b, -p))
+}
+
+/// Divides two values, returning the quotient and the sign of its error
+fn two_div(a: f64, b: f64) -> Rounded {
+    let q = a / b;
+    let r = (-q).mul_add(b, a);
+    (q, if b < 0.0 { -r } else { r })
+}
+
+/// Returns the square root of a value, along with the sign of its error
+fn two_sqrt(a: f64) -> Rounded {
+    let s = a.sqrt();
+    (s, (-s).mul_add(s, a))
+}
+
+impl<const SOUND: bool> From<f64> for Interval64<SOUND> {
+    fn from(v: f64) -> Self {
+        Interval64::new(v, v)
+    }
+}
+
+impl<const SOUND: bool> From<f32> for Interval64<SOUND> {
+    fn from(v: f32) -> Self {
+        Interval64::from(v as f64)
+    }
+}
+
+impl<const SOUND: bool> From<Interval> for Interval64<SOUND> {
+    fn from(i: Interval) -> Self {
+        Interval64::new(i.lower() as f64, i.upper() as f64)
+    }
+}
+
+impl<const SOUND: bool> From<Interval64<SOUND>> for Interval {
+    /// Converts to single precision, rounding outwards so that the result
+    /// contains the original interval
+    fn from(i: Interval64<SOUND>) -> Self {
+        if i.has_nan() {
+            return f32::NAN.into();
+        }
+        let mut lower = i.lower as f32;
+        if lower as f64 > i.lower {
+            lower = lower.prev();
+        }
+        let mut upper = i.upper as f32;
+        if (upper as f64) < i.upper {
+            upper = upper.next();
+        }
+        Interval::new(lower, upper)
+    }
+}
+
+impl<const SOUND: bool> Value for Interval64<SOUND> {
+    fn abs(self) -> Self {
+        if self.lower < 0.0 {
+            if self.upper > 0.0 {
+                Interval64::new(0.0, self.upper.max(-self.lower))
+            } else {
+                Interval64::new(-self.upper, -self.lower)
+            }
+        } else {
+            self
+        }
+    }
+    fn sqrt(self) -> Self {
+        if self.lower < 0.0 {
+            if self.upper > 0.0 {
+                Interval64::new(0.0, Self::up(two_sqrt(self.upper)))
+            } else {
+                f64::NAN.into()
+            }
+        } else {
+            Interval64::new(
+                Self::down(two_sqrt(self.lower)),
+                Self::up(two_sqrt(self.upper)),
+            )
+        }
+    }
+    fn recip(self) -> Self {
+        if self.lower > 0.0 || self.upper < 0.0 {
+            Interval64::new(
+                Self::down(two_div(1.0, self.upper)),
+                Self::up(two_div(1.0, self.lower)),
+            )
+        } else {
+            f64::NAN.into()
+        }
+    }
+    fn square(self) -> Self {
+        let (a, b) = (self.lower, self.upper);
+        if b < 0.0 {
+            Interval64::new(
+                Self::down(two_prod(b, b)),
+                Self::up(two_prod(a, a)),
+            )
+        } else if a > 0.0 {
+            Interval64::new(
+                Self::down(two_prod(a, a)),
+                Self::up(two_prod(b, b)),
+            )
+        } else if self.has_nan() {
+            f64::NAN.into()
+        } else {
+            let m = a.abs().max(b.abs());
+            Interval64::new(0.0, Self::up(two_prod(m, m)))
+        }
+    }
+    fn exp(self) -> Self {
+        if self.has_nan() {
+            f64::NAN.into()
+        } else {
+            Interval64::new(self.lower.exp(), self.upper.exp()).widen()
+        }
+    }
+    fn sin(self) -> Self {
+        self.periodic(PI / 2.0, f64::sin)
+    }
+    fn cos(self) -> Self {
+        self.periodic(0.0, f64::cos)
+    }
+    fn tan(self) -> Self {
+        if self.has_nan() {
+            return f64::NAN.into();
+        }
+        let branch = |v: f64| ((v + PI / 2.0) / PI).floor();
+        if self.upper - self.lower >= PI
+            || branch(self.lower) != branch(self.upper)
+        {
+            f64::NAN.into()
+        } else {
+            Interval64::new(self.lower.tan(), self.upper.tan()).widen()
+        }
+    }
+    fn asin(self) -> Self {
+        if self.has_nan() || self.lower > 1.0 || self.upper < -1.0 {
+            f64::NAN.into()
+        } else {
+            Interval64::new(
+                self.lower.max(-1.0).asin(),
+                self.upper.min(1.0).asin(),
+            )
+            .widen()
+        }
+    }
+    fn acos(self) -> Self {
+        if self.has_nan() || self.lower > 1.0 || self.upper < -1.0 {
+            f64::NAN.into()
+        } else {
+            Interval64::new(
+                self.upper.min(1.0).acos(),
+                self.lower.max(-1.0).acos(),
+            )
+            .widen()
+        }
+    }
+    fn atan(self) -> Self {
+        if self.has_nan() {
+            f64::NAN.into()
+        } else {
+            Interval64::new(self.lower.atan(), self.upper.atan()).widen()
+        }
+    }
+    fn atan2(self, rhs: Self) -> Self {
+        if self.has_nan() || rhs.has_nan() {
+            f64::NAN.into()
+        } else if self.lower <= 0.0 && self.upper >= 0.0 && rhs.lower <= 0.0 {
+            // `PI` is slightly smaller than π, so this must be widened too
+            Interval64::new(-PI, PI).widen()
+        } else {
+            let (y0, y1, x0, x1) =
+                (self.lower, self.upper, rhs.lower, rhs.upper);
+            Interval64::hull([
+                y0.atan2(x0),
+                y0.atan2(x1),
+                y1.atan2(x0),
+                y1.atan2(x1),
+            ])
+            .widen()
+        }
+    }
+    fn ln(self) -> Self {
+        if self.lower < 0.0 {
+            if self.upper > 0.0 {
+                Interval64::new(-f64::INFINITY, self.upper.ln()).widen()
+            } else {
+                f64::NAN.into()
+            }
+        } else {
+            Interval64::new(self.lower.ln(), self.upper.ln()).widen()
+        }
+    }
+    fn pow(self, rhs: Self) -> Self {
+        if self.has_nan() || rhs.has_nan() {
+            return f64::NAN.into();
+        }
+        let base = if self.lower >= 0.0 {
+            self
+        } else if rhs.lower == rhs.upper && rhs.lower == rhs.lower as i16 as f64
+        {
+            return self.powi(rhs.lower as i32);
+        } else if rhs.lower == rhs.upper && self.upper >= 0.0 {
+            Interval64::new(0.0, self.upper)
+        } else {
+            return f64::NAN.into();
+        };
+        let (x0, x1, y0, y1) = (base.lower, base.upper, rhs.lower, rhs.upper);
+        Interval64::hull([x0.powf(y0), x0.powf(y1), x1.powf(y0), x1.powf(y1)])
+            .widen()
+    }
+    fn powi(self, n: i32) -> Self {
+        if self.has_nan() {
+            f64::NAN.into()
+        } else if n == 0 {
+            1.0.into()
+        } else if self.lower <= 0.0 && self.upper >= 0.0 {
+            if n < 0 {
+                f64::NAN.into()
+            } else if n % 2 == 0 {
+                let m = self.lower.abs().max(self.upper.abs());
+                Interval64::new(0.0, m.powi(n)).widen()
+            } else {
+                Interval64::new(self.lower.powi(n), self.upper.powi(n)).widen()
+            }
+        } else {
+            Interval64::hull([self.lower.powi(n), self.upper.powi(n)]).widen()
+        }
+    }
+    fn floor(self) -> Self {
+        if self.has_nan() {
+            f64::NAN.into()
+        } else {
+            Interval64::new(self.lower.floor(), self.upper.floor())
+        }
+    }
+    fn ceil(self) -> Self {
+        if self.has_nan() {
+            f64::NAN.into()
+        } else {
+            Interval64::new(self.lower.ceil(), self.upper.ceil())
+        }
+    }
+    fn round(self) -> Self {
+        if self.has_nan() {
+            f64::NAN.into()
+        } else {
+            Interval64::new(self.lower.round(), self.upper.round())
+        }
+    }
+    fn modulo(self, rhs: Self) -> Self {
+        if !self.is_finite()
+            || !rhs.is_finite()
+            || (rhs.lower <= 0.0 && rhs.upper >= 0.0)
+        {
+            return f64::NAN.into();
+        }
+        let m = rhs.abs();
+        if m.lower == m.upper {
+            let b = m.lower;
+            if (self.lower / b).floor() == (self.upper / b).floor() {
+                let a = self.lower.rem_euclid(b);
+                let c = self.upper.rem_euclid(b);
+                return Interval64::hull([a, c]).widen();
+            }
+        } else if self.lower >= 0.0 && self.upper < m.lower {
+            return self;
+        }
+        Interval64::new(0.0, m.upper)
+    }
+    fn lt(self, rhs: Self) -> Self {
+        if self.has_nan() || rhs.has_nan() {
+            f64::NAN.into()
+        } else if self.upper < rhs.lower {
+            1.0.into()
+        } else if self.lower >= rhs.upper {
+            0.0.into()
+        } else {
+            Interval64::new(0.0, 1.0)
+        }
+    }
+    fn eq(self, rhs: Self) -> Self {
+        if self.has_nan() || rhs.has_nan() {
+            f64::NAN.into()
+        } else if self.lower == self.upper
+            && rhs.lower == rhs.upper
+            && self.lower == rhs.lower
+        {
+            1.0.into()
+        } else if self.upper < rhs.lower || rhs.upper < self.lower {
+            0.0.into()
+        } else {
+            Interval64::new(0.0, 1.0)
+        }
+    }
+    fn not(self) -> Self {
+        if self.has_nan() {
+            f64::NAN.into()
+        } else if self.lower == 0.0 && self.upper == 0.0 {
+            1.0.into()
+        } else if self.lower > 0.0 || self.upper < 0.0 {
+            0.0.into()
+        } else {
+            Interval64::new(0.0, 1.0)
+        }
+    }
+}
+
+impl<const SOUND: bool> TracingValue for Interval64<SOUND> {
+    fn and_choice(self, rhs: Self) -> (Self, Choice) {
+        if self.has_nan() || rhs.has_nan() {
+            (f64::NAN.into(), Choice::Both)
+        } else if self.lower == 0.0 && self.upper == 0.0 {
+            (self, Choice::Left)
+        } else if self.lower > 0.0 || self.upper < 0.0 {
+            (rhs, Choice::Right)
+        } else {
+            let out = Interval64::new(rhs.lower.min(0.0), rhs.upper.max(0.0));
+            (out, Choice::Both)
+        }
+    }
+    fn or_choice(self, rhs: Self) -> (Self, Choice) {
+        if self.has_nan() || rhs.has_nan() {
+            (f64::NAN.into(), Choice::Both)
+        } else if self.lower > 0.0 || self.upper < 0.0 {
+            (self, Choice::Left)
+        } else if self.lower == 0.0 && self.upper == 0.0 {
+            (rhs, Choice::Right)
+        } else {
+            let out = Interval64::new(
+                self.lower.min(rhs.lower),
+                self.upper.max(rhs.upper),
+            );
+            (out, Choice::Both)
+        }
+    }
+    fn select_choice(self, lhs: Self, rhs: Self) -> (Self, Choice) {
+        if self.has_nan() || lhs.has_nan() || rhs.has_nan() {
+            (f64::NAN.into(), Choice::Both)
+        } else if self.upper < 0.0 {
+            (lhs, Choice::Left)
+        } else if self.lower >= 0.0 {
+            (rhs, Choice::Right)
+        } else {
+            let out = Interval64::new(
+                lhs.lower.min(rhs.lower),
+                lhs.upper.max(rhs.upper),
+            );
+            (out, Choice::Both)
+        }
+    }
+    fn min_choice(self, rhs: Self) -> (Self, Choice) {
+        if self.has_nan() || rhs.has_nan() {
+            return (f64::NAN.into(), Choice::Both);
+        }
+        let choice = if self.upper < rhs.lower {
+            Choice::Left
+        } else if rhs.upper < self.lower {
+            Choice::Right
+        } else {
+            Choice::Both
+        };
+        let out = Interval64::new(
+            self.lower.min(rhs.lower),
+            self.upper.min(rhs.upper),
+        );
+        (out, choice)
+    }
+    fn max_choice(self, rhs: Self) -> (Self, Choice) {
+        if self.has_nan() || rhs.has_nan() {
+            return (f64::NAN.into(), Choice::Both);
+        }
+        let choice = if self.lower > rhs.upper {
+            Choice::Left
+        } else if rhs.lower > self.upper {
+            Choice::Right
+        } else {
+            Choice::Both
+        };
+        let out = Interval64::new(
+            self.lower.max(rhs.lower),
+            self.upper.max(rhs.upper),
+        );
+        (out, choice)
+    }
+}
+
+impl<const SOUND: bool> std::ops::Add for Interval64<SOUND> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Interval64::new_or_nan(
+            Self::down(two_sum(self.lower, rhs.lower)),
+            Self::up(two_sum(self.upper, rhs.upper)),
+        )
+    }
+}
+
+impl<const SOUND: bool> std::ops::Sub for Interval64<SOUND> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Interval64::new_or_nan(
+            Self::down(two_sum(self.lower, -rhs.upper)),
+            Self::up(two_sum(self.upper, -rhs.lower)),
+        )
+    }
+}
+
+impl<const SOUND: bool> std::ops::Mul for Interval64<SOUND> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        if self.has_nan() || rhs.has_nan() {
+            return f64::NAN.into();
+        }
+        let (a, b, c, d) = (self.lower, self.upper, rhs.lower, rhs.upper);
+        Interval64::hull_rounded([
+            two_prod(a, c),
+            two_prod(a, d),
+            two_prod(b, c),
+            two_prod(b, d),
+        ])
+    }
+}
+
+impl<const SOUND: bool> std::ops::Div for Interval64<SOUND> {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        if self.has_nan() || !(rhs.lower > 0.0 || rhs.upper < 0.0) {
+            return f64::NAN.into();
+        }
+        let (a, b, c, d) = (self.lower, self.upper, rhs.lower, rhs.upper);
+        Interval64::hull_rounded([
+            two_div(a, c),
+            two_div(a, d),
+            two_div(b, c),
+            two_div(b, d),
+        ])
+    }
+}
+
+impl<const SOUND: bool> std::ops::Neg for Interval64<SOUND> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Interval64::new(-self.upper, -self.lower)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Double-precision equivalent of [`Grad`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Grad64 {
+    v: f64,
+    d: [f64; 3],
+}
+
+impl Grad64 {
+    pub(super) fn new(v: f64, dx: f64, dy: f64, dz: f64) -> Self {
+        Self { v, d: [dx, dy, dz] }
+    }
+
+    /// Applies a function of one argument, given its value and derivative
+    fn chain(self, v: f64, d: f64) -> Self {
+        Self {
+            v,
+            d: self.d.map(|g| g * d),
+        }
+    }
+
+    /// Applies a function of two arguments, given its value and partial
+    /// derivatives with respect to each argument
+    fn chain2(self, rhs: Self, v: f64, da: f64, db: f64) -> Self {
+        let mut d = [0.0; 3];
+        for (i, d) in d.iter_mut().enumerate() {
+            *d = da * self.d[i] + db * rhs.d[i];
+        }
+        Self { v, d }
+    }
+}
+
+impl From<f64> for Grad64 {
+    fn from(v: f64) -> Self {
+        Grad64 { v, d: [0.0; 3] }
+    }
+}
+
+impl From<f32> for Grad64 {
+    fn from(v: f32) -> Self {
+        Grad64::from(v as f64)
+    }
+}
+
+impl From<Grad64> for Grad {
+    fn from(g: Grad64) -> Self {
+        Grad::new(g.v as f32, g.d[0] as f32, g.d[1] as f32, g.d[2] as f32)
+    }
+}
+
+impl Value for Grad64 {
+    fn abs(self) -> Self {
+        if self.v < 0.0 {
+            -self
+        } else {
+            self
+        }
+    }
+    fn sqrt(self) -> Self {
+        let v = self.v.sqrt();
+        self.chain(v, 0.5 / v)
+    }
+    fn recip(self) -> Self {
+        self.chain(1.0 / self.v, -1.0 / self.v.powi(2))
+    }
+    fn square(self) -> Self {
+        self * self
+    }
+    fn exp(self) -> Self {
+        let v = self.v.exp();
+        self.chain(v, v)
+    }
+    fn sin(self) -> Self {
+        self.chain(self.v.sin(), self.v.cos())
+    }
+    fn cos(self) -> Self {
+        self.chain(self.v.cos(), -self.v.sin())
+    }
+    fn tan(self) -> Self {
+        let v = self.v.tan();
+        self.chain(v, 1.0 + v * v)
+    }
+    fn asin(self) -> Self {
+        let d = (1.0 - self.v * self.v).sqrt();
+        self.chain(self.v.asin(), 1.0 / d)
+    }
+    fn acos(self) -> Self {
+        let d = (1.0 - self.v * self.v).sqrt();
+        self.chain(self.v.acos(), -1.0 / d)
+    }
+    fn atan(self) -> Self {
+        self.chain(self.v.atan(), 1.0 / (1.0 + self.v * self.v))
+    }
+    fn atan2(self, rhs: Self) -> Self {
+        let (y, x) = (self.v, rhs.v);
+        let d = x * x + y * y;
+        self.chain2(rhs, y.atan2(x), x / d, -y / d)
+    }
+    fn ln(self) -> Self {
+        self.chain(self.v.ln(), 1.0 / self.v)
+    }
+    fn pow(self, rhs: Self) -> Self {
+        let v = self.v.powf(rhs.v);
+        let da = rhs.v * self.v.powf(rhs.v - 1.0);
+        // As with `Grad::pow`, the derivative with respect to the exponent is
+        // skipped wherever the exponent is constant.
+        let db = v * self.v.ln();
+        let mut d = [0.0; 3];
+        for (i, d) in d.iter_mut().enumerate() {
+            *d = da * self.d[i];
+            if rhs.d[i] != 0.0 {
+                *d += db * rhs.d[i];
+            }
+        }
+        Self { v, d }
+    }
+    fn powi(self, n: i32) -> Self {
+        let d = if n == 0 {
+            0.0
+        } else {
+            n as f64 * self.v.powi(n - 1)
+        };
+        self.chain(self.v.powi(n), d)
+    }
+    fn floor(self) -> Self {
+        self.v.floor().into()
+    }
+    fn ceil(self) -> Self {
+        self.v.ceil().into()
+    }
+    fn round(self) -> Self {
+        self.v.round().into()
+    }
+    fn modulo(self, rhs: Self) -> Self {
+        let v = self.v.rem_euclid(rhs.v);
+        let q = ((self.v - v) / rhs.v).round();
+        self.chain2(rhs, v, 1.0, -q)
+    }
+    fn lt(self, rhs: Self) -> Self {
+        ((self.v < rhs.v) as u8 as f64).into()
+    }
+    fn eq(self, rhs: Self) -> Self {
+        ((self.v == rhs.v) as u8 as f64).into()
+    }
+    fn not(self) -> Self {
+        ((self.v == 0.0) as u8 as f64).into()
+    }
+}
+
+impl BulkValue for Grad64 {
+    fn and(self, rhs: Self) -> Self {
+        if self.v == 0.0 {
+            self
+        } else {
+            rhs
+        }
+    }
+    fn or(self, rhs: Self) -> Self {
+        if self.v != 0.0 {
+            self
+        } else {
+            rhs
+        }
+    }
+    fn select(self, lhs: Self, rhs: Self) -> Self {
+        if self.v < 0.0 {
+            lhs
+        } else {
+            rhs
+        }
+    }
+    fn min(self, rhs: Self) -> Self {
+        if self.v < rhs.v {
+            self
+        } else {
+            rhs
+        }
+    }
+    fn max(self, rhs: Self) -> Self {
+        if self.v > rhs.v {
+            self
+        } else {
+            rhs
+        }
+    }
+}
+
+impl std::ops::Add for Grad64 {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        self.chain2(rhs, self.v + rhs.v, 1.0, 1.0)
+    }
+}
+
+impl std::ops::Sub for Grad64 {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self.chain2(rhs, self.v - rhs.v, 1.0, -1.0)
+    }
+}
+
+impl std::ops::Mul for Grad64 {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        self.chain2(rhs, self.v * rhs.v, rhs.v, self.v)
+    }
+}
+
+impl std::ops::Div for Grad64 {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        let d = rhs.v.powi(2);
+        self.chain2(rhs, self.v / rhs.v, 1.0 / rhs.v, -self.v / d)
+    }
+}
+
+impl std::ops::Neg for Grad64 {
+    type Output = Self;
+    fn neg(self) -> Self {
+        self.chain(-self.v, -1.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Applies every operation to every pair of intervals from a set which
+    /// includes infinite and `NaN` bounds, checking that none of them panic
+    fn check_interval_ops<const SOUND: bool>() {
+        let inf = f64::INFINITY;
+        let vs = [-inf, -2.0, -1.0, -0.5, 0.0, 0.5, 1.0, 3.0, inf];
+        let mut is = vec![Interval64::<SOUND>::from(f64::NAN)];
+        for &a in &vs {
+            for &b in vs.iter().filter(|b| **b >= a) {
+                is.push(Interval64::new(a, b));
+            }
+        }
+        for &a in &is {
+            let _ = [
+                a.abs(),
+                a.sqrt(),
+                a.recip(),
+                a.square(),
+                a.exp(),
+                a.sin(),
+                a.cos(),
+                a.tan(),
+                a.asin(),
+                a.acos(),
+                a.atan(),
+                a.ln(),
+                a.floor(),
+                a.ceil(),
+                a.round(),
+                a.not(),
+                -a,
+                a.powi(0),
+                a.powi(3),
+                a.powi(-2),
+            ];
+            for &b in &is {
+                let _ = [
+                    a + b,
+                    a - b,
+                    a * b,
+                    a / b,
+                    a.atan2(b),
+                    a.pow(b),
+                    a.modulo(b),
+                    a.lt(b),
+                    a.eq(b),
+                    a.min_choice(b).0,
+                    a.max_choice(b).0,
+                    a.and_choice(b).0,
+                    a.or_choice(b).0,
+                    a.select_choice(a, b).0,
+                ];
+            }
+        }
+    }
+
+    #[test]
+    fn test_interval_infinite_ops() {
+        check_interval_ops::<false>();
+        check_interval_ops::<true>();
+    }
+}
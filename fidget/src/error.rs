@@ -10,6 +10,9 @@ pub enum Error {
     /// Variable is not present in this `Context`
     #[error("variable is not present in this `Context`")]
     BadVar,
+    /// Node is not a variable
+    #[error("node is not a variable")]
+    NotAVar,
 
     /// `Context` is empty
     #[error("`Context` is empty")]
@@ -28,6 +31,40 @@ pub enum Error {
     /// Empty file
     #[error("empty file")]
     EmptyFile,
+    /// Binary data is malformed
+    #[error("invalid binary data: {0}")]
+    BadBinary(String),
+    /// Error at a particular position in a text input
+    #[error("line {line}, column {column}: {error}")]
+    TextError {
+        /// Line number (1-indexed)
+        line: usize,
+        /// Column number (1-indexed, in characters)
+        column: usize,
+        /// The underlying error
+        error: Box<Error>,
+    },
+    /// Expected a token but found the end of the line
+    #[error("expected {0}")]
+    ExpectedToken(&'static str),
+    /// Found an unexpected token
+    #[error("unexpected token `{0}`")]
+    UnexpectedToken(String),
+    /// Could not parse a number
+    #[error("invalid number `{0}`")]
+    BadNumber(String),
+    /// Function was called with the wrong number of arguments
+    #[error("`{0}` takes {1} argument(s), but {2} were given")]
+    BadArgCount(String, usize, usize),
+    /// Expression is nested more deeply than the parser allows
+    #[error("expression is nested too deeply (the limit is {0})")]
+    TooDeep(usize),
+    /// Variable name cannot be represented in the text format
+    #[error("variable name `{0}` cannot be written as text")]
+    BadVarName(String),
+    /// Binary format version is not supported
+    #[error("unsupported binary format version {0}")]
+    UnsupportedVersion(u32),
 
     /// Choice slice length does not match choice count
     #[error("choice slice length ({0}) does not match choice count ({1})")]
@@ -41,6 +78,10 @@ pub enum Error {
     #[error("var slice length ({0}) does not match var count ({1})")]
     BadVarSlice(usize, usize),
 
+    /// Output count is zero or exceeds the register limit
+    #[error("bad output count ({0}); must be between 1 and {1}")]
+    BadOutputCount(usize, u8),
+
     /// This name is reserved for 3D coordinates
     #[error("this name is reserved for 3D coordinates")]
     ReservedName,
@@ -0,0 +1,331 @@
+//! Fitting variables to sample data
+//!
+//! Given a tape containing variables (from
+//! [`Context::var`](crate::context::Context::var)) and a set of samples, the
+//! [`fit`] function adjusts the variables to minimize the sum of squared
+//! residuals, using the Levenberg-Marquardt algorithm.
+//!
+//! ```
+//! use fidget::{context::Context, eval::Vars, fit, vm};
+//!
+//! // Build a circle with a variable radius
+//! let mut ctx = Context::new();
+//! let x = ctx.x();
+//! let y = ctx.y();
+//! let r = ctx.var("r")?;
+//! let x2 = ctx.square(x)?;
+//! let y2 = ctx.square(y)?;
+//! let sum = ctx.add(x2, y2)?;
+//! let len = ctx.sqrt(sum)?;
+//! let circle = ctx.sub(len, r)?;
+//! let tape = ctx.get_tape::<vm::Eval>(circle)?;
+//!
+//! // Samples on the surface of a circle with radius 2
+//! let samples = [(2.0, 0.0), (0.0, -2.0), (1.2, 1.6)]
+//!     .map(|(x, y)| fit::Sample::new(x, y, 0.0, 0.0));
+//!
+//! let mut vars = Vars::new(&tape);
+//! vars.set("r", 1.0);
+//! vars.set_grad_vars(["r"])?;
+//! let report = fit::fit(&tape, &samples, &mut vars, &Default::default())?;
+//! assert!(report.converged);
+//! assert!((report.values[0] - 2.0).abs() < 1e-4);
+//! # Ok::<(), fidget::Error>(())
+//! ```
+use crate::{
+    eval::{
+        float_slice::FloatSliceEvalData, var_grad_slice::VarGradSliceEvalData,
+        Family, Tape, Vars,
+    },
+    Error,
+};
+use nalgebra::{DMatrix, DVector};
+
+/// A single sample point, with the desired value at that point
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sample {
+    /// X position
+    pub x: f32,
+    /// Y position
+    pub y: f32,
+    /// Z position
+    pub z: f32,
+    /// Target value at this position
+    pub target: f32,
+}
+
+impl Sample {
+    /// Builds a new sample
+    pub fn new(x: f32, y: f32, z: f32, target: f32) -> Self {
+        Self { x, y, z, target }
+    }
+}
+
+/// Settings for [`fit`]
+#[derive(Copy, Clone, Debug)]
+pub struct Settings {
+    /// Maximum number of iterations (i.e. Jacobian evaluations)
+    pub max_iterations: usize,
+
+    /// Relative decrease in error below which the fit is considered converged
+    pub tolerance: f64,
+
+    /// Initial damping factor
+    ///
+    /// Small values behave like Gauss-Newton; larger values behave like
+    /// gradient descent with a short step.
+    pub damping: f64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            max_iterations: 100,
+            tolerance: 1e-10,
+            damping: 1e-3,
+        }
+    }
+}
+
+/// Result of a call to [`fit`]
+#[derive(Clone, Debug)]
+pub struct Report {
+    /// Fitted values, in the order of [`Vars::grad_vars`]
+    pub values: Vec<f32>,
+    /// Number of iterations performed
+    pub iterations: usize,
+    /// Sum of squared residuals with the initial values
+    pub initial_error: f64,
+    /// Sum of squared residuals with the fitted values
+    pub final_error: f64,
+    /// Whether the fit converged before reaching the iteration limit
+    ///
+    /// This is also `false` if the fit stalled, i.e. no step could reduce the
+    /// error even with very large damping.
+    pub converged: bool,
+}
+
+/// Largest damping factor before we give up on finding a better step
+const MAX_DAMPING: f64 = 1e12;
+
+/// Fits variables to the given samples
+///
+/// The variables to fit are selected with [`Vars::set_grad_vars`], and their
+/// starting values are taken from `vars`; other variables are held constant.
+/// When this function returns, `vars` contains the fitted values.
+///
+/// Residuals are calculated with a
+/// [`FloatSliceEval`](crate::eval::FloatSliceEval), and the Jacobian with a
+/// [`VarGradSliceEval`](crate::eval::VarGradSliceEval).
+pub fn fit<F: Family>(
+    tape: &Tape<F>,
+    samples: &[Sample],
+    vars: &mut Vars,
+    settings: &Settings,
+) -> Result<Report, Error> {
+    let xs = samples.iter().map(|s| s.x).collect::<Vec<_>>();
+    let ys = samples.iter().map(|s| s.y).collect::<Vec<_>>();
+    let zs = samples.iter().map(|s| s.z).collect::<Vec<_>>();
+
+    let float_eval = tape.new_float_slice_evaluator();
+    let grad_eval = tape.new_var_grad_slice_evaluator();
+    let mut float_data = FloatSliceEvalData::<F>::default();
+    let mut grad_data = VarGradSliceEvalData::default();
+
+    let grad_vars = vars.grad_vars().to_vec();
+    let get_values = |vars: &Vars| {
+        grad_vars
+            .iter()
+            .map(|i| vars.as_slice()[*i as usize])
+            .collect::<Vec<f32>>()
+    };
+    let sum_squares = |out: &[f32]| -> f64 {
+        out.iter()
+            .zip(samples)
+            .map(|(v, s)| (*v as f64 - s.target as f64).powi(2))
+            .sum()
+    };
+
+    let out = float_eval.eval_with(
+        &xs,
+        &ys,
+        &zs,
+        vars.as_slice(),
+        &mut float_data,
+    )?;
+    let initial_error = sum_squares(out);
+    let mut err = initial_error;
+    let mut damping = settings.damping;
+    let mut converged = grad_vars.is_empty() || err == 0.0;
+    let mut iterations = 0;
+
+    while !converged && iterations < settings.max_iterations {
+        iterations += 1;
+
+        // Build the normal equations, JᵀJ δ = -Jᵀr
+        let n = grad_vars.len();
+        let out = grad_eval.eval_with(&xs, &ys, &zs, vars, &mut grad_data)?;
+        let r = DVector::from_iterator(
+            samples.len(),
+            out.v
+                .iter()
+                .zip(samples)
+                .map(|(v, s)| (v - s.target) as f64),
+        );
+        let jac = DMatrix::from_fn(samples.len(), n, |i, k| out.d[k][i] as f64);
+        let jtj = jac.transpose() * &jac;
+        let jtr = jac.transpose() * r;
+
+        let start = get_values(vars);
+        let mut improved = false;
+        while damping < MAX_DAMPING {
+            // Marquardt's scaling, with a small floor so that variables which
+            // don't affect the output still give a solvable system
+            let mut a = jtj.clone();
+            for k in 0..n {
+                a[(k, k)] += damping * jtj[(k, k)].max(1e-12);
+            }
+            let Some(step) = a.cholesky().map(|c| c.solve(&-&jtr)) else {
+                damping *= 10.0;
+                continue;
+            };
+            for (k, i) in grad_vars.iter().enumerate() {
+                vars.as_mut_slice()[*i as usize] = start[k] + step[k] as f32;
+            }
+            let out = float_eval.eval_with(
+                &xs,
+                &ys,
+                &zs,
+                vars.as_slice(),
+                &mut float_data,
+            )?;
+            let next = sum_squares(out);
+            if next <= err {
+                converged =
+                    next == 0.0 || err - next <= settings.tolerance * err;
+                err = next;
+                damping = (damping / 10.0).max(f64::EPSILON);
+                improved = true;
+                break;
+            }
+            damping *= 10.0;
+        }
+
+        if !improved {
+            // Restore the best values found so far and give up
+            for (k, i) in grad_vars.iter().enumerate() {
+                vars.as_mut_slice()[*i as usize] = start[k];
+            }
+            break;
+        }
+    }
+
+    Ok(Report {
+        values: get_values(vars),
+        iterations,
+        initial_error,
+        final_error: err,
+        converged,
+    })
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::context::Context;
+
+    fn circle_samples(cx: f32, cy: f32, r: f32) -> Vec<Sample> {
+        (0..16)
+            .map(|i| {
+                let t = i as f32 / 16.0 * std::f32::consts::TAU;
+                Sample::new(cx + r * t.cos(), cy + r * t.sin(), 0.0, 0.0)
+            })
+            .collect()
+    }
+
+    fn test_fit_circle<F: Family>() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let cx = ctx.var("cx").unwrap();
+        let cy = ctx.var("cy").unwrap();
+        let r = ctx.var("r").unwrap();
+        let dx = ctx.sub(x, cx).unwrap();
+        let dy = ctx.sub(y, cy).unwrap();
+        let dx2 = ctx.square(dx).unwrap();
+        let dy2 = ctx.square(dy).unwrap();
+        let sum = ctx.add(dx2, dy2).unwrap();
+        let len = ctx.sqrt(sum).unwrap();
+        let circle = ctx.sub(len, r).unwrap();
+        let tape = ctx.get_tape::<F>(circle).unwrap();
+
+        let samples = circle_samples(0.5, -0.25, 1.5);
+        let mut vars = Vars::new(&tape);
+        vars.bind([("cx", 0.0), ("cy", 0.0), ("r", 1.0)].into_iter());
+        vars.set_grad_vars(["r", "cx", "cy"]).unwrap();
+
+        let report = fit(&tape, &samples, &mut vars, &Settings::default());
+        let report = report.unwrap();
+        assert!(report.converged);
+        assert!(report.final_error < 1e-8, "{report:?}");
+        assert!(report.initial_error > report.final_error);
+        for (v, e) in report.values.iter().zip([1.5, 0.5, -0.25]) {
+            assert!((v - e).abs() < 1e-4, "{report:?}");
+        }
+
+        // Fit only the radius, holding the center fixed at the wrong position
+        let mut vars = Vars::new(&tape);
+        vars.bind([("cx", 0.5), ("cy", 0.0), ("r", 1.0)].into_iter());
+        vars.set_grad_vars(["r"]).unwrap();
+        let report = fit(&tape, &samples, &mut vars, &Settings::default());
+        let report = report.unwrap();
+        assert!(report.converged);
+        assert!(report.final_error > 0.0);
+        assert_eq!(report.values.len(), 1);
+        let names = tape.vars();
+        assert_eq!(vars.as_slice()[names["cx"] as usize], 0.5);
+        assert_eq!(vars.as_slice()[names["cy"] as usize], 0.0);
+    }
+
+    #[test]
+    fn test_fit_circle_vm() {
+        test_fit_circle::<crate::vm::Eval>()
+    }
+
+    #[cfg(feature = "jit")]
+    #[test]
+    fn test_fit_circle_jit() {
+        test_fit_circle::<crate::jit::Eval>()
+    }
+
+    #[test]
+    fn test_fit_trivial() {
+        let mut ctx = Context::new();
+        let x = ctx.x();
+        let a = ctx.var("a").unwrap();
+        let b = ctx.var("b").unwrap();
+        let ax = ctx.mul(a, x).unwrap();
+        let line = ctx.add(ax, b).unwrap();
+        let tape = ctx.get_tape::<crate::vm::Eval>(line).unwrap();
+        let samples = [(0.0, 1.0), (1.0, 3.0), (2.0, 5.0)]
+            .map(|(x, t)| Sample::new(x, 0.0, 0.0, t));
+
+        // No variables to fit
+        let mut vars = Vars::new(&tape);
+        let report = fit(&tape, &samples, &mut vars, &Settings::default());
+        let report = report.unwrap();
+        assert_eq!(report.iterations, 0);
+        assert!(report.values.is_empty());
+
+        // A linear model converges quickly, since the Jacobian is constant
+        vars.set_grad_vars(["a", "b"]).unwrap();
+        let report = fit(&tape, &samples, &mut vars, &Settings::default());
+        let report = report.unwrap();
+        assert!(report.converged);
+        assert!(report.iterations < 10, "{report:?}");
+        assert!((report.values[0] - 2.0).abs() < 1e-3);
+        assert!((report.values[1] - 1.0).abs() < 1e-3);
+    }
+}
@@ -16,6 +16,7 @@ pub const SIMD_WIDTH: usize = 4;
 /// | vars     | `x3`     | `*const f32`        |
 /// | out      | `x4`     | `*mut [f32; 4]`     |
 /// | size     | `x5`     | `u64`               |
+/// | W        | `x6`     | `*const [f32; 4]`   |
 ///
 /// The arrays (other than `vars`) must be an even multiple of 4 floats, since
 /// we're using NEON and 128-bit wide operations for everything.  The `vars`
@@ -42,6 +43,10 @@ impl AssemblerT for FloatSliceAssembler {
 
         );
         out.prepare_stack(slot_count);
+        dynasm!(out.ops
+            // Stash the W pointer on the stack, since it's not preserved
+            ; stur x6, [x29, #-88]
+        );
 
         dynasm!(out.ops
             // The loop returns here, and we check whether we need to loop
@@ -53,6 +58,7 @@ impl AssemblerT for FloatSliceAssembler {
             //  x3: vars input array pointer (non-advancing)
             //  x4: output array pointer
             //  x5: number of points to evaluate
+            //  [x29, #-88]: w input array pointer
             //
             // We'll be advancing x0, x1, x2 here (and decrementing x5 by 4);
             // x4 is advanced in finalize().
@@ -85,6 +91,12 @@ impl AssemblerT for FloatSliceAssembler {
             ; ldr q1, [x1], #16
             ; ldr q2, [x2], #16
             ; sub x5, x5, #4 // We handle 4 items at a time
+
+            // W is loaded through its pointer on the stack
+            ; ldur x15, [x29, #-88]
+            ; ldr q7, [x15], #16
+            ; stur x15, [x29, #-88]
+            ; stur q7, [x29, #-80]
         );
 
         Self(out)
@@ -110,7 +122,13 @@ impl AssemblerT for FloatSliceAssembler {
     }
     /// Copies the given input to `out_reg`
     fn build_input(&mut self, out_reg: u8, src_arg: u8) {
-        dynasm!(self.0.ops ; mov V(reg(out_reg)).b16, V(src_arg as u32).b16);
+        if src_arg == 3 {
+            dynasm!(self.0.ops ; ldur Q(reg(out_reg)), [x29, #-80]);
+        } else {
+            dynasm!(self.0.ops
+                ; mov V(reg(out_reg)).b16, V(src_arg as u32).b16
+            );
+        }
     }
     fn build_var(&mut self, out_reg: u8, src_arg: u32) {
         assert!(src_arg * 4 < 16384);
@@ -143,6 +161,138 @@ impl AssemblerT for FloatSliceAssembler {
             ; fmul V(reg(out_reg)).s4, V(reg(lhs_reg)).s4, V(reg(lhs_reg)).s4
         )
     }
+    fn build_exp(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn float_exp(v: &mut [f32; SIMD_WIDTH]) {
+            for f in v {
+                *f = f.exp();
+            }
+        }
+        self.call_fn_unary(out_reg, lhs_reg, float_exp);
+    }
+    fn build_sin(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn float_sin(v: &mut [f32; SIMD_WIDTH]) {
+            for f in v {
+                *f = f.sin();
+            }
+        }
+        self.call_fn_unary(out_reg, lhs_reg, float_sin);
+    }
+    fn build_cos(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn float_cos(v: &mut [f32; SIMD_WIDTH]) {
+            for f in v {
+                *f = f.cos();
+            }
+        }
+        self.call_fn_unary(out_reg, lhs_reg, float_cos);
+    }
+    fn build_tan(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn float_tan(v: &mut [f32; SIMD_WIDTH]) {
+            for f in v {
+                *f = f.tan();
+            }
+        }
+        self.call_fn_unary(out_reg, lhs_reg, float_tan);
+    }
+    fn build_asin(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn float_asin(v: &mut [f32; SIMD_WIDTH]) {
+            for f in v {
+                *f = f.asin();
+            }
+        }
+        self.call_fn_unary(out_reg, lhs_reg, float_asin);
+    }
+    fn build_acos(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn float_acos(v: &mut [f32; SIMD_WIDTH]) {
+            for f in v {
+                *f = f.acos();
+            }
+        }
+        self.call_fn_unary(out_reg, lhs_reg, float_acos);
+    }
+    fn build_atan(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn float_atan(v: &mut [f32; SIMD_WIDTH]) {
+            for f in v {
+                *f = f.atan();
+            }
+        }
+        self.call_fn_unary(out_reg, lhs_reg, float_atan);
+    }
+    fn build_atan2(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "C" fn float_atan2(
+            lhs: &mut [f32; SIMD_WIDTH],
+            rhs: &[f32; SIMD_WIDTH],
+        ) {
+            for (a, b) in lhs.iter_mut().zip(rhs) {
+                *a = a.atan2(*b);
+            }
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, float_atan2);
+    }
+    fn build_ln(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn float_ln(v: &mut [f32; SIMD_WIDTH]) {
+            for f in v {
+                *f = f.ln();
+            }
+        }
+        self.call_fn_unary(out_reg, lhs_reg, float_ln);
+    }
+    fn build_pow(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "C" fn float_pow(
+            lhs: &mut [f32; SIMD_WIDTH],
+            rhs: &[f32; SIMD_WIDTH],
+        ) {
+            for (a, b) in lhs.iter_mut().zip(rhs) {
+                *a = a.powf(*b);
+            }
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, float_pow);
+    }
+    fn build_powi(&mut self, out_reg: u8, lhs_reg: u8, n: i32) {
+        extern "C" fn float_powi(
+            lhs: &mut [f32; SIMD_WIDTH],
+            rhs: &[f32; SIMD_WIDTH],
+        ) {
+            for (a, b) in lhs.iter_mut().zip(rhs) {
+                *a = a.powi(*b as i32);
+            }
+        }
+        let imm = self.load_imm(n as f32);
+        self.call_fn_binary(out_reg, lhs_reg, imm, float_powi);
+    }
+    fn build_floor(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; frintm V(reg(out_reg)).s4, V(reg(lhs_reg)).s4
+        )
+    }
+    fn build_ceil(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; frintp V(reg(out_reg)).s4, V(reg(lhs_reg)).s4
+        )
+    }
+    fn build_round(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; frinta V(reg(out_reg)).s4, V(reg(lhs_reg)).s4
+        )
+    }
+    fn build_mod(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "C" fn float_mod(
+            lhs: &mut [f32; SIMD_WIDTH],
+            rhs: &[f32; SIMD_WIDTH],
+        ) {
+            for (a, b) in lhs.iter_mut().zip(rhs) {
+                *a = a.rem_euclid(*b);
+            }
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, float_mod);
+    }
+    fn build_not(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops
+            // Build a mask of (lhs == 0), then convert it to 0.0 / 1.0
+            ; fcmeq V(reg(out_reg)).s4, V(reg(lhs_reg)).s4, 0.0
+            ; fmov v7.s4, #1.0
+            ; and V(reg(out_reg)).b16, V(reg(out_reg)).b16, v7.b16
+        )
+    }
     fn build_add(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
         dynasm!(self.0.ops
             ; fadd V(reg(out_reg)).s4, V(reg(lhs_reg)).s4, V(reg(rhs_reg)).s4
@@ -173,6 +323,50 @@ impl AssemblerT for FloatSliceAssembler {
             ; fmin V(reg(out_reg)).s4, V(reg(lhs_reg)).s4, V(reg(rhs_reg)).s4
         )
     }
+    fn build_lt(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; fcmgt V(reg(out_reg)).s4, V(reg(rhs_reg)).s4, V(reg(lhs_reg)).s4
+            ; fmov v7.s4, #1.0
+            ; and V(reg(out_reg)).b16, V(reg(out_reg)).b16, v7.b16
+        )
+    }
+    fn build_eq(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; fcmeq V(reg(out_reg)).s4, V(reg(lhs_reg)).s4, V(reg(rhs_reg)).s4
+            ; fmov v7.s4, #1.0
+            ; and V(reg(out_reg)).b16, V(reg(out_reg)).b16, v7.b16
+        )
+    }
+    fn build_and(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            // Pick lhs where it is zero, and rhs everywhere else
+            ; fcmeq v4.s4, V(reg(lhs_reg)).s4, 0.0
+            ; bsl v4.b16, V(reg(lhs_reg)).b16, V(reg(rhs_reg)).b16
+            ; mov V(reg(out_reg)).b16, v4.b16
+        )
+    }
+    fn build_or(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            // Pick rhs where lhs is zero, and lhs everywhere else
+            ; fcmeq v4.s4, V(reg(lhs_reg)).s4, 0.0
+            ; bsl v4.b16, V(reg(rhs_reg)).b16, V(reg(lhs_reg)).b16
+            ; mov V(reg(out_reg)).b16, v4.b16
+        )
+    }
+    fn build_select(
+        &mut self,
+        out_reg: u8,
+        cond_reg: u8,
+        lhs_reg: u8,
+        rhs_reg: u8,
+    ) {
+        dynasm!(self.0.ops
+            // Pick lhs where cond is less than zero, and rhs everywhere else
+            ; fcmlt v4.s4, V(reg(cond_reg)).s4, 0.0
+            ; bsl v4.b16, V(reg(lhs_reg)).b16, V(reg(rhs_reg)).b16
+            ; mov V(reg(out_reg)).b16, v4.b16
+        )
+    }
 
     /// Loads an immediate into register V4, using W9 as an intermediary
     fn load_imm(&mut self, imm: f32) -> u8 {
@@ -185,16 +379,122 @@ impl AssemblerT for FloatSliceAssembler {
         IMM_REG.wrapping_sub(OFFSET)
     }
 
-    fn finalize(mut self, out_reg: u8) -> Result<Mmap, Error> {
+    fn finalize(mut self, output_count: u8) -> Result<Mmap, Error> {
+        for i in 0..output_count {
+            dynasm!(self.0.ops
+                // Prepare our return value, writing to the pointer in x4
+                // It's fine to overwrite X at this point in V0, since we're not
+                // using it anymore.
+                ; mov v0.d[0], V(reg(i)).d[1]
+                ; stp D(reg(i)), d0, [x4], #16
+            );
+        }
         dynasm!(self.0.ops
-            // Prepare our return value, writing to the pointer in x4
-            // It's fine to overwrite X at this point in V0, since we're not
-            // using it anymore.
-            ; mov v0.d[0], V(reg(out_reg)).d[1]
-            ; stp D(reg(out_reg)), d0, [x4], #16
             ; b ->L
         );
 
         self.0.ops.finalize()
     }
 }
+
+#[cfg(target_arch = "aarch64")]
+impl FloatSliceAssembler {
+    /// Offset of the scratch arrays used to pass arguments to Rust functions
+    const CALL_SCRATCH: u32 = 96 + 16 * REGISTER_LIMIT as u32;
+
+    /// Stack space used by [`Self::backup_registers`]: 6 pointers + 3 inputs +
+    /// 24 tape registers + 2 scratch arrays, already 16-byte aligned
+    const CALL_STACK_SIZE: u32 = Self::CALL_SCRATCH + 32;
+
+    /// Saves our argument pointers, X/Y/Z inputs, and tape registers to the
+    /// stack
+    ///
+    /// The callee is only required to preserve the lower 64 bits of `v8-15`,
+    /// so this must be called before calling into a Rust function.
+    fn backup_registers(&mut self) {
+        dynasm!(self.0.ops
+            ; sub sp, sp, #(Self::CALL_STACK_SIZE)
+            ; stp x0, x1, [sp]
+            ; stp x2, x3, [sp, #16]
+            ; stp x4, x5, [sp, #32]
+        );
+        for i in 0..3 {
+            dynasm!(self.0.ops ; str Q(i), [sp, #(48 + 16 * i)]);
+        }
+        for i in 0..REGISTER_LIMIT {
+            let offset = 96 + 16 * i as u32;
+            dynasm!(self.0.ops ; str Q(reg(i)), [sp, #(offset)]);
+        }
+    }
+
+    /// Calls the function at `addr`, then restores the registers saved by
+    /// [`Self::backup_registers`]
+    ///
+    /// The function modifies the first scratch array in place; its contents
+    /// are written to `out_reg` afterwards.
+    fn call_and_restore(&mut self, out_reg: u8, addr: u64) {
+        dynasm!(self.0.ops
+            ; movz x9, #((addr >> 48) as u32 & 0xffff), lsl 48
+            ; movk x9, #((addr >> 32) as u32 & 0xffff), lsl 32
+            ; movk x9, #((addr >> 16) as u32 & 0xffff), lsl 16
+            ; movk x9, #(addr as u32 & 0xffff)
+            ; blr x9
+        );
+        for i in 0..REGISTER_LIMIT {
+            let offset = 96 + 16 * i as u32;
+            dynasm!(self.0.ops ; ldr Q(reg(i)), [sp, #(offset)]);
+        }
+        for i in 0..3 {
+            dynasm!(self.0.ops ; ldr Q(i), [sp, #(48 + 16 * i)]);
+        }
+        dynasm!(self.0.ops
+            ; ldr Q(reg(out_reg)), [sp, #(Self::CALL_SCRATCH)]
+            ; ldp x4, x5, [sp, #32]
+            ; ldp x2, x3, [sp, #16]
+            ; ldp x0, x1, [sp]
+            ; add sp, sp, #(Self::CALL_STACK_SIZE)
+        );
+    }
+
+    /// Calls a Rust function which modifies an array in place, writing the
+    /// result to `out_reg`
+    ///
+    /// The argument is written to a scratch array on the stack, and a pointer
+    /// to that array is passed in `x0`.
+    fn call_fn_unary(
+        &mut self,
+        out_reg: u8,
+        arg_reg: u8,
+        f: extern "C" fn(&mut [f32; SIMD_WIDTH]),
+    ) {
+        let scratch = Self::CALL_SCRATCH;
+        self.backup_registers();
+        dynasm!(self.0.ops
+            ; str Q(reg(arg_reg)), [sp, #(scratch)]
+            ; add x0, sp, #(scratch)
+        );
+        self.call_and_restore(out_reg, f as usize as u64);
+    }
+
+    /// Calls a binary Rust function which writes its result into its first
+    /// argument, writing that result to `out_reg`
+    ///
+    /// Pointers to the two scratch arrays are passed in `x0` and `x1`.
+    fn call_fn_binary(
+        &mut self,
+        out_reg: u8,
+        lhs_reg: u8,
+        rhs_reg: u8,
+        f: extern "C" fn(&mut [f32; SIMD_WIDTH], &[f32; SIMD_WIDTH]),
+    ) {
+        let scratch = Self::CALL_SCRATCH;
+        self.backup_registers();
+        dynasm!(self.0.ops
+            ; str Q(reg(lhs_reg)), [sp, #(scratch)]
+            ; str Q(reg(rhs_reg)), [sp, #(scratch + 16)]
+            ; add x0, sp, #(scratch)
+            ; add x1, sp, #(scratch + 16)
+        );
+        self.call_and_restore(out_reg, f as usize as u64);
+    }
+}
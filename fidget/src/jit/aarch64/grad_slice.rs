@@ -20,6 +20,7 @@ use dynasmrt::{dynasm, DynasmApi, DynasmLabelApi};
 /// | `vars`     | `x3`     | `*const f32`       |
 /// | `out`      | `x4`     | `*const [f32; 4]`  |
 /// | `count`    | `x5`     | `u64`              |
+/// | W          | `x6`     | `*const f32`       |
 ///
 /// During evaluation, X, Y, and Z are stored in `V0-3.S4`.  Each SIMD register
 /// is in the order `[value, dx, dy, dz]`, e.g. the value for X is in `V0.S0`.
@@ -40,6 +41,10 @@ impl AssemblerT for GradSliceAssembler {
             ; stp   d14, d15, [sp, #-16]!
         );
         out.prepare_stack(slot_count);
+        dynasm!(out.ops
+            // Stash the W pointer on the stack, since it's not preserved
+            ; stur x6, [x29, #-88]
+        );
 
         dynasm!(out.ops
             // The loop returns here, and we check whether we need to loop
@@ -51,6 +56,7 @@ impl AssemblerT for GradSliceAssembler {
             //  x3: vars input array pointer (non-advancing)
             //  x4: output array pointer
             //  x5: number of points to evaluate
+            //  [x29, #-88]: w input array pointer
             //
             // We'll be advancing x0, x1, x2 here (and decrementing x5 by 1);
             // x3 is advanced in finalize().
@@ -86,6 +92,13 @@ impl AssemblerT for GradSliceAssembler {
             ; mov v2.S[3], v6.S[0]
             ; sub x5, x5, #1 // We handle 1 item at a time
 
+            // W is loaded through its pointer on the stack, with a zero
+            // gradient (loading into `s7` clears the upper lanes)
+            ; ldur x15, [x29, #-88]
+            ; ldr s7, [x15], #4
+            ; stur x15, [x29, #-88]
+            ; stur q7, [x29, #-80]
+
             // Math begins below!
         );
 
@@ -111,7 +124,13 @@ impl AssemblerT for GradSliceAssembler {
     }
     /// Copies the given input to `out_reg`
     fn build_input(&mut self, out_reg: u8, src_arg: u8) {
-        dynasm!(self.0.ops ; mov V(reg(out_reg)).b16, V(src_arg as u32).b16);
+        if src_arg == 3 {
+            dynasm!(self.0.ops ; ldur Q(reg(out_reg)), [x29, #-80]);
+        } else {
+            dynasm!(self.0.ops
+                ; mov V(reg(out_reg)).b16, V(src_arg as u32).b16
+            );
+        }
     }
     fn build_var(&mut self, out_reg: u8, src_arg: u32) {
         assert!(src_arg * 4 < 16384);
@@ -176,6 +195,98 @@ impl AssemblerT for GradSliceAssembler {
             // out is [v*v, 2*v*dx, 2*v*dy, 2*v*dz]
         )
     }
+    fn build_exp(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn grad_exp(v: Grad) -> Grad {
+            v.exp()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, grad_exp);
+    }
+    fn build_sin(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn grad_sin(v: Grad) -> Grad {
+            v.sin()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, grad_sin);
+    }
+    fn build_cos(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn grad_cos(v: Grad) -> Grad {
+            v.cos()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, grad_cos);
+    }
+    fn build_tan(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn grad_tan(v: Grad) -> Grad {
+            v.tan()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, grad_tan);
+    }
+    fn build_asin(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn grad_asin(v: Grad) -> Grad {
+            v.asin()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, grad_asin);
+    }
+    fn build_acos(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn grad_acos(v: Grad) -> Grad {
+            v.acos()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, grad_acos);
+    }
+    fn build_atan(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn grad_atan(v: Grad) -> Grad {
+            v.atan()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, grad_atan);
+    }
+    fn build_atan2(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "C" fn grad_atan2(lhs: Grad, rhs: Grad) -> Grad {
+            lhs.atan2(rhs)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, grad_atan2);
+    }
+    fn build_ln(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn grad_ln(v: Grad) -> Grad {
+            v.ln()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, grad_ln);
+    }
+    fn build_pow(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "C" fn grad_pow(lhs: Grad, rhs: Grad) -> Grad {
+            lhs.pow(rhs)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, grad_pow);
+    }
+    fn build_powi(&mut self, out_reg: u8, lhs_reg: u8, n: i32) {
+        extern "C" fn grad_powi(lhs: Grad, rhs: Grad) -> Grad {
+            lhs.powi(rhs.v as i32)
+        }
+        let imm = self.load_imm(n as f32);
+        self.call_fn_binary(out_reg, lhs_reg, imm, grad_powi);
+    }
+    fn build_floor(&mut self, out_reg: u8, lhs_reg: u8) {
+        // Writing to the scalar register clears the partial derivatives
+        dynasm!(self.0.ops ; frintm S(reg(out_reg)), S(reg(lhs_reg)))
+    }
+    fn build_ceil(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops ; frintp S(reg(out_reg)), S(reg(lhs_reg)))
+    }
+    fn build_round(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops ; frinta S(reg(out_reg)), S(reg(lhs_reg)))
+    }
+    fn build_mod(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "C" fn grad_mod(lhs: Grad, rhs: Grad) -> Grad {
+            lhs.modulo(rhs)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, grad_mod);
+    }
+    fn build_not(&mut self, out_reg: u8, lhs_reg: u8) {
+        // The scalar comparison clears the partial derivatives, and the mask
+        // is then converted to 0.0 / 1.0
+        dynasm!(self.0.ops
+            ; fcmeq S(reg(out_reg)), S(reg(lhs_reg)), 0.0
+            ; fmov s7, #1.0
+            ; and V(reg(out_reg)).b16, V(reg(out_reg)).b16, v7.b16
+        )
+    }
     fn build_add(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
         dynasm!(self.0.ops
             ; fadd V(reg(out_reg)).s4, V(reg(lhs_reg)).s4, V(reg(rhs_reg)).s4
@@ -263,6 +374,59 @@ impl AssemblerT for GradSliceAssembler {
             // end:
         )
     }
+    fn build_lt(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; fcmgt S(reg(out_reg)), S(reg(rhs_reg)), S(reg(lhs_reg))
+            ; fmov s7, #1.0
+            ; and V(reg(out_reg)).b16, V(reg(out_reg)).b16, v7.b16
+        )
+    }
+    fn build_eq(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; fcmeq S(reg(out_reg)), S(reg(lhs_reg)), S(reg(rhs_reg))
+            ; fmov s7, #1.0
+            ; and V(reg(out_reg)).b16, V(reg(out_reg)).b16, v7.b16
+        )
+    }
+    fn build_and(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; fcmp S(reg(lhs_reg)), 0.0
+            ; b.ne #12 // -> rhs
+            ; mov V(reg(out_reg)).b16, V(reg(lhs_reg)).b16
+            ; b #8 // -> end
+            // rhs:
+            ; mov V(reg(out_reg)).b16, V(reg(rhs_reg)).b16
+            // end:
+        )
+    }
+    fn build_or(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; fcmp S(reg(lhs_reg)), 0.0
+            ; b.ne #12 // -> lhs
+            ; mov V(reg(out_reg)).b16, V(reg(rhs_reg)).b16
+            ; b #8 // -> end
+            // lhs:
+            ; mov V(reg(out_reg)).b16, V(reg(lhs_reg)).b16
+            // end:
+        )
+    }
+    fn build_select(
+        &mut self,
+        out_reg: u8,
+        cond_reg: u8,
+        lhs_reg: u8,
+        rhs_reg: u8,
+    ) {
+        dynasm!(self.0.ops
+            ; fcmp S(reg(cond_reg)), 0.0
+            ; b.mi #12 // -> lhs (not taken for NaN)
+            ; mov V(reg(out_reg)).b16, V(reg(rhs_reg)).b16
+            ; b #8 // -> end
+            // lhs:
+            ; mov V(reg(out_reg)).b16, V(reg(lhs_reg)).b16
+            // end:
+        )
+    }
 
     /// Loads an immediate into register S4, using W9 as an intermediary
     fn load_imm(&mut self, imm: f32) -> u8 {
@@ -275,13 +439,132 @@ impl AssemblerT for GradSliceAssembler {
         IMM_REG.wrapping_sub(OFFSET)
     }
 
-    fn finalize(mut self, out_reg: u8) -> Result<Mmap, Error> {
+    fn finalize(mut self, output_count: u8) -> Result<Mmap, Error> {
+        for i in 0..output_count {
+            dynasm!(self.0.ops
+                // Prepare our return value, writing to the pointer in x4
+                ; str Q(reg(i)), [x4], #16
+            );
+        }
         dynasm!(self.0.ops
-            // Prepare our return value, writing to the pointer in x4
-            ; str Q(reg(out_reg)), [x4], #16
             ; b ->L // Jump back to the loop start
         );
 
         self.0.ops.finalize()
     }
 }
+
+impl GradSliceAssembler {
+    /// Stack space used by [`Self::backup_registers`]: 6 pointers + 3 inputs +
+    /// 24 tape registers, already 16-byte aligned
+    const CALL_STACK_SIZE: u32 = 480;
+
+    /// Saves our argument pointers, X/Y/Z inputs, and tape registers to the
+    /// stack
+    ///
+    /// The callee is only required to preserve the lower 64 bits of `v8-15`,
+    /// so this must be called before calling into a Rust function.
+    fn backup_registers(&mut self) {
+        dynasm!(self.0.ops
+            ; sub sp, sp, #(Self::CALL_STACK_SIZE)
+            ; stp x0, x1, [sp]
+            ; stp x2, x3, [sp, #16]
+            ; stp x4, x5, [sp, #32]
+        );
+        for i in 0..3 {
+            dynasm!(self.0.ops ; str Q(i), [sp, #(48 + 16 * i)]);
+        }
+        for i in 0..REGISTER_LIMIT {
+            let offset = 96 + 16 * i as u32;
+            dynasm!(self.0.ops ; str Q(reg(i)), [sp, #(offset)]);
+        }
+    }
+
+    /// Restores the registers saved by [`Self::backup_registers`]
+    fn restore_registers(&mut self) {
+        for i in 0..REGISTER_LIMIT {
+            let offset = 96 + 16 * i as u32;
+            dynasm!(self.0.ops ; ldr Q(reg(i)), [sp, #(offset)]);
+        }
+        for i in 0..3 {
+            dynasm!(self.0.ops ; ldr Q(i), [sp, #(48 + 16 * i)]);
+        }
+        dynasm!(self.0.ops
+            ; ldp x4, x5, [sp, #32]
+            ; ldp x2, x3, [sp, #16]
+            ; ldp x0, x1, [sp]
+            ; add sp, sp, #(Self::CALL_STACK_SIZE)
+        );
+    }
+
+    /// Calls a unary Rust function, writing its result to `out_reg`
+    ///
+    /// A `Grad` is passed and returned in `(s0, s1, s2, s3)`.
+    fn call_fn_unary(
+        &mut self,
+        out_reg: u8,
+        arg_reg: u8,
+        f: extern "C" fn(Grad) -> Grad,
+    ) {
+        let addr = f as usize as u64;
+        self.backup_registers();
+        dynasm!(self.0.ops
+            ; mov s0, V(reg(arg_reg)).s[0]
+            ; mov s1, V(reg(arg_reg)).s[1]
+            ; mov s2, V(reg(arg_reg)).s[2]
+            ; mov s3, V(reg(arg_reg)).s[3]
+            ; movz x9, #((addr >> 48) as u32 & 0xffff), lsl 48
+            ; movk x9, #((addr >> 32) as u32 & 0xffff), lsl 32
+            ; movk x9, #((addr >> 16) as u32 & 0xffff), lsl 16
+            ; movk x9, #(addr as u32 & 0xffff)
+            ; blr x9
+            // Stash the result in a scratch register while restoring
+            ; mov v7.s[0], v0.s[0]
+            ; mov v7.s[1], v1.s[0]
+            ; mov v7.s[2], v2.s[0]
+            ; mov v7.s[3], v3.s[0]
+        );
+        self.restore_registers();
+        dynasm!(self.0.ops
+            ; mov V(reg(out_reg)).b16, v7.b16
+        );
+    }
+
+    /// Calls a binary Rust function, writing its result to `out_reg`
+    ///
+    /// The second `Grad` argument is passed in `(s4, s5, s6, s7)`.
+    fn call_fn_binary(
+        &mut self,
+        out_reg: u8,
+        lhs_reg: u8,
+        rhs_reg: u8,
+        f: extern "C" fn(Grad, Grad) -> Grad,
+    ) {
+        let addr = f as usize as u64;
+        self.backup_registers();
+        dynasm!(self.0.ops
+            // Either argument may be in v3 (IMM_REG), so set s3 last
+            ; mov s4, V(reg(rhs_reg)).s[0]
+            ; mov s5, V(reg(rhs_reg)).s[1]
+            ; mov s6, V(reg(rhs_reg)).s[2]
+            ; mov s7, V(reg(rhs_reg)).s[3]
+            ; mov s0, V(reg(lhs_reg)).s[0]
+            ; mov s1, V(reg(lhs_reg)).s[1]
+            ; mov s2, V(reg(lhs_reg)).s[2]
+            ; mov s3, V(reg(lhs_reg)).s[3]
+            ; movz x9, #((addr >> 48) as u32 & 0xffff), lsl 48
+            ; movk x9, #((addr >> 32) as u32 & 0xffff), lsl 32
+            ; movk x9, #((addr >> 16) as u32 & 0xffff), lsl 16
+            ; movk x9, #(addr as u32 & 0xffff)
+            ; blr x9
+            ; mov v7.s[0], v0.s[0]
+            ; mov v7.s[1], v1.s[0]
+            ; mov v7.s[2], v2.s[0]
+            ; mov v7.s[3], v3.s[0]
+        );
+        self.restore_registers();
+        dynasm!(self.0.ops
+            ; mov V(reg(out_reg)).b16, v7.b16
+        );
+    }
+}
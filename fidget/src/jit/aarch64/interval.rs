@@ -1,10 +1,12 @@
 use crate::{
     eval::types::Interval,
     jit::{
-        interval::IntervalAssembler, mmap::Mmap, reg, AssemblerData,
-        AssemblerT, CHOICE_BOTH, CHOICE_LEFT, CHOICE_RIGHT, IMM_REG, OFFSET,
-        REGISTER_LIMIT,
+        interval::{binary, imm_interval, powi, unary, IntervalAssembler},
+        mmap::Mmap,
+        reg, AssemblerData, AssemblerT, CHOICE_BOTH, CHOICE_LEFT, CHOICE_RIGHT,
+        IMM_REG, OFFSET, REGISTER_LIMIT,
     },
+    vm::{SoundInterval, Value},
     Error,
 };
 use dynasmrt::{dynasm, DynasmApi};
@@ -18,6 +20,7 @@ use dynasmrt::{dynasm, DynasmApi};
 /// | X          | `(s0, s1)` | `(f32, f32)`            |
 /// | Y          | `(s2, s3)` | `(f32, f32)`            |
 /// | Z          | `(s4, s5)` | `(f32, f32)`            |
+/// | W          | `(s6, s7)` | `(f32, f32)`            |
 /// | `vars`     | `x0`       | `*const f32` (array)    |
 /// | `choices`  | `x1`       | `*const u8` (array)     |
 /// | `simplify` | `x2`       | `*const u8` (single)    |
@@ -25,7 +28,8 @@ use dynasmrt::{dynasm, DynasmApi};
 /// During evaluation, X, Y, and Z are stored in `V0-3.S2`.  Each SIMD register
 /// stores an interval.  `s[0]` is the lower bound of the interval and `s[1]` is
 /// the upper bound; for example, `V0.S0` represents the lower bound for X.
-impl AssemblerT for IntervalAssembler {
+/// W is stored on the stack, since `v3` is used for immediates.
+impl<const SOUND: bool> AssemblerT for IntervalAssembler<SOUND> {
     type Data = Interval;
 
     fn init(mmap: Mmap, slot_count: usize) -> Self {
@@ -49,6 +53,10 @@ impl AssemblerT for IntervalAssembler {
             ; mov v2.s[1], v5.s[0]
         );
         out.prepare_stack(slot_count);
+        dynasm!(out.ops
+            ; stur s6, [x29, #-80]
+            ; stur s7, [x29, #-76]
+        );
         Self(out)
     }
     /// Reads from `src_mem` to `dst_reg`
@@ -67,7 +75,11 @@ impl AssemblerT for IntervalAssembler {
     }
     /// Copies the given input to `out_reg`
     fn build_input(&mut self, out_reg: u8, src_arg: u8) {
-        dynasm!(self.0.ops ; fmov D(reg(out_reg)), D(src_arg as u32));
+        if src_arg == 3 {
+            dynasm!(self.0.ops ; ldur D(reg(out_reg)), [x29, #-80]);
+        } else {
+            dynasm!(self.0.ops ; fmov D(reg(out_reg)), D(src_arg as u32));
+        }
     }
     fn build_var(&mut self, out_reg: u8, src_arg: u32) {
         assert!(src_arg * 4 < 16384);
@@ -117,7 +129,8 @@ impl AssemblerT for IntervalAssembler {
             ; rev64 V(reg(out_reg)).s2, V(reg(out_reg)).s2
 
             // <- end
-        )
+        );
+        self.clamp_non_negative(out_reg);
     }
     fn build_recip(&mut self, out_reg: u8, lhs_reg: u8) {
         let nan_u32 = f32::NAN.to_bits();
@@ -144,7 +157,8 @@ impl AssemblerT for IntervalAssembler {
             ; rev64 V(reg(out_reg)).s2, V(reg(out_reg)).s2
 
             // <- end
-        )
+        );
+        self.round_outward(out_reg);
     }
     fn build_sqrt(&mut self, out_reg: u8, lhs_reg: u8) {
         let nan_u32 = f32::NAN.to_bits();
@@ -177,7 +191,9 @@ impl AssemblerT for IntervalAssembler {
             ; dup V(reg(out_reg)).s2, w9
 
             // <- end
-        )
+        );
+        self.round_outward(out_reg);
+        self.clamp_non_negative(out_reg);
     }
     fn build_square(&mut self, out_reg: u8, lhs_reg: u8) {
         dynasm!(self.0.ops
@@ -205,24 +221,141 @@ impl AssemblerT for IntervalAssembler {
             ; rev64 V(reg(out_reg)).s2, V(reg(out_reg)).s2
 
             // <- end
+        );
+        self.round_outward(out_reg);
+        self.clamp_non_negative(out_reg);
+    }
+    fn build_exp(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn interval_exp<const SOUND: bool>(v: Interval) -> Interval {
+            unary::<SOUND>(v, Interval::exp, SoundInterval::exp)
+        }
+        self.call_fn_unary(out_reg, lhs_reg, interval_exp::<SOUND>);
+    }
+    fn build_sin(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn interval_sin<const SOUND: bool>(v: Interval) -> Interval {
+            unary::<SOUND>(v, Interval::sine, SoundInterval::sin)
+        }
+        self.call_fn_unary(out_reg, lhs_reg, interval_sin::<SOUND>);
+    }
+    fn build_cos(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn interval_cos<const SOUND: bool>(v: Interval) -> Interval {
+            unary::<SOUND>(v, Interval::cosine, SoundInterval::cos)
+        }
+        self.call_fn_unary(out_reg, lhs_reg, interval_cos::<SOUND>);
+    }
+    fn build_tan(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn interval_tan<const SOUND: bool>(v: Interval) -> Interval {
+            unary::<SOUND>(v, Interval::tan, SoundInterval::tan)
+        }
+        self.call_fn_unary(out_reg, lhs_reg, interval_tan::<SOUND>);
+    }
+    fn build_asin(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn interval_asin<const SOUND: bool>(
+            v: Interval,
+        ) -> Interval {
+            unary::<SOUND>(v, Interval::asin, SoundInterval::asin)
+        }
+        self.call_fn_unary(out_reg, lhs_reg, interval_asin::<SOUND>);
+    }
+    fn build_acos(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn interval_acos<const SOUND: bool>(
+            v: Interval,
+        ) -> Interval {
+            unary::<SOUND>(v, Interval::acos, SoundInterval::acos)
+        }
+        self.call_fn_unary(out_reg, lhs_reg, interval_acos::<SOUND>);
+    }
+    fn build_atan(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn interval_atan<const SOUND: bool>(
+            v: Interval,
+        ) -> Interval {
+            unary::<SOUND>(v, Interval::atan, SoundInterval::atan)
+        }
+        self.call_fn_unary(out_reg, lhs_reg, interval_atan::<SOUND>);
+    }
+    fn build_atan2(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "C" fn interval_atan2<const SOUND: bool>(
+            lhs: Interval,
+            rhs: Interval,
+        ) -> Interval {
+            binary::<SOUND>(lhs, rhs, Interval::atan2, SoundInterval::atan2)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, interval_atan2::<SOUND>);
+    }
+    fn build_ln(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn interval_ln<const SOUND: bool>(v: Interval) -> Interval {
+            unary::<SOUND>(v, Interval::ln, SoundInterval::ln)
+        }
+        self.call_fn_unary(out_reg, lhs_reg, interval_ln::<SOUND>);
+    }
+    fn build_pow(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "C" fn interval_pow<const SOUND: bool>(
+            lhs: Interval,
+            rhs: Interval,
+        ) -> Interval {
+            binary::<SOUND>(lhs, rhs, Interval::pow, SoundInterval::pow)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, interval_pow::<SOUND>);
+    }
+    fn build_powi(&mut self, out_reg: u8, lhs_reg: u8, n: i32) {
+        extern "C" fn interval_powi<const SOUND: bool>(
+            lhs: Interval,
+            rhs: Interval,
+        ) -> Interval {
+            powi::<SOUND>(lhs, rhs.lower() as i32)
+        }
+        let imm = self.load_imm(n as f32);
+        self.call_fn_binary(out_reg, lhs_reg, imm, interval_powi::<SOUND>);
+    }
+    fn build_floor(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; frintm V(reg(out_reg)).s2, V(reg(lhs_reg)).s2
+        )
+    }
+    fn build_ceil(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; frintp V(reg(out_reg)).s2, V(reg(lhs_reg)).s2
+        )
+    }
+    fn build_round(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; frinta V(reg(out_reg)).s2, V(reg(lhs_reg)).s2
         )
     }
+    fn build_mod(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "C" fn interval_mod<const SOUND: bool>(
+            lhs: Interval,
+            rhs: Interval,
+        ) -> Interval {
+            binary::<SOUND>(lhs, rhs, Interval::modulo, SoundInterval::modulo)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, interval_mod::<SOUND>);
+    }
+    fn build_not(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn interval_not(v: Interval) -> Interval {
+            !v
+        }
+        self.call_fn_unary(out_reg, lhs_reg, interval_not);
+    }
     fn build_add(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
         dynasm!(self.0.ops
             ; fadd V(reg(out_reg)).s2, V(reg(lhs_reg)).s2, V(reg(rhs_reg)).s2
-        )
+        );
+        self.round_outward(out_reg);
     }
     fn build_sub(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
         dynasm!(self.0.ops
             ; rev64 v4.s2, V(reg(rhs_reg)).s2
             ; fsub V(reg(out_reg)).s2, V(reg(lhs_reg)).s2, v4.s2
-        )
+        );
+        self.round_outward(out_reg);
     }
     fn build_sub_reg_imm(&mut self, out_reg: u8, arg: u8, imm: f32) {
         let imm = self.load_imm(imm);
         dynasm!(self.0.ops
             ; fsub V(reg(out_reg)).s2, V(reg(arg)).s2, V(reg(imm)).s2
-        )
+        );
+        self.round_outward(out_reg);
     }
     fn build_mul(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
         dynasm!(self.0.ops
@@ -242,7 +375,8 @@ impl AssemblerT for IntervalAssembler {
             ; fminnmv S(reg(out_reg)), v4.s4
             ; fmaxnmv s5, v4.s4
             ; mov V(reg(out_reg)).s[1], v5.s[0]
-        )
+        );
+        self.round_outward(out_reg);
     }
 
     fn build_mul_imm(&mut self, out_reg: u8, lhs_reg: u8, imm: f32) {
@@ -255,6 +389,7 @@ impl AssemblerT for IntervalAssembler {
                 ; rev64 V(reg(out_reg)).s2, V(reg(out_reg)).s2
             );
         }
+        self.round_outward(out_reg);
     }
     fn build_div(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
         let nan_u32 = f32::NAN.to_bits();
@@ -293,7 +428,8 @@ impl AssemblerT for IntervalAssembler {
             ; mov V(reg(out_reg)).s[1], v5.s[0]
 
             // >end
-        )
+        );
+        self.round_outward(out_reg);
     }
     fn build_max(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
         dynasm!(self.0.ops
@@ -379,6 +515,215 @@ impl AssemblerT for IntervalAssembler {
             ; strb w14, [x1], #1 // post-increment
         )
     }
+    fn build_lt(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "C" fn interval_lt(lhs: Interval, rhs: Interval) -> Interval {
+            lhs.lt(rhs)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, interval_lt);
+    }
+    fn build_eq(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "C" fn interval_eq(lhs: Interval, rhs: Interval) -> Interval {
+            lhs.eq(rhs)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, interval_eq);
+    }
+    fn build_and(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        let nan_u32 = f32::NAN.to_bits();
+        dynasm!(self.0.ops
+            //  if lhs or rhs contains NaN
+            //      *choices++ |= CHOICE_BOTH
+            //      out = NaN
+            //  elif lhs does not contain 0
+            //      *choices++ |= CHOICE_RIGHT
+            //      out = rhs
+            //  elif lhs == [0, 0]
+            //      *choices++ |= CHOICE_LEFT
+            //      out = lhs
+            //  else
+            //      *choices++ |= CHOICE_BOTH
+            //      out = [min(rhs.lower, 0), max(rhs.upper, 0)]
+            ; ldrb w14, [x1]
+            ; mov s4, V(reg(lhs_reg)).s[1] // s4 = lhs.upper
+            ; mov s5, V(reg(rhs_reg)).s[1] // s5 = rhs.upper
+
+            // Unordered comparisons mean that one of the values is NaN
+            ; fcmp S(reg(lhs_reg)), s4
+            ; b.vs #100 // -> nan
+            ; fcmp S(reg(rhs_reg)), s5
+            ; b.vs #92 // -> nan
+
+            ; fcmp S(reg(lhs_reg)), 0.0
+            ; b.gt #44 // -> rhs
+            ; fcmp s4, 0.0
+            ; b.mi #36 // -> rhs
+            ; fcmp S(reg(lhs_reg)), 0.0
+            ; b.ne #44 // -> both
+            ; fcmp s4, 0.0
+            ; b.ne #36 // -> both
+
+            // Fallthrough: lhs is exactly zero
+            ; fmov D(reg(out_reg)), D(reg(lhs_reg))
+            ; orr w14, w14, #CHOICE_LEFT
+            ; strb w14, [x2, #0] // write a non-zero value to simplify
+            ; b #60 // -> end
+
+            // <- rhs
+            ; fmov D(reg(out_reg)), D(reg(rhs_reg))
+            ; orr w14, w14, #CHOICE_RIGHT
+            ; strb w14, [x2, #0] // write a non-zero value to simplify
+            ; b #44 // -> end
+
+            // <- both
+            ; movi d6, #0
+            ; fmin s7, S(reg(rhs_reg)), s6
+            ; fmax s5, s5, s6
+            ; zip1 V(reg(out_reg)).s2, v7.s2, v5.s2
+            ; orr w14, w14, #CHOICE_BOTH
+            ; b #20 // -> end
+
+            // <- nan
+            ; movz w15, #(nan_u32 >> 16), lsl 16
+            ; movk w15, #(nan_u32)
+            ; dup V(reg(out_reg)).s2, w15
+            ; orr w14, w14, #CHOICE_BOTH
+
+            // <- end
+            ; strb w14, [x1], #1 // post-increment
+        )
+    }
+    fn build_or(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        let nan_u32 = f32::NAN.to_bits();
+        dynasm!(self.0.ops
+            //  if lhs or rhs contains NaN
+            //      *choices++ |= CHOICE_BOTH
+            //      out = NaN
+            //  elif lhs does not contain 0
+            //      *choices++ |= CHOICE_LEFT
+            //      out = lhs
+            //  elif lhs == [0, 0]
+            //      *choices++ |= CHOICE_RIGHT
+            //      out = rhs
+            //  else
+            //      *choices++ |= CHOICE_BOTH
+            //      out = [min(lhs.lower, rhs.lower), max(lhs.upper, rhs.upper)]
+            ; ldrb w14, [x1]
+            ; mov s4, V(reg(lhs_reg)).s[1] // s4 = lhs.upper
+            ; mov s5, V(reg(rhs_reg)).s[1] // s5 = rhs.upper
+
+            // Unordered comparisons mean that one of the values is NaN
+            ; fcmp S(reg(lhs_reg)), s4
+            ; b.vs #100 // -> nan
+            ; fcmp S(reg(rhs_reg)), s5
+            ; b.vs #92 // -> nan
+
+            ; fcmp S(reg(lhs_reg)), 0.0
+            ; b.gt #44 // -> lhs
+            ; fcmp s4, 0.0
+            ; b.mi #36 // -> lhs
+            ; fcmp S(reg(lhs_reg)), 0.0
+            ; b.ne #44 // -> both
+            ; fcmp s4, 0.0
+            ; b.ne #36 // -> both
+
+            // Fallthrough: lhs is exactly zero
+            ; fmov D(reg(out_reg)), D(reg(rhs_reg))
+            ; orr w14, w14, #CHOICE_RIGHT
+            ; strb w14, [x2, #0] // write a non-zero value to simplify
+            ; b #60 // -> end
+
+            // <- lhs
+            ; fmov D(reg(out_reg)), D(reg(lhs_reg))
+            ; orr w14, w14, #CHOICE_LEFT
+            ; strb w14, [x2, #0] // write a non-zero value to simplify
+            ; b #44 // -> end
+
+            // <- both
+            ; fmin v6.s2, V(reg(lhs_reg)).s2, V(reg(rhs_reg)).s2
+            ; fmax v7.s2, V(reg(lhs_reg)).s2, V(reg(rhs_reg)).s2
+            ; mov v6.s[1], v7.s[1]
+            ; fmov D(reg(out_reg)), d6
+            ; orr w14, w14, #CHOICE_BOTH
+            ; b #20 // -> end
+
+            // <- nan
+            ; movz w15, #(nan_u32 >> 16), lsl 16
+            ; movk w15, #(nan_u32)
+            ; dup V(reg(out_reg)).s2, w15
+            ; orr w14, w14, #CHOICE_BOTH
+
+            // <- end
+            ; strb w14, [x1], #1 // post-increment
+        )
+    }
+    fn build_select(
+        &mut self,
+        out_reg: u8,
+        cond_reg: u8,
+        lhs_reg: u8,
+        rhs_reg: u8,
+    ) {
+        let nan_u32 = f32::NAN.to_bits();
+        dynasm!(self.0.ops
+            //  if cond, lhs, or rhs contains NaN
+            //      *choices++ |= CHOICE_BOTH
+            //      out = NaN
+            //  elif cond.upper < 0
+            //      *choices++ |= CHOICE_LEFT
+            //      out = lhs
+            //  elif cond.lower >= 0
+            //      *choices++ |= CHOICE_RIGHT
+            //      out = rhs
+            //  else
+            //      *choices++ |= CHOICE_BOTH
+            //      out = [min(lhs.lower, rhs.lower), max(lhs.upper, rhs.upper)]
+            ; ldrb w14, [x1]
+            ; mov s4, V(reg(cond_reg)).s[1] // s4 = cond.upper
+            ; mov s5, V(reg(lhs_reg)).s[1] // s5 = lhs.upper
+            ; mov s6, V(reg(rhs_reg)).s[1] // s6 = rhs.upper
+
+            // Unordered comparisons mean that one of the values is NaN
+            ; fcmp S(reg(cond_reg)), s4
+            ; b.vs #92 // -> nan
+            ; fcmp S(reg(lhs_reg)), s5
+            ; b.vs #84 // -> nan
+            ; fcmp S(reg(rhs_reg)), s6
+            ; b.vs #76 // -> nan
+
+            ; fcmp s4, 0.0
+            ; b.mi #36 // -> lhs
+            ; fcmp S(reg(cond_reg)), 0.0
+            ; b.ge #44 // -> rhs
+
+            // Fallthrough: ambiguous case, so take the union of both intervals
+            ; fmin v6.s2, V(reg(lhs_reg)).s2, V(reg(rhs_reg)).s2
+            ; fmax v7.s2, V(reg(lhs_reg)).s2, V(reg(rhs_reg)).s2
+            ; mov v6.s[1], v7.s[1]
+            ; fmov D(reg(out_reg)), d6
+            ; orr w14, w14, #CHOICE_BOTH
+            ; b #52 // -> end
+
+            // <- lhs
+            ; fmov D(reg(out_reg)), D(reg(lhs_reg))
+            ; orr w14, w14, #CHOICE_LEFT
+            ; strb w14, [x2, #0] // write a non-zero value to simplify
+            ; b #36 // -> end
+
+            // <- rhs
+            ; fmov D(reg(out_reg)), D(reg(rhs_reg))
+            ; orr w14, w14, #CHOICE_RIGHT
+            ; strb w14, [x2, #0] // write a non-zero value to simplify
+            ; b #20 // -> end
+
+            // <- nan
+            ; movz w15, #(nan_u32 >> 16), lsl 16
+            ; movk w15, #(nan_u32)
+            ; dup V(reg(out_reg)).s2, w15
+            ; orr w14, w14, #CHOICE_BOTH
+
+            // <- end
+            ; strb w14, [x1], #1 // post-increment
+        )
+    }
 
     /// Loads an immediate into register S4, using W9 as an intermediary
     fn load_imm(&mut self, imm: f32) -> u8 {
@@ -391,12 +736,28 @@ impl AssemblerT for IntervalAssembler {
         IMM_REG.wrapping_sub(OFFSET)
     }
 
-    fn finalize(mut self, out_reg: u8) -> Result<Mmap, Error> {
+    fn load_imm64(&mut self, imm: f64) -> u8 {
+        let Some(i) = imm_interval::<SOUND>(imm) else {
+            return self.load_imm(imm as f32);
+        };
+        let (lower, upper) = (i.lower().to_bits(), i.upper().to_bits());
+        dynasm!(self.0.ops
+            ; movz w15, #(lower >> 16), lsl 16
+            ; movk w15, #(lower)
+            ; fmov S(IMM_REG as u32), w15
+            ; movz w15, #(upper >> 16), lsl 16
+            ; movk w15, #(upper)
+            ; mov V(IMM_REG as u32).s[1], w15
+        );
+        IMM_REG.wrapping_sub(OFFSET)
+    }
+
+    fn finalize(mut self, _output_count: u8) -> Result<Mmap, Error> {
         assert!(self.0.mem_offset < 4096);
         dynasm!(self.0.ops
             // Prepare our return value
-            ; mov  s0, V(reg(out_reg)).s[0]
-            ; mov  s1, V(reg(out_reg)).s[1]
+            ; mov  s0, V(reg(0)).s[0]
+            ; mov  s1, V(reg(0)).s[1]
             // Restore stack space used for spills
             ; add   sp, sp, #(self.0.mem_offset as u32)
             // Restore callee-saved floating-point registers
@@ -412,3 +773,155 @@ impl AssemblerT for IntervalAssembler {
         self.0.ops.finalize()
     }
 }
+
+impl<const SOUND: bool> IntervalAssembler<SOUND> {
+    /// If `SOUND` is set, widens the interval in `out_reg` by at least one ulp
+    /// in each direction
+    ///
+    /// This is called after operations which may round their results.  The
+    /// lower bound is negated, so that both bounds are rounded upwards by
+    /// adding `|v| * 2^-23` (at least one ulp) plus the smallest subnormal.
+    /// Taking the maximum of the original and widened values keeps infinities
+    /// in place, since `fmaxnm` ignores the NaN produced by `inf - inf`.
+    fn round_outward(&mut self, out_reg: u8) {
+        if !SOUND {
+            return;
+        }
+        dynasm!(self.0.ops
+            // v4 = [-0.0, 0.0], used to negate the lower bound
+            ; movz w15, #0x8000, lsl 16
+            ; fmov s4, w15
+            ; eor V(reg(out_reg)).b8, V(reg(out_reg)).b8, v4.b8
+
+            // v5 = |v| * 2^-23 + MIN_SUBNORMAL
+            ; fabs v5.s2, V(reg(out_reg)).s2
+            ; movz w15, #0x3400, lsl 16 // 2^-23
+            ; dup v6.s2, w15
+            ; fmul v5.s2, v5.s2, v6.s2
+            ; movz w15, #1 // smallest subnormal
+            ; dup v6.s2, w15
+            ; fadd v5.s2, v5.s2, v6.s2
+
+            ; fadd v5.s2, v5.s2, V(reg(out_reg)).s2
+            ; fmaxnm V(reg(out_reg)).s2, v5.s2, V(reg(out_reg)).s2
+            ; eor V(reg(out_reg)).b8, V(reg(out_reg)).b8, v4.b8
+        );
+    }
+
+    /// If `SOUND` is set, clamps the interval in `out_reg` to be non-negative
+    ///
+    /// This is called after operations whose true result is never negative
+    /// (e.g. `sqrt`), so that rounding outwards can't push a lower bound of
+    /// zero below it.  NaN bounds are left in place, since `fmax` propagates
+    /// them.
+    fn clamp_non_negative(&mut self, out_reg: u8) {
+        if !SOUND {
+            return;
+        }
+        dynasm!(self.0.ops
+            ; movi d4, #0
+            ; fmax V(reg(out_reg)).s2, V(reg(out_reg)).s2, v4.s2
+        );
+    }
+
+    /// Stack space used by [`Self::backup_registers`]: 3 pointers + 3 inputs +
+    /// 24 tape registers, already 16-byte aligned
+    const CALL_STACK_SIZE: u32 = 240;
+
+    /// Saves our argument pointers, X/Y/Z inputs, and tape registers to the
+    /// stack
+    ///
+    /// The callee is only required to preserve the lower 64 bits of `v8-15`,
+    /// so this must be called before calling into a Rust function.
+    fn backup_registers(&mut self) {
+        dynasm!(self.0.ops
+            ; sub sp, sp, #(Self::CALL_STACK_SIZE)
+            ; stp x0, x1, [sp]
+            ; str x2, [sp, #16]
+        );
+        for i in 0..3 {
+            dynasm!(self.0.ops ; str D(i), [sp, #(24 + 8 * i)]);
+        }
+        for i in 0..REGISTER_LIMIT {
+            let offset = 48 + 8 * i as u32;
+            dynasm!(self.0.ops ; str D(reg(i)), [sp, #(offset)]);
+        }
+    }
+
+    /// Restores the registers saved by [`Self::backup_registers`]
+    fn restore_registers(&mut self) {
+        for i in 0..REGISTER_LIMIT {
+            let offset = 48 + 8 * i as u32;
+            dynasm!(self.0.ops ; ldr D(reg(i)), [sp, #(offset)]);
+        }
+        for i in 0..3 {
+            dynasm!(self.0.ops ; ldr D(i), [sp, #(24 + 8 * i)]);
+        }
+        dynasm!(self.0.ops
+            ; ldr x2, [sp, #16]
+            ; ldp x0, x1, [sp]
+            ; add sp, sp, #(Self::CALL_STACK_SIZE)
+        );
+    }
+
+    /// Calls a unary Rust function, writing its result to `out_reg`
+    ///
+    /// An `Interval` is passed and returned in `(s0, s1)`.
+    fn call_fn_unary(
+        &mut self,
+        out_reg: u8,
+        arg_reg: u8,
+        f: extern "C" fn(Interval) -> Interval,
+    ) {
+        let addr = f as usize as u64;
+        self.backup_registers();
+        dynasm!(self.0.ops
+            ; mov s1, V(reg(arg_reg)).s[1]
+            ; fmov s0, S(reg(arg_reg))
+            ; movz x9, #((addr >> 48) as u32 & 0xffff), lsl 48
+            ; movk x9, #((addr >> 32) as u32 & 0xffff), lsl 32
+            ; movk x9, #((addr >> 16) as u32 & 0xffff), lsl 16
+            ; movk x9, #(addr as u32 & 0xffff)
+            ; blr x9
+            // Stash the result in a scratch register while restoring
+            ; mov v0.s[1], v1.s[0]
+            ; fmov d7, d0
+        );
+        self.restore_registers();
+        dynasm!(self.0.ops
+            ; fmov D(reg(out_reg)), d7
+        );
+    }
+
+    /// Calls a binary Rust function, writing its result to `out_reg`
+    ///
+    /// The second `Interval` argument is passed in `(s2, s3)`.
+    fn call_fn_binary(
+        &mut self,
+        out_reg: u8,
+        lhs_reg: u8,
+        rhs_reg: u8,
+        f: extern "C" fn(Interval, Interval) -> Interval,
+    ) {
+        let addr = f as usize as u64;
+        self.backup_registers();
+        dynasm!(self.0.ops
+            // Either argument may be in v3 (IMM_REG), so set s3 last
+            ; mov s1, V(reg(lhs_reg)).s[1]
+            ; fmov s0, S(reg(lhs_reg))
+            ; fmov s2, S(reg(rhs_reg))
+            ; mov s3, V(reg(rhs_reg)).s[1]
+            ; movz x9, #((addr >> 48) as u32 & 0xffff), lsl 48
+            ; movk x9, #((addr >> 32) as u32 & 0xffff), lsl 32
+            ; movk x9, #((addr >> 16) as u32 & 0xffff), lsl 16
+            ; movk x9, #(addr as u32 & 0xffff)
+            ; blr x9
+            ; mov v0.s[1], v1.s[0]
+            ; fmov d7, d0
+        );
+        self.restore_registers();
+        dynasm!(self.0.ops
+            ; fmov D(reg(out_reg)), d7
+        );
+    }
+}
@@ -8,7 +8,12 @@
 //! This means that the input tape must be planned with a <= 24 register limit;
 //! any spills will live on the stack.
 //!
-//! Right now, we never call anything, so don't worry about saving stuff.
+//! Transcendental functions (`exp`, `sin`, `atan2`, etc) are implemented by
+//! calling back into Rust.  Because callees only preserve the lower 64 bits of
+//! `v8-15`, each assembler's `backup_registers` saves every tape register
+//! (along with the inputs in `v0-2` and the argument pointers) to the stack
+//! before the call.  The W input is kept on the stack (see `prepare_stack`),
+//! so it survives these calls without special handling.
 //!
 //! Within a single operation, you'll often need to make use of scratch
 //! registers.  `s3` / `v3` is used when loading immediates, and should not be
@@ -17,6 +17,7 @@ use dynasmrt::{dynasm, DynasmApi};
 /// | X          | `s0`     | `f32`                 |
 /// | Y          | `s1`     | `f32`                 |
 /// | Z          | `s2`     | `f32`                 |
+/// | W          | `s3`     | `f32`                 |
 /// | `vars`     | `x0`     | `*const f32` (array)  |
 /// | `out`      | `x1`     | `*mut u8` (array)     |
 /// | `count`    | `x2`     | `*mut u8` (single)    |
@@ -37,6 +38,10 @@ impl AssemblerT for PointAssembler {
             ; stp   d14, d15, [sp, #-16]!
         );
         out.prepare_stack(slot_count);
+        dynasm!(out.ops
+            // W arrives in `s3`, which is also IMM_REG, so move it to the stack
+            ; stur s3, [x29, #-80]
+        );
 
         Self(out)
     }
@@ -56,7 +61,11 @@ impl AssemblerT for PointAssembler {
     }
     /// Copies the given input to `out_reg`
     fn build_input(&mut self, out_reg: u8, src_arg: u8) {
-        dynasm!(self.0.ops ; fmov S(reg(out_reg)), S(src_arg as u32));
+        if src_arg == 3 {
+            dynasm!(self.0.ops ; ldur S(reg(out_reg)), [x29, #-80]);
+        } else {
+            dynasm!(self.0.ops ; fmov S(reg(out_reg)), S(src_arg as u32));
+        }
     }
     fn build_var(&mut self, out_reg: u8, src_arg: u32) {
         assert!(src_arg * 4 < 16384);
@@ -85,6 +94,96 @@ impl AssemblerT for PointAssembler {
     fn build_square(&mut self, out_reg: u8, lhs_reg: u8) {
         dynasm!(self.0.ops ; fmul S(reg(out_reg)), S(reg(lhs_reg)), S(reg(lhs_reg)))
     }
+    fn build_exp(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn point_exp(v: f32) -> f32 {
+            v.exp()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, point_exp);
+    }
+    fn build_sin(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn point_sin(v: f32) -> f32 {
+            v.sin()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, point_sin);
+    }
+    fn build_cos(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn point_cos(v: f32) -> f32 {
+            v.cos()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, point_cos);
+    }
+    fn build_tan(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn point_tan(v: f32) -> f32 {
+            v.tan()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, point_tan);
+    }
+    fn build_asin(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn point_asin(v: f32) -> f32 {
+            v.asin()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, point_asin);
+    }
+    fn build_acos(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn point_acos(v: f32) -> f32 {
+            v.acos()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, point_acos);
+    }
+    fn build_atan(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn point_atan(v: f32) -> f32 {
+            v.atan()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, point_atan);
+    }
+    fn build_atan2(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "C" fn point_atan2(lhs: f32, rhs: f32) -> f32 {
+            lhs.atan2(rhs)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, point_atan2);
+    }
+    fn build_ln(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "C" fn point_ln(v: f32) -> f32 {
+            v.ln()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, point_ln);
+    }
+    fn build_pow(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "C" fn point_pow(lhs: f32, rhs: f32) -> f32 {
+            lhs.powf(rhs)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, point_pow);
+    }
+    fn build_powi(&mut self, out_reg: u8, lhs_reg: u8, n: i32) {
+        extern "C" fn point_powi(lhs: f32, rhs: f32) -> f32 {
+            lhs.powi(rhs as i32)
+        }
+        let imm = self.load_imm(n as f32);
+        self.call_fn_binary(out_reg, lhs_reg, imm, point_powi);
+    }
+    fn build_floor(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops ; frintm S(reg(out_reg)), S(reg(lhs_reg)))
+    }
+    fn build_ceil(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops ; frintp S(reg(out_reg)), S(reg(lhs_reg)))
+    }
+    fn build_round(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops ; frinta S(reg(out_reg)), S(reg(lhs_reg)))
+    }
+    fn build_mod(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "C" fn point_mod(lhs: f32, rhs: f32) -> f32 {
+            lhs.rem_euclid(rhs)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, point_mod);
+    }
+    fn build_not(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops
+            // Build a mask of (lhs == 0), then convert it to 0.0 / 1.0
+            ; fcmeq S(reg(out_reg)), S(reg(lhs_reg)), 0.0
+            ; fmov s7, #1.0
+            ; and V(reg(out_reg)).b8, V(reg(out_reg)).b8, v7.b8
+        )
+    }
     fn build_add(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
         dynasm!(self.0.ops
             ; fadd S(reg(out_reg)), S(reg(lhs_reg)), S(reg(rhs_reg))
@@ -161,6 +260,89 @@ impl AssemblerT for PointAssembler {
             ; strb w14, [x1], #1 // post-increment
         )
     }
+    fn build_lt(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; fcmgt S(reg(out_reg)), S(reg(rhs_reg)), S(reg(lhs_reg))
+            ; fmov s7, #1.0
+            ; and V(reg(out_reg)).b8, V(reg(out_reg)).b8, v7.b8
+        )
+    }
+    fn build_eq(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; fcmeq S(reg(out_reg)), S(reg(lhs_reg)), S(reg(rhs_reg))
+            ; fmov s7, #1.0
+            ; and V(reg(out_reg)).b8, V(reg(out_reg)).b8, v7.b8
+        )
+    }
+    fn build_and(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; ldrb w14, [x1]
+            ; fcmp S(reg(lhs_reg)), 0.0
+            ; b.ne #16 // -> RHS (also taken for NaN)
+
+            // LHS is zero
+            ; fmov S(reg(out_reg)), S(reg(lhs_reg))
+            ; orr w14, w14, #CHOICE_LEFT
+            ; b #12 // -> end
+
+            // RHS
+            ; fmov S(reg(out_reg)), S(reg(rhs_reg))
+            ; orr w14, w14, #CHOICE_RIGHT
+            // fall-through to end
+
+            // <- end
+            ; strb w14, [x2, #0] // write a non-zero value to simplify
+            ; strb w14, [x1], #1 // post-increment
+        )
+    }
+    fn build_or(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; ldrb w14, [x1]
+            ; fcmp S(reg(lhs_reg)), 0.0
+            ; b.ne #16 // -> LHS (also taken for NaN)
+
+            // LHS is zero, so pick RHS
+            ; fmov S(reg(out_reg)), S(reg(rhs_reg))
+            ; orr w14, w14, #CHOICE_RIGHT
+            ; b #12 // -> end
+
+            // LHS
+            ; fmov S(reg(out_reg)), S(reg(lhs_reg))
+            ; orr w14, w14, #CHOICE_LEFT
+            // fall-through to end
+
+            // <- end
+            ; strb w14, [x2, #0] // write a non-zero value to simplify
+            ; strb w14, [x1], #1 // post-increment
+        )
+    }
+    fn build_select(
+        &mut self,
+        out_reg: u8,
+        cond_reg: u8,
+        lhs_reg: u8,
+        rhs_reg: u8,
+    ) {
+        dynasm!(self.0.ops
+            ; ldrb w14, [x1]
+            ; fcmp S(reg(cond_reg)), 0.0
+            ; b.mi #16 // -> LHS (not taken for NaN)
+
+            // cond >= 0, so pick RHS
+            ; fmov S(reg(out_reg)), S(reg(rhs_reg))
+            ; orr w14, w14, #CHOICE_RIGHT
+            ; b #12 // -> end
+
+            // LHS
+            ; fmov S(reg(out_reg)), S(reg(lhs_reg))
+            ; orr w14, w14, #CHOICE_LEFT
+            // fall-through to end
+
+            // <- end
+            ; strb w14, [x2, #0] // write a non-zero value to simplify
+            ; strb w14, [x1], #1 // post-increment
+        )
+    }
 
     /// Loads an immediate into register S4, using W9 as an intermediary
     fn load_imm(&mut self, imm: f32) -> u8 {
@@ -173,10 +355,10 @@ impl AssemblerT for PointAssembler {
         IMM_REG.wrapping_sub(OFFSET)
     }
 
-    fn finalize(mut self, out_reg: u8) -> Result<Mmap, Error> {
+    fn finalize(mut self, _output_count: u8) -> Result<Mmap, Error> {
         dynasm!(self.0.ops
             // Prepare our return value
-            ; fmov  s0, S(reg(out_reg))
+            ; fmov  s0, S(reg(0))
             // Restore stack space used for spills
             ; add   sp, sp, #(self.0.mem_offset as u32)
             // Restore callee-saved floating-point registers
@@ -192,3 +374,96 @@ impl AssemblerT for PointAssembler {
         self.0.ops.finalize()
     }
 }
+
+impl PointAssembler {
+    /// Stack space used by [`Self::backup_registers`]: 3 pointers + 3 inputs +
+    /// 24 tape registers, rounded up for 16-byte alignment
+    const CALL_STACK_SIZE: u32 = 144;
+
+    /// Saves our argument pointers, X/Y/Z inputs, and tape registers to the
+    /// stack
+    ///
+    /// The callee is only required to preserve the lower 64 bits of `v8-15`,
+    /// so this must be called before calling into a Rust function.
+    fn backup_registers(&mut self) {
+        dynasm!(self.0.ops
+            ; sub sp, sp, #(Self::CALL_STACK_SIZE)
+            ; stp x0, x1, [sp]
+            ; str x2, [sp, #16]
+        );
+        for i in 0..3 {
+            dynasm!(self.0.ops ; str S(i), [sp, #(24 + 4 * i)]);
+        }
+        for i in 0..REGISTER_LIMIT {
+            let offset = 36 + 4 * i as u32;
+            dynasm!(self.0.ops ; str S(reg(i)), [sp, #(offset)]);
+        }
+    }
+
+    /// Restores the registers saved by [`Self::backup_registers`]
+    fn restore_registers(&mut self) {
+        for i in 0..REGISTER_LIMIT {
+            let offset = 36 + 4 * i as u32;
+            dynasm!(self.0.ops ; ldr S(reg(i)), [sp, #(offset)]);
+        }
+        for i in 0..3 {
+            dynasm!(self.0.ops ; ldr S(i), [sp, #(24 + 4 * i)]);
+        }
+        dynasm!(self.0.ops
+            ; ldr x2, [sp, #16]
+            ; ldp x0, x1, [sp]
+            ; add sp, sp, #(Self::CALL_STACK_SIZE)
+        );
+    }
+
+    /// Calls a unary Rust function, writing its result to `out_reg`
+    fn call_fn_unary(
+        &mut self,
+        out_reg: u8,
+        arg_reg: u8,
+        f: extern "C" fn(f32) -> f32,
+    ) {
+        let addr = f as usize as u64;
+        self.backup_registers();
+        dynasm!(self.0.ops
+            ; fmov s0, S(reg(arg_reg))
+            ; movz x9, #((addr >> 48) as u32 & 0xffff), lsl 48
+            ; movk x9, #((addr >> 32) as u32 & 0xffff), lsl 32
+            ; movk x9, #((addr >> 16) as u32 & 0xffff), lsl 16
+            ; movk x9, #(addr as u32 & 0xffff)
+            ; blr x9
+            // Stash the result in a scratch register while restoring
+            ; fmov s7, s0
+        );
+        self.restore_registers();
+        dynasm!(self.0.ops
+            ; fmov S(reg(out_reg)), s7
+        );
+    }
+
+    /// Calls a binary Rust function, writing its result to `out_reg`
+    fn call_fn_binary(
+        &mut self,
+        out_reg: u8,
+        lhs_reg: u8,
+        rhs_reg: u8,
+        f: extern "C" fn(f32, f32) -> f32,
+    ) {
+        let addr = f as usize as u64;
+        self.backup_registers();
+        dynasm!(self.0.ops
+            ; fmov s0, S(reg(lhs_reg))
+            ; fmov s1, S(reg(rhs_reg))
+            ; movz x9, #((addr >> 48) as u32 & 0xffff), lsl 48
+            ; movk x9, #((addr >> 32) as u32 & 0xffff), lsl 32
+            ; movk x9, #((addr >> 16) as u32 & 0xffff), lsl 16
+            ; movk x9, #(addr as u32 & 0xffff)
+            ; blr x9
+            ; fmov s7, s0
+        );
+        self.restore_registers();
+        dynasm!(self.0.ops
+            ; fmov S(reg(out_reg)), s7
+        );
+    }
+}
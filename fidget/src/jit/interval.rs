@@ -1,4 +1,85 @@
-use crate::jit::{AssemblerData, JitTracingEval};
+use crate::{
+    eval::types::Interval,
+    jit::{AssemblerData, JitTracingEval},
+    vm::{SoundInterval, Value},
+};
 
-pub struct IntervalAssembler(pub(crate) AssemblerData<[f32; 2]>);
-pub type JitIntervalEval = JitTracingEval<IntervalAssembler>;
+/// Assembler for interval arithmetic
+///
+/// If `SOUND` is set, every operation which may round its result also rounds
+/// outwards, so that the result is guaranteed to contain the true range.
+pub struct IntervalAssembler<const SOUND: bool>(
+    pub(crate) AssemblerData<[f32; 2]>,
+);
+pub type JitIntervalEval = JitTracingEval<IntervalAssembler<false>>;
+pub type JitSoundIntervalEval = JitTracingEval<IntervalAssembler<true>>;
+
+/// Runs the body of a JIT callback, returning NaN if it panics
+///
+/// Callbacks are called from compiled code, which can't be unwound through, so
+/// a panic would otherwise abort the process.
+fn nan_on_panic(f: impl FnOnce() -> Interval) -> Interval {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f))
+        .unwrap_or(f32::NAN.into())
+}
+
+/// Evaluates a unary function for a JIT callback
+///
+/// If `SOUND` is set, the function is evaluated in double precision with
+/// outward rounding (using `g`); otherwise, it uses `f`.  If either bound is
+/// NaN, the result is NaN.
+pub(crate) fn unary<const SOUND: bool>(
+    v: Interval,
+    f: fn(Interval) -> Interval,
+    g: fn(SoundInterval) -> SoundInterval,
+) -> Interval {
+    if v.has_nan() {
+        f32::NAN.into()
+    } else if SOUND {
+        nan_on_panic(|| g(v.into()).into())
+    } else {
+        nan_on_panic(|| f(v))
+    }
+}
+
+/// Evaluates a binary function for a JIT callback
+///
+/// See [`unary`] for details.
+pub(crate) fn binary<const SOUND: bool>(
+    lhs: Interval,
+    rhs: Interval,
+    f: fn(Interval, Interval) -> Interval,
+    g: fn(SoundInterval, SoundInterval) -> SoundInterval,
+) -> Interval {
+    if lhs.has_nan() || rhs.has_nan() {
+        f32::NAN.into()
+    } else if SOUND {
+        nan_on_panic(|| g(lhs.into(), rhs.into()).into())
+    } else {
+        nan_on_panic(|| f(lhs, rhs))
+    }
+}
+
+/// Evaluates an integer power for a JIT callback
+///
+/// See [`unary`] for details.
+pub(crate) fn powi<const SOUND: bool>(lhs: Interval, n: i32) -> Interval {
+    if lhs.has_nan() {
+        f32::NAN.into()
+    } else if SOUND {
+        nan_on_panic(|| SoundInterval::from(lhs).powi(n).into())
+    } else {
+        nan_on_panic(|| lhs.powi(n))
+    }
+}
+
+/// Converts an immediate to an interval, rounding outwards if `SOUND` is set
+///
+/// Returns `None` if the immediate is exactly representable as an `f32`.
+pub(crate) fn imm_interval<const SOUND: bool>(imm: f64) -> Option<Interval> {
+    if SOUND && imm as f32 as f64 != imm && !imm.is_nan() {
+        Some(SoundInterval::from(imm).into())
+    } else {
+        None
+    }
+}
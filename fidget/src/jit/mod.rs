@@ -19,7 +19,10 @@
 
 use crate::{
     eval::{
-        bulk::BulkEvaluator, tape::Data as TapeData, tracing::TracingEvaluator,
+        batch::UnbatchedEvaluator,
+        bulk::{BulkEvaluator, BulkEvaluatorData},
+        tape::Data as TapeData,
+        tracing::TracingEvaluator,
         Choice, EvaluatorStorage, Family, Tape,
     },
     jit::mmap::Mmap,
@@ -30,7 +33,7 @@ use dynasmrt::{
     components::PatchLoc, dynasm, AssemblyOffset, DynamicLabel, DynasmApi,
     DynasmError, DynasmLabelApi, TargetKind,
 };
-use std::{sync::Arc, todo};
+use std::sync::Arc;
 
 mod mmap;
 
@@ -148,6 +151,48 @@ pub trait AssemblerT {
         self.build_mul(out_reg, lhs_reg, lhs_reg)
     }
 
+    /// Natural exponent
+    fn build_exp(&mut self, out_reg: u8, lhs_reg: u8);
+
+    /// Sine
+    fn build_sin(&mut self, out_reg: u8, lhs_reg: u8);
+
+    /// Cosine
+    fn build_cos(&mut self, out_reg: u8, lhs_reg: u8);
+
+    /// Tangent
+    fn build_tan(&mut self, out_reg: u8, lhs_reg: u8);
+
+    /// Arcsine
+    fn build_asin(&mut self, out_reg: u8, lhs_reg: u8);
+
+    /// Arccosine
+    fn build_acos(&mut self, out_reg: u8, lhs_reg: u8);
+
+    /// Arctangent
+    fn build_atan(&mut self, out_reg: u8, lhs_reg: u8);
+
+    /// Natural logarithm
+    fn build_ln(&mut self, out_reg: u8, lhs_reg: u8);
+
+    /// Integer power
+    ///
+    /// The SSA builder only emits integer powers in the `i16` range, so `n` is
+    /// exactly representable as an `f32` (e.g. if loaded as an immediate).
+    fn build_powi(&mut self, out_reg: u8, lhs_reg: u8, n: i32);
+
+    /// Round down to the nearest integer
+    fn build_floor(&mut self, out_reg: u8, lhs_reg: u8);
+
+    /// Round up to the nearest integer
+    fn build_ceil(&mut self, out_reg: u8, lhs_reg: u8);
+
+    /// Round to the nearest integer, with ties rounded away from zero
+    fn build_round(&mut self, out_reg: u8, lhs_reg: u8);
+
+    /// Logical not, returning 1 if the argument is zero and 0 otherwise
+    fn build_not(&mut self, out_reg: u8, lhs_reg: u8);
+
     /// Addition
     fn build_add(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8);
 
@@ -160,6 +205,15 @@ pub trait AssemblerT {
     /// Division
     fn build_div(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8);
 
+    /// Four-quadrant arctangent, with `lhs_reg` as `y` and `rhs_reg` as `x`
+    fn build_atan2(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8);
+
+    /// Power, raising `lhs_reg` to the `rhs_reg` power
+    fn build_pow(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8);
+
+    /// Euclidean remainder of `lhs_reg / rhs_reg`
+    fn build_mod(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8);
+
     /// Maximum of two values
     ///
     /// In a tracing evaluator, this function must also write to the `choices`
@@ -172,6 +226,36 @@ pub trait AssemblerT {
     /// array and may set `simplify` if one branch is always taken.
     fn build_min(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8);
 
+    /// Less-than comparison, returning 1 if `lhs < rhs` and 0 otherwise
+    fn build_lt(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8);
+
+    /// Equality comparison, returning 1 if `lhs == rhs` and 0 otherwise
+    fn build_eq(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8);
+
+    /// Logical and, returning `lhs` if it is zero and `rhs` otherwise
+    ///
+    /// In a tracing evaluator, this function must also write to the `choices`
+    /// array and may set `simplify` if one branch is always taken.
+    fn build_and(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8);
+
+    /// Logical or, returning `lhs` if it is non-zero and `rhs` otherwise
+    ///
+    /// In a tracing evaluator, this function must also write to the `choices`
+    /// array and may set `simplify` if one branch is always taken.
+    fn build_or(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8);
+
+    /// Selection, returning `lhs` if `cond < 0` and `rhs` otherwise
+    ///
+    /// In a tracing evaluator, this function must also write to the `choices`
+    /// array and may set `simplify` if one branch is always taken.
+    fn build_select(
+        &mut self,
+        out_reg: u8,
+        cond_reg: u8,
+        lhs_reg: u8,
+        rhs_reg: u8,
+    );
+
     // Special-case functions for immediates.  In some cases, you can be more
     // efficient if you know that an argument is an immediate (for example, both
     // values in the interval will be the same, and it wlll have no gradients).
@@ -208,8 +292,21 @@ pub trait AssemblerT {
     /// Loads an immediate into a register, returning that register
     fn load_imm(&mut self, imm: f32) -> u8;
 
+    /// Loads a double-precision immediate into a register, returning that
+    /// register
+    ///
+    /// This has a default implementation which rounds to the nearest `f32`,
+    /// but can be overloaded (e.g. to load an interval that contains `imm`)
+    fn load_imm64(&mut self, imm: f64) -> u8 {
+        self.load_imm(imm as f32)
+    }
+
     /// Finalize the assembly code, returning a memory-mapped region
-    fn finalize(self, out_reg: u8) -> Result<Mmap, Error>;
+    ///
+    /// The tape's outputs are in registers `0..output_count`; bulk assemblers
+    /// write all of them for each chunk, while tracing assemblers only return
+    /// the first.
+    fn finalize(self, output_count: u8) -> Result<Mmap, Error>;
 }
 
 /// Trait defining SIMD width
@@ -243,11 +340,14 @@ impl<T> AssemblerData<T> {
 
     #[cfg(target_arch = "aarch64")]
     fn prepare_stack(&mut self, slot_count: usize) {
-        if slot_count < REGISTER_LIMIT as usize {
-            return;
-        }
-        let stack_slots = slot_count - REGISTER_LIMIT as usize;
-        let mem = (stack_slots + 1) * std::mem::size_of::<T>();
+        let stack_slots = slot_count.saturating_sub(REGISTER_LIMIT as usize);
+
+        // We reserve 32 bytes at the top of the stack for the W input, which
+        // doesn't fit into the argument registers alongside X/Y/Z.  Its value
+        // is stored at `[x29, #-80]` (below the saved floating-point
+        // registers); bulk evaluators also keep the advancing W pointer at
+        // `[x29, #-88]`.
+        let mem = (stack_slots + 1) * std::mem::size_of::<T>() + 32;
 
         // Round up to the nearest multiple of 16 bytes, for alignment
         self.mem_offset = ((mem + 15) / 16) * 16;
@@ -262,9 +362,9 @@ impl<T> AssemblerData<T> {
         // We always use the stack on x86_64, if only to store X/Y/Z
         let stack_slots = slot_count.saturating_sub(REGISTER_LIMIT as usize);
 
-        // We put X/Y/Z values at the top of the stack, where they can be
+        // We put X/Y/Z/W values at the top of the stack, where they can be
         // accessed with `movss [rbp - i*size_of(T)] xmm`.  This frees up the
-        // incoming registers (xmm0-2) in the point evaluator.
+        // incoming registers (xmm0-3) in the point evaluator.
         let mem = (stack_slots + 4) * std::mem::size_of::<T>();
 
         // Round up to the nearest multiple of 16 bytes, for alignment
@@ -296,7 +396,7 @@ struct MmapAssembler {
     local_labels: [Option<AssemblyOffset>; 26],
 
     global_relocs: arrayvec::ArrayVec<(PatchLoc<Relocation>, u8), 1>,
-    local_relocs: arrayvec::ArrayVec<(PatchLoc<Relocation>, u8), 8>,
+    local_relocs: arrayvec::ArrayVec<(PatchLoc<Relocation>, u8), 16>,
 }
 
 impl Extend<u8> for MmapAssembler {
@@ -364,9 +464,9 @@ impl DynasmApi for MmapAssembler {
 ///
 /// - Labels must be a single character
 /// - Local labels must be committed before they're reused, using `commit_local`
-/// - Only 8 local jumps are available at any given time; this is reset when
+/// - Only 16 local jumps are available at any given time; this is reset when
 ///   `commit_local` is called.  (if this becomes problematic, it can be
-///   increased by tweaking the size of `local_relocs: ArrayVec<..., 8>`.
+///   increased by tweaking the size of `local_relocs: ArrayVec<..., 16>`.
 ///
 /// In exchange for these limitations, it allocates no memory at runtime, and all
 /// label lookups are done in constant time.
@@ -580,6 +680,18 @@ impl From<Mmap> for MmapAssembler {
 
 /////////////////////////////////////////////////////////////////////////////////////////
 
+/// Loads an immediate into a register, returning that register
+///
+/// If the immediate's exact value isn't representable as an `f32`, it's loaded
+/// with [`AssemblerT::load_imm64`], so that assemblers can choose how to round
+/// it.
+fn load_imm<A: AssemblerT>(asm: &mut A, imm: f32, imm64: Option<f64>) -> u8 {
+    match imm64 {
+        Some(imm) => asm.load_imm64(imm),
+        None => asm.load_imm(imm),
+    }
+}
+
 fn build_asm_fn_with_storage<A: AssemblerT>(t: &TapeData, s: Mmap) -> Mmap {
     // This guard may be a unit value on some systems
     #[allow(clippy::let_unit_value)]
@@ -588,7 +700,7 @@ fn build_asm_fn_with_storage<A: AssemblerT>(t: &TapeData, s: Mmap) -> Mmap {
     s.make_write();
     let mut asm = A::init(s, t.slot_count());
 
-    for op in t.iter_asm() {
+    for (op, imm64) in t.iter_asm_imm64() {
         match op {
             Op::Load(reg, mem) => {
                 asm.build_load(reg, mem);
@@ -620,8 +732,8 @@ fn build_asm_fn_with_storage<A: AssemblerT>(t: &TapeData, s: Mmap) -> Mmap {
             Op::SquareReg(out, arg) => {
                 asm.build_square(out, arg);
             }
-            Op::ExpReg(..) => {
-                todo!();
+            Op::ExpReg(out, arg) => {
+                asm.build_exp(out, arg);
             }
             Op::AddRegReg(out, lhs, rhs) => {
                 asm.build_add(out, lhs, rhs);
@@ -641,48 +753,164 @@ fn build_asm_fn_with_storage<A: AssemblerT>(t: &TapeData, s: Mmap) -> Mmap {
             Op::MaxRegReg(out, lhs, rhs) => {
                 asm.build_max(out, lhs, rhs);
             }
-            Op::AddRegImm(out, arg, imm) => {
-                asm.build_add_imm(out, arg, imm);
-            }
-            Op::MulRegImm(out, arg, imm) => {
-                asm.build_mul_imm(out, arg, imm);
-            }
+            Op::AddRegImm(out, arg, imm) => match imm64 {
+                None => asm.build_add_imm(out, arg, imm),
+                Some(imm) => {
+                    let reg = asm.load_imm64(imm);
+                    asm.build_add(out, arg, reg);
+                }
+            },
+            Op::MulRegImm(out, arg, imm) => match imm64 {
+                None => asm.build_mul_imm(out, arg, imm),
+                Some(imm) => {
+                    let reg = asm.load_imm64(imm);
+                    asm.build_mul(out, arg, reg);
+                }
+            },
             Op::DivRegImm(out, arg, imm) => {
-                let reg = asm.load_imm(imm);
+                let reg = load_imm(&mut asm, imm, imm64);
                 asm.build_div(out, arg, reg);
             }
             Op::DivImmReg(out, arg, imm) => {
-                let reg = asm.load_imm(imm);
+                let reg = load_imm(&mut asm, imm, imm64);
                 asm.build_div(out, reg, arg);
             }
-            Op::SubImmReg(out, arg, imm) => {
-                asm.build_sub_imm_reg(out, arg, imm);
+            Op::SubImmReg(out, arg, imm) => match imm64 {
+                None => asm.build_sub_imm_reg(out, arg, imm),
+                Some(imm) => {
+                    let reg = asm.load_imm64(imm);
+                    asm.build_sub(out, reg, arg);
+                }
+            },
+            Op::SubRegImm(out, arg, imm) => match imm64 {
+                None => asm.build_sub_reg_imm(out, arg, imm),
+                Some(imm) => {
+                    let reg = asm.load_imm64(imm);
+                    asm.build_sub(out, arg, reg);
+                }
+            },
+            Op::SineReg(out, arg) => {
+                asm.build_sin(out, arg);
+            }
+            Op::CosineReg(out, arg) => {
+                asm.build_cos(out, arg);
+            }
+            Op::TanReg(out, arg) => {
+                asm.build_tan(out, arg);
+            }
+            Op::AsinReg(out, arg) => {
+                asm.build_asin(out, arg);
+            }
+            Op::AcosReg(out, arg) => {
+                asm.build_acos(out, arg);
+            }
+            Op::AtanReg(out, arg) => {
+                asm.build_atan(out, arg);
+            }
+            Op::Atan2RegReg(out, lhs, rhs) => {
+                asm.build_atan2(out, lhs, rhs);
+            }
+            Op::Atan2RegImm(out, arg, imm) => {
+                let reg = load_imm(&mut asm, imm, imm64);
+                asm.build_atan2(out, arg, reg);
+            }
+            Op::Atan2ImmReg(out, arg, imm) => {
+                let reg = load_imm(&mut asm, imm, imm64);
+                asm.build_atan2(out, reg, arg);
+            }
+            Op::LnReg(out, arg) => {
+                asm.build_ln(out, arg);
+            }
+            Op::PowRegReg(out, lhs, rhs) => {
+                asm.build_pow(out, lhs, rhs);
+            }
+            Op::PowRegImm(out, arg, imm) => {
+                let reg = load_imm(&mut asm, imm, imm64);
+                asm.build_pow(out, arg, reg);
+            }
+            Op::PowImmReg(out, arg, imm) => {
+                let reg = load_imm(&mut asm, imm, imm64);
+                asm.build_pow(out, reg, arg);
             }
-            Op::SubRegImm(out, arg, imm) => {
-                asm.build_sub_reg_imm(out, arg, imm);
+            Op::PowiReg(out, arg, n) => {
+                asm.build_powi(out, arg, n);
             }
-            Op::SineReg(..) => {
-                todo!();
+            Op::FloorReg(out, arg) => {
+                asm.build_floor(out, arg);
             }
-            Op::CosineReg(..) => {
-                todo!();
+            Op::CeilReg(out, arg) => {
+                asm.build_ceil(out, arg);
+            }
+            Op::RoundReg(out, arg) => {
+                asm.build_round(out, arg);
+            }
+            Op::ModRegReg(out, lhs, rhs) => {
+                asm.build_mod(out, lhs, rhs);
+            }
+            Op::ModRegImm(out, arg, imm) => {
+                let reg = load_imm(&mut asm, imm, imm64);
+                asm.build_mod(out, arg, reg);
+            }
+            Op::ModImmReg(out, arg, imm) => {
+                let reg = load_imm(&mut asm, imm, imm64);
+                asm.build_mod(out, reg, arg);
+            }
+            Op::NotReg(out, arg) => {
+                asm.build_not(out, arg);
+            }
+            Op::LtRegReg(out, lhs, rhs) => {
+                asm.build_lt(out, lhs, rhs);
+            }
+            Op::LtRegImm(out, arg, imm) => {
+                let reg = load_imm(&mut asm, imm, imm64);
+                asm.build_lt(out, arg, reg);
+            }
+            Op::LtImmReg(out, arg, imm) => {
+                let reg = load_imm(&mut asm, imm, imm64);
+                asm.build_lt(out, reg, arg);
+            }
+            Op::EqRegReg(out, lhs, rhs) => {
+                asm.build_eq(out, lhs, rhs);
+            }
+            Op::EqRegImm(out, arg, imm) => {
+                let reg = load_imm(&mut asm, imm, imm64);
+                asm.build_eq(out, arg, reg);
+            }
+            Op::AndRegReg(out, lhs, rhs) => {
+                asm.build_and(out, lhs, rhs);
+            }
+            Op::AndRegImm(out, arg, imm) => {
+                let reg = load_imm(&mut asm, imm, imm64);
+                asm.build_and(out, arg, reg);
+            }
+            Op::OrRegReg(out, lhs, rhs) => {
+                asm.build_or(out, lhs, rhs);
+            }
+            Op::OrRegImm(out, arg, imm) => {
+                let reg = load_imm(&mut asm, imm, imm64);
+                asm.build_or(out, arg, reg);
+            }
+            Op::Select(out, cond, lhs, rhs) => {
+                asm.build_select(out, cond, lhs, rhs);
             }
             Op::MinRegImm(out, arg, imm) => {
-                let reg = asm.load_imm(imm);
+                let reg = load_imm(&mut asm, imm, imm64);
                 asm.build_min(out, arg, reg);
             }
             Op::MaxRegImm(out, arg, imm) => {
-                let reg = asm.load_imm(imm);
+                let reg = load_imm(&mut asm, imm, imm64);
                 asm.build_max(out, arg, reg);
             }
             Op::CopyImm(out, imm) => {
-                let reg = asm.load_imm(imm);
+                let reg = load_imm(&mut asm, imm, imm64);
                 asm.build_copy(out, reg);
             }
         }
     }
 
-    asm.finalize(0).expect("failed to build JIT function")
+    let output_count = t.output_count().try_into().unwrap();
+    asm.finalize(output_count)
+        .expect("failed to build JIT function")
     // JIT execute mode is restored here when the _guard is dropped
 }
 
@@ -696,6 +924,7 @@ impl Family for Eval {
     type PointEval = point::JitPointEval;
     type FloatSliceEval = float_slice::JitFloatSliceEval;
     type GradSliceEval = grad_slice::JitGradSliceEval;
+    type IntervalSliceEval = UnbatchedEvaluator<interval::JitIntervalEval>;
 
     fn tile_sizes_3d() -> &'static [usize] {
         &[64, 16, 8]
@@ -711,6 +940,43 @@ impl Family for Eval {
     }
 }
 
+/// JIT evaluator family with sound interval arithmetic
+///
+/// This is identical to [`Eval`], except that interval evaluation rounds
+/// outwards: each operation which may round its result widens it by at least
+/// one ulp in each direction, and constants which aren't exactly representable
+/// as an `f32` are loaded as intervals which contain them.  Functions which are
+/// implemented by calling back into Rust (`exp`, `sin`, etc) are evaluated in
+/// double precision with outward rounding.
+///
+/// As a result, interval results are guaranteed to contain the true range of
+/// the expression, at the cost of slightly wider intervals and slower
+/// evaluation.  See [`vm::SoundEval`](crate::vm::SoundEval) for an interpreted
+/// equivalent.
+#[derive(Clone)]
+pub enum SoundEval {}
+impl Family for SoundEval {
+    const REG_LIMIT: u8 = REGISTER_LIMIT;
+
+    type IntervalEval = interval::JitSoundIntervalEval;
+    type PointEval = point::JitPointEval;
+    type FloatSliceEval = float_slice::JitFloatSliceEval;
+    type GradSliceEval = grad_slice::JitGradSliceEval;
+    type IntervalSliceEval = UnbatchedEvaluator<interval::JitSoundIntervalEval>;
+
+    fn tile_sizes_3d() -> &'static [usize] {
+        Eval::tile_sizes_3d()
+    }
+
+    fn tile_sizes_2d() -> &'static [usize] {
+        Eval::tile_sizes_2d()
+    }
+
+    fn simplify_tree_during_meshing(d: usize) -> bool {
+        Eval::simplify_tree_during_meshing(d)
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 // Selects the calling convention based on platform; this is forward-looking for
@@ -750,6 +1016,7 @@ pub struct JitTracingEval<I: AssemblerT> {
             I::Data,    // X
             I::Data,    // Y
             I::Data,    // Z
+            I::Data,    // W
             *const f32, // vars
             *mut u8,    // choices
             *mut u8,    // simplify (single boolean)
@@ -772,9 +1039,10 @@ impl<I: AssemblerT> Clone for JitTracingEval<I> {
 unsafe impl<I: AssemblerT> Send for JitTracingEval<I> {}
 unsafe impl<I: AssemblerT> Sync for JitTracingEval<I> {}
 
-impl<I: AssemblerT> EvaluatorStorage<Eval> for JitTracingEval<I> {
+impl<I: AssemblerT, F> EvaluatorStorage<F> for JitTracingEval<I> {
     type Storage = Mmap;
-    fn new_with_storage(t: &Tape<Eval>, prev: Self::Storage) -> Self {
+    fn new_with_storage(t: &Tape<F>, prev: Self::Storage) -> Self {
+        assert!(t.reg_limit() <= REGISTER_LIMIT);
         let mmap = build_asm_fn_with_storage::<I>(t, prev);
         let ptr = mmap.as_ptr();
         Self {
@@ -789,7 +1057,7 @@ impl<I: AssemblerT> EvaluatorStorage<Eval> for JitTracingEval<I> {
     }
 }
 
-impl<I: AssemblerT> TracingEvaluator<I::Data, Eval> for JitTracingEval<I> {
+impl<I: AssemblerT, F> TracingEvaluator<I::Data, F> for JitTracingEval<I> {
     type Data = ();
 
     /// Evaluates a single point, capturing execution in `choices`
@@ -798,6 +1066,7 @@ impl<I: AssemblerT> TracingEvaluator<I::Data, Eval> for JitTracingEval<I> {
         x: I::Data,
         y: I::Data,
         z: I::Data,
+        w: I::Data,
         vars: &[f32],
         choices: &mut [Choice],
         _data: &mut (),
@@ -809,6 +1078,7 @@ impl<I: AssemblerT> TracingEvaluator<I::Data, Eval> for JitTracingEval<I> {
                 x,
                 y,
                 z,
+                w,
                 vars.as_ptr(),
                 choices.as_mut_ptr() as *mut u8,
                 &mut simplify,
@@ -827,6 +1097,9 @@ impl<I: AssemblerT> TracingEvaluator<I::Data, Eval> for JitTracingEval<I> {
 pub struct JitBulkEval<I: AssemblerT> {
     mmap: Arc<Mmap>,
     var_count: usize,
+    output_count: usize,
+    // W is passed last, so that the other arguments keep the same registers
+    // as they had before it was added; on `x86_64`, it's passed on the stack.
     fn_bulk: jit_fn!(
         unsafe fn(
             *const f32,   // X
@@ -835,6 +1108,7 @@ pub struct JitBulkEval<I: AssemblerT> {
             *const f32,   // vars
             *mut I::Data, // out
             u64,          // size
+            *const f32,   // W
         ) -> I::Data
     ),
 }
@@ -844,6 +1118,7 @@ impl<I: AssemblerT> Clone for JitBulkEval<I> {
         Self {
             mmap: self.mmap.clone(),
             var_count: self.var_count,
+            output_count: self.output_count,
             fn_bulk: self.fn_bulk,
         }
     }
@@ -854,14 +1129,16 @@ impl<I: AssemblerT> Clone for JitBulkEval<I> {
 unsafe impl<I: AssemblerT> Send for JitBulkEval<I> {}
 unsafe impl<I: AssemblerT> Sync for JitBulkEval<I> {}
 
-impl<I: AssemblerT> EvaluatorStorage<Eval> for JitBulkEval<I> {
+impl<I: AssemblerT, F> EvaluatorStorage<F> for JitBulkEval<I> {
     type Storage = Mmap;
-    fn new_with_storage(t: &Tape<Eval>, prev: Self::Storage) -> Self {
+    fn new_with_storage(t: &Tape<F>, prev: Self::Storage) -> Self {
+        assert!(t.reg_limit() <= REGISTER_LIMIT);
         let mmap = build_asm_fn_with_storage::<I>(t, prev);
         let ptr = mmap.as_ptr();
         Self {
             mmap: Arc::new(mmap),
             var_count: t.var_count(),
+            output_count: t.output_count(),
             fn_bulk: unsafe { std::mem::transmute(ptr) },
         }
     }
@@ -871,12 +1148,31 @@ impl<I: AssemblerT> EvaluatorStorage<Eval> for JitBulkEval<I> {
     }
 }
 
-impl<I: AssemblerT + SimdAssembler> BulkEvaluator<I::Data, Eval>
+/// Scratch data for a [`JitBulkEval`]
+///
+/// This is only used for tapes with multiple outputs, which are written by the
+/// JIT function in chunks of [`SimdAssembler::SIMD_SIZE`] items per output and
+/// must be rearranged afterwards.
+pub struct JitBulkEvalData<T>(Vec<T>);
+
+impl<T> Default for JitBulkEvalData<T> {
+    fn default() -> Self {
+        Self(vec![])
+    }
+}
+
+impl<T, F> BulkEvaluatorData<F> for JitBulkEvalData<T> {
+    fn prepare(&mut self, _tape: &Tape<F>, _size: usize) {
+        // Nothing to do here; the buffer is resized during evaluation
+    }
+}
+
+impl<I: AssemblerT + SimdAssembler, F> BulkEvaluator<I::Data, F>
     for JitBulkEval<I>
 where
-    I::Data: Copy + From<f32>,
+    I::Data: Copy + From<f32> + Send,
 {
-    type Data = ();
+    type Data = JitBulkEvalData<I::Data>;
 
     /// Evaluate multiple points
     fn eval_with(
@@ -884,15 +1180,22 @@ where
         xs: &[f32],
         ys: &[f32],
         zs: &[f32],
+        ws: &[f32],
         vars: &[f32],
         out: &mut [I::Data],
-        _data: &mut (),
+        data: &mut Self::Data,
     ) {
         assert_eq!(xs.len(), ys.len());
         assert_eq!(ys.len(), zs.len());
-        assert_eq!(zs.len(), out.len());
+        assert_eq!(zs.len(), ws.len());
+        assert_eq!(ws.len() * self.output_count, out.len());
         assert_eq!(vars.len(), self.var_count);
 
+        if self.output_count > 1 {
+            self.eval_multi(xs, ys, zs, ws, vars, out, &mut data.0);
+            return;
+        }
+
         let n = xs.len();
 
         // Special case for when we have fewer items than the native SIMD size,
@@ -907,11 +1210,13 @@ where
             let mut x = [0.0; MAX_SIMD_WIDTH];
             let mut y = [0.0; MAX_SIMD_WIDTH];
             let mut z = [0.0; MAX_SIMD_WIDTH];
+            let mut w = [0.0; MAX_SIMD_WIDTH];
             assert!(I::SIMD_SIZE <= MAX_SIMD_WIDTH);
 
             x[0..n].copy_from_slice(xs);
             y[0..n].copy_from_slice(ys);
             z[0..n].copy_from_slice(zs);
+            w[0..n].copy_from_slice(ws);
 
             let mut tmp = [std::f32::NAN.into(); MAX_SIMD_WIDTH];
 
@@ -923,6 +1228,7 @@ where
                     vars.as_ptr(),
                     tmp.as_mut_ptr(),
                     I::SIMD_SIZE as u64,
+                    w.as_ptr(),
                 );
             }
             out[0..n].copy_from_slice(&tmp[0..n]);
@@ -939,6 +1245,7 @@ where
                     vars.as_ptr(),
                     out.as_mut_ptr(),
                     m as u64,
+                    ws.as_ptr(),
                 );
             }
             // If we weren't given an even multiple of vector width, then we'll
@@ -953,6 +1260,7 @@ where
                         vars.as_ptr(),
                         out.as_mut_ptr().add(n - I::SIMD_SIZE),
                         I::SIMD_SIZE as u64,
+                        ws.as_ptr().add(n - I::SIMD_SIZE),
                     );
                 }
             }
@@ -960,13 +1268,172 @@ where
     }
 }
 
+impl<I: AssemblerT + SimdAssembler> JitBulkEval<I>
+where
+    I::Data: Copy + From<f32>,
+{
+    /// Evaluates a tape with multiple outputs
+    ///
+    /// The JIT function writes every output for one chunk before moving on to
+    /// the next, so results are collected in `buf` then rearranged into `out`
+    /// (which is output-major).
+    #[allow(clippy::too_many_arguments)]
+    fn eval_multi(
+        &self,
+        xs: &[f32],
+        ys: &[f32],
+        zs: &[f32],
+        ws: &[f32],
+        vars: &[f32],
+        out: &mut [I::Data],
+        buf: &mut Vec<I::Data>,
+    ) {
+        let n = xs.len();
+        let s = I::SIMD_SIZE;
+        let count = self.output_count;
+
+        // Full chunks are evaluated in place, and the remainder (if any) is
+        // evaluated in an extra chunk at the end of the buffer
+        let m = (n / s) * s;
+        buf.resize((m + s) * count, f32::NAN.into());
+        if m > 0 {
+            unsafe {
+                (self.fn_bulk)(
+                    xs.as_ptr(),
+                    ys.as_ptr(),
+                    zs.as_ptr(),
+                    vars.as_ptr(),
+                    buf.as_mut_ptr(),
+                    m as u64,
+                    ws.as_ptr(),
+                );
+            }
+        }
+
+        // The remainder is copied into padded arrays, starting from the last
+        // full vector (or the start of the array, if there isn't one)
+        let start = n.saturating_sub(s);
+        if n != m {
+            const MAX_SIMD_WIDTH: usize = 8;
+            let mut x = [0.0; MAX_SIMD_WIDTH];
+            let mut y = [0.0; MAX_SIMD_WIDTH];
+            let mut z = [0.0; MAX_SIMD_WIDTH];
+            let mut w = [0.0; MAX_SIMD_WIDTH];
+            assert!(s <= MAX_SIMD_WIDTH);
+
+            x[0..n - start].copy_from_slice(&xs[start..]);
+            y[0..n - start].copy_from_slice(&ys[start..]);
+            z[0..n - start].copy_from_slice(&zs[start..]);
+            w[0..n - start].copy_from_slice(&ws[start..]);
+            unsafe {
+                (self.fn_bulk)(
+                    x.as_ptr(),
+                    y.as_ptr(),
+                    z.as_ptr(),
+                    vars.as_ptr(),
+                    buf.as_mut_ptr().add(m * count),
+                    s as u64,
+                    w.as_ptr(),
+                );
+            }
+        }
+
+        for k in 0..count {
+            for i in 0..n {
+                let (chunk, lane) = if i < m {
+                    (i / s, i % s)
+                } else {
+                    (m / s, i - start)
+                };
+                out[k * n + i] = buf[(chunk * count + k) * s + lane];
+            }
+        }
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::eval::types::Interval;
     crate::grad_slice_tests!(Eval);
+    crate::var_grad_slice_tests!(Eval);
+    crate::hessian_slice_tests!(Eval);
     crate::interval_tests!(Eval);
+    crate::sound_interval_tests!(SoundEval);
+    crate::interval_slice_tests!(Eval);
+    crate::affine_tests!(Eval);
     crate::float_slice_tests!(Eval);
     crate::point_tests!(Eval);
+
+    #[test]
+    fn test_interval_choice_stores_are_bytes() {
+        let mut ctx = crate::Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let min = ctx.min(x, y).unwrap();
+        let max = ctx.max(x, y).unwrap();
+        let root = ctx.sub(min, max).unwrap();
+        let tape = ctx.get_tape::<Eval>(root).unwrap();
+        let eval =
+            JitTracingEval::<interval::IntervalAssembler<false>>::new_with_storage(
+                &tape,
+                Mmap::default(),
+            );
+
+        // Each choice and the simplify flag is a single byte, so the bytes
+        // after them must not be touched by the evaluator.
+        let n = tape.choice_count();
+        assert_eq!(n, 2);
+        let mut choices = vec![0u8; n + 1];
+        choices[n] = 0xAA;
+        let mut simplify = [0u8, 0xAA];
+        let out = unsafe {
+            (eval.fn_trace)(
+                Interval::new(0.0, 1.0),
+                Interval::new(2.0, 3.0),
+                Interval::new(0.0, 0.0),
+                Interval::new(0.0, 0.0),
+                [].as_ptr(),
+                choices.as_mut_ptr(),
+                simplify.as_mut_ptr(),
+            )
+        };
+        assert_eq!(out, Interval::new(-3.0, -1.0));
+        assert_eq!(simplify, [1, 0xAA]);
+        assert_eq!(choices[n], 0xAA);
+    }
+
+    #[test]
+    fn test_i_sound_infinite_bounds() {
+        crate::eval::interval::eval_tests::test_i_infinite_bounds::<SoundEval>()
+    }
+
+    #[test]
+    fn test_i_sound_callback_nan() {
+        // The callbacks are called from JIT code and must not panic, because
+        // unwinding through the JIT function would abort the process.
+        let mut ctx = crate::Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let lt = ctx.lt(y, x).unwrap();
+        let floor = ctx.floor(lt).unwrap();
+        let ln = ctx.ln(floor).unwrap();
+        let root = ctx.sin(ln).unwrap();
+        let tape = ctx.get_tape::<SoundEval>(root).unwrap();
+        let eval = tape.new_interval_evaluator();
+        let out = eval.eval_xy([0.0, 1.0], [0.0, 0.0]);
+        assert!(out.lower().is_nan());
+        assert!(out.upper().is_nan());
+    }
+
+    #[test]
+    fn test_interval_slice_is_not_batched() {
+        // There's no batched kernel, so callers should pass one item at a time
+        use crate::eval::IntervalSliceEval;
+        assert!(!IntervalSliceEval::<Eval>::is_batched());
+        assert!(!IntervalSliceEval::<SoundEval>::is_batched());
+        assert!(IntervalSliceEval::<crate::vm::Eval>::is_batched());
+    }
 }
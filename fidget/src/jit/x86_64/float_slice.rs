@@ -18,14 +18,16 @@ pub const SIMD_WIDTH: usize = 8;
 /// | vars     | `rcx`    | `*const f32`        |
 /// | out      | `r8`     | `*mut [f32; 8]`     |
 /// | size     | `r9`     | `u64`               |
+/// | W        | stack    | `*const [f32; 8]`   |
 ///
 /// The arrays (other than `vars`) must be an even multiple of 8 floats, since
 /// we're using AVX2 and 256-bit wide operations for everything.  The `vars`
 /// array contains single `f32` values, which are broadcast into SIMD registers
 /// when they are used.
 ///
-/// During evaluation, X, Y, and Z values are stored on the stack to keep
-/// registers unoccupied.
+/// During evaluation, X, Y, Z, and W values are stored on the stack to keep
+/// registers unoccupied.  The W pointer is the seventh argument, so it's
+/// passed on the stack (at `[rbp + 16]`) and advanced in place.
 impl AssemblerT for FloatSliceAssembler {
     type Data = f32;
 
@@ -64,6 +66,11 @@ impl AssemblerT for FloatSliceAssembler {
             ; vmovups ymm0, [rdx]
             ; vmovups [rbp - 96], ymm0
             ; add rdx, 32
+
+            ; mov rax, [rbp + 16]
+            ; vmovups ymm0, [rax]
+            ; vmovups [rbp - 128], ymm0
+            ; add QWORD [rbp + 16], 32
         );
         Self(out)
     }
@@ -130,6 +137,144 @@ impl AssemblerT for FloatSliceAssembler {
             ; vmulps Ry(reg(out_reg)), Ry(reg(lhs_reg)), Ry(reg(lhs_reg))
         );
     }
+    fn build_exp(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn float_exp(v: &mut [f32; SIMD_WIDTH]) {
+            for f in v {
+                *f = f.exp();
+            }
+        }
+        self.call_fn_unary(out_reg, lhs_reg, float_exp);
+    }
+    fn build_sin(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn float_sin(v: &mut [f32; SIMD_WIDTH]) {
+            for f in v {
+                *f = f.sin();
+            }
+        }
+        self.call_fn_unary(out_reg, lhs_reg, float_sin);
+    }
+    fn build_cos(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn float_cos(v: &mut [f32; SIMD_WIDTH]) {
+            for f in v {
+                *f = f.cos();
+            }
+        }
+        self.call_fn_unary(out_reg, lhs_reg, float_cos);
+    }
+    fn build_tan(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn float_tan(v: &mut [f32; SIMD_WIDTH]) {
+            for f in v {
+                *f = f.tan();
+            }
+        }
+        self.call_fn_unary(out_reg, lhs_reg, float_tan);
+    }
+    fn build_asin(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn float_asin(v: &mut [f32; SIMD_WIDTH]) {
+            for f in v {
+                *f = f.asin();
+            }
+        }
+        self.call_fn_unary(out_reg, lhs_reg, float_asin);
+    }
+    fn build_acos(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn float_acos(v: &mut [f32; SIMD_WIDTH]) {
+            for f in v {
+                *f = f.acos();
+            }
+        }
+        self.call_fn_unary(out_reg, lhs_reg, float_acos);
+    }
+    fn build_atan(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn float_atan(v: &mut [f32; SIMD_WIDTH]) {
+            for f in v {
+                *f = f.atan();
+            }
+        }
+        self.call_fn_unary(out_reg, lhs_reg, float_atan);
+    }
+    fn build_atan2(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "sysv64" fn float_atan2(
+            lhs: &mut [f32; SIMD_WIDTH],
+            rhs: &[f32; SIMD_WIDTH],
+        ) {
+            for (a, b) in lhs.iter_mut().zip(rhs) {
+                *a = a.atan2(*b);
+            }
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, float_atan2);
+    }
+    fn build_ln(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn float_ln(v: &mut [f32; SIMD_WIDTH]) {
+            for f in v {
+                *f = f.ln();
+            }
+        }
+        self.call_fn_unary(out_reg, lhs_reg, float_ln);
+    }
+    fn build_pow(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "sysv64" fn float_pow(
+            lhs: &mut [f32; SIMD_WIDTH],
+            rhs: &[f32; SIMD_WIDTH],
+        ) {
+            for (a, b) in lhs.iter_mut().zip(rhs) {
+                *a = a.powf(*b);
+            }
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, float_pow);
+    }
+    fn build_powi(&mut self, out_reg: u8, lhs_reg: u8, n: i32) {
+        extern "sysv64" fn float_powi(
+            lhs: &mut [f32; SIMD_WIDTH],
+            rhs: &[f32; SIMD_WIDTH],
+        ) {
+            for (a, b) in lhs.iter_mut().zip(rhs) {
+                *a = a.powi(*b as i32);
+            }
+        }
+        let imm = self.load_imm(n as f32);
+        self.call_fn_binary(out_reg, lhs_reg, imm, float_powi);
+    }
+    fn build_floor(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; vroundps Ry(reg(out_reg)), Ry(reg(lhs_reg)), 9
+        );
+    }
+    fn build_ceil(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; vroundps Ry(reg(out_reg)), Ry(reg(lhs_reg)), 10
+        );
+    }
+    fn build_round(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn float_round(v: &mut [f32; SIMD_WIDTH]) {
+            for f in v {
+                *f = f.round();
+            }
+        }
+        self.call_fn_unary(out_reg, lhs_reg, float_round);
+    }
+    fn build_mod(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "sysv64" fn float_mod(
+            lhs: &mut [f32; SIMD_WIDTH],
+            rhs: &[f32; SIMD_WIDTH],
+        ) {
+            for (a, b) in lhs.iter_mut().zip(rhs) {
+                *a = a.rem_euclid(*b);
+            }
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, float_mod);
+    }
+    fn build_not(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops
+            // Build a mask of (lhs == 0), then convert it to 0.0 / 1.0
+            ; vxorps ymm1, ymm1, ymm1
+            ; vcmpeqps ymm1, ymm1, Ry(reg(lhs_reg))
+            ; mov eax, 1f32.to_bits() as i32
+            ; vmovd xmm2, eax
+            ; vbroadcastss ymm2, xmm2
+            ; vandps Ry(reg(out_reg)), ymm1, ymm2
+        );
+    }
     fn build_add(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
         dynasm!(self.0.ops
             ; vaddps Ry(reg(out_reg)), Ry(reg(lhs_reg)), Ry(reg(rhs_reg))
@@ -162,6 +307,54 @@ impl AssemblerT for FloatSliceAssembler {
             ; vminps Ry(reg(out_reg)), Ry(reg(lhs_reg)), Ry(reg(rhs_reg))
         );
     }
+    fn build_lt(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; vcmpltps ymm1, Ry(reg(lhs_reg)), Ry(reg(rhs_reg))
+            ; mov eax, 1f32.to_bits() as i32
+            ; vmovd xmm2, eax
+            ; vbroadcastss ymm2, xmm2
+            ; vandps Ry(reg(out_reg)), ymm1, ymm2
+        );
+    }
+    fn build_eq(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; vcmpeqps ymm1, Ry(reg(lhs_reg)), Ry(reg(rhs_reg))
+            ; mov eax, 1f32.to_bits() as i32
+            ; vmovd xmm2, eax
+            ; vbroadcastss ymm2, xmm2
+            ; vandps Ry(reg(out_reg)), ymm1, ymm2
+        );
+    }
+    fn build_and(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            // Pick lhs where it is zero, and rhs everywhere else
+            ; vxorps ymm1, ymm1, ymm1
+            ; vcmpeqps ymm1, ymm1, Ry(reg(lhs_reg))
+            ; vblendvps Ry(reg(out_reg)), Ry(reg(rhs_reg)), Ry(reg(lhs_reg)), ymm1
+        );
+    }
+    fn build_or(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            // Pick rhs where lhs is zero, and lhs everywhere else
+            ; vxorps ymm1, ymm1, ymm1
+            ; vcmpeqps ymm1, ymm1, Ry(reg(lhs_reg))
+            ; vblendvps Ry(reg(out_reg)), Ry(reg(lhs_reg)), Ry(reg(rhs_reg)), ymm1
+        );
+    }
+    fn build_select(
+        &mut self,
+        out_reg: u8,
+        cond_reg: u8,
+        lhs_reg: u8,
+        rhs_reg: u8,
+    ) {
+        dynasm!(self.0.ops
+            // Pick lhs where cond is less than zero, and rhs everywhere else
+            ; vxorps ymm1, ymm1, ymm1
+            ; vcmpltps ymm1, Ry(reg(cond_reg)), ymm1
+            ; vblendvps Ry(reg(out_reg)), Ry(reg(rhs_reg)), Ry(reg(lhs_reg)), ymm1
+        );
+    }
     fn load_imm(&mut self, imm: f32) -> u8 {
         dynasm!(self.0.ops
             ; mov eax, imm.to_bits() as i32
@@ -170,11 +363,16 @@ impl AssemblerT for FloatSliceAssembler {
         );
         IMM_REG.wrapping_sub(OFFSET)
     }
-    fn finalize(mut self, out_reg: u8) -> Result<Mmap, Error> {
+    fn finalize(mut self, output_count: u8) -> Result<Mmap, Error> {
+        // Copy data from each output register into the out array
+        for i in 0..output_count {
+            dynasm!(self.0.ops
+                ; vmovups [r8 + 32 * i as i32], Ry(reg(i))
+            );
+        }
         dynasm!(self.0.ops
-            // Copy data from out_reg into the out array, then adjust it
-            ; vmovups [r8], Ry(reg(out_reg))
-            ; add r8, 32
+            // Adjust the out pointer and remaining item count
+            ; add r8, 32 * output_count as i32
             ; sub r9, 8
             ; jmp ->L
         );
@@ -182,3 +380,105 @@ impl AssemblerT for FloatSliceAssembler {
         self.0.ops.finalize()
     }
 }
+
+impl FloatSliceAssembler {
+    /// Offset of the scratch arrays used to pass arguments to Rust functions
+    const CALL_SCRATCH: i32 = 48 + 32 * REGISTER_LIMIT as i32;
+
+    /// Stack space used by [`Self::backup_registers`]: 6 pointers + 12 tape
+    /// registers + 2 scratch arrays, already 16-byte aligned
+    const CALL_STACK_SIZE: i32 = Self::CALL_SCRATCH + 64;
+
+    /// Saves our argument pointers and tape registers to the stack
+    ///
+    /// Every `ymm` register is caller-saved in the System V ABI, so this must
+    /// be called before calling into a Rust function.
+    fn backup_registers(&mut self) {
+        dynasm!(self.0.ops
+            ; sub rsp, Self::CALL_STACK_SIZE
+            ; mov [rsp], rdi
+            ; mov [rsp + 8], rsi
+            ; mov [rsp + 16], rdx
+            ; mov [rsp + 24], rcx
+            ; mov [rsp + 32], r8
+            ; mov [rsp + 40], r9
+        );
+        for i in 0..REGISTER_LIMIT {
+            let offset = 48 + 32 * i as i32;
+            dynasm!(self.0.ops
+                ; vmovups [rsp + offset], Ry(reg(i))
+            );
+        }
+    }
+
+    /// Calls the function at `addr`, then restores the registers saved by
+    /// [`Self::backup_registers`]
+    ///
+    /// The function modifies the first scratch array in place; its contents
+    /// are written to `out_reg` afterwards.
+    fn call_and_restore(&mut self, out_reg: u8, addr: usize) {
+        let scratch = Self::CALL_SCRATCH;
+        dynasm!(self.0.ops
+            // Avoid AVX-SSE transition penalties in the callee
+            ; vzeroupper
+            ; mov rax, QWORD addr as i64
+            ; call rax
+        );
+        for i in 0..REGISTER_LIMIT {
+            let offset = 48 + 32 * i as i32;
+            dynasm!(self.0.ops
+                ; vmovups Ry(reg(i)), [rsp + offset]
+            );
+        }
+        dynasm!(self.0.ops
+            ; vmovups Ry(reg(out_reg)), [rsp + scratch]
+            ; mov rdi, [rsp]
+            ; mov rsi, [rsp + 8]
+            ; mov rdx, [rsp + 16]
+            ; mov rcx, [rsp + 24]
+            ; mov r8, [rsp + 32]
+            ; mov r9, [rsp + 40]
+            ; add rsp, Self::CALL_STACK_SIZE
+        );
+    }
+
+    /// Calls a Rust function which modifies an array in place, writing the
+    /// result to `out_reg`
+    ///
+    /// The argument is written to a scratch array on the stack, and a pointer
+    /// to that array is passed to the function.
+    fn call_fn_unary(
+        &mut self,
+        out_reg: u8,
+        arg_reg: u8,
+        f: extern "sysv64" fn(&mut [f32; SIMD_WIDTH]),
+    ) {
+        let scratch = Self::CALL_SCRATCH;
+        self.backup_registers();
+        dynasm!(self.0.ops
+            ; vmovups [rsp + scratch], Ry(reg(arg_reg))
+            ; lea rdi, [rsp + scratch]
+        );
+        self.call_and_restore(out_reg, f as usize);
+    }
+
+    /// Calls a binary Rust function which writes its result into its first
+    /// argument, writing that result to `out_reg`
+    fn call_fn_binary(
+        &mut self,
+        out_reg: u8,
+        lhs_reg: u8,
+        rhs_reg: u8,
+        f: extern "sysv64" fn(&mut [f32; SIMD_WIDTH], &[f32; SIMD_WIDTH]),
+    ) {
+        let scratch = Self::CALL_SCRATCH;
+        self.backup_registers();
+        dynasm!(self.0.ops
+            ; vmovups [rsp + scratch], Ry(reg(lhs_reg))
+            ; vmovups [rsp + scratch + 32], Ry(reg(rhs_reg))
+            ; lea rdi, [rsp + scratch]
+            ; lea rsi, [rsp + scratch + 32]
+        );
+        self.call_and_restore(out_reg, f as usize);
+    }
+}
@@ -20,9 +20,11 @@ use dynasmrt::{dynasm, DynasmApi, DynasmLabelApi};
 /// | `vars`     | `rcx`    | `*const f32`       |
 /// | `out`      | `r8`     | `*const [f32; 4]`  |
 /// | `count`    | `r9`     | `u64`              |
+/// | W          | stack    | `*const f32`       |
 ///
-/// During evaluation, X, Y, and Z values are stored on the stack to keep
-/// registers unoccupied.
+/// During evaluation, X, Y, Z, and W values are stored on the stack to keep
+/// registers unoccupied.  The W pointer is the seventh argument, so it's
+/// passed on the stack (at `[rbp + 16]`) and advanced in place.
 impl AssemblerT for GradSliceAssembler {
     type Data = Grad;
 
@@ -67,6 +69,11 @@ impl AssemblerT for GradSliceAssembler {
             ; mov [rbp - 48], eax // Z
             ; add rdx, 4
 
+            ; mov rax, [rbp + 16]
+            ; mov eax, [rax]
+            ; mov [rbp - 64], eax // W
+            ; add QWORD [rbp + 16], 4
+
             ; mov eax, 0.0f32.to_bits() as i32
             ; mov [rbp - 8], eax // 0
             ; mov [rbp - 4], eax // 0
@@ -74,6 +81,9 @@ impl AssemblerT for GradSliceAssembler {
             ; mov [rbp - 20], eax // 0
             ; mov [rbp - 40], eax // 0
             ; mov [rbp - 44], eax // 0
+            ; mov [rbp - 52], eax // 0
+            ; mov [rbp - 56], eax // 0
+            ; mov [rbp - 60], eax // 0
         );
         Self(out)
     }
@@ -205,6 +215,108 @@ impl AssemblerT for GradSliceAssembler {
             ; vmulps Rx(reg(out_reg)), xmm0, Rx(reg(lhs_reg))
         );
     }
+    fn build_exp(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn grad_exp(v: Grad) -> Grad {
+            v.exp()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, grad_exp);
+    }
+    fn build_sin(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn grad_sin(v: Grad) -> Grad {
+            v.sin()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, grad_sin);
+    }
+    fn build_cos(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn grad_cos(v: Grad) -> Grad {
+            v.cos()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, grad_cos);
+    }
+    fn build_tan(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn grad_tan(v: Grad) -> Grad {
+            v.tan()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, grad_tan);
+    }
+    fn build_asin(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn grad_asin(v: Grad) -> Grad {
+            v.asin()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, grad_asin);
+    }
+    fn build_acos(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn grad_acos(v: Grad) -> Grad {
+            v.acos()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, grad_acos);
+    }
+    fn build_atan(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn grad_atan(v: Grad) -> Grad {
+            v.atan()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, grad_atan);
+    }
+    fn build_atan2(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "sysv64" fn grad_atan2(lhs: Grad, rhs: Grad) -> Grad {
+            lhs.atan2(rhs)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, grad_atan2);
+    }
+    fn build_ln(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn grad_ln(v: Grad) -> Grad {
+            v.ln()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, grad_ln);
+    }
+    fn build_pow(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "sysv64" fn grad_pow(lhs: Grad, rhs: Grad) -> Grad {
+            lhs.pow(rhs)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, grad_pow);
+    }
+    fn build_powi(&mut self, out_reg: u8, lhs_reg: u8, n: i32) {
+        extern "sysv64" fn grad_powi(lhs: Grad, rhs: Grad) -> Grad {
+            lhs.powi(rhs.v as i32)
+        }
+        let imm = self.load_imm(n as f32);
+        self.call_fn_binary(out_reg, lhs_reg, imm, grad_powi);
+    }
+    fn build_floor(&mut self, out_reg: u8, lhs_reg: u8) {
+        // The upper lanes (i.e. the partial derivatives) come from xmm0
+        dynasm!(self.0.ops
+            ; vpxor xmm0, xmm0, xmm0
+            ; vroundss Rx(reg(out_reg)), xmm0, Rx(reg(lhs_reg)), 9
+        );
+    }
+    fn build_ceil(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; vpxor xmm0, xmm0, xmm0
+            ; vroundss Rx(reg(out_reg)), xmm0, Rx(reg(lhs_reg)), 10
+        );
+    }
+    fn build_round(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn grad_round(v: Grad) -> Grad {
+            v.round()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, grad_round);
+    }
+    fn build_mod(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "sysv64" fn grad_mod(lhs: Grad, rhs: Grad) -> Grad {
+            lhs.modulo(rhs)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, grad_mod);
+    }
+    fn build_not(&mut self, out_reg: u8, lhs_reg: u8) {
+        // Comparisons have no partial derivatives, so we mask with [1, 0, 0, 0]
+        dynasm!(self.0.ops
+            ; vpxor xmm1, xmm1, xmm1
+            ; vcmpeqss xmm1, xmm1, Rx(reg(lhs_reg))
+            ; mov eax, 1f32.to_bits() as i32
+            ; vmovd xmm2, eax
+            ; vandps Rx(reg(out_reg)), xmm1, xmm2
+        );
+    }
     fn build_add(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
         dynasm!(self.0.ops
             ; vaddps Rx(reg(out_reg)), Rx(reg(lhs_reg)), Rx(reg(rhs_reg))
@@ -290,6 +402,86 @@ impl AssemblerT for GradSliceAssembler {
         );
         self.0.ops.commit_local().unwrap();
     }
+    fn build_lt(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; vcmpltss xmm1, Rx(reg(lhs_reg)), Rx(reg(rhs_reg))
+            ; mov eax, 1f32.to_bits() as i32
+            ; vmovd xmm2, eax
+            ; vandps Rx(reg(out_reg)), xmm1, xmm2
+        );
+    }
+    fn build_eq(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; vcmpeqss xmm1, Rx(reg(lhs_reg)), Rx(reg(rhs_reg))
+            ; mov eax, 1f32.to_bits() as i32
+            ; vmovd xmm2, eax
+            ; vandps Rx(reg(out_reg)), xmm1, xmm2
+        );
+    }
+    fn build_and(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; vpxor xmm1, xmm1, xmm1
+            ; vcomiss Rx(reg(lhs_reg)), xmm1
+            ; jp >R
+            ; jne >R
+
+            // Fallthrough
+            ; vmovups Rx(reg(out_reg)), Rx(reg(lhs_reg))
+            ; jmp >E
+
+            ; R:
+            ; vmovups Rx(reg(out_reg)), Rx(reg(rhs_reg))
+            // Fallthrough
+
+            ; E:
+        );
+        self.0.ops.commit_local().unwrap();
+    }
+    fn build_or(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; vpxor xmm1, xmm1, xmm1
+            ; vcomiss Rx(reg(lhs_reg)), xmm1
+            ; jp >L
+            ; jne >L
+
+            // Fallthrough
+            ; vmovups Rx(reg(out_reg)), Rx(reg(rhs_reg))
+            ; jmp >E
+
+            ; L:
+            ; vmovups Rx(reg(out_reg)), Rx(reg(lhs_reg))
+            // Fallthrough
+
+            ; E:
+        );
+        self.0.ops.commit_local().unwrap();
+    }
+    fn build_select(
+        &mut self,
+        out_reg: u8,
+        cond_reg: u8,
+        lhs_reg: u8,
+        rhs_reg: u8,
+    ) {
+        dynasm!(self.0.ops
+            ; vpxor xmm1, xmm1, xmm1
+            ; vcomiss Rx(reg(cond_reg)), xmm1
+            ; jp >R
+            ; jb >L
+
+            // Fallthrough for cond >= 0
+            ; R:
+            ; vmovups Rx(reg(out_reg)), Rx(reg(rhs_reg))
+            ; jmp >E
+
+            ; L:
+            ; vmovups Rx(reg(out_reg)), Rx(reg(lhs_reg))
+            // Fallthrough
+
+            ; E:
+        );
+        self.0.ops.commit_local().unwrap();
+    }
     fn load_imm(&mut self, imm: f32) -> u8 {
         let imm_u32 = imm.to_bits();
         dynasm!(self.0.ops
@@ -299,11 +491,16 @@ impl AssemblerT for GradSliceAssembler {
         );
         IMM_REG.wrapping_sub(OFFSET)
     }
-    fn finalize(mut self, out_reg: u8) -> Result<Mmap, Error> {
+    fn finalize(mut self, output_count: u8) -> Result<Mmap, Error> {
+        // Copy data from each output register into the out array
+        for i in 0..output_count {
+            dynasm!(self.0.ops
+                ; vmovups [r8 + 16 * i as i32], Rx(reg(i))
+            );
+        }
         dynasm!(self.0.ops
-            // Copy data from out_reg into the out array, then adjust it
-            ; vmovups [r8], Rx(reg(out_reg))
-            ; add r8, 16 // 4x float
+            // Adjust the out pointer and remaining item count
+            ; add r8, 16 * output_count as i32 // 4x float per output
             ; sub r9, 1
             ; jmp ->L
         );
@@ -311,3 +508,102 @@ impl AssemblerT for GradSliceAssembler {
         self.0.ops.finalize()
     }
 }
+
+impl GradSliceAssembler {
+    /// Stack space used by [`Self::backup_registers`]: 6 pointers + 12 tape
+    /// registers, already 16-byte aligned
+    const CALL_STACK_SIZE: i32 = 240;
+
+    /// Saves our argument pointers and tape registers to the stack
+    ///
+    /// Every `xmm` register is caller-saved in the System V ABI, so this must
+    /// be called before calling into a Rust function.
+    fn backup_registers(&mut self) {
+        dynasm!(self.0.ops
+            ; sub rsp, Self::CALL_STACK_SIZE
+            ; mov [rsp], rdi
+            ; mov [rsp + 8], rsi
+            ; mov [rsp + 16], rdx
+            ; mov [rsp + 24], rcx
+            ; mov [rsp + 32], r8
+            ; mov [rsp + 40], r9
+        );
+        for i in 0..REGISTER_LIMIT {
+            let offset = 48 + 16 * i as i32;
+            dynasm!(self.0.ops
+                ; vmovups [rsp + offset], Rx(reg(i))
+            );
+        }
+    }
+
+    /// Restores the registers saved by [`Self::backup_registers`]
+    fn restore_registers(&mut self) {
+        for i in 0..REGISTER_LIMIT {
+            let offset = 48 + 16 * i as i32;
+            dynasm!(self.0.ops
+                ; vmovups Rx(reg(i)), [rsp + offset]
+            );
+        }
+        dynasm!(self.0.ops
+            ; mov rdi, [rsp]
+            ; mov rsi, [rsp + 8]
+            ; mov rdx, [rsp + 16]
+            ; mov rcx, [rsp + 24]
+            ; mov r8, [rsp + 32]
+            ; mov r9, [rsp + 40]
+            ; add rsp, Self::CALL_STACK_SIZE
+        );
+    }
+
+    /// Calls a unary Rust function, writing its result to `out_reg`
+    ///
+    /// A `Grad` is passed and returned split across the lower 64 bits of
+    /// `xmm0` (value, dx) and `xmm1` (dy, dz).
+    fn call_fn_unary(
+        &mut self,
+        out_reg: u8,
+        arg_reg: u8,
+        f: extern "sysv64" fn(Grad) -> Grad,
+    ) {
+        let addr = f as usize;
+        self.backup_registers();
+        dynasm!(self.0.ops
+            ; vmovhlps xmm1, xmm1, Rx(reg(arg_reg))
+            ; vmovq xmm0, Rx(reg(arg_reg))
+            ; mov rax, QWORD addr as i64
+            ; call rax
+        );
+        self.restore_registers();
+        dynasm!(self.0.ops
+            // The result is in xmm0-1, which aren't tape registers
+            ; vmovlhps Rx(reg(out_reg)), xmm0, xmm1
+        );
+    }
+
+    /// Calls a binary Rust function, writing its result to `out_reg`
+    ///
+    /// The second `Grad` argument is passed in `xmm2` and `xmm3`.
+    fn call_fn_binary(
+        &mut self,
+        out_reg: u8,
+        lhs_reg: u8,
+        rhs_reg: u8,
+        f: extern "sysv64" fn(Grad, Grad) -> Grad,
+    ) {
+        let addr = f as usize;
+        self.backup_registers();
+        dynasm!(self.0.ops
+            // Either argument may be in xmm0 (IMM_REG), so set it last
+            ; vmovhlps xmm3, xmm3, Rx(reg(rhs_reg))
+            ; vmovq xmm2, Rx(reg(rhs_reg))
+            ; vmovhlps xmm1, xmm1, Rx(reg(lhs_reg))
+            ; vmovq xmm0, Rx(reg(lhs_reg))
+            ; mov rax, QWORD addr as i64
+            ; call rax
+        );
+        self.restore_registers();
+        dynasm!(self.0.ops
+            ; vmovlhps Rx(reg(out_reg)), xmm0, xmm1
+        );
+    }
+}
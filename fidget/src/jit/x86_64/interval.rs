@@ -1,10 +1,12 @@
 use crate::{
     eval::types::Interval,
     jit::{
-        interval::IntervalAssembler, mmap::Mmap, reg, AssemblerData,
-        AssemblerT, CHOICE_BOTH, CHOICE_LEFT, CHOICE_RIGHT, IMM_REG, OFFSET,
-        REGISTER_LIMIT,
+        interval::{binary, imm_interval, powi, unary, IntervalAssembler},
+        mmap::Mmap,
+        reg, AssemblerData, AssemblerT, CHOICE_BOTH, CHOICE_LEFT, CHOICE_RIGHT,
+        IMM_REG, OFFSET, REGISTER_LIMIT,
     },
+    vm::{SoundInterval, Value},
     Error,
 };
 use dynasmrt::{dynasm, DynasmApi, DynasmLabelApi};
@@ -18,11 +20,12 @@ use dynasmrt::{dynasm, DynasmApi, DynasmLabelApi};
 /// | X          | `xmm0`   | `[f32; 2]`            |
 /// | Y          | `xmm1`   | `[f32; 2]`            |
 /// | Z          | `xmm2`   | `[f32; 2]`            |
+/// | W          | `xmm3`   | `[f32; 2]`            |
 /// | `vars`     | `rdi`    | `*const f32` (array)  |
 /// | `choices`  | `rsi`    | `*mut u8` (array)     |
 /// | `simplify` | `rdx`    | `*mut u8` (single)    |
 #[cfg(target_arch = "x86_64")]
-impl AssemblerT for IntervalAssembler {
+impl<const SOUND: bool> AssemblerT for IntervalAssembler<SOUND> {
     type Data = Interval;
 
     fn init(mmap: Mmap, slot_count: usize) -> Self {
@@ -32,10 +35,11 @@ impl AssemblerT for IntervalAssembler {
             ; mov rbp, rsp
             ; vzeroupper
 
-            // Put X/Y/Z on the stack so we can use those registers
+            // Put X/Y/Z/W on the stack so we can use those registers
             ; movq [rbp - 8], xmm0
             ; movq [rbp - 16], xmm1
             ; movq [rbp - 24], xmm2
+            ; movq [rbp - 32], xmm3
         );
         out.prepare_stack(slot_count);
         Self(out)
@@ -138,6 +142,7 @@ impl AssemblerT for IntervalAssembler {
             ; E:
         );
         self.0.ops.commit_local().unwrap();
+        self.clamp_non_negative(out_reg);
     }
     fn build_recip(&mut self, out_reg: u8, lhs_reg: u8) {
         dynasm!(self.0.ops
@@ -166,6 +171,7 @@ impl AssemblerT for IntervalAssembler {
             ; E:
         );
         self.0.ops.commit_local().unwrap();
+        self.round_outward(out_reg);
     }
     fn build_sqrt(&mut self, out_reg: u8, lhs_reg: u8) {
         dynasm!(self.0.ops
@@ -196,6 +202,8 @@ impl AssemblerT for IntervalAssembler {
             ; E:
         );
         self.0.ops.commit_local().unwrap();
+        self.round_outward(out_reg);
+        self.clamp_non_negative(out_reg);
     }
     fn build_square(&mut self, out_reg: u8, lhs_reg: u8) {
         dynasm!(self.0.ops
@@ -227,17 +235,144 @@ impl AssemblerT for IntervalAssembler {
             ; E:
         );
         self.0.ops.commit_local().unwrap();
+        self.round_outward(out_reg);
+        self.clamp_non_negative(out_reg);
+    }
+    fn build_exp(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn interval_exp<const SOUND: bool>(
+            v: Interval,
+        ) -> Interval {
+            unary::<SOUND>(v, Interval::exp, SoundInterval::exp)
+        }
+        self.call_fn_unary(out_reg, lhs_reg, interval_exp::<SOUND>);
+    }
+    fn build_sin(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn interval_sin<const SOUND: bool>(
+            v: Interval,
+        ) -> Interval {
+            unary::<SOUND>(v, Interval::sine, SoundInterval::sin)
+        }
+        self.call_fn_unary(out_reg, lhs_reg, interval_sin::<SOUND>);
+    }
+    fn build_cos(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn interval_cos<const SOUND: bool>(
+            v: Interval,
+        ) -> Interval {
+            unary::<SOUND>(v, Interval::cosine, SoundInterval::cos)
+        }
+        self.call_fn_unary(out_reg, lhs_reg, interval_cos::<SOUND>);
+    }
+    fn build_tan(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn interval_tan<const SOUND: bool>(
+            v: Interval,
+        ) -> Interval {
+            unary::<SOUND>(v, Interval::tan, SoundInterval::tan)
+        }
+        self.call_fn_unary(out_reg, lhs_reg, interval_tan::<SOUND>);
+    }
+    fn build_asin(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn interval_asin<const SOUND: bool>(
+            v: Interval,
+        ) -> Interval {
+            unary::<SOUND>(v, Interval::asin, SoundInterval::asin)
+        }
+        self.call_fn_unary(out_reg, lhs_reg, interval_asin::<SOUND>);
+    }
+    fn build_acos(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn interval_acos<const SOUND: bool>(
+            v: Interval,
+        ) -> Interval {
+            unary::<SOUND>(v, Interval::acos, SoundInterval::acos)
+        }
+        self.call_fn_unary(out_reg, lhs_reg, interval_acos::<SOUND>);
+    }
+    fn build_atan(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn interval_atan<const SOUND: bool>(
+            v: Interval,
+        ) -> Interval {
+            unary::<SOUND>(v, Interval::atan, SoundInterval::atan)
+        }
+        self.call_fn_unary(out_reg, lhs_reg, interval_atan::<SOUND>);
+    }
+    fn build_atan2(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "sysv64" fn interval_atan2<const SOUND: bool>(
+            lhs: Interval,
+            rhs: Interval,
+        ) -> Interval {
+            binary::<SOUND>(lhs, rhs, Interval::atan2, SoundInterval::atan2)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, interval_atan2::<SOUND>);
+    }
+    fn build_ln(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn interval_ln<const SOUND: bool>(
+            v: Interval,
+        ) -> Interval {
+            unary::<SOUND>(v, Interval::ln, SoundInterval::ln)
+        }
+        self.call_fn_unary(out_reg, lhs_reg, interval_ln::<SOUND>);
+    }
+    fn build_pow(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "sysv64" fn interval_pow<const SOUND: bool>(
+            lhs: Interval,
+            rhs: Interval,
+        ) -> Interval {
+            binary::<SOUND>(lhs, rhs, Interval::pow, SoundInterval::pow)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, interval_pow::<SOUND>);
+    }
+    fn build_powi(&mut self, out_reg: u8, lhs_reg: u8, n: i32) {
+        extern "sysv64" fn interval_powi<const SOUND: bool>(
+            lhs: Interval,
+            rhs: Interval,
+        ) -> Interval {
+            powi::<SOUND>(lhs, rhs.lower() as i32)
+        }
+        let imm = self.load_imm(n as f32);
+        self.call_fn_binary(out_reg, lhs_reg, imm, interval_powi::<SOUND>);
+    }
+    fn build_floor(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; vroundps Rx(reg(out_reg)), Rx(reg(lhs_reg)), 9
+        );
+    }
+    fn build_ceil(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; vroundps Rx(reg(out_reg)), Rx(reg(lhs_reg)), 10
+        );
+    }
+    fn build_round(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn interval_round(v: Interval) -> Interval {
+            v.round()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, interval_round);
+    }
+    fn build_mod(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "sysv64" fn interval_mod<const SOUND: bool>(
+            lhs: Interval,
+            rhs: Interval,
+        ) -> Interval {
+            binary::<SOUND>(lhs, rhs, Interval::modulo, SoundInterval::modulo)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, interval_mod::<SOUND>);
+    }
+    fn build_not(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn interval_not(v: Interval) -> Interval {
+            !v
+        }
+        self.call_fn_unary(out_reg, lhs_reg, interval_not);
     }
     fn build_add(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
         dynasm!(self.0.ops
             ; vaddps Rx(reg(out_reg)), Rx(reg(lhs_reg)), Rx(reg(rhs_reg))
         );
+        self.round_outward(out_reg);
     }
     fn build_sub(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
         dynasm!(self.0.ops
             ; vpshufd xmm1, Rx(reg(rhs_reg)), 0b11110001u8 as i8
             ; vsubps Rx(reg(out_reg)), Rx(reg(lhs_reg)), xmm1
         );
+        self.round_outward(out_reg);
     }
     fn build_mul(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
         dynasm!(self.0.ops
@@ -260,6 +395,7 @@ impl AssemblerT for IntervalAssembler {
             // Splice the two together
             ; vunpcklps Rx(reg(out_reg)), Rx(reg(out_reg)), xmm2
         );
+        self.round_outward(out_reg);
     }
     fn build_div(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
         dynasm!(self.0.ops
@@ -301,10 +437,11 @@ impl AssemblerT for IntervalAssembler {
             ; E:
         );
         self.0.ops.commit_local().unwrap();
+        self.round_outward(out_reg);
     }
     fn build_max(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
         dynasm!(self.0.ops
-            ; mov ax, [rsi]
+            ; mov al, [rsi]
 
             // xmm1 = lhs.upper
             ; vpshufd xmm1, Rx(reg(lhs_reg)), 0b11111101u8 as i8
@@ -320,11 +457,11 @@ impl AssemblerT for IntervalAssembler {
 
             // Fallthrough: ambiguous case
             ; vmaxps Rx(reg(out_reg)), Rx(reg(lhs_reg)), Rx(reg(rhs_reg))
-            ; or ax, CHOICE_BOTH as i16
+            ; or al, CHOICE_BOTH as i8
             ; jmp >E
 
             ; N:
-            ; or ax, CHOICE_BOTH as i16
+            ; or al, CHOICE_BOTH as i8
             // Load NaN into out_reg
             ; vpcmpeqw Rx(reg(out_reg)), Rx(reg(out_reg)), Rx(reg(out_reg))
             ; vpslld Rx(reg(out_reg)), Rx(reg(out_reg)), 23
@@ -334,21 +471,19 @@ impl AssemblerT for IntervalAssembler {
             // lhs.upper < rhs.lower
             ; L:
             ; vmovq Rx(reg(out_reg)), Rx(reg(lhs_reg))
-            ; or ax, CHOICE_LEFT as i16
-            ; mov cx, 1 // TODO: why can't we write 1 to [rdx] directly?
-            ; mov [rdx], cx
+            ; or al, CHOICE_LEFT as i8
+            ; mov BYTE [rdx], 1
             ; jmp >E
 
             // rhs.upper < lhs.lower
             ; R:
             ; vmovq Rx(reg(out_reg)), Rx(reg(rhs_reg))
-            ; or ax, CHOICE_RIGHT as i16
-            ; mov cx, 1
-            ; mov [rdx], cx
+            ; or al, CHOICE_RIGHT as i8
+            ; mov BYTE [rdx], 1
             // Fallthrough
 
             ; E:
-            ; mov [rsi], ax
+            ; mov [rsi], al
             ; add rsi, 1
         );
         self.0.ops.commit_local().unwrap();
@@ -366,7 +501,7 @@ impl AssemblerT for IntervalAssembler {
             //      *choices++ |= CHOICE_BOTH
             //      out = fmin(lhs, rhs)
 
-            ; mov ax, [rsi]
+            ; mov al, [rsi]
 
             // TODO: use cmpltss to do both comparisons?
 
@@ -384,11 +519,11 @@ impl AssemblerT for IntervalAssembler {
 
             // Fallthrough: ambiguous case
             ; vminps Rx(reg(out_reg)), Rx(reg(lhs_reg)), Rx(reg(rhs_reg))
-            ; or ax, CHOICE_BOTH as i16
+            ; or al, CHOICE_BOTH as i8
             ; jmp >E
 
             ; N:
-            ; or ax, CHOICE_BOTH as i16
+            ; or al, CHOICE_BOTH as i8
             // Load NAN into out_reg
             ; vpcmpeqw Rx(reg(out_reg)), Rx(reg(out_reg)), Rx(reg(out_reg))
             ; vpslld Rx(reg(out_reg)), Rx(reg(out_reg)), 23
@@ -398,21 +533,246 @@ impl AssemblerT for IntervalAssembler {
             // lhs.upper < rhs.lower
             ; L:
             ; vmovq Rx(reg(out_reg)), Rx(reg(lhs_reg))
-            ; or ax, CHOICE_LEFT as i16
-            ; mov cx, 1 // TODO: why can't we write 1 to [rdx] directly?
-            ; mov [rdx], cx
+            ; or al, CHOICE_LEFT as i8
+            ; mov BYTE [rdx], 1
             ; jmp >E
 
             // rhs.upper < lhs.lower
             ; R:
             ; vmovq Rx(reg(out_reg)), Rx(reg(rhs_reg))
-            ; or ax, CHOICE_RIGHT as i16
-            ; mov cx, 1
-            ; mov [rdx], cx
+            ; or al, CHOICE_RIGHT as i8
+            ; mov BYTE [rdx], 1
             // Fallthrough
 
             ; E:
-            ; mov [rsi], ax
+            ; mov [rsi], al
+            ; add rsi, 1
+        );
+        self.0.ops.commit_local().unwrap();
+    }
+    fn build_lt(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "sysv64" fn interval_lt(
+            lhs: Interval,
+            rhs: Interval,
+        ) -> Interval {
+            lhs.lt(rhs)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, interval_lt);
+    }
+    fn build_eq(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "sysv64" fn interval_eq(
+            lhs: Interval,
+            rhs: Interval,
+        ) -> Interval {
+            lhs.eq(rhs)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, interval_eq);
+    }
+    fn build_and(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            //  if lhs or rhs contains NaN
+            //      *choices++ |= CHOICE_BOTH
+            //      out = NaN
+            //  elif lhs does not contain 0
+            //      *choices++ |= CHOICE_RIGHT
+            //      out = rhs
+            //  elif lhs == [0, 0]
+            //      *choices++ |= CHOICE_LEFT
+            //      out = lhs
+            //  else
+            //      *choices++ |= CHOICE_BOTH
+            //      out = [min(rhs.lower, 0), max(rhs.upper, 0)]
+
+            ; mov al, [rsi]
+
+            // xmm1 = lhs.upper, xmm2 = rhs.upper
+            ; vpshufd xmm1, Rx(reg(lhs_reg)), 0b11111101u8 as i8
+            ; vpshufd xmm2, Rx(reg(rhs_reg)), 0b11111101u8 as i8
+            ; vcomiss xmm1, Rx(reg(lhs_reg))
+            ; jp >N
+            ; vcomiss xmm2, Rx(reg(rhs_reg))
+            ; jp >N
+
+            ; vpxor xmm3, xmm3, xmm3 // xmm3 = 0.0
+            ; vcomiss Rx(reg(lhs_reg)), xmm3
+            ; ja >R // lhs.lower > 0
+            ; vcomiss xmm1, xmm3
+            ; jb >R // lhs.upper < 0
+            ; vcomiss Rx(reg(lhs_reg)), xmm3
+            ; jne >B
+            ; vcomiss xmm1, xmm3
+            ; jne >B
+
+            // Fallthrough: lhs is exactly zero
+            ; vmovq Rx(reg(out_reg)), Rx(reg(lhs_reg))
+            ; or al, CHOICE_LEFT as i8
+            ; mov BYTE [rdx], 1
+            ; jmp >E
+
+            ; R:
+            ; vmovq Rx(reg(out_reg)), Rx(reg(rhs_reg))
+            ; or al, CHOICE_RIGHT as i8
+            ; mov BYTE [rdx], 1
+            ; jmp >E
+
+            // Ambiguous case: extend rhs to include zero
+            ; B:
+            ; vminps xmm1, Rx(reg(rhs_reg)), xmm3
+            ; vmaxps xmm2, Rx(reg(rhs_reg)), xmm3
+            // dynasm takes the lanes selected by the mask from the first source
+            ; vblendps Rx(reg(out_reg)), xmm2, xmm1, 0b10
+            ; or al, CHOICE_BOTH as i8
+            ; jmp >E
+
+            ; N:
+            ; or al, CHOICE_BOTH as i8
+            ; vpcmpeqw Rx(reg(out_reg)), Rx(reg(out_reg)), Rx(reg(out_reg))
+            ; vpslld Rx(reg(out_reg)), Rx(reg(out_reg)), 23
+            ; vpsrld Rx(reg(out_reg)), Rx(reg(out_reg)), 1
+            // Fallthrough
+
+            ; E:
+            ; mov [rsi], al
+            ; add rsi, 1
+        );
+        self.0.ops.commit_local().unwrap();
+    }
+    fn build_or(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            //  if lhs or rhs contains NaN
+            //      *choices++ |= CHOICE_BOTH
+            //      out = NaN
+            //  elif lhs does not contain 0
+            //      *choices++ |= CHOICE_LEFT
+            //      out = lhs
+            //  elif lhs == [0, 0]
+            //      *choices++ |= CHOICE_RIGHT
+            //      out = rhs
+            //  else
+            //      *choices++ |= CHOICE_BOTH
+            //      out = [min(lhs.lower, rhs.lower), max(lhs.upper, rhs.upper)]
+
+            ; mov al, [rsi]
+
+            // xmm1 = lhs.upper, xmm2 = rhs.upper
+            ; vpshufd xmm1, Rx(reg(lhs_reg)), 0b11111101u8 as i8
+            ; vpshufd xmm2, Rx(reg(rhs_reg)), 0b11111101u8 as i8
+            ; vcomiss xmm1, Rx(reg(lhs_reg))
+            ; jp >N
+            ; vcomiss xmm2, Rx(reg(rhs_reg))
+            ; jp >N
+
+            ; vpxor xmm3, xmm3, xmm3 // xmm3 = 0.0
+            ; vcomiss Rx(reg(lhs_reg)), xmm3
+            ; ja >L // lhs.lower > 0
+            ; vcomiss xmm1, xmm3
+            ; jb >L // lhs.upper < 0
+            ; vcomiss Rx(reg(lhs_reg)), xmm3
+            ; jne >B
+            ; vcomiss xmm1, xmm3
+            ; jne >B
+
+            // Fallthrough: lhs is exactly zero
+            ; vmovq Rx(reg(out_reg)), Rx(reg(rhs_reg))
+            ; or al, CHOICE_RIGHT as i8
+            ; mov BYTE [rdx], 1
+            ; jmp >E
+
+            ; L:
+            ; vmovq Rx(reg(out_reg)), Rx(reg(lhs_reg))
+            ; or al, CHOICE_LEFT as i8
+            ; mov BYTE [rdx], 1
+            ; jmp >E
+
+            // Ambiguous case: take the union of both intervals
+            ; B:
+            ; vminps xmm1, Rx(reg(lhs_reg)), Rx(reg(rhs_reg))
+            ; vmaxps xmm2, Rx(reg(lhs_reg)), Rx(reg(rhs_reg))
+            ; vblendps Rx(reg(out_reg)), xmm2, xmm1, 0b10
+            ; or al, CHOICE_BOTH as i8
+            ; jmp >E
+
+            ; N:
+            ; or al, CHOICE_BOTH as i8
+            ; vpcmpeqw Rx(reg(out_reg)), Rx(reg(out_reg)), Rx(reg(out_reg))
+            ; vpslld Rx(reg(out_reg)), Rx(reg(out_reg)), 23
+            ; vpsrld Rx(reg(out_reg)), Rx(reg(out_reg)), 1
+            // Fallthrough
+
+            ; E:
+            ; mov [rsi], al
+            ; add rsi, 1
+        );
+        self.0.ops.commit_local().unwrap();
+    }
+    fn build_select(
+        &mut self,
+        out_reg: u8,
+        cond_reg: u8,
+        lhs_reg: u8,
+        rhs_reg: u8,
+    ) {
+        dynasm!(self.0.ops
+            //  if cond, lhs, or rhs contains NaN
+            //      *choices++ |= CHOICE_BOTH
+            //      out = NaN
+            //  elif cond.upper < 0
+            //      *choices++ |= CHOICE_LEFT
+            //      out = lhs
+            //  elif cond.lower >= 0
+            //      *choices++ |= CHOICE_RIGHT
+            //      out = rhs
+            //  else
+            //      *choices++ |= CHOICE_BOTH
+            //      out = [min(lhs.lower, rhs.lower), max(lhs.upper, rhs.upper)]
+
+            ; mov al, [rsi]
+
+            // xmm1 = cond.upper
+            ; vpshufd xmm1, Rx(reg(cond_reg)), 0b11111101u8 as i8
+            ; vcomiss xmm1, Rx(reg(cond_reg))
+            ; jp >N
+            ; vpshufd xmm2, Rx(reg(lhs_reg)), 0b11111101u8 as i8
+            ; vcomiss xmm2, Rx(reg(lhs_reg))
+            ; jp >N
+            ; vpshufd xmm2, Rx(reg(rhs_reg)), 0b11111101u8 as i8
+            ; vcomiss xmm2, Rx(reg(rhs_reg))
+            ; jp >N
+
+            ; vpxor xmm2, xmm2, xmm2 // xmm2 = 0.0
+            ; vcomiss xmm1, xmm2
+            ; jb >L // cond.upper < 0
+            ; vcomiss Rx(reg(cond_reg)), xmm2
+            ; jae >R // cond.lower >= 0
+
+            // Fallthrough: ambiguous case, so take the union of both intervals
+            ; vminps xmm1, Rx(reg(lhs_reg)), Rx(reg(rhs_reg))
+            ; vmaxps xmm2, Rx(reg(lhs_reg)), Rx(reg(rhs_reg))
+            ; vblendps Rx(reg(out_reg)), xmm2, xmm1, 0b10
+            ; or al, CHOICE_BOTH as i8
+            ; jmp >E
+
+            ; L:
+            ; vmovq Rx(reg(out_reg)), Rx(reg(lhs_reg))
+            ; or al, CHOICE_LEFT as i8
+            ; mov BYTE [rdx], 1
+            ; jmp >E
+
+            ; R:
+            ; vmovq Rx(reg(out_reg)), Rx(reg(rhs_reg))
+            ; or al, CHOICE_RIGHT as i8
+            ; mov BYTE [rdx], 1
+            ; jmp >E
+
+            ; N:
+            ; or al, CHOICE_BOTH as i8
+            ; vpcmpeqw Rx(reg(out_reg)), Rx(reg(out_reg)), Rx(reg(out_reg))
+            ; vpslld Rx(reg(out_reg)), Rx(reg(out_reg)), 23
+            ; vpsrld Rx(reg(out_reg)), Rx(reg(out_reg)), 1
+            // Fallthrough
+
+            ; E:
+            ; mov [rsi], al
             ; add rsi, 1
         );
         self.0.ops.commit_local().unwrap();
@@ -426,9 +786,21 @@ impl AssemblerT for IntervalAssembler {
         );
         IMM_REG.wrapping_sub(OFFSET)
     }
-    fn finalize(mut self, out_reg: u8) -> Result<Mmap, Error> {
+    fn load_imm64(&mut self, imm: f64) -> u8 {
+        let Some(i) = imm_interval::<SOUND>(imm) else {
+            return self.load_imm(imm as f32);
+        };
         dynasm!(self.0.ops
-            ; vmovq xmm0, Rx(reg(out_reg))
+            ; mov eax, i.lower().to_bits() as i32
+            ; vmovd Rx(IMM_REG), eax
+            ; mov eax, i.upper().to_bits() as i32
+            ; vpinsrd Rx(IMM_REG), Rx(IMM_REG), eax, 1
+        );
+        IMM_REG.wrapping_sub(OFFSET)
+    }
+    fn finalize(mut self, _output_count: u8) -> Result<Mmap, Error> {
+        dynasm!(self.0.ops
+            ; vmovq xmm0, Rx(reg(0))
             ; add rsp, self.0.mem_offset as i32
             ; pop rbp
             ; emms
@@ -438,3 +810,147 @@ impl AssemblerT for IntervalAssembler {
         Ok(out)
     }
 }
+
+#[cfg(target_arch = "x86_64")]
+impl<const SOUND: bool> IntervalAssembler<SOUND> {
+    /// If `SOUND` is set, widens the interval in `out_reg` by at least one ulp
+    /// in each direction
+    ///
+    /// This is called after operations which may round their results.  The
+    /// lower bound is negated, so that both bounds are rounded upwards by
+    /// adding `|v| * 2^-23` (at least one ulp) plus the smallest subnormal.
+    /// Taking the maximum of the original and widened values keeps infinities
+    /// in place, since `vmaxps` returns its second argument if either is NaN.
+    fn round_outward(&mut self, out_reg: u8) {
+        if !SOUND {
+            return;
+        }
+        dynasm!(self.0.ops
+            // xmm3 = [-0.0, 0.0, 0.0, 0.0], used to negate the lower bound
+            ; mov eax, 0x8000_0000u32 as i32
+            ; vmovd xmm3, eax
+            ; vxorps Rx(reg(out_reg)), Rx(reg(out_reg)), xmm3
+
+            // xmm1 = |v| * 2^-23 + MIN_SUBNORMAL, in the lower two lanes
+            // only; other code assumes that the upper lanes are zero.
+            ; vpslld xmm1, Rx(reg(out_reg)), 1
+            ; vpsrld xmm1, xmm1, 1
+            ; mov eax, 0x3400_0000 // 2^-23
+            ; vmovd xmm2, eax
+            ; vpunpckldq xmm2, xmm2, xmm2
+            ; vmulps xmm1, xmm1, xmm2
+            ; mov eax, 1 // smallest subnormal
+            ; vmovd xmm2, eax
+            ; vpunpckldq xmm2, xmm2, xmm2
+            ; vaddps xmm1, xmm1, xmm2
+
+            ; vaddps xmm1, xmm1, Rx(reg(out_reg))
+            ; vmaxps Rx(reg(out_reg)), xmm1, Rx(reg(out_reg))
+            ; vxorps Rx(reg(out_reg)), Rx(reg(out_reg)), xmm3
+        );
+    }
+
+    /// If `SOUND` is set, clamps the interval in `out_reg` to be non-negative
+    ///
+    /// This is called after operations whose true result is never negative
+    /// (e.g. `sqrt`), so that rounding outwards can't push a lower bound of
+    /// zero below it.  NaN bounds are left in place, since `vmaxps` returns its
+    /// second argument if either is NaN.
+    fn clamp_non_negative(&mut self, out_reg: u8) {
+        if !SOUND {
+            return;
+        }
+        dynasm!(self.0.ops
+            ; vpxor xmm0, xmm0, xmm0
+            ; vmaxps Rx(reg(out_reg)), xmm0, Rx(reg(out_reg))
+        );
+    }
+
+    /// Stack space used by [`Self::backup_registers`]: 3 pointers + 12 tape
+    /// registers, rounded up for 16-byte alignment
+    const CALL_STACK_SIZE: i32 = 128;
+
+    /// Saves our argument pointers and tape registers to the stack
+    ///
+    /// Every `xmm` register is caller-saved in the System V ABI, so this must
+    /// be called before calling into a Rust function.
+    fn backup_registers(&mut self) {
+        dynasm!(self.0.ops
+            ; sub rsp, Self::CALL_STACK_SIZE
+            ; mov [rsp], rdi
+            ; mov [rsp + 8], rsi
+            ; mov [rsp + 16], rdx
+        );
+        for i in 0..REGISTER_LIMIT {
+            let offset = 24 + 8 * i as i32;
+            dynasm!(self.0.ops
+                ; movq [rsp + offset], Rx(reg(i))
+            );
+        }
+    }
+
+    /// Restores the registers saved by [`Self::backup_registers`]
+    fn restore_registers(&mut self) {
+        for i in 0..REGISTER_LIMIT {
+            let offset = 24 + 8 * i as i32;
+            dynasm!(self.0.ops
+                ; movq Rx(reg(i)), [rsp + offset]
+            );
+        }
+        dynasm!(self.0.ops
+            ; mov rdi, [rsp]
+            ; mov rsi, [rsp + 8]
+            ; mov rdx, [rsp + 16]
+            ; add rsp, Self::CALL_STACK_SIZE
+        );
+    }
+
+    /// Calls a unary Rust function, writing its result to `out_reg`
+    ///
+    /// An `Interval` is passed and returned in the lower 64 bits of `xmm0`.
+    fn call_fn_unary(
+        &mut self,
+        out_reg: u8,
+        arg_reg: u8,
+        f: extern "sysv64" fn(Interval) -> Interval,
+    ) {
+        let addr = f as usize;
+        self.backup_registers();
+        dynasm!(self.0.ops
+            ; vmovq xmm0, Rx(reg(arg_reg))
+            ; mov rax, QWORD addr as i64
+            ; call rax
+        );
+        self.restore_registers();
+        dynasm!(self.0.ops
+            // The result is in xmm0, which isn't one of our tape registers
+            ; vmovq Rx(reg(out_reg)), xmm0
+        );
+    }
+
+    /// Calls a binary Rust function, writing its result to `out_reg`
+    ///
+    /// The second `Interval` argument is passed in the lower 64 bits of
+    /// `xmm1`.
+    fn call_fn_binary(
+        &mut self,
+        out_reg: u8,
+        lhs_reg: u8,
+        rhs_reg: u8,
+        f: extern "sysv64" fn(Interval, Interval) -> Interval,
+    ) {
+        let addr = f as usize;
+        self.backup_registers();
+        dynasm!(self.0.ops
+            // Either argument may be in xmm0 (IMM_REG), so set it last
+            ; vmovq xmm1, Rx(reg(rhs_reg))
+            ; vmovq xmm0, Rx(reg(lhs_reg))
+            ; mov rax, QWORD addr as i64
+            ; call rax
+        );
+        self.restore_registers();
+        dynasm!(self.0.ops
+            ; vmovq Rx(reg(out_reg)), xmm0
+        );
+    }
+}
@@ -4,7 +4,12 @@
 //! tape must be planned with a <= 12 register limit; any spills will live on
 //! the stack.
 //!
-//! Right now, we never call anything, so don't worry about saving stuff.
+//! Transcendental functions (`exp`, `sin`, `atan2`, etc) are implemented by
+//! calling back into Rust.  All of the `xmm` / `ymm` registers are caller-saved
+//! in the System V ABI, so each assembler's `backup_registers` saves every tape
+//! register (and the argument pointers) to the stack before the call.  `round`
+//! is also implemented this way, because the SSE rounding modes only support
+//! breaking ties towards the even integer.
 //!
 //! Within a single operation, you'll often need to make use of scratch
 //! registers.  `xmm0` is used when loading immediates, and should not be used
@@ -17,6 +17,7 @@ use dynasmrt::{dynasm, DynasmApi, DynasmLabelApi};
 /// | X          | `xmm0`   | `f32`                 |
 /// | Y          | `xmm1`   | `f32`                 |
 /// | Z          | `xmm2`   | `f32`                 |
+/// | W          | `xmm3`   | `f32`                 |
 /// | `vars`     | `rdi`    | `*const f32` (array)  |
 /// | `choices`  | `rsi`    | `*mut u8` (array)     |
 /// | `simplify` | `rdx`    | `*mut u8` (single)    |
@@ -30,10 +31,11 @@ impl AssemblerT for PointAssembler {
             ; push rbp
             ; mov rbp, rsp
             ; vzeroupper
-            // Put X/Y/Z on the stack so we can use those registers
+            // Put X/Y/Z/W on the stack so we can use those registers
             ; vmovss [rbp - 4], xmm0
             ; vmovss [rbp - 8], xmm1
             ; vmovss [rbp - 12], xmm2
+            ; vmovss [rbp - 16], xmm3
         );
         out.prepare_stack(slot_count);
         Self(out)
@@ -55,7 +57,7 @@ impl AssemblerT for PointAssembler {
     }
     fn build_input(&mut self, out_reg: u8, src_arg: u8) {
         dynasm!(self.0.ops
-            // Pull X/Y/Z from the stack, where they've been placed by init()
+            // Pull X/Y/Z/W from the stack, where they've been placed by init()
             ; vmovss Rx(reg(out_reg)), [rbp - 4 * (src_arg as i32 + 1)]
         );
     }
@@ -103,6 +105,105 @@ impl AssemblerT for PointAssembler {
             ; vmulss Rx(reg(out_reg)), Rx(reg(lhs_reg)), Rx(reg(lhs_reg))
         );
     }
+    fn build_exp(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn point_exp(v: f32) -> f32 {
+            v.exp()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, point_exp);
+    }
+    fn build_sin(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn point_sin(v: f32) -> f32 {
+            v.sin()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, point_sin);
+    }
+    fn build_cos(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn point_cos(v: f32) -> f32 {
+            v.cos()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, point_cos);
+    }
+    fn build_tan(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn point_tan(v: f32) -> f32 {
+            v.tan()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, point_tan);
+    }
+    fn build_asin(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn point_asin(v: f32) -> f32 {
+            v.asin()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, point_asin);
+    }
+    fn build_acos(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn point_acos(v: f32) -> f32 {
+            v.acos()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, point_acos);
+    }
+    fn build_atan(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn point_atan(v: f32) -> f32 {
+            v.atan()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, point_atan);
+    }
+    fn build_atan2(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "sysv64" fn point_atan2(lhs: f32, rhs: f32) -> f32 {
+            lhs.atan2(rhs)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, point_atan2);
+    }
+    fn build_ln(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn point_ln(v: f32) -> f32 {
+            v.ln()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, point_ln);
+    }
+    fn build_pow(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "sysv64" fn point_pow(lhs: f32, rhs: f32) -> f32 {
+            lhs.powf(rhs)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, point_pow);
+    }
+    fn build_powi(&mut self, out_reg: u8, lhs_reg: u8, n: i32) {
+        extern "sysv64" fn point_powi(lhs: f32, rhs: f32) -> f32 {
+            lhs.powi(rhs as i32)
+        }
+        let imm = self.load_imm(n as f32);
+        self.call_fn_binary(out_reg, lhs_reg, imm, point_powi);
+    }
+    fn build_floor(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; vroundss Rx(reg(out_reg)), Rx(reg(lhs_reg)), Rx(reg(lhs_reg)), 9
+        );
+    }
+    fn build_ceil(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; vroundss Rx(reg(out_reg)), Rx(reg(lhs_reg)), Rx(reg(lhs_reg)), 10
+        );
+    }
+    fn build_round(&mut self, out_reg: u8, lhs_reg: u8) {
+        extern "sysv64" fn point_round(v: f32) -> f32 {
+            v.round()
+        }
+        self.call_fn_unary(out_reg, lhs_reg, point_round);
+    }
+    fn build_mod(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        extern "sysv64" fn point_mod(lhs: f32, rhs: f32) -> f32 {
+            lhs.rem_euclid(rhs)
+        }
+        self.call_fn_binary(out_reg, lhs_reg, rhs_reg, point_mod);
+    }
+    fn build_not(&mut self, out_reg: u8, lhs_reg: u8) {
+        dynasm!(self.0.ops
+            // Build a mask of (lhs == 0), then convert it to 0.0 / 1.0
+            ; vpxor xmm1, xmm1, xmm1
+            ; vcmpeqss xmm1, xmm1, Rx(reg(lhs_reg))
+            ; mov eax, 1f32.to_bits() as i32
+            ; vmovd xmm2, eax
+            ; vandps Rx(reg(out_reg)), xmm1, xmm2
+        );
+    }
     fn build_add(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
         dynasm!(self.0.ops
             ; vaddss Rx(reg(out_reg)), Rx(reg(lhs_reg)), Rx(reg(rhs_reg))
@@ -156,6 +257,7 @@ impl AssemblerT for PointAssembler {
             // fallthrough to out
 
             ; O:
+            ; add rsi, 1
         );
         self.0.ops.commit_local().unwrap()
     }
@@ -190,6 +292,100 @@ impl AssemblerT for PointAssembler {
             // fallthrough to out
 
             ; O:
+            ; add rsi, 1
+        );
+        self.0.ops.commit_local().unwrap()
+    }
+    fn build_lt(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; vcmpltss xmm1, Rx(reg(lhs_reg)), Rx(reg(rhs_reg))
+            ; mov eax, 1f32.to_bits() as i32
+            ; vmovd xmm2, eax
+            ; vandps Rx(reg(out_reg)), xmm1, xmm2
+        );
+    }
+    fn build_eq(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; vcmpeqss xmm1, Rx(reg(lhs_reg)), Rx(reg(rhs_reg))
+            ; mov eax, 1f32.to_bits() as i32
+            ; vmovd xmm2, eax
+            ; vandps Rx(reg(out_reg)), xmm1, xmm2
+        );
+    }
+    fn build_and(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; vpxor xmm1, xmm1, xmm1
+            ; vcomiss Rx(reg(lhs_reg)), xmm1
+            ; jp >R
+            ; jne >R
+
+            // Fallthrough for lhs == 0, which is returned unchanged
+            ; vmovss Rx(reg(out_reg)), Rx(reg(out_reg)), Rx(reg(lhs_reg))
+            ; or [rsi], CHOICE_LEFT as i8
+            ; jmp >O
+
+            ; R:
+            ; vmovss Rx(reg(out_reg)), Rx(reg(out_reg)), Rx(reg(rhs_reg))
+            ; or [rsi], CHOICE_RIGHT as i8
+            // fallthrough to out
+
+            ; O:
+            ; or [rdx], 1
+            ; add rsi, 1
+        );
+        self.0.ops.commit_local().unwrap()
+    }
+    fn build_or(&mut self, out_reg: u8, lhs_reg: u8, rhs_reg: u8) {
+        dynasm!(self.0.ops
+            ; vpxor xmm1, xmm1, xmm1
+            ; vcomiss Rx(reg(lhs_reg)), xmm1
+            ; jp >L
+            ; jne >L
+
+            // Fallthrough for lhs == 0, so we pick the right-hand side
+            ; vmovss Rx(reg(out_reg)), Rx(reg(out_reg)), Rx(reg(rhs_reg))
+            ; or [rsi], CHOICE_RIGHT as i8
+            ; jmp >O
+
+            ; L:
+            ; vmovss Rx(reg(out_reg)), Rx(reg(out_reg)), Rx(reg(lhs_reg))
+            ; or [rsi], CHOICE_LEFT as i8
+            // fallthrough to out
+
+            ; O:
+            ; or [rdx], 1
+            ; add rsi, 1
+        );
+        self.0.ops.commit_local().unwrap()
+    }
+    fn build_select(
+        &mut self,
+        out_reg: u8,
+        cond_reg: u8,
+        lhs_reg: u8,
+        rhs_reg: u8,
+    ) {
+        dynasm!(self.0.ops
+            ; vpxor xmm1, xmm1, xmm1
+            ; vcomiss Rx(reg(cond_reg)), xmm1
+            ; jp >R
+            ; jb >L
+
+            // Fallthrough for cond >= 0 (or NAN), so we pick the right-hand
+            // side
+            ; R:
+            ; vmovss Rx(reg(out_reg)), Rx(reg(out_reg)), Rx(reg(rhs_reg))
+            ; or [rsi], CHOICE_RIGHT as i8
+            ; jmp >O
+
+            ; L:
+            ; vmovss Rx(reg(out_reg)), Rx(reg(out_reg)), Rx(reg(lhs_reg))
+            ; or [rsi], CHOICE_LEFT as i8
+            // fallthrough to out
+
+            ; O:
+            ; or [rdx], 1
+            ; add rsi, 1
         );
         self.0.ops.commit_local().unwrap()
     }
@@ -201,10 +397,10 @@ impl AssemblerT for PointAssembler {
         );
         IMM_REG.wrapping_sub(OFFSET)
     }
-    fn finalize(mut self, out_reg: u8) -> Result<Mmap, Error> {
+    fn finalize(mut self, _output_count: u8) -> Result<Mmap, Error> {
         dynasm!(self.0.ops
             // Prepare our return value
-            ; vmovss xmm0, xmm0, Rx(reg(out_reg))
+            ; vmovss xmm0, xmm0, Rx(reg(0))
             ; add rsp, self.0.mem_offset as i32
             ; pop rbp
             ; emms
@@ -213,3 +409,89 @@ impl AssemblerT for PointAssembler {
         self.0.ops.finalize()
     }
 }
+
+#[cfg(target_arch = "x86_64")]
+impl PointAssembler {
+    /// Stack space used by [`Self::backup_registers`]: 3 pointers + 12 tape
+    /// registers, rounded up for 16-byte alignment
+    const CALL_STACK_SIZE: i32 = 80;
+
+    /// Saves our argument pointers and tape registers to the stack
+    ///
+    /// Every `xmm` register is caller-saved in the System V ABI, so this must
+    /// be called before calling into a Rust function.
+    fn backup_registers(&mut self) {
+        dynasm!(self.0.ops
+            ; sub rsp, Self::CALL_STACK_SIZE
+            ; mov [rsp], rdi
+            ; mov [rsp + 8], rsi
+            ; mov [rsp + 16], rdx
+        );
+        for i in 0..REGISTER_LIMIT {
+            let offset = 24 + 4 * i as i32;
+            dynasm!(self.0.ops
+                ; vmovss [rsp + offset], Rx(reg(i))
+            );
+        }
+    }
+
+    /// Restores the registers saved by [`Self::backup_registers`]
+    fn restore_registers(&mut self) {
+        for i in 0..REGISTER_LIMIT {
+            let offset = 24 + 4 * i as i32;
+            dynasm!(self.0.ops
+                ; vmovss Rx(reg(i)), [rsp + offset]
+            );
+        }
+        dynasm!(self.0.ops
+            ; mov rdi, [rsp]
+            ; mov rsi, [rsp + 8]
+            ; mov rdx, [rsp + 16]
+            ; add rsp, Self::CALL_STACK_SIZE
+        );
+    }
+
+    /// Calls a unary Rust function, writing its result to `out_reg`
+    fn call_fn_unary(
+        &mut self,
+        out_reg: u8,
+        arg_reg: u8,
+        f: extern "sysv64" fn(f32) -> f32,
+    ) {
+        let addr = f as usize;
+        self.backup_registers();
+        dynasm!(self.0.ops
+            ; vmovss xmm0, xmm0, Rx(reg(arg_reg))
+            ; mov rax, QWORD addr as i64
+            ; call rax
+        );
+        self.restore_registers();
+        dynasm!(self.0.ops
+            // The result is in xmm0, which isn't one of our tape registers
+            ; vmovss Rx(reg(out_reg)), Rx(reg(out_reg)), xmm0
+        );
+    }
+
+    /// Calls a binary Rust function, writing its result to `out_reg`
+    fn call_fn_binary(
+        &mut self,
+        out_reg: u8,
+        lhs_reg: u8,
+        rhs_reg: u8,
+        f: extern "sysv64" fn(f32, f32) -> f32,
+    ) {
+        let addr = f as usize;
+        self.backup_registers();
+        dynasm!(self.0.ops
+            // Either argument may be in xmm0 (IMM_REG), so set it last
+            ; vmovss xmm1, xmm1, Rx(reg(rhs_reg))
+            ; vmovss xmm0, xmm0, Rx(reg(lhs_reg))
+            ; mov rax, QWORD addr as i64
+            ; call rax
+        );
+        self.restore_registers();
+        dynasm!(self.0.ops
+            ; vmovss Rx(reg(out_reg)), Rx(reg(out_reg)), xmm0
+        );
+    }
+}
@@ -82,7 +82,7 @@
 //! registers](crate::eval::Family::REG_LIMIT), which affects tape planning;
 //! don't worry, this won't be on the test)
 //!
-//! At the moment, Fidget implements two evaluator families:
+//! At the moment, Fidget implements five evaluator families:
 //!
 //! - [`fidget::jit::Eval`](crate::jit::Eval) performs fast evaluation by
 //!   compiling shapes down to native code.  This is only functional on an ARM64
@@ -90,6 +90,12 @@
 //! - [`fidget::vm::Eval`](crate::vm::Eval) evaluates
 //!   using an interpreter.  This is slower, but can run in more situations (e.g.
 //!   x86 machines or in WebAssembly).
+//! - [`fidget::vm::Eval64`](crate::vm::Eval64) uses an interpreter which works
+//!   in double precision, for models where `f32` rounding is visible.
+//! - [`fidget::vm::SoundEval`](crate::vm::SoundEval) and
+//!   [`fidget::jit::SoundEval`](crate::jit::SoundEval) are variants of
+//!   `vm::Eval64` and `jit::Eval` whose interval arithmetic rounds outwards, so
+//!   interval results are guaranteed to contain the true range of the function.
 //!
 //! Looking at the [`eval::Family`](crate::eval::Family) trait, you may notice
 //! that it requires four different kinds of evaluation:
@@ -252,3 +258,6 @@ pub mod jit;
 
 #[cfg(feature = "mesh")]
 pub mod mesh;
+
+#[cfg(feature = "fit")]
+pub mod fit;
@@ -55,4 +55,15 @@ pub struct Settings {
     ///
     /// This is **much slower**.
     pub max_depth: u8,
+
+    /// Use affine arithmetic (instead of interval arithmetic) to decide which
+    /// cells are empty, full, or need subdivision
+    ///
+    /// See [`AffineEval`](crate::eval::AffineEval) for details.
+    pub affine: bool,
+
+    /// Value for the W input, used for every sample
+    ///
+    /// Varying this produces 3D slices of a 4D model.
+    pub w: f32,
 }
@@ -223,8 +223,8 @@ impl<I: Family> OctreeWorker<I> {
             }
 
             if let Some(task) = self.queue.pop() {
-                // Each task represents 8 cells, so evaluate them one by one
-                // here and return results.
+                // Each task represents 8 cells, so evaluate them here (as a
+                // batch, if the evaluator supports it) and return results.
 
                 // Prepare a set of 8x cells for storage
                 let index = self.octree.o.cells.len();
@@ -232,16 +232,18 @@ impl<I: Family> OctreeWorker<I> {
                     self.octree.o.cells.push(Cell::Invalid.into());
                 }
 
-                for i in Corner::iter() {
-                    let sub_cell = task.target_cell.child(index, i);
-
-                    match self.octree.eval_cell(
-                        &task.eval,
-                        &mut self.data,
-                        &mut storage,
-                        sub_cell,
-                        settings,
-                    ) {
+                let sub_cells: [_; 8] = std::array::from_fn(|i| {
+                    task.target_cell.child(index, Corner::new(i as u8))
+                });
+                let results = self.octree.eval_cells(
+                    &task.eval,
+                    &mut self.data,
+                    &mut storage,
+                    sub_cells,
+                    settings,
+                );
+                for (sub_cell, r) in sub_cells.into_iter().zip(results) {
+                    match r {
                         // If this child is finished, then record it locally.
                         // If it's a branching cell, then we'll let a caller
                         // fill it in eventually (via the done queue).
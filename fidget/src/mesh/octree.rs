@@ -13,10 +13,14 @@ use super::{
     Mesh, Settings,
 };
 use crate::eval::{
+    affine::{AffineEval, AffineEvalData},
     float_slice::{FloatSliceEvalData, FloatSliceEvalStorage},
     grad_slice::{GradSliceEvalData, GradSliceEvalStorage},
-    interval::{IntervalEvalData, IntervalEvalStorage},
-    tape, Family, FloatSliceEval, GradSliceEval, IntervalEval, Tape,
+    interval_slice::{IntervalSliceEvalData, IntervalSliceEvalStorage},
+    tape,
+    tracing::BorrowedTracingEvalResult,
+    types::{Affine, Interval},
+    Family, FloatSliceEval, GradSliceEval, IntervalSliceEval, Tape,
 };
 use once_cell::sync::OnceCell;
 use std::{num::NonZeroUsize, sync::Arc};
@@ -30,7 +34,7 @@ pub struct EvalGroup<I: Family> {
     // TODO: passing around an `Arc<EvalGroup>` ends up with two layers of
     // indirection (since the evaluators also contain `Arc`); could we flatten
     // them out?  (same with `Tape`, which is an `Arc<Data>`)
-    pub interval: OnceCell<IntervalEval<I>>,
+    pub interval: OnceCell<IntervalSliceEval<I>>,
     pub float_slice: OnceCell<FloatSliceEval<I>>,
     pub grad_slice: OnceCell<GradSliceEval<I>>,
 }
@@ -46,10 +50,10 @@ impl<I: Family> EvalGroup<I> {
     }
     fn interval(
         &self,
-        s: &mut Vec<IntervalEvalStorage<I>>,
-    ) -> &IntervalEval<I> {
+        s: &mut Vec<IntervalSliceEvalStorage<I>>,
+    ) -> &IntervalSliceEval<I> {
         self.interval.get_or_init(|| {
-            self.tape.new_interval_evaluator_with_storage(
+            self.tape.new_interval_slice_evaluator_with_storage(
                 s.pop().unwrap_or_default(),
             )
         })
@@ -79,7 +83,8 @@ impl<I: Family> EvalGroup<I> {
 pub struct EvalData<I: Family> {
     float_data: FloatSliceEvalData<I>,
     grad_data: GradSliceEvalData<I>,
-    interval_data: IntervalEvalData<I>,
+    interval_data: IntervalSliceEvalData<I>,
+    affine_data: AffineEvalData<I>,
 }
 
 impl<I: Family> Default for EvalData<I> {
@@ -88,6 +93,7 @@ impl<I: Family> Default for EvalData<I> {
             float_data: Default::default(),
             grad_data: Default::default(),
             interval_data: Default::default(),
+            affine_data: Default::default(),
         }
     }
 }
@@ -96,7 +102,7 @@ pub struct EvalStorage<I: Family> {
     pub workspace: tape::Workspace,
     pub tape_storage: Vec<tape::Data>,
     pub float_storage: Vec<FloatSliceEvalStorage<I>>,
-    pub interval_storage: Vec<IntervalEvalStorage<I>>,
+    pub interval_storage: Vec<IntervalSliceEvalStorage<I>>,
     pub grad_storage: Vec<GradSliceEvalStorage<I>>,
 }
 
@@ -250,6 +256,7 @@ impl Octree {
                 &mut EvalStorage::default(),
                 CellIndex::default(),
                 &fixup.needs_fixing,
+                settings.w,
             );
             octree = b.into();
         }
@@ -548,40 +555,126 @@ impl OctreeBuilder {
         cell: CellIndex,
         settings: Settings,
     ) -> CellResult<I> {
-        let (i, r) = eval
-            .interval(&mut storage.interval_storage)
-            .eval_with(
-                cell.bounds.x,
-                cell.bounds.y,
-                cell.bounds.z,
-                &[],
-                &mut data.interval_data,
-            )
-            .unwrap();
-        if i.upper() < 0.0 {
-            CellResult::Done(Cell::Full)
-        } else if i.lower() > 0.0 {
-            CellResult::Done(Cell::Empty)
-        } else {
-            let sub_tape = if I::simplify_tree_during_meshing(cell.depth) {
-                r.map(|r| {
-                    Arc::new(EvalGroup::new(
-                        r.simplify_with(
-                            &mut storage.workspace,
-                            storage.tape_storage.pop().unwrap_or_default(),
+        let [r] = self.eval_cells(eval, data, storage, [cell], settings);
+        r
+    }
+
+    /// Evaluates a group of cells in the octree
+    ///
+    /// When using interval arithmetic with an evaluator that supports it, all of
+    /// the cells are evaluated in a single batch (typically, the 8 children of
+    /// a cell being subdivided).
+    ///
+    /// As with [`eval_cell`](Self::eval_cell), cell results are **not** written
+    /// back to the `cells` array.
+    pub(crate) fn eval_cells<I: Family, const N: usize>(
+        &mut self,
+        eval: &Arc<EvalGroup<I>>,
+        data: &mut EvalData<I>,
+        storage: &mut EvalStorage<I>,
+        cells: [CellIndex; N],
+        settings: Settings,
+    ) -> [CellResult<I>; N] {
+        let results: [(Interval, Option<Arc<EvalGroup<I>>>); N] =
+            if settings.affine {
+                cells.map(|cell| {
+                    let (a, r) = AffineEval::new(&eval.tape)
+                        .eval_xyzw_with(
+                            Affine::x(cell.bounds.x),
+                            Affine::y(cell.bounds.y),
+                            Affine::z(cell.bounds.z),
+                            Affine::from(settings.w),
+                            &[],
+                            &mut data.affine_data,
                         )
-                        .unwrap(),
-                    ))
+                        .unwrap();
+                    let i = Interval::from(a);
+                    (i, Self::sub_tape(storage, cell, i, r))
+                })
+            } else if !IntervalSliceEval::<I>::is_batched() {
+                // Evaluators without a batched kernel (e.g. the JIT) are given
+                // one cell at a time
+                cells.map(|cell| {
+                    let out = eval
+                        .interval(&mut storage.interval_storage)
+                        .eval_xyzw_with(
+                            &[cell.bounds.x],
+                            &[cell.bounds.y],
+                            &[cell.bounds.z],
+                            &[Interval::from(settings.w)],
+                            &[],
+                            &mut data.interval_data,
+                        )
+                        .unwrap();
+                    let (i, r) = out.get(0);
+                    (i, Self::sub_tape(storage, cell, i, r))
                 })
             } else {
-                None
+                let xs = cells.map(|c| c.bounds.x);
+                let ys = cells.map(|c| c.bounds.y);
+                let zs = cells.map(|c| c.bounds.z);
+                let ws = [Interval::from(settings.w); N];
+                let out = eval
+                    .interval(&mut storage.interval_storage)
+                    .eval_xyzw_with(
+                        &xs,
+                        &ys,
+                        &zs,
+                        &ws,
+                        &[],
+                        &mut data.interval_data,
+                    )
+                    .unwrap();
+                let mut j = 0;
+                cells.map(|cell| {
+                    let (i, r) = out.get(j);
+                    j += 1;
+                    (i, Self::sub_tape(storage, cell, i, r))
+                })
             };
-            if cell.depth == settings.min_depth as usize {
+
+        let mut iter = cells.into_iter().zip(results);
+        std::array::from_fn(|_| {
+            let (cell, (i, sub_tape)) = iter.next().unwrap();
+            if i.upper() < 0.0 {
+                CellResult::Done(Cell::Full)
+            } else if i.lower() > 0.0 {
+                CellResult::Done(Cell::Empty)
+            } else if cell.depth == settings.min_depth as usize {
                 let eval = sub_tape.unwrap_or_else(|| eval.clone());
-                CellResult::Done(self.leaf(&eval, data, storage, cell))
+                CellResult::Done(
+                    self.leaf(&eval, data, storage, cell, settings.w),
+                )
             } else {
                 CellResult::Recurse(sub_tape.unwrap_or_else(|| eval.clone()))
             }
+        })
+    }
+
+    /// Simplifies the tape using the trace from a cell's evaluation
+    ///
+    /// Returns `None` if the tape can't (or shouldn't) be simplified, or if the
+    /// cell is already known to be filled or empty.
+    fn sub_tape<I: Family, T>(
+        storage: &mut EvalStorage<I>,
+        cell: CellIndex,
+        i: Interval,
+        r: Option<BorrowedTracingEvalResult<'_, T, I>>,
+    ) -> Option<Arc<EvalGroup<I>>> {
+        if i.upper() < 0.0 || i.lower() > 0.0 {
+            None
+        } else if I::simplify_tree_during_meshing(cell.depth) {
+            r.map(|r| {
+                Arc::new(EvalGroup::new(
+                    r.simplify_with(
+                        &mut storage.workspace,
+                        storage.tape_storage.pop().unwrap_or_default(),
+                    )
+                    .unwrap(),
+                ))
+            })
+        } else {
+            None
         }
     }
 
@@ -629,16 +722,35 @@ impl OctreeBuilder {
         cell: CellIndex,
         settings: Settings,
     ) {
-        match self.eval_cell(eval, data, storage, cell, settings) {
+        let r = self.eval_cell(eval, data, storage, cell, settings);
+        self.recurse_result(data, storage, cell, r, settings);
+    }
+
+    /// Records the result of evaluating the given cell, recursing if needed
+    ///
+    /// The children of a branching cell are evaluated in a single batch.
+    fn recurse_result<I: Family>(
+        &mut self,
+        data: &mut EvalData<I>,
+        storage: &mut EvalStorage<I>,
+        cell: CellIndex,
+        result: CellResult<I>,
+        settings: Settings,
+    ) {
+        match result {
             CellResult::Done(c) => self.o[cell] = c.into(),
             CellResult::Recurse(sub_eval) => {
                 let index = self.o.cells.len();
                 for _ in Corner::iter() {
                     self.o.cells.push(Cell::Invalid.into());
                 }
-                for i in Corner::iter() {
-                    let cell = cell.child(index, i);
-                    self.recurse(&sub_eval, data, storage, cell, settings);
+                let cells: [_; 8] = std::array::from_fn(|i| {
+                    cell.child(index, Corner::new(i as u8))
+                });
+                let results =
+                    self.eval_cells(&sub_eval, data, storage, cells, settings);
+                for (cell, r) in cells.into_iter().zip(results) {
+                    self.recurse_result(data, storage, cell, r, settings);
                 }
 
                 let r = self.check_done(cell, index).unwrap();
@@ -668,12 +780,15 @@ impl OctreeBuilder {
     /// Writes the leaf vertex to `self.o.verts`, hermite data to
     /// `self.hermite`, and the leaf data to `self.leafs`.  Does **not** write
     /// anything to `self.o.cells`; the cell is returned instead.
+    ///
+    /// `w` is the (constant) value of the W input for every sample.
     fn leaf<I: Family>(
         &mut self,
         eval: &EvalGroup<I>,
         data: &mut EvalData<I>,
         storage: &mut EvalStorage<I>,
         cell: CellIndex,
+        w: f32,
     ) -> Cell {
         let float_eval = eval.float_slice(&mut storage.float_storage);
 
@@ -688,7 +803,7 @@ impl OctreeBuilder {
         }
 
         let out = float_eval
-            .eval_with(&xs, &ys, &zs, &[], &mut data.float_data)
+            .eval_xyzw_with(&xs, &ys, &zs, &[w; 8], &[], &mut data.float_data)
             .unwrap();
         debug_assert_eq!(out.len(), 8);
 
@@ -763,6 +878,7 @@ impl OctreeBuilder {
             &mut [0f32; 12 * EDGE_SEARCH_SIZE][..edge_count * EDGE_SEARCH_SIZE];
         let zs =
             &mut [0f32; 12 * EDGE_SEARCH_SIZE][..edge_count * EDGE_SEARCH_SIZE];
+        let ws = &[w; 12 * EDGE_SEARCH_SIZE][..edge_count * EDGE_SEARCH_SIZE];
 
         // This part looks hairy, but it's just doing an N-ary search along each
         // edge to find the intersection point.
@@ -788,7 +904,7 @@ impl OctreeBuilder {
 
             // Do the actual evaluation
             let out = float_eval
-                .eval_with(xs, ys, zs, &[], &mut data.float_data)
+                .eval_xyzw_with(xs, ys, zs, ws, &[], &mut data.float_data)
                 .unwrap();
 
             // Update start and end positions based on evaluation
@@ -847,7 +963,7 @@ impl OctreeBuilder {
         // TODO: special case for cells with multiple gradients ("features")
         let grad_eval = eval.grad_slice(&mut storage.grad_storage);
         let grads = grad_eval
-            .eval_with(xs, ys, zs, &[], &mut data.grad_data)
+            .eval_xyzw_with(xs, ys, zs, ws, &[], &mut data.grad_data)
             .unwrap();
 
         let mut verts: arrayvec::ArrayVec<_, 4> = arrayvec::ArrayVec::new();
@@ -1102,6 +1218,7 @@ impl OctreeBuilder {
         storage: &mut EvalStorage<I>,
         cell: CellIndex,
         needs_fixing: &[bool],
+        w: f32,
     ) {
         match self.o[cell].into() {
             Cell::Empty | Cell::Full | Cell::Leaf(..)
@@ -1114,7 +1231,7 @@ impl OctreeBuilder {
                 // Evaluate all 8 leafs
                 for i in Corner::iter() {
                     let subcell = cell.child(index, i);
-                    let leaf = self.leaf(eval, data, storage, subcell);
+                    let leaf = self.leaf(eval, data, storage, subcell, w);
                     match leaf {
                         Cell::Leaf(Leaf { index, .. }) => {
                             // Discard hermite data immediately, because we
@@ -1143,6 +1260,7 @@ impl OctreeBuilder {
                         storage,
                         cell.child(index, i),
                         needs_fixing,
+                        w,
                     )
                 }
             }
@@ -1378,11 +1496,15 @@ mod test {
         min_depth: 0,
         max_depth: 0,
         threads: 0,
+        affine: false,
+        w: 0.0,
     };
     const DEPTH1_SINGLE_THREAD: Settings = Settings {
         min_depth: 1,
         max_depth: 1,
         threads: 0,
+        affine: false,
+        w: 0.0,
     };
 
     fn sphere(
@@ -1411,15 +1533,14 @@ mod test {
         x_bounds.max(y_bounds).max(z_bounds)
     }
 
-    #[test]
-    fn test_cube_edge() {
+    fn test_cube_edge<F: crate::eval::Family>() {
         const EPSILON: f32 = 1e-3;
         let ctx = BoundContext::new();
         let f = 2.0;
         let cube = cube(&ctx, [-f, f], [-f, 0.3], [-f, 0.6]);
         // This should be a cube with a single edge running through the root
         // node of the octree, with an edge vertex at [0, 0.3, 0.6]
-        let tape = cube.get_tape::<crate::vm::Eval>().unwrap();
+        let tape = cube.get_tape::<F>().unwrap();
         let octree = Octree::build(&tape, DEPTH0_SINGLE_THREAD);
         assert_eq!(octree.verts.len(), 5);
         let v = octree.verts[0].pos;
@@ -1430,6 +1551,16 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_cube_edge_vm() {
+        test_cube_edge::<crate::vm::Eval>()
+    }
+
+    #[test]
+    fn test_cube_edge_vm64() {
+        test_cube_edge::<crate::vm::Eval64>()
+    }
+
     fn cone(
         ctx: &BoundContext,
         corner: nalgebra::Vector3<f32>,
@@ -1547,6 +1678,49 @@ mod test {
         assert_eq!(edge_count, 6);
     }
 
+    #[test]
+    fn test_sphere_w() {
+        // A sphere whose radius is set by the W input
+        let mut ctx = crate::Context::new();
+        let x = ctx.x();
+        let y = ctx.y();
+        let z = ctx.z();
+        let w = ctx.w();
+        let x2 = ctx.square(x).unwrap();
+        let y2 = ctx.square(y).unwrap();
+        let z2 = ctx.square(z).unwrap();
+        let r = ctx.add(x2, y2).unwrap();
+        let r = ctx.add(r, z2).unwrap();
+        let r = ctx.sqrt(r).unwrap();
+        let shape = ctx.sub(r, w).unwrap();
+        let tape = ctx.get_tape::<crate::vm::Eval>(shape).unwrap();
+
+        for (threads, affine) in [(0, false), (0, true), (8, false)] {
+            let settings = Settings {
+                w: 0.3,
+                threads,
+                affine,
+                ..DEPTH1_SINGLE_THREAD
+            };
+            let octree = Octree::build(&tape, settings);
+            let mesh = octree.walk_dual(settings);
+            let mut edge_count = 0;
+            for v in &mesh.vertices {
+                let edge_sum = (v.x != 0.0) as u8
+                    + (v.y != 0.0) as u8
+                    + (v.z != 0.0) as u8;
+                if edge_sum == 1 {
+                    assert!(
+                        (v.norm() - 0.3).abs() < 2.0 / u16::MAX as f32,
+                        "edge vertex {v:?} is not at radius 0.3"
+                    );
+                    edge_count += 1;
+                }
+            }
+            assert_eq!(edge_count, 6);
+        }
+    }
+
     #[test]
     fn test_sphere_manifold() {
         let ctx = BoundContext::new();
@@ -1558,6 +1732,8 @@ mod test {
                 min_depth: 5,
                 max_depth: 5,
                 threads,
+                affine: false,
+                w: 0.0,
             };
             let octree = Octree::build(&tape, settings);
             let sphere_mesh = octree.walk_dual(settings);
@@ -1717,6 +1893,8 @@ mod test {
                     min_depth: 2,
                     max_depth: 2,
                     threads,
+                    affine: false,
+                    w: 0.0,
                 };
                 let octree = Octree::build(&tape, settings);
 
@@ -1784,6 +1962,8 @@ mod test {
                 min_depth: 1,
                 max_depth: 1,
                 threads,
+                affine: false,
+                w: 0.0,
             };
             let octree = Octree::build(&tape, settings);
             assert_eq!(
@@ -1800,17 +1980,19 @@ mod test {
         let (ctx, root) =
             crate::Context::from_text(COLONNADE.as_bytes()).unwrap();
         let tape = ctx.get_tape::<crate::vm::Eval>(root).unwrap();
-        for threads in [0, 8] {
+        for (threads, affine) in [(0, false), (8, false), (8, true)] {
             let settings = Settings {
                 min_depth: 5,
                 max_depth: 5,
                 threads,
+                affine,
+                w: 0.0,
             };
             let octree = Octree::build(&tape, settings);
             let mesh = octree.walk_dual(settings);
             // Note: the model has duplicate vertices!
             if let Err(e) = check_for_edge_matching(&mesh) {
-                panic!("colonnade model has {e}");
+                panic!("colonnade model has {e} (affine: {affine})");
             }
         }
     }
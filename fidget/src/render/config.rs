@@ -35,6 +35,21 @@ where
     /// By default, we render a cube spanning ±1 on all axes; `mat` allows for
     /// rotation, scaling, transformation, and even perspective.
     pub mat: Transform<f32, nalgebra::TGeneral, N>,
+
+    /// Use affine arithmetic (instead of interval arithmetic) to decide which
+    /// tiles to fill or subdivide; `false` by default
+    ///
+    /// Affine arithmetic is slower per tile, but often produces tighter bounds
+    /// on deep tapes, which means fewer tiles to evaluate and shorter tapes.
+    /// See [`AffineEval`](crate::eval::AffineEval) for details.
+    pub affine: bool,
+
+    /// Value for the W input, which is constant across the image; 0 by
+    /// default
+    ///
+    /// Rendering a model at several values of `w` is an easy way to produce
+    /// animation frames (or 4D slices).
+    pub w: f32,
 }
 
 impl<const N: usize> Default for RenderConfig<N>
@@ -52,6 +67,8 @@ where
             },
             threads: 8,
             mat: Transform::identity(),
+            affine: false,
+            w: 0.0,
         }
     }
 }
@@ -117,6 +134,8 @@ where
             tile_sizes,
             threads: self.threads,
             mat,
+            affine: self.affine,
+            w: self.w,
         }
     }
 }
@@ -137,6 +156,8 @@ where
     pub threads: usize,
 
     pub mat: NPlusOneMatrix<N>,
+    pub affine: bool,
+    pub w: f32,
 }
 
 /// Type for a static `f32` matrix of size `N + 1`
@@ -247,6 +268,8 @@ mod test {
             tile_sizes: vec![64, 32],
             threads: 8,
             mat: Transform::identity(),
+            affine: false,
+            w: 0.0,
         };
         let aligned = config.align();
         assert_eq!(aligned.image_size, config.image_size);
@@ -270,6 +293,8 @@ mod test {
             tile_sizes: vec![64, 32],
             threads: 8,
             mat: Transform::identity(),
+            affine: false,
+            w: 0.0,
         };
         let aligned = config.align();
         assert_eq!(aligned.orig_image_size, 575);
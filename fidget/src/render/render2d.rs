@@ -1,12 +1,16 @@
 //! 2D bitmap rendering / rasterization
 use crate::{
     eval::{
+        affine::{AffineEval, AffineEvalData},
         float_slice::{
             FloatSliceEval, FloatSliceEvalData, FloatSliceEvalStorage,
         },
-        interval::{IntervalEval, IntervalEvalData, IntervalEvalStorage},
+        interval_slice::{
+            IntervalSliceEval, IntervalSliceEvalData, IntervalSliceEvalStorage,
+        },
         tape::{Data as TapeData, Tape, Workspace},
-        types::Interval,
+        tracing::BorrowedTracingEvalResult,
+        types::{Affine, Interval},
         Family,
     },
     render::config::{AlignedRenderConfig, Queue, RenderConfig, Tile},
@@ -160,14 +164,27 @@ struct Scratch {
     x: Vec<f32>,
     y: Vec<f32>,
     z: Vec<f32>,
+    w: Vec<f32>,
+
+    /// Interval bounds of each tile in a batch
+    xi: Vec<Interval>,
+    yi: Vec<Interval>,
+    zi: Vec<Interval>,
+    wi: Vec<Interval>,
 }
 
 impl Scratch {
-    fn new(size: usize) -> Self {
+    fn new(size: usize, w: f32) -> Self {
         Self {
             x: vec![0.0; size],
             y: vec![0.0; size],
             z: vec![0.0; size],
+            w: vec![w; size],
+
+            xi: vec![],
+            yi: vec![],
+            zi: vec![],
+            wi: vec![],
         }
     }
 }
@@ -184,30 +201,29 @@ struct Worker<'a, I: Family, M: RenderMode> {
     float_storage: [FloatSliceEvalStorage<I>; 2],
 
     /// Storage for interval evaluators, based on recursion depth
-    interval_storage: Vec<IntervalEvalStorage<I>>,
+    interval_storage: Vec<IntervalSliceEvalStorage<I>>,
 
     /// Workspace for interval evaluators, based on recursion depth
-    interval_data: Vec<IntervalEvalData<I>>,
+    interval_data: Vec<IntervalSliceEvalData<I>>,
+
+    /// Workspace for affine evaluators, based on recursion depth
+    affine_data: Vec<AffineEvalData<I>>,
 
     /// Workspace for pixel evaluators
     float_data: FloatSliceEvalData<I>,
 
+    /// Subtiles to be evaluated as a batch, based on recursion depth
+    tiles: Vec<Vec<Tile<2>>>,
+
     spare_tapes: Vec<TapeData>,
     workspace: Workspace,
 }
 
 impl<I: Family, M: RenderMode> Worker<'_, I, M> {
-    fn render_tile_recurse(
-        &mut self,
-        i_handle: &mut IntervalEval<I>,
-        depth: usize,
-        tile: Tile<2>,
-        float_handle: &mut Option<FloatSliceEval<I>>,
-        mode: &M,
-    ) {
-        let tile_size = self.config.tile_sizes[depth];
-
+    /// Finds the (interval) bounding box of the given tile
+    fn tile_bounds(&self, tile: Tile<2>, depth: usize) -> [Interval; 2] {
         // Brute-force way to find the (interval) bounding box of the region
+        let tile_size = self.config.tile_sizes[depth];
         let mut x_min = f32::INFINITY;
         let mut x_max = f32::NEG_INFINITY;
         let mut y_min = f32::INFINITY;
@@ -225,14 +241,116 @@ impl<I: Family, M: RenderMode> Worker<'_, I, M> {
             y_min = y_min.min(p.y);
             y_max = y_max.max(p.y);
         }
-        let x = Interval::new(x_min, x_max);
-        let y = Interval::new(y_min, y_max);
-        let z = Interval::new(0.0, 0.0);
+        [Interval::new(x_min, x_max), Interval::new(y_min, y_max)]
+    }
 
+    /// Evaluates a set of tiles at the same depth, then renders each of them
+    ///
+    /// Interval evaluation is done as a single batch if the evaluator supports
+    /// it; affine evaluation is done one tile at a time.
+    fn render_tiles(
+        &mut self,
+        i_handle: &IntervalSliceEval<I>,
+        depth: usize,
+        tiles: &[Tile<2>],
+        mode: &M,
+    ) {
+        let tape = i_handle.tape();
+        let z = Interval::new(0.0, 0.0);
+        let w = Interval::from(self.config.w);
         let mut data = std::mem::take(&mut self.interval_data[depth]);
-        let (i, simplify) =
-            i_handle.eval_with(x, y, z, &[], &mut data).unwrap();
+        let mut affine_data = std::mem::take(&mut self.affine_data[depth]);
+        let mut float_handle = None;
+        if self.config.affine {
+            let eval = AffineEval::new(&tape);
+            for &tile in tiles {
+                let [x, y] = self.tile_bounds(tile, depth);
+                let (a, simplify) = eval
+                    .eval_xyzw_with(
+                        Affine::x(x),
+                        Affine::y(y),
+                        Affine::z(z),
+                        Affine::from(w),
+                        &[],
+                        &mut affine_data,
+                    )
+                    .unwrap();
+                self.render_tile_recurse(
+                    &tape,
+                    depth,
+                    tile,
+                    Interval::from(a),
+                    simplify.map(|s| s.cast()),
+                    &mut float_handle,
+                    mode,
+                );
+            }
+        } else {
+            // Evaluators without a batched kernel (e.g. the JIT) are given one
+            // tile at a time
+            let chunk_size = if IntervalSliceEval::<I>::is_batched() {
+                tiles.len().max(1)
+            } else {
+                1
+            };
+            for tiles in tiles.chunks(chunk_size) {
+                self.scratch.xi.clear();
+                self.scratch.yi.clear();
+                self.scratch.zi.clear();
+                self.scratch.wi.clear();
+                for &tile in tiles {
+                    let [x, y] = self.tile_bounds(tile, depth);
+                    self.scratch.xi.push(x);
+                    self.scratch.yi.push(y);
+                    self.scratch.zi.push(z);
+                    self.scratch.wi.push(w);
+                }
+                let out = i_handle
+                    .eval_xyzw_with(
+                        &self.scratch.xi,
+                        &self.scratch.yi,
+                        &self.scratch.zi,
+                        &self.scratch.wi,
+                        &[],
+                        &mut data,
+                    )
+                    .unwrap();
+                for (n, &tile) in tiles.iter().enumerate() {
+                    let (i, simplify) = out.get(n);
+                    self.render_tile_recurse(
+                        &tape,
+                        depth,
+                        tile,
+                        i,
+                        simplify,
+                        &mut float_handle,
+                        mode,
+                    );
+                }
+            }
+        }
+        if let Some(f) = float_handle {
+            self.float_storage[0] = f.take().unwrap();
+        }
 
+        // Return the data
+        self.interval_data[depth] = data;
+        self.affine_data[depth] = affine_data;
+    }
+
+    /// Renders a single tile, given the result of its interval evaluation
+    #[allow(clippy::too_many_arguments)]
+    fn render_tile_recurse(
+        &mut self,
+        tape: &Tape<I>,
+        depth: usize,
+        tile: Tile<2>,
+        i: Interval,
+        simplify: Option<BorrowedTracingEvalResult<'_, Interval, I>>,
+        float_handle: &mut Option<FloatSliceEval<I>>,
+        mode: &M,
+    ) {
+        let tile_size = self.config.tile_sizes[depth];
         let fill = mode.interval(i, depth);
 
         if let Some(fill) = fill {
@@ -250,31 +368,25 @@ impl<I: Family, M: RenderMode> Worker<'_, I, M> {
                 )
                 .unwrap()
             } else {
-                i_handle.tape()
+                tape.clone()
             };
             let storage = std::mem::take(&mut self.interval_storage[depth]);
-            let mut sub_jit =
-                sub_tape.new_interval_evaluator_with_storage(storage);
+            let sub_jit =
+                sub_tape.new_interval_slice_evaluator_with_storage(storage);
             let n = tile_size / next_tile_size;
-            let mut float_handle = None;
+            let mut tiles = std::mem::take(&mut self.tiles[depth + 1]);
+            tiles.clear();
             for j in 0..n {
                 for i in 0..n {
-                    self.render_tile_recurse(
-                        &mut sub_jit,
-                        depth + 1,
-                        self.config.new_tile([
-                            tile.corner[0] + i * next_tile_size,
-                            tile.corner[1] + j * next_tile_size,
-                        ]),
-                        &mut float_handle,
-                        mode,
-                    );
+                    tiles.push(self.config.new_tile([
+                        tile.corner[0] + i * next_tile_size,
+                        tile.corner[1] + j * next_tile_size,
+                    ]));
                 }
             }
+            self.render_tiles(&sub_jit, depth + 1, &tiles, mode);
+            self.tiles[depth + 1] = tiles;
             self.interval_storage[depth] = sub_jit.take().unwrap();
-            if let Some(f) = float_handle {
-                self.float_storage[0] = f.take().unwrap();
-            }
             if simplify.is_some() {
                 self.spare_tapes[depth] = sub_tape.take().unwrap();
             }
@@ -288,10 +400,10 @@ impl<I: Family, M: RenderMode> Worker<'_, I, M> {
                     )
                     .unwrap()
             } else {
-                i_handle.tape()
+                tape.clone()
             };
             self.render_tile_pixels(
-                i_handle.tape(),
+                tape.clone(),
                 &sub_tape,
                 tile_size,
                 tile,
@@ -303,9 +415,6 @@ impl<I: Family, M: RenderMode> Worker<'_, I, M> {
                     sub_tape.take().unwrap();
             }
         }
-
-        // Return the data
-        self.interval_data[depth] = data;
     }
 
     fn render_tile_pixels(
@@ -343,10 +452,11 @@ impl<I: Family, M: RenderMode> Worker<'_, I, M> {
             let func = sub_tape.new_float_slice_evaluator_with_storage(storage);
 
             let out = func
-                .eval_with(
+                .eval_xyzw_with(
                     &self.scratch.x,
                     &self.scratch.y,
                     &self.scratch.z,
+                    &self.scratch.w,
                     &[],
                     &mut self.float_data,
                 )
@@ -365,10 +475,11 @@ impl<I: Family, M: RenderMode> Worker<'_, I, M> {
                 prev_tape.new_float_slice_evaluator_with_storage(storage)
             });
 
-            func.eval_with(
+            func.eval_xyzw_with(
                 &self.scratch.x,
                 &self.scratch.y,
                 &self.scratch.z,
+                &self.scratch.w,
                 &[],
                 &mut self.float_data,
             )
@@ -392,13 +503,14 @@ impl<I: Family, M: RenderMode> Worker<'_, I, M> {
 ////////////////////////////////////////////////////////////////////////////////
 
 fn worker<I: Family, M: RenderMode>(
-    mut i_handle: IntervalEval<I>,
+    i_handle: IntervalSliceEval<I>,
     queue: &Queue<2>,
     config: &AlignedRenderConfig<2>,
     mode: &M,
 ) -> Vec<(Tile<2>, Vec<M::Output>)> {
     let mut out = vec![];
-    let scratch = Scratch::new(config.tile_sizes.last().unwrap_or(&0).pow(2));
+    let scratch =
+        Scratch::new(config.tile_sizes.last().unwrap_or(&0).pow(2), config.w);
 
     let mut w: Worker<I, M> = Worker {
         scratch,
@@ -411,15 +523,19 @@ fn worker<I: Family, M: RenderMode>(
         interval_data: (0..config.tile_sizes.len())
             .map(|_| Default::default())
             .collect(),
+        affine_data: (0..config.tile_sizes.len())
+            .map(|_| Default::default())
+            .collect(),
         spare_tapes: (0..config.tile_sizes.len())
             .map(|_| Default::default())
             .collect(),
         float_data: Default::default(),
+        tiles: (0..config.tile_sizes.len()).map(|_| vec![]).collect(),
         workspace: Default::default(),
     };
     while let Some(tile) = queue.next() {
         w.image = vec![M::Output::default(); config.tile_sizes[0].pow(2)];
-        w.render_tile_recurse(&mut i_handle, 0, tile, &mut None, mode);
+        w.render_tiles(&i_handle, 0, &[tile], mode);
         let pixels = std::mem::take(&mut w.image);
         out.push((tile, pixels))
     }
@@ -448,7 +564,7 @@ pub fn render<I: Family, M: RenderMode + Sync>(
         assert!(config.tile_sizes[i] % config.tile_sizes[i + 1] == 0);
     }
 
-    let i_handle = tape.new_interval_evaluator();
+    let i_handle = tape.new_interval_slice_evaluator();
     let mut tiles = vec![];
     for i in 0..config.image_size / config.tile_sizes[0] {
         for j in 0..config.image_size / config.tile_sizes[0] {
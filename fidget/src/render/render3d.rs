@@ -1,14 +1,18 @@
 //! 3D bitmap rendering / rasterization
 use crate::{
     eval::{
+        affine::{AffineEval, AffineEvalData},
         float_slice::{
             FloatSliceEval, FloatSliceEvalData, FloatSliceEvalStorage,
         },
         grad_slice::{GradSliceEval, GradSliceEvalData, GradSliceEvalStorage},
-        interval::{IntervalEval, IntervalEvalData},
+        interval_slice::{
+            IntervalSliceEval, IntervalSliceEvalData, IntervalSliceEvalStorage,
+        },
         tape::{Data as TapeData, Tape, Workspace},
-        types::{Grad, Interval},
-        Choice, EvaluatorStorage, Family,
+        tracing::BorrowedTracingEvalResult,
+        types::{Affine, Grad, Interval},
+        Choice, Family,
     },
     render::config::{AlignedRenderConfig, Queue, RenderConfig, Tile},
 };
@@ -39,28 +43,53 @@ struct Scratch<F: Family> {
     x: Vec<f32>,
     y: Vec<f32>,
     z: Vec<f32>,
+    w: Vec<f32>,
+
+    /// Interval bounds of each tile in a batch
+    xi: Vec<Interval>,
+    yi: Vec<Interval>,
+    zi: Vec<Interval>,
+    wi: Vec<Interval>,
 
     data_float: FloatSliceEvalData<F>,
-    data_interval: IntervalEvalData<F>,
     data_grad: GradSliceEvalData<F>,
 
+    /// Interval and affine evaluation data, based on render level
+    ///
+    /// Results from a batch of tiles are borrowed from this data while each
+    /// tile in the batch is rendered, so each level needs its own.
+    data_interval: Vec<IntervalSliceEvalData<F>>,
+    data_affine: Vec<AffineEvalData<F>>,
+
+    /// Tiles to be evaluated as a batch, based on render level
+    tiles: Vec<Vec<Tile<3>>>,
+
     /// Depth of each column
     columns: Vec<usize>,
 }
 
 impl<F: Family> Scratch<F> {
-    fn new(tile_size: usize) -> Self {
+    fn new(tile_size: usize, levels: usize, w: f32) -> Self {
         let size2 = tile_size.pow(2);
         let size3 = tile_size.pow(3);
         Self {
             x: vec![0.0; size3],
             y: vec![0.0; size3],
             z: vec![0.0; size3],
+            w: vec![w; size3],
+
+            xi: vec![],
+            yi: vec![],
+            zi: vec![],
+            wi: vec![],
 
             data_float: Default::default(),
-            data_interval: Default::default(),
             data_grad: Default::default(),
 
+            data_interval: (0..levels).map(|_| Default::default()).collect(),
+            data_affine: (0..levels).map(|_| Default::default()).collect(),
+            tiles: (0..levels).map(|_| vec![]).collect(),
+
             columns: vec![0; size2],
         }
     }
@@ -70,10 +99,11 @@ impl<F: Family> Scratch<F> {
         size: usize,
         data: &'a mut FloatSliceEvalData<F>,
     ) -> &'a [f32] {
-        f.eval_with(
+        f.eval_xyzw_with(
             &self.x[0..size],
             &self.y[0..size],
             &self.z[0..size],
+            &self.w[0..size],
             &[],
             data,
         )
@@ -85,10 +115,11 @@ impl<F: Family> Scratch<F> {
         size: usize,
         data: &'a mut GradSliceEvalData<F>,
     ) -> &'a [Grad] {
-        f.eval_with(
+        f.eval_xyzw_with(
             &self.x[0..size],
             &self.y[0..size],
             &self.z[0..size],
+            &self.w[0..size],
             &[],
             data,
         )
@@ -101,7 +132,7 @@ impl<F: Family> Scratch<F> {
 struct Evaluators<I: Family> {
     level: usize,
     tape: Tape<I>,
-    interval: Option<IntervalEval<I>>,
+    interval: Option<IntervalSliceEval<I>>,
     float_slice: Option<FloatSliceEval<I>>,
     grad: Option<GradSliceEval<I>>,
 }
@@ -139,9 +170,7 @@ struct Worker<'a, I: Family> {
     /// slot is always `None`, because philosophically, it represents the
     /// storage of `i_handle` (but `i_handle` is shared between multiple
     /// threads, so we never actually reclaim its storage).
-    interval_storage: Vec<
-        Option<<<I as Family>::IntervalEval as EvaluatorStorage<I>>::Storage>,
-    >,
+    interval_storage: Vec<Option<IntervalSliceEvalStorage<I>>>,
 
     /// Spare tapes to avoid allocation churn
     ///
@@ -170,24 +199,21 @@ impl<I: Family> Worker<'_, I> {
         eval.tape.take().unwrap()
     }
 
-    fn render_tile_recurse(
-        &mut self,
-        eval: &mut Evaluators<I>,
-        sibling: Option<(Vec<Choice>, Evaluators<I>)>,
-        level: usize,
-        tile: Tile<3>,
-    ) -> Option<(Vec<Choice>, Evaluators<I>)> {
-        // Early exit if every single pixel is filled
+    /// Checks whether every pixel in the given tile is already filled by a
+    /// closer tile
+    fn is_occluded(&self, tile: Tile<3>, level: usize) -> bool {
         let tile_size = self.config.tile_sizes[level];
         let fill_z = (tile.corner[2] + tile_size + 1).try_into().unwrap();
-        if (0..tile_size).all(|y| {
+        (0..tile_size).all(|y| {
             let i = self.config.tile_to_offset(tile, 0, y);
             (0..tile_size).all(|x| self.depth[i + x] >= fill_z)
-        }) {
-            return sibling;
-        }
+        })
+    }
 
+    /// Finds the (interval) bounding box of the given tile
+    fn tile_bounds(&self, tile: Tile<3>, level: usize) -> [Interval; 3] {
         // Brute-force way to find the (interval) bounding box of the region
+        let tile_size = self.config.tile_sizes[level];
         let mut x_min = f32::INFINITY;
         let mut x_max = f32::NEG_INFINITY;
         let mut y_min = f32::INFINITY;
@@ -211,22 +237,124 @@ impl<I: Family> Worker<'_, I> {
             z_max = z_max.max(p.z);
         }
 
-        let x = Interval::new(x_min, x_max);
-        let y = Interval::new(y_min, y_max);
-        let z = Interval::new(z_min, z_max);
-
-        let mut data_interval = std::mem::take(&mut self.scratch.data_interval);
-        let (i, simplify) = eval
-            .interval
-            .get_or_insert_with(|| {
-                let storage = self.interval_storage[eval.level].take().unwrap();
-                eval.tape.new_interval_evaluator_with_storage(storage)
-            })
-            .eval_with(x, y, z, &[], &mut data_interval)
-            .unwrap();
-
-        // Return early if this tile is completely empty or full, returning
-        // `data_interval` to scratch memory for reuse.
+        [
+            Interval::new(x_min, x_max),
+            Interval::new(y_min, y_max),
+            Interval::new(z_min, z_max),
+        ]
+    }
+
+    /// Evaluates a set of tiles at the same level, then renders each of them
+    ///
+    /// Interval evaluation is done as a single batch if the evaluator supports
+    /// it; affine evaluation is done one tile at a time.
+    fn render_tiles(
+        &mut self,
+        eval: &mut Evaluators<I>,
+        mut sibling: Option<(Vec<Choice>, Evaluators<I>)>,
+        level: usize,
+        tiles: &[Tile<3>],
+    ) -> Option<(Vec<Choice>, Evaluators<I>)> {
+        let mut data_interval =
+            std::mem::take(&mut self.scratch.data_interval[level]);
+        let mut data_affine =
+            std::mem::take(&mut self.scratch.data_affine[level]);
+        if self.config.affine {
+            let affine = AffineEval::new(&eval.tape);
+            for &tile in tiles {
+                let [x, y, z] = self.tile_bounds(tile, level);
+                let (a, simplify) = affine
+                    .eval_xyzw_with(
+                        Affine::x(x),
+                        Affine::y(y),
+                        Affine::z(z),
+                        Affine::from(self.config.w),
+                        &[],
+                        &mut data_affine,
+                    )
+                    .unwrap();
+                sibling = self.render_tile_recurse(
+                    eval,
+                    sibling,
+                    level,
+                    tile,
+                    Interval::from(a),
+                    simplify.map(|s| s.cast()),
+                );
+            }
+        } else {
+            // Evaluators without a batched kernel (e.g. the JIT) are given one
+            // tile at a time, so tiles hidden by their siblings are skipped
+            let chunk_size = if IntervalSliceEval::<I>::is_batched() {
+                tiles.len().max(1)
+            } else {
+                1
+            };
+            let w = Interval::from(self.config.w);
+            for tiles in tiles.chunks(chunk_size) {
+                if tiles.iter().all(|&t| self.is_occluded(t, level)) {
+                    continue;
+                }
+                self.scratch.xi.clear();
+                self.scratch.yi.clear();
+                self.scratch.zi.clear();
+                self.scratch.wi.clear();
+                for &tile in tiles {
+                    let [x, y, z] = self.tile_bounds(tile, level);
+                    self.scratch.xi.push(x);
+                    self.scratch.yi.push(y);
+                    self.scratch.zi.push(z);
+                    self.scratch.wi.push(w);
+                }
+                let out = eval
+                    .interval
+                    .get_or_insert_with(|| {
+                        let storage =
+                            self.interval_storage[eval.level].take().unwrap();
+                        eval.tape
+                            .new_interval_slice_evaluator_with_storage(storage)
+                    })
+                    .eval_xyzw_with(
+                        &self.scratch.xi,
+                        &self.scratch.yi,
+                        &self.scratch.zi,
+                        &self.scratch.wi,
+                        &[],
+                        &mut data_interval,
+                    )
+                    .unwrap();
+                for (n, &tile) in tiles.iter().enumerate() {
+                    let (i, simplify) = out.get(n);
+                    sibling = self.render_tile_recurse(
+                        eval, sibling, level, tile, i, simplify,
+                    );
+                }
+            }
+        }
+        self.scratch.data_interval[level] = data_interval;
+        self.scratch.data_affine[level] = data_affine;
+        sibling
+    }
+
+    /// Renders a single tile, given the result of its interval evaluation
+    fn render_tile_recurse(
+        &mut self,
+        eval: &mut Evaluators<I>,
+        sibling: Option<(Vec<Choice>, Evaluators<I>)>,
+        level: usize,
+        tile: Tile<3>,
+        i: Interval,
+        simplify: Option<BorrowedTracingEvalResult<'_, Interval, I>>,
+    ) -> Option<(Vec<Choice>, Evaluators<I>)> {
+        // Early exit if every single pixel is filled (which may have happened
+        // since this tile was evaluated, as its siblings were rendered)
+        let tile_size = self.config.tile_sizes[level];
+        let fill_z = (tile.corner[2] + tile_size + 1).try_into().unwrap();
+        if self.is_occluded(tile, level) {
+            return sibling;
+        }
+
+        // Return early if this tile is completely empty or full
         if i.upper() < 0.0 {
             for y in 0..tile_size {
                 let i = self.config.tile_to_offset(tile, 0, y);
@@ -235,10 +363,8 @@ impl<I: Family> Worker<'_, I> {
                 }
             }
             // TODO: handle gradients here as well?
-            self.scratch.data_interval = data_interval;
             return sibling;
         } else if i.lower() > 0.0 {
-            self.scratch.data_interval = data_interval;
             return sibling;
         }
 
@@ -311,9 +437,6 @@ impl<I: Family> Worker<'_, I> {
             (None, sibling)
         };
 
-        // Return `data_interval` to our scratch buffer
-        self.scratch.data_interval = data_interval;
-
         // At this point, only one of `sub_eval` and `prev_sibling` can be
         // `Some`; both could also be `None`, if we consumed `prev_sibling` then
         // realized that it didn't shorten the tape.
@@ -335,22 +458,31 @@ impl<I: Family> Worker<'_, I> {
                 None
             };
 
+            // Skip subtiles which are already hidden, then evaluate the rest
+            // as a single batch
+            let mut tiles = std::mem::take(&mut self.scratch.tiles[level + 1]);
+            tiles.clear();
             for j in 0..n {
                 for i in 0..n {
                     for k in (0..n).rev() {
-                        sibling = self.render_tile_recurse(
-                            sub_eval.as_mut().map(|c| &mut c.1).unwrap_or(eval),
-                            sibling,
-                            level + 1,
-                            self.config.new_tile([
-                                tile.corner[0] + i * next_size,
-                                tile.corner[1] + j * next_size,
-                                tile.corner[2] + k * next_size,
-                            ]),
-                        );
+                        let t = self.config.new_tile([
+                            tile.corner[0] + i * next_size,
+                            tile.corner[1] + j * next_size,
+                            tile.corner[2] + k * next_size,
+                        ]);
+                        if !self.is_occluded(t, level + 1) {
+                            tiles.push(t);
+                        }
                     }
                 }
             }
+            sibling = self.render_tiles(
+                sub_eval.as_mut().map(|c| &mut c.1).unwrap_or(eval),
+                sibling,
+                level + 1,
+                &tiles,
+            );
+            self.scratch.tiles[level + 1] = tiles;
             sibling
         } else {
             self.render_tile_pixels(
@@ -546,7 +678,7 @@ impl Image {
 ////////////////////////////////////////////////////////////////////////////////
 
 fn worker<I: Family>(
-    i_handle: IntervalEval<I>,
+    i_handle: IntervalSliceEval<I>,
     queues: &[Queue<3>],
     mut index: usize,
     config: &AlignedRenderConfig<3>,
@@ -555,7 +687,7 @@ fn worker<I: Family>(
 
     // Calculate maximum evaluation buffer size
     let buf_size = *config.tile_sizes.last().unwrap();
-    let scratch = Scratch::new(buf_size);
+    let scratch = Scratch::new(buf_size, config.tile_sizes.len(), config.w);
     let mut w: Worker<I> = Worker {
         scratch,
         depth: vec![],
@@ -601,9 +733,7 @@ fn worker<I: Family>(
                 float_slice: None,
                 grad: None,
             };
-            if let Some((_, e)) =
-                w.render_tile_recurse(&mut eval, None, 0, tile)
-            {
+            if let Some((_, e)) = w.render_tiles(&mut eval, None, 0, &[tile]) {
                 if let Some(i) = e.interval {
                     w.interval_storage[1].give(i.take().unwrap());
                 }
@@ -661,7 +791,7 @@ pub fn render<I: Family>(
         assert!(config.tile_sizes[i] % config.tile_sizes[i + 1] == 0);
     }
 
-    let i_handle = tape.new_interval_evaluator();
+    let i_handle = tape.new_interval_slice_evaluator();
     let mut tiles = vec![];
     for i in 0..config.image_size / config.tile_sizes[0] {
         for j in 0..config.image_size / config.tile_sizes[0] {
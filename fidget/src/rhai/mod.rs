@@ -3,7 +3,7 @@
 //! There are two main ways to use these bindings.
 //!
 //! The simplest option is to call [`eval`](eval), which evaluates a single
-//! expression with pre-defined variables `x`, `y`, `z`.
+//! expression with pre-defined variables `x`, `y`, `z`, and `w`.
 //!
 //! ```
 //! use fidget::{vm, rhai::eval};
@@ -41,6 +41,9 @@
 //! This means that the lambda function must be a **pure function** that only
 //! uses [Fidget-friendly math operations](crate::context::Op).
 //!
+//! The `w()` function returns the W input axis, which can be used to animate a
+//! shape (e.g. `draw(|x, y| x + y - w())`).
+//!
 //! Scripts are evaluated in a Rhai context that includes [`core.rhai`](core),
 //! which defines a few simple shapes and transforms.
 use std::sync::{Arc, Mutex};
@@ -75,8 +78,11 @@ impl Engine {
         engine.register_type_with_name::<Node>("Node");
         engine.register_fn("__var_x", var_x);
         engine.register_fn("__var_y", var_y);
+        engine.register_fn("w", var_w);
         engine.register_fn("__draw", draw);
         engine.register_fn("__draw_rgb", draw_rgb);
+        engine.register_fn("powi", powi);
+        engine.register_fn("select", select);
 
         macro_rules! register_binary_fns {
             ($op:literal, $name:ident, $engine:ident) => {
@@ -99,6 +105,14 @@ impl Engine {
         register_binary_fns!("/", div, engine);
         register_binary_fns!("min", min, engine);
         register_binary_fns!("max", max, engine);
+        register_binary_fns!("atan2", atan2, engine);
+        register_binary_fns!("pow", pow, engine);
+        register_binary_fns!("modulo", modulo, engine);
+        register_binary_fns!("lt", lt, engine);
+        register_binary_fns!("gt", gt, engine);
+        register_binary_fns!("eq", eq, engine);
+        register_binary_fns!("and", and, engine);
+        register_binary_fns!("or", or, engine);
         register_unary_fns!("abs", abs, engine);
         register_unary_fns!("sqrt", sqrt, engine);
         register_unary_fns!("square", square, engine);
@@ -106,6 +120,16 @@ impl Engine {
         register_unary_fns!("-", neg, engine);
         register_unary_fns!("sin", sin, engine);
         register_unary_fns!("cos", cos, engine);
+        register_unary_fns!("tan", tan, engine);
+        register_unary_fns!("asin", asin, engine);
+        register_unary_fns!("acos", acos, engine);
+        register_unary_fns!("atan", atan, engine);
+        register_unary_fns!("ln", ln, engine);
+        register_unary_fns!("log2", log2, engine);
+        register_unary_fns!("floor", floor, engine);
+        register_unary_fns!("ceil", ceil, engine);
+        register_unary_fns!("round", round, engine);
+        register_unary_fns!("not", not, engine);
 
         engine.set_fast_operators(false);
 
@@ -136,17 +160,18 @@ impl Engine {
         Ok(std::mem::take(&mut lock))
     }
 
-    /// Evaluates a single expression, in terms of `x`, `y`, and `z`
+    /// Evaluates a single expression, in terms of `x`, `y`, `z`, and `w`
     pub fn eval(&mut self, script: &str) -> Result<(Node, Context), Error> {
         let mut scope = {
             let mut ctx = self.context.lock().unwrap();
             ctx.clear();
 
-            // Create initialized scope with x/y/z
+            // Create initialized scope with x/y/z/w
             let mut scope = rhai::Scope::new();
             scope.push("x", ctx.context.x());
             scope.push("y", ctx.context.y());
             scope.push("z", ctx.context.z());
+            scope.push("w", ctx.context.w());
             scope
         };
 
@@ -167,11 +192,12 @@ impl Engine {
         let mut scope = {
             let mut ctx = self.context.lock().unwrap();
 
-            // Create initialized scope with x/y/z
+            // Create initialized scope with x/y/z/w
             let mut scope = rhai::Scope::new();
             scope.push("x", ctx.context.x());
             scope.push("y", ctx.context.y());
             scope.push("z", ctx.context.z());
+            scope.push("w", ctx.context.w());
             scope
         };
 
@@ -271,6 +297,38 @@ fn var_x(ctx: rhai::NativeCallContext) -> Node {
 fn var_y(ctx: rhai::NativeCallContext) -> Node {
     ctx.with_fidget_context(|c| c.y())
 }
+fn var_w(ctx: rhai::NativeCallContext) -> Node {
+    ctx.with_fidget_context(|c| c.w())
+}
+fn powi(ctx: rhai::NativeCallContext, a: Node, n: i64) -> Node {
+    ctx.with_fidget_context(|c| c.powi(a, n as i32).unwrap())
+}
+
+fn select(
+    ctx: rhai::NativeCallContext,
+    cond: Node,
+    a: rhai::Dynamic,
+    b: rhai::Dynamic,
+) -> Result<Node, Box<rhai::EvalAltResult>> {
+    // Branches may be either nodes or numbers, which are converted to
+    // constants (this avoids registering every combination of types)
+    let to_node = |c: &mut Context, v: &rhai::Dynamic| {
+        if let Some(n) = v.clone().try_cast::<Node>() {
+            Ok(n)
+        } else if let Ok(f) = v.as_float() {
+            Ok(c.constant(f))
+        } else if let Ok(i) = v.as_int() {
+            Ok(c.constant(i as f64))
+        } else {
+            Err(format!("select: invalid argument type {}", v.type_name()))
+        }
+    };
+    ctx.with_fidget_context(|c| {
+        let a = to_node(c, &a)?;
+        let b = to_node(c, &b)?;
+        Ok(c.select(cond, a, b).unwrap())
+    })
+}
 
 fn draw(ctx: rhai::NativeCallContext, node: Node) {
     let ctx = ctx.tag().unwrap().clone_cast::<Arc<Mutex<ScriptContext>>>();
@@ -351,6 +409,14 @@ define_binary_fns!(mul);
 define_binary_fns!(div);
 define_binary_fns!(min);
 define_binary_fns!(max);
+define_binary_fns!(atan2);
+define_binary_fns!(pow);
+define_binary_fns!(modulo);
+define_binary_fns!(lt);
+define_binary_fns!(gt);
+define_binary_fns!(eq);
+define_binary_fns!(and);
+define_binary_fns!(or);
 define_unary_fns!(abs);
 define_unary_fns!(sqrt);
 define_unary_fns!(square);
@@ -358,10 +424,20 @@ define_unary_fns!(exp);
 define_unary_fns!(neg);
 define_unary_fns!(sin);
 define_unary_fns!(cos);
+define_unary_fns!(tan);
+define_unary_fns!(asin);
+define_unary_fns!(acos);
+define_unary_fns!(atan);
+define_unary_fns!(ln);
+define_unary_fns!(log2);
+define_unary_fns!(floor);
+define_unary_fns!(ceil);
+define_unary_fns!(round);
+define_unary_fns!(not);
 
 ////////////////////////////////////////////////////////////////////////////////
 
-/// One-shot evaluation of a single expression, in terms of `x, y, z`
+/// One-shot evaluation of a single expression, in terms of `x, y, z, w`
 pub fn eval(s: &str) -> Result<(Node, Context), Error> {
     let mut engine = Engine::new(None);
     engine.eval(s)
@@ -386,6 +462,76 @@ mod test {
         let (sum, ctx) = engine.eval("x + y").unwrap();
         assert_eq!(ctx.eval_xyz(sum, 1.0, 2.0, 0.0).unwrap(), 3.0);
     }
+
+    #[test]
+    fn test_pow() {
+        let mut engine = Engine::new(None);
+        let (out, ctx) =
+            engine.eval("pow(x, 0.5) + powi(y, 3) + ln(x)").unwrap();
+        let v = ctx.eval_xyz(out, 4.0, -2.0, 0.0).unwrap();
+        assert_eq!(v, 2.0 - 8.0 + 4f64.ln());
+
+        let (out, ctx) = engine.eval("log2(x)").unwrap();
+        let v = ctx.eval_xyz(out, 8.0, 0.0, 0.0).unwrap();
+        assert_eq!(v, 3.0);
+    }
+
+    #[test]
+    fn test_repeat() {
+        let mut engine = Engine::new(None);
+        let out = engine.run("draw(repeat_xy(circle(0, 0, 0.5), 2))").unwrap();
+        let shape = out.shapes[0].shape;
+        for (x, y, v) in [(0.0, 0.0, -0.5), (4.0, -2.0, -0.5), (1.0, 0.0, 0.5)]
+        {
+            assert_eq!(out.context.eval_xyz(shape, x, y, 0.0).unwrap(), v);
+        }
+
+        let (out, ctx) = engine.eval("floor(x) + ceil(y) + round(z)").unwrap();
+        let v = ctx.eval_xyz(out, 1.5, 1.5, 1.5).unwrap();
+        assert_eq!(v, 1.0 + 2.0 + 2.0);
+    }
+
+    #[test]
+    fn test_logic() {
+        let mut engine = Engine::new(None);
+        let (out, ctx) = engine
+            .eval("or(and(lt(x, 1), gt(y, 2)), not(eq(z, 3)))")
+            .unwrap();
+        for (x, y, z, v) in [
+            (0.0, 3.0, 3.0, 1.0),
+            (0.0, 1.0, 3.0, 0.0),
+            (2.0, 3.0, 3.0, 0.0),
+            (2.0, 3.0, 4.0, 1.0),
+        ] {
+            assert_eq!(ctx.eval_xyz(out, x, y, z).unwrap(), v);
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let mut engine = Engine::new(None);
+        let (out, ctx) = engine.eval("select(x, y, 2)").unwrap();
+        assert_eq!(ctx.eval_xyz(out, -1.0, 3.0, 0.0).unwrap(), 3.0);
+        assert_eq!(ctx.eval_xyz(out, 1.0, 3.0, 0.0).unwrap(), 2.0);
+
+        let (out, ctx) = engine.eval("select(x - 1, 0.5, y * 2)").unwrap();
+        assert_eq!(ctx.eval_xyz(out, 0.0, 3.0, 0.0).unwrap(), 0.5);
+        assert_eq!(ctx.eval_xyz(out, 2.0, 3.0, 0.0).unwrap(), 6.0);
+
+        assert!(engine.eval("select(x, y, \"z\")").is_err());
+    }
+
+    #[test]
+    fn test_w() {
+        let mut engine = Engine::new(None);
+        let (out, ctx) = engine.eval("x + w * 2").unwrap();
+        assert_eq!(ctx.eval_xyzw(out, 1.0, 0.0, 0.0, 3.0).unwrap(), 7.0);
+
+        let out = engine.run("draw(|x, y| x + y - w())").unwrap();
+        let shape = out.shapes[0].shape;
+        let v = out.context.eval_xyzw(shape, 1.0, 2.0, 0.0, 0.5).unwrap();
+        assert_eq!(v, 2.5);
+    }
 }
 
 pub mod core;
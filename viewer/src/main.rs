@@ -170,6 +170,10 @@ fn render(
                 threads: 8,
 
                 mat,
+
+                affine: false,
+
+                w: 0.0,
             };
             match mode {
                 TwoDMode::Color => {
@@ -233,6 +237,10 @@ fn render(
                 threads: 8,
 
                 mat,
+
+                affine: false,
+
+                w: 0.0,
             };
             let (depth, color) = fidget::render::render3d(tape, &config);
             match mode {